@@ -0,0 +1,100 @@
+/// IP-XACT access semantics of a spreadsheet `ATTRIBUTE` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessSpec {
+    pub access: &'static str,
+    pub modified_write_value: Option<&'static str>,
+    pub read_action: Option<&'static str>,
+}
+
+const fn spec(
+    access: &'static str,
+    modified_write_value: Option<&'static str>,
+    read_action: Option<&'static str>,
+) -> AccessSpec {
+    AccessSpec {
+        access,
+        modified_write_value,
+        read_action,
+    }
+}
+
+const RW: &str = "read-write";
+const RO: &str = "read-only";
+const WO: &str = "write-only";
+
+/// Translates an access shorthand (UVM style `W1C`, `RC`, `WRC`, ... or the
+/// IP-XACT spelling `read-write`) into access/modifiedWriteValue/readAction.
+///
+/// Matching ignores case, spaces, `-`, `_` and `/`, so `R/W`, `rw` and
+/// `Read-Write` are all accepted. Returns `None` for unknown values.
+pub fn parse_access(attr: &str) -> Option<AccessSpec> {
+    let key: String = attr
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+
+    let spec = match key.as_str() {
+        "RW" | "READWRITE" => spec(RW, None, None),
+        "RO" | "R" | "READONLY" => spec(RO, None, None),
+        "WO" | "W" | "WRITEONLY" => spec(WO, None, None),
+
+        // read side effects
+        "RC" | "RCLR" => spec(RO, None, Some("clear")),
+        "RS" | "RSET" => spec(RO, None, Some("set")),
+        "WRC" => spec(RW, None, Some("clear")),
+        "WRS" => spec(RW, None, Some("set")),
+
+        // write side effects
+        "WC" => spec(RW, Some("clear"), None),
+        "WS" => spec(RW, Some("set"), None),
+        "W1C" | "RW1C" | "WRITE1TOCLEAR" | "WOCLR" => spec(RW, Some("oneToClear"), None),
+        "W1S" | "RW1S" | "WRITE1TOSET" | "WOSET" => spec(RW, Some("oneToSet"), None),
+        "W1T" | "RW1T" | "WRITE1TOTOGGLE" => spec(RW, Some("oneToToggle"), None),
+        "W0C" | "RW0C" | "WRITE0TOCLEAR" => spec(RW, Some("zeroToClear"), None),
+        "W0S" | "RW0S" | "WRITE0TOSET" => spec(RW, Some("zeroToSet"), None),
+        "W0T" | "RW0T" | "WRITE0TOTOGGLE" => spec(RW, Some("zeroToToggle"), None),
+        "WOC" => spec(WO, Some("clear"), None),
+        "WOS" => spec(WO, Some("set"), None),
+
+        // combined write and read side effects
+        "WSRC" | "WSC" => spec(RW, Some("set"), Some("clear")),
+        "WCRS" | "WCS" => spec(RW, Some("clear"), Some("set")),
+        "W1SRC" => spec(RW, Some("oneToSet"), Some("clear")),
+        "W1CRS" => spec(RW, Some("oneToClear"), Some("set")),
+        "W0SRC" => spec(RW, Some("zeroToSet"), Some("clear")),
+        "W0CRS" => spec(RW, Some("zeroToClear"), Some("set")),
+
+        // write once
+        "W1" | "RW1" | "READWRITEONCE" => spec("read-writeOnce", None, None),
+        "WO1" | "WRITEONCE" => spec("writeOnce", None, None),
+
+        _ => return None,
+    };
+
+    Some(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_access_maps_shorthands_to_ipxact_semantics() {
+        assert_eq!(parse_access("rw"), Some(spec(RW, None, None)));
+        assert_eq!(parse_access("Read-Only"), Some(spec(RO, None, None)));
+        assert_eq!(
+            parse_access("W1C"),
+            Some(spec(RW, Some("oneToClear"), None))
+        );
+        assert_eq!(parse_access("RC"), Some(spec(RO, None, Some("clear"))));
+        assert_eq!(
+            parse_access(" w1crs "),
+            Some(spec(RW, Some("oneToClear"), Some("set")))
+        );
+        assert_eq!(parse_access("WO1"), Some(spec("writeOnce", None, None)));
+        assert_eq!(parse_access("R/W"), Some(spec(RW, None, None)));
+        assert_eq!(parse_access("bogus"), None);
+        assert_eq!(parse_access(""), None);
+    }
+}
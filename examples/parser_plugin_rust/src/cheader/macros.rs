@@ -0,0 +1,290 @@
+//! Object-like `#define`s of a C header and their integer values.
+
+use std::collections::HashMap;
+
+use crate::expr::eval;
+
+#[derive(Debug, Clone)]
+pub struct Macro {
+    pub name: String,
+    /// Replacement text, comments removed
+    pub body: String,
+    pub line: usize,
+    /// Comment on the line of the `#define`, used as description
+    pub comment: Option<String>,
+    pub value: Option<u64>,
+    /// Pointer type the body casts to (`(UART_TypeDef *) UART0_BASE`), if any
+    pub cast: Option<String>,
+    /// The body dereferences an address: `(*(volatile uint32_t *)0x40000004)`
+    pub deref: bool,
+}
+
+/// Removes comments while keeping line breaks; returns the comment text found
+/// on each line (1-based).
+pub fn strip_comments(src: &str) -> (String, HashMap<usize, String>) {
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = String::with_capacity(src.len());
+    let mut comments: HashMap<usize, String> = HashMap::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            let start = i + 2;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            note(&mut comments, line, chars[start..i].iter().collect());
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start_line = line;
+            let start = i + 2;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                    out.push('\n');
+                }
+                i += 1;
+            }
+            let end = i.min(chars.len());
+            note(
+                &mut comments,
+                start_line,
+                chars[start..end].iter().collect(),
+            );
+            out.push(' ');
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            // literals may hold `//`
+            out.push(c);
+            i += 1;
+            while i < chars.len() && chars[i] != c && chars[i] != '\n' {
+                if chars[i] == '\\' {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+                if let Some(&ch) = chars.get(i) {
+                    out.push(ch);
+                }
+                i += 1;
+            }
+            if i < chars.len() && chars[i] == c {
+                out.push(c);
+                i += 1;
+            }
+        } else {
+            if c == '\n' {
+                line += 1;
+            }
+            out.push(c);
+            i += 1;
+        }
+    }
+    (out, comments)
+}
+
+fn note(comments: &mut HashMap<usize, String>, line: usize, text: String) {
+    let text = text
+        .trim_matches(|c: char| c.is_whitespace() || c == '*' || c == '!' || c == '<')
+        .lines()
+        .map(|l| l.trim().trim_start_matches('*').trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !text.is_empty() {
+        comments.entry(line).or_insert(text);
+    }
+}
+
+/// Object-like macros of comment-free source, in order; function-like macros
+/// are skipped. `types` names the struct typedefs casts may refer to.
+pub fn collect(src: &str, comments: &HashMap<usize, String>, types: &[String]) -> Vec<Macro> {
+    let mut macros = Vec::new();
+    let lines: Vec<&str> = src.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = i + 1;
+        let mut text = lines[i].to_string();
+        // line continuations
+        while text.trim_end().ends_with('\\') && i + 1 < lines.len() {
+            text = text.trim_end().trim_end_matches('\\').to_string();
+            i += 1;
+            text.push(' ');
+            text.push_str(lines[i]);
+        }
+        i += 1;
+
+        let Some(rest) = text.trim_start().strip_prefix('#') else {
+            continue;
+        };
+        let Some(rest) = rest.trim_start().strip_prefix("define") else {
+            continue;
+        };
+        if !rest.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let rest = rest.trim_start();
+        let name_end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (name, body) = rest.split_at(name_end);
+        if name.is_empty() || body.starts_with('(') {
+            continue;
+        }
+        let body = body.trim().to_string();
+        let (cleaned, cast, deref) = clean(&body, types);
+        macros.push(Macro {
+            name: name.to_string(),
+            body: cleaned,
+            line,
+            comment: comments.get(&line).cloned(),
+            value: None,
+            cast,
+            deref,
+        });
+    }
+    evaluate(&mut macros);
+    macros
+}
+
+/// Resolves the value of every macro, in as many passes as forward references need.
+fn evaluate(macros: &mut [Macro]) {
+    let mut values: HashMap<String, u64> = HashMap::new();
+    loop {
+        let mut progress = false;
+        for m in macros.iter_mut().filter(|m| m.value.is_none()) {
+            if let Some(value) = eval(&m.body, &|name| values.get(name).copied()) {
+                m.value = Some(value);
+                values.insert(m.name.clone(), value);
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+}
+
+/// Words that make a parenthesized group a cast.
+const TYPE_WORDS: [&str; 14] = [
+    "volatile", "const", "unsigned", "signed", "int", "long", "short", "char", "__IO", "__I",
+    "__O", "__IOM", "__IM", "__OM",
+];
+
+fn is_type_word(word: &str, types: &[String]) -> bool {
+    TYPE_WORDS.contains(&word) || word.ends_with("_t") || types.iter().any(|t| t == word)
+}
+
+/// Makes a macro body evaluable: casts removed, `U`/`L` suffixes dropped,
+/// `BIT(n)`/`_BV(n)` expanded, dereferences unwrapped. Returns the body, the
+/// struct type of a pointer cast and whether the body dereferences an address.
+fn clean(body: &str, types: &[String]) -> (String, Option<String>, bool) {
+    let chars: Vec<char> = body.chars().collect();
+    let mut out = String::new();
+    let mut cast = None;
+    let mut pointer_cast = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '(' {
+            if let Some(close) = chars[i..].iter().position(|&c| c == ')').map(|p| p + i) {
+                let inner: String = chars[i + 1..close].iter().collect();
+                let words: Vec<&str> = inner
+                    .split(|c: char| c.is_whitespace() || c == '*')
+                    .filter(|w| !w.is_empty())
+                    .collect();
+                let followed = chars[close + 1..]
+                    .iter()
+                    .find(|c| !c.is_whitespace())
+                    .is_some_and(|&c| c.is_alphanumeric() || c == '(' || c == '_');
+                if !words.is_empty() && followed && words.iter().all(|w| is_type_word(w, types)) {
+                    if inner.contains('*') {
+                        pointer_cast = true;
+                        cast = words
+                            .iter()
+                            .find(|w| types.iter().any(|t| t == *w))
+                            .map(|w| w.to_string());
+                    }
+                    i = close + 1;
+                    continue;
+                }
+            }
+        }
+        if c.is_ascii_digit()
+            && !chars[..i]
+                .last()
+                .is_some_and(|p| p.is_alphanumeric() || *p == '_')
+        {
+            // literal: keep digits, drop the u/l suffix
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '\'') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            out.push_str(literal.trim_end_matches(['u', 'U', 'l', 'L']));
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if matches!(word.as_str(), "BIT" | "_BV" | "BIT_MASK") {
+                out.push_str("1 << ");
+            } else {
+                out.push_str(&word);
+            }
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+
+    // `*(addr)` once the cast is gone
+    let mut deref = false;
+    let trimmed: String = out
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    if pointer_cast && cast.is_none() && trimmed.trim_start_matches('(').starts_with('*') {
+        deref = true;
+        if let Some(pos) = out.find('*') {
+            out.replace_range(pos..pos + 1, "");
+        }
+    }
+    (out.trim().to_string(), cast, deref)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_register_macros() {
+        let src = "#define PERIPH_BASE   (0x40000000UL) /*!< Peripheral base */\n\
+                   #define UART0_BASE    (PERIPH_BASE + 0x1000U)\n\
+                   #define UART0         ((UART_TypeDef *) UART0_BASE)\n\
+                   #define UART_CR_EN_Msk  (0x3UL << UART_CR_EN_Pos)\n\
+                   #define UART_CR_EN_Pos  4U\n\
+                   #define UART_DR  (*(volatile uint32_t *)(UART0_BASE + 0x8))\n\
+                   #define FLAG     BIT(7)\n\
+                   #define MAX(a, b) ((a) > (b) ? (a) : (b))\n";
+        let (clean_src, comments) = strip_comments(src);
+        let macros = collect(&clean_src, &comments, &["UART_TypeDef".to_string()]);
+        let find = |name: &str| macros.iter().find(|m| m.name == name).unwrap();
+
+        assert_eq!(macros.len(), 7);
+        assert_eq!(
+            find("PERIPH_BASE").comment.as_deref(),
+            Some("Peripheral base")
+        );
+        assert_eq!(find("UART0_BASE").value, Some(0x4000_1000));
+        assert_eq!(find("UART0").cast.as_deref(), Some("UART_TypeDef"));
+        assert_eq!(find("UART0").value, Some(0x4000_1000));
+        assert_eq!(find("UART_CR_EN_Msk").value, Some(0x30));
+        let dr = find("UART_DR");
+        assert!(dr.deref);
+        assert_eq!(dr.value, Some(0x4000_1008));
+        assert_eq!(find("FLAG").value, Some(0x80));
+    }
+}
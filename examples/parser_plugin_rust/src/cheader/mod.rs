@@ -0,0 +1,1034 @@
+//! Heuristic import of C headers: register `#define` naming conventions
+//! (`_OFFSET`, `_SHIFT`/`_POS`, `_MASK`/`_MSK`, ...) and struct based
+//! peripheral layouts.
+
+mod macros;
+mod structs;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::layout::{is_reserved, FieldBits};
+use crate::number::hex;
+use crate::options::ParseOptions;
+use crate::types::*;
+use macros::{collect, strip_comments, Macro};
+use structs::{Definition, Layout, Member};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// Stated by the header: a base, offset or mask macro, a struct member
+    High,
+    /// Inferred from a naming convention or an address
+    Medium,
+    /// A guess worth checking by hand
+    Low,
+}
+
+/// How a recovered block, register or field was found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceItem {
+    /// `BLOCK`, `BLOCK.REG` or `BLOCK.REG.FIELD`
+    pub path: String,
+    pub confidence: Confidence,
+    pub reason: String,
+    pub line: Option<usize>,
+}
+
+/// Companion of a C header import, one item per block, register and field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceReport {
+    pub items: Vec<ConfidenceItem>,
+}
+
+impl ConfidenceReport {
+    pub fn count(&self, confidence: Confidence) -> usize {
+        self.items
+            .iter()
+            .filter(|i| i.confidence == confidence)
+            .count()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ConfidenceItem> {
+        self.items.iter().find(|i| i.path == path)
+    }
+}
+
+const BASE: [&str; 4] = ["_BASE_ADDRESS", "_BASE_ADDR", "_BASEADDR", "_BASE"];
+const OFFSET: [&str; 4] = ["_OFFSET", "_Offset", "_OFFS", "_OFF"];
+const ADDRESS: [&str; 2] = ["_ADDRESS", "_ADDR"];
+const POSITION: [&str; 6] = ["_SHIFT", "_Shift", "_SHFT", "_POS", "_Pos", "_LSB"];
+const MASK: [&str; 5] = ["_MASK", "_Mask", "_BMSK", "_MSK", "_Msk"];
+const WIDTH: [&str; 4] = ["_WIDTH", "_Width", "_LEN", "_BITS"];
+const RESET: [&str; 6] = [
+    "_RESET_VALUE",
+    "_RESETVAL",
+    "_RESET",
+    "_Reset",
+    "_RST",
+    "_DEFAULT",
+];
+/// Suffixes of struct typedef names, dropped to get the macro prefix
+/// (`UART_TypeDef` has its fields in `UART_CR_EN_Pos`)
+const TYPE_SUFFIXES: [&str; 6] = ["_TypeDef", "_Type", "_RegDef", "_regs", "_Regs", "_REGS"];
+
+/// The key of `name` without one of `suffixes`.
+fn split<'a>(name: &'a str, suffixes: &[&str]) -> Option<&'a str> {
+    suffixes
+        .iter()
+        .find_map(|s| name.strip_suffix(s))
+        .filter(|key| !key.is_empty())
+}
+
+/// Lsb and width of a contiguous mask.
+fn span(mask: u64) -> Option<(u64, u64)> {
+    let lsb = mask.trailing_zeros() as u64;
+    let bits = mask.checked_shr(lsb as u32)?;
+    (mask != 0 && bits & bits.wrapping_add(1) == 0).then(|| (lsb, bits.count_ones() as u64))
+}
+
+/// Parses a C header into `ImportData`, describing in `report` how sure it is
+/// about every block, register and field.
+///
+/// Blocks come from struct instances (`#define UART0 ((UART_TypeDef *) UART0_BASE)`)
+/// and `_BASE` macros; registers from struct members, `_OFFSET` macros, and
+/// `_ADDR` or dereferencing macros relative to a base; fields from bit-fields
+/// and `_SHIFT`/`_POS`/`_MASK`/`_MSK`/`_WIDTH` macros named after a register.
+pub fn read_header(
+    src: &str,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+    report: &mut ConfidenceReport,
+) -> Result<Option<ImportData>, Error> {
+    let (clean, comments) = strip_comments(src);
+    // array lengths first, then casts to the struct types found with them
+    let values: HashMap<String, u64> = collect(&clean, &comments, &[])
+        .into_iter()
+        .filter_map(|m| Some((m.name, m.value?)))
+        .collect();
+    let mut guesses = Vec::new();
+    let defs = structs::parse(&clean, &values, &mut guesses);
+    let types: Vec<String> = defs.iter().map(|d| d.name.clone()).collect();
+
+    let mut importer = Importer {
+        opts,
+        diags,
+        report,
+        macros: collect(&clean, &comments, &types),
+        comments,
+        consumed: HashSet::new(),
+        blocks: Vec::new(),
+        regs: Vec::new(),
+    };
+    for guess in guesses {
+        importer.fallback("struct-layout", guess.line, guess.problem, &guess.default);
+    }
+    importer.structs(&defs);
+    importer.bases();
+    importer.offsets();
+    importer.addresses();
+    importer.resets();
+    importer.fields();
+    importer.bare_macros();
+    Ok(importer.finish())
+}
+
+struct Block {
+    name: String,
+    base: u64,
+    /// Size of the struct laid out at the base
+    size: u64,
+    line: usize,
+    confidence: Confidence,
+    reason: String,
+}
+
+struct Reg {
+    block: usize,
+    name: String,
+    /// Macro prefixes of its fields (`UART_CR`, `UART0_CR`)
+    keys: Vec<String>,
+    offset: u64,
+    bits: u64,
+    access: &'static str,
+    description: Option<String>,
+    reset: u64,
+    fields: Vec<Field>,
+    /// Bits taken by `fields`
+    taken: FieldBits,
+    line: usize,
+    confidence: Confidence,
+    reason: String,
+}
+
+#[derive(Clone)]
+struct Field {
+    name: String,
+    key: String,
+    lsb: u64,
+    width: u64,
+    description: Option<String>,
+    enums: Vec<ImportEnumeratedValue>,
+    line: usize,
+    confidence: Confidence,
+    reason: String,
+}
+
+/// Position, mask and width macros of one field.
+#[derive(Default)]
+struct Parts {
+    position: Option<(u64, usize)>,
+    /// The position is a nested `_OFFSET` macro
+    nested: bool,
+    mask: Option<(u64, usize)>,
+    width: Option<u64>,
+    line: usize,
+    description: Option<String>,
+}
+
+/// A flattened struct member.
+struct Flat {
+    name: String,
+    /// Name without array indices
+    plain: String,
+    offset: u64,
+    bits: u64,
+    access: Option<&'static str>,
+    fields: Vec<structs::BitField>,
+    line: usize,
+}
+
+fn flatten(members: &[Member], prefix: &str, plain: &str, base: u64, out: &mut Vec<Flat>) {
+    for m in members.iter().filter(|m| !is_reserved(&m.name)) {
+        let indices: Vec<Option<u64>> = match m.count {
+            Some(n) => (0..n).map(Some).collect(),
+            None => vec![None],
+        };
+        for index in indices {
+            let name = match index {
+                Some(i) => format!("{}{}_{}", prefix, m.name, i),
+                None => format!("{}{}", prefix, m.name),
+            };
+            let plain = format!("{}{}", plain, m.name);
+            let offset = base + m.offset + index.unwrap_or(0) * m.ty.size;
+            match &m.ty.layout {
+                Layout::Registers(inner) => {
+                    let mut nested = Vec::new();
+                    flatten(
+                        inner,
+                        &format!("{}_", name),
+                        &format!("{}_", plain),
+                        offset,
+                        &mut nested,
+                    );
+                    out.extend(nested.into_iter().map(|f| Flat {
+                        access: f.access.or(m.access),
+                        ..f
+                    }));
+                }
+                layout => out.push(Flat {
+                    name,
+                    plain,
+                    offset,
+                    bits: m.ty.size * 8,
+                    access: m.access,
+                    fields: match layout {
+                        Layout::Fields(bits) => bits.clone(),
+                        _ => Vec::new(),
+                    },
+                    line: m.line,
+                }),
+            }
+        }
+    }
+}
+
+struct Importer<'a> {
+    opts: &'a ParseOptions,
+    diags: &'a mut Vec<Diagnostic>,
+    report: &'a mut ConfidenceReport,
+    comments: HashMap<usize, String>,
+    macros: Vec<Macro>,
+    /// Macros already turned into something
+    consumed: HashSet<usize>,
+    blocks: Vec<Block>,
+    regs: Vec<Reg>,
+}
+
+impl Importer<'_> {
+    /// `Diagnostic::fallback` at `line`.
+    fn fallback(&mut self, code: &str, line: usize, problem: String, default: &str) {
+        let diag = Diagnostic::fallback(self.opts.strict, code, problem, default);
+        self.diags.push(diag.at(Some(line), None));
+    }
+
+    /// Unconsumed macros with a value whose name ends with one of `suffixes`.
+    fn candidates(&self, suffixes: &[&str]) -> Vec<(usize, String)> {
+        self.macros
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| !self.consumed.contains(i) && m.cast.is_none())
+            .filter_map(|(i, m)| Some((i, split(&m.name, suffixes)?.to_string())))
+            .collect()
+    }
+
+    /// The block whose name is the longest prefix of `key`.
+    fn block_for(&self, key: &str) -> Option<usize> {
+        (0..self.blocks.len())
+            .filter(|&b| {
+                key.strip_prefix(&self.blocks[b].name)
+                    .is_some_and(|rest| rest.starts_with('_'))
+            })
+            .max_by_key(|&b| self.blocks[b].name.len())
+    }
+
+    /// Block named `name` at `base`, created when missing.
+    fn block_named(&mut self, name: &str, base: u64, line: usize, reason: String) -> usize {
+        if let Some(b) = self.blocks.iter().position(|b| b.name == name) {
+            return b;
+        }
+        self.blocks.push(Block {
+            name: name.to_string(),
+            base,
+            size: 0,
+            line,
+            confidence: Confidence::Medium,
+            reason,
+        });
+        self.blocks.len() - 1
+    }
+
+    /// The longest register key prefixing `key`, with the registers having it.
+    fn registers_for(&self, key: &str) -> Option<(String, Vec<usize>)> {
+        let prefix = self
+            .regs
+            .iter()
+            .flat_map(|r| r.keys.iter())
+            .filter(|k| {
+                key.strip_prefix(k.as_str())
+                    .is_some_and(|rest| rest.len() > 1 && rest.starts_with('_'))
+            })
+            .max_by_key(|k| k.len())?
+            .clone();
+        let regs = (0..self.regs.len())
+            .filter(|&r| self.regs[r].keys.contains(&prefix))
+            .collect();
+        Some((prefix, regs))
+    }
+
+    /// Register `key` of `block`, named without the block prefix, found from
+    /// macro `m` as `(confidence, reason)` says.
+    fn push_reg(
+        &mut self,
+        block: usize,
+        key: &str,
+        offset: u64,
+        m: usize,
+        (confidence, reason): (Confidence, String),
+    ) {
+        let name = key
+            .strip_prefix(&format!("{}_", self.blocks[block].name))
+            .unwrap_or(key);
+        let mut keys = vec![key.to_string()];
+        if let Some(short) = key.strip_suffix("_REG") {
+            keys.push(short.to_string());
+        }
+        let name = name.strip_suffix("_REG").unwrap_or(name);
+        self.regs.push(Reg {
+            block,
+            name: name.to_string(),
+            keys,
+            offset,
+            bits: 32,
+            access: "read-write",
+            description: self.macros[m].comment.clone(),
+            reset: 0,
+            fields: Vec::new(),
+            taken: FieldBits::new(32),
+            line: self.macros[m].line,
+            confidence,
+            reason,
+        });
+    }
+
+    /// Blocks laid out by a struct: one per instance macro, or one per register
+    /// struct at 0 when the header instantiates none.
+    fn structs(&mut self, defs: &[Definition]) {
+        let mut instanced = false;
+        for i in 0..self.macros.len() {
+            let m = &self.macros[i];
+            let (Some(ty), Some(base)) = (&m.cast, m.value) else {
+                continue;
+            };
+            let Some(def) = defs.iter().find(|d| &d.name == ty) else {
+                continue;
+            };
+            if !matches!(def.ty.layout, Layout::Registers(_)) {
+                continue;
+            }
+            let (name, line) = (m.name.clone(), m.line);
+            let reason = format!("{} instance at 0x{:X}", def.name, base);
+            self.consumed.insert(i);
+            instanced = true;
+            self.struct_block(def, &name, base, line, Confidence::High, reason);
+        }
+        if instanced {
+            return;
+        }
+        // struct types used inside another one are not blocks
+        let nested: Vec<&structs::Type> = defs
+            .iter()
+            .filter_map(|d| match &d.ty.layout {
+                Layout::Registers(members) => Some(members),
+                _ => None,
+            })
+            .flatten()
+            .map(|m| &m.ty)
+            .collect();
+        for def in defs {
+            let registers = matches!(def.ty.layout, Layout::Registers(_));
+            let prefix = split(&def.name, &TYPE_SUFFIXES);
+            if let (true, Some(prefix), false) = (registers, prefix, nested.contains(&&def.ty)) {
+                let reason = format!("{} has no instance macro, base address 0", def.name);
+                self.struct_block(def, prefix, 0, def.line, Confidence::Medium, reason);
+            }
+        }
+    }
+
+    fn struct_block(
+        &mut self,
+        def: &Definition,
+        name: &str,
+        base: u64,
+        line: usize,
+        confidence: Confidence,
+        reason: String,
+    ) {
+        let Layout::Registers(members) = &def.ty.layout else {
+            return;
+        };
+        let block = self.blocks.len();
+        self.blocks.push(Block {
+            name: name.to_string(),
+            base,
+            size: def.ty.size,
+            line,
+            confidence,
+            reason,
+        });
+        let prefix = split(&def.name, &TYPE_SUFFIXES).unwrap_or(&def.name);
+        let mut flat = Vec::new();
+        flatten(members, "", "", 0, &mut flat);
+        for f in flat {
+            let mut keys = vec![
+                format!("{}_{}", prefix, f.name),
+                format!("{}_{}", name, f.name),
+                format!("{}_{}", prefix, f.plain),
+            ];
+            keys.dedup();
+            let fields: Vec<Field> = f
+                .fields
+                .iter()
+                .map(|b| Field {
+                    name: b.name.clone(),
+                    key: format!("{}_{}", keys[0], b.name),
+                    lsb: b.lsb,
+                    width: b.width,
+                    description: self.comments.get(&b.line).cloned(),
+                    enums: Vec::new(),
+                    line: b.line,
+                    confidence: Confidence::High,
+                    reason: "struct bit-field".to_string(),
+                })
+                .collect();
+            self.regs.push(Reg {
+                block,
+                name: f.name,
+                keys,
+                offset: f.offset,
+                bits: f.bits,
+                access: f.access.unwrap_or("read-write"),
+                description: self.comments.get(&f.line).cloned(),
+                reset: 0,
+                fields: Vec::new(),
+                taken: FieldBits::new(f.bits),
+                line: f.line,
+                confidence: Confidence::High,
+                reason: format!("member of {}", def.name),
+            });
+            let r = self.regs.len() - 1;
+            for field in &fields {
+                self.place(r, field, true);
+            }
+        }
+    }
+
+    /// `UART_BASE` style macros, those of struct instances aside.
+    fn bases(&mut self) {
+        for (i, key) in self.candidates(&BASE) {
+            self.consumed.insert(i);
+            let m = &self.macros[i];
+            let Some(base) = m.value.filter(|_| !m.deref) else {
+                continue;
+            };
+            if self.blocks.iter().any(|b| b.name == key) {
+                continue;
+            }
+            let reason = format!("base address from {}", m.name);
+            let line = m.line;
+            self.blocks.push(Block {
+                name: key,
+                base,
+                size: 0,
+                line,
+                confidence: Confidence::High,
+                reason,
+            });
+        }
+    }
+
+    /// `FOO_CTRL_OFFSET` registers; `FOO_CTRL_EN_OFFSET` under a register is
+    /// left to `fields` as a bit position.
+    fn offsets(&mut self) {
+        let candidates = self.candidates(&OFFSET);
+        let keys: Vec<String> = candidates
+            .iter()
+            .chain(&self.candidates(&ADDRESS))
+            .map(|(_, k)| k.clone())
+            .collect();
+        for (i, key) in candidates {
+            let value = self.macros[i].value;
+            let nested = keys.iter().any(|k| {
+                key.strip_prefix(k.as_str())
+                    .is_some_and(|r| r.starts_with('_'))
+            });
+            if nested && value.is_some_and(|v| v < 64) {
+                continue;
+            }
+            self.consumed.insert(i);
+            let Some(offset) = value else {
+                let problem = format!(
+                    "Register {}: offset \"{}\" is not a number",
+                    key, self.macros[i].body
+                );
+                self.fallback("invalid-number", self.macros[i].line, problem, "skipped");
+                continue;
+            };
+            let m = &self.macros[i];
+            let (block, confidence, reason) = match self.block_for(&key) {
+                Some(b) => (b, Confidence::High, format!("offset from {}", m.name)),
+                None => {
+                    let prefix = key.split('_').next().unwrap_or(&key).to_string();
+                    let reason = format!("no base address macro, block named after {}_", prefix);
+                    let line = m.line;
+                    let name = m.name.clone();
+                    let b = self.block_named(&prefix, 0, line, reason);
+                    (
+                        b,
+                        Confidence::Medium,
+                        format!("offset from {}, block from its name", name),
+                    )
+                }
+            };
+            self.push_reg(block, &key, offset, i, (confidence, reason));
+        }
+    }
+
+    /// `FOO_CTRL_ADDR` and `(*(volatile uint32_t *)0x...)` registers, placed in
+    /// the block they are named after, else the closest base below them.
+    fn addresses(&mut self) {
+        let mut unplaced: Vec<(usize, String, u64)> = Vec::new();
+        let candidates: Vec<(usize, String)> = self
+            .macros
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| !self.consumed.contains(i) && m.cast.is_none())
+            .filter_map(|(i, m)| match split(&m.name, &ADDRESS) {
+                Some(key) => Some((i, key.to_string())),
+                None => m.deref.then(|| (i, m.name.clone())),
+            })
+            .collect();
+        for (i, key) in candidates {
+            let Some(address) = self.macros[i].value else {
+                continue;
+            };
+            self.consumed.insert(i);
+            let named = self
+                .block_for(&key)
+                .filter(|&b| self.blocks[b].base <= address);
+            let (block, confidence, reason) = match named {
+                Some(b) => (
+                    b,
+                    Confidence::High,
+                    format!("address from {}", self.macros[i].name),
+                ),
+                None => {
+                    let below = (0..self.blocks.len())
+                        .filter(|&b| self.blocks[b].base <= address)
+                        .max_by_key(|&b| self.blocks[b].base);
+                    match below {
+                        Some(b) => (
+                            b,
+                            Confidence::Medium,
+                            format!(
+                                "address from {}, in the closest block below it",
+                                self.macros[i].name
+                            ),
+                        ),
+                        None => {
+                            unplaced.push((i, key, address));
+                            continue;
+                        }
+                    }
+                }
+            };
+            let offset = address - self.blocks[block].base;
+            self.push_reg(block, &key, offset, i, (confidence, reason));
+        }
+
+        // no base at all: registers grouped by name prefix, from the lowest address
+        for (i, key, address) in &unplaced {
+            let prefix = key.split('_').next().unwrap_or(key).to_string();
+            let base = unplaced
+                .iter()
+                .filter(|(_, k, _)| k.split('_').next() == Some(prefix.as_str()))
+                .map(|(_, _, a)| *a)
+                .min()
+                .unwrap_or(*address);
+            let reason = format!(
+                "no base address macro, base is the lowest {}_ address",
+                prefix
+            );
+            let line = self.macros[*i].line;
+            let block = self.block_named(&prefix, base, line, reason);
+            self.blocks[block].confidence = Confidence::Low;
+            let reason = format!("address from {}", self.macros[*i].name);
+            let offset = address - self.blocks[block].base;
+            self.push_reg(block, key, offset, *i, (Confidence::Medium, reason));
+        }
+    }
+
+    fn resets(&mut self) {
+        for (i, key) in self.candidates(&RESET) {
+            let Some(value) = self.macros[i].value else {
+                continue;
+            };
+            let mut found = false;
+            for reg in self.regs.iter_mut().filter(|r| r.keys.contains(&key)) {
+                reg.reset = value;
+                found = true;
+            }
+            if found {
+                self.consumed.insert(i);
+            }
+        }
+    }
+
+    /// Groups position, mask and width macros by field and places the fields.
+    fn fields(&mut self) {
+        let mut order: Vec<String> = Vec::new();
+        let mut groups: HashMap<String, Parts> = HashMap::new();
+        for (suffixes, kind) in [
+            (&POSITION[..], 0),
+            (&OFFSET[..], 1),
+            (&MASK[..], 2),
+            (&WIDTH[..], 3),
+        ] {
+            for (i, key) in self.candidates(suffixes) {
+                self.consumed.insert(i);
+                let m = &self.macros[i];
+                let Some(value) = m.value else {
+                    let problem = format!("{}: \"{}\" is not a number", m.name, m.body);
+                    self.fallback("invalid-number", m.line, problem, "skipped");
+                    continue;
+                };
+                if !groups.contains_key(&key) {
+                    order.push(key.clone());
+                }
+                let parts = groups.entry(key).or_default();
+                if parts.line == 0 {
+                    parts.line = m.line;
+                }
+                match kind {
+                    0 | 1 => {
+                        parts.position = Some((value, i));
+                        parts.nested = kind == 1;
+                    }
+                    2 => {
+                        parts.mask = Some((value, i));
+                        parts.description = m.comment.clone().or(parts.description.take());
+                    }
+                    _ => parts.width = Some(value),
+                }
+                if parts.description.is_none() {
+                    parts.description = m.comment.clone();
+                }
+            }
+        }
+
+        order.sort_by_key(|key| groups[key].line);
+        for key in order {
+            let parts = &groups[&key];
+            let Some((prefix, regs)) = self.registers_for(&key) else {
+                self.diags.push(
+                    Diagnostic::warning(
+                        "missing-offset",
+                        format!("Field macros {}_* match no register, skipped", key),
+                    )
+                    .at(Some(parts.line), None),
+                );
+                continue;
+            };
+            let name = key[prefix.len() + 1..].to_string();
+            let what = format!("Field {}.{}", self.regs[regs[0]].name, name);
+            let (lsb, width, confidence, reason) = match (parts.position, parts.mask) {
+                (Some((position, _)), Some((mask, m))) => match span(mask) {
+                    Some((lsb, width)) if lsb == position => (
+                        lsb,
+                        width,
+                        Confidence::High,
+                        "position and mask".to_string(),
+                    ),
+                    Some((0, width)) => (
+                        position,
+                        width,
+                        Confidence::High,
+                        "position and unshifted mask".to_string(),
+                    ),
+                    _ => {
+                        let width = parts.width.unwrap_or(1);
+                        let problem = format!(
+                            "{}: {} 0x{:X} is not a contiguous mask at bit {}",
+                            what, self.macros[m].name, mask, position
+                        );
+                        self.fallback(
+                            "not-a-mask",
+                            parts.line,
+                            problem,
+                            &format!("using width {}", width),
+                        );
+                        (
+                            position,
+                            width,
+                            Confidence::Low,
+                            "mask does not match the position".to_string(),
+                        )
+                    }
+                },
+                (None, Some((mask, m))) => match span(mask) {
+                    Some((lsb, width)) => (lsb, width, Confidence::High, "mask".to_string()),
+                    None => {
+                        let problem = format!(
+                            "{}: {} 0x{:X} is not a contiguous mask",
+                            what, self.macros[m].name, mask
+                        );
+                        self.fallback("not-a-mask", parts.line, problem, "skipped");
+                        continue;
+                    }
+                },
+                (Some((position, _)), None) => match parts.width {
+                    Some(width) => (
+                        position,
+                        width,
+                        Confidence::High,
+                        "position and width".to_string(),
+                    ),
+                    None if parts.nested => (
+                        position,
+                        1,
+                        Confidence::Medium,
+                        "bit position from a nested _OFFSET macro, width assumed 1".to_string(),
+                    ),
+                    None => (
+                        position,
+                        1,
+                        Confidence::Medium,
+                        "position without mask or width, width assumed 1".to_string(),
+                    ),
+                },
+                (None, None) => continue,
+            };
+            let field = Field {
+                name,
+                key: key.clone(),
+                lsb,
+                width,
+                description: parts.description.clone(),
+                enums: Vec::new(),
+                line: parts.line,
+                confidence,
+                reason,
+            };
+            for r in regs {
+                self.place(r, &field, true);
+            }
+        }
+    }
+
+    /// Adds `field` to register `r` unless it does not fit; `report` tells
+    /// whether that is worth a diagnostic.
+    fn place(&mut self, r: usize, field: &Field, report: bool) -> bool {
+        let reg = &mut self.regs[r];
+        let what = format!("Field {}.{}", reg.name, field.name);
+        match reg.taken.take(&what, field.lsb, field.width) {
+            Ok(()) => {
+                reg.fields.push(field.clone());
+                true
+            }
+            Err(diag) => {
+                if report {
+                    self.fallback(&diag.code, field.line, diag.message, "skipped");
+                }
+                false
+            }
+        }
+    }
+
+    /// Macros named after a field (`FOO_CTRL_MODE_FAST`) become its enumerated
+    /// values; contiguous masks named after a register without field macros
+    /// (`FOO_CTRL_EN 0x1`) become low-confidence fields.
+    fn bare_macros(&mut self) {
+        let without_fields: Vec<bool> = self.regs.iter().map(|r| r.fields.is_empty()).collect();
+        for i in 0..self.macros.len() {
+            let m = &self.macros[i];
+            let Some(value) = m
+                .value
+                .filter(|_| !self.consumed.contains(&i) && m.cast.is_none() && !m.deref)
+            else {
+                continue;
+            };
+            let name = m.name.clone();
+
+            // the longest field key prefixing the name, or equal to it
+            let key = self
+                .regs
+                .iter()
+                .flat_map(|r| r.fields.iter())
+                .map(|f| &f.key)
+                .filter(|k| {
+                    name == **k
+                        || name
+                            .strip_prefix(k.as_str())
+                            .is_some_and(|rest| rest.len() > 1 && rest.starts_with('_'))
+                })
+                .max_by_key(|k| k.len())
+                .cloned();
+            if let Some(key) = key {
+                self.consumed.insert(i);
+                // `UART_CR_EN` repeats the mask, `UART_CR_MODE_0` names one of its bits
+                let Some(label) = name[key.len()..]
+                    .strip_prefix('_')
+                    .filter(|s| !s.chars().all(|c| c.is_ascii_digit()))
+                else {
+                    continue;
+                };
+                let label = label.to_string();
+                let comment = self.macros[i].comment.clone();
+                for reg in self.regs.iter_mut() {
+                    for fd in reg.fields.iter_mut().filter(|fd| fd.key == key) {
+                        let mask = (1u64 << fd.width.min(63)) - 1;
+                        let value = if value <= mask {
+                            value
+                        } else if value & !(mask << fd.lsb) == 0 {
+                            value >> fd.lsb
+                        } else {
+                            continue;
+                        };
+                        fd.enums.push(ImportEnumeratedValue {
+                            name: label.clone(),
+                            value: value.to_string(),
+                            description: comment.clone(),
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let Some((prefix, regs)) = self.registers_for(&name) else {
+                continue;
+            };
+            let label = &name[prefix.len() + 1..];
+            let Some((lsb, width)) = span(value) else {
+                continue;
+            };
+            if [
+                &POSITION[..],
+                &MASK,
+                &WIDTH,
+                &OFFSET,
+                &RESET,
+                &ADDRESS,
+                &BASE,
+            ]
+            .iter()
+            .any(|s| split(label, s).is_some())
+            {
+                continue;
+            }
+            let field = Field {
+                name: label.to_string(),
+                key: name.clone(),
+                lsb,
+                width,
+                description: self.macros[i].comment.clone(),
+                enums: Vec::new(),
+                line: self.macros[i].line,
+                confidence: Confidence::Low,
+                reason: format!("bare mask {} named after the register", name),
+            };
+            let mut placed = false;
+            for r in regs.into_iter().filter(|&r| without_fields[r]) {
+                placed |= self.place(r, &field, false);
+            }
+            if placed {
+                self.consumed.insert(i);
+            }
+        }
+    }
+
+    /// Project name from the include guard (`FOO_REGS_H` gives `FOO_REGS`).
+    fn guard(&self) -> Option<String> {
+        self.macros.iter().find_map(|m| {
+            if !m.body.is_empty() {
+                return None;
+            }
+            let name = m.name.trim_matches('_');
+            ["_H", "_HPP", "_INCLUDED", "_H_INCLUDED"]
+                .iter()
+                .rev()
+                .find_map(|s| name.strip_suffix(s))
+                .filter(|n| !n.is_empty())
+                .map(str::to_string)
+        })
+    }
+
+    fn item(&mut self, path: String, confidence: Confidence, reason: String, line: usize) {
+        if confidence == Confidence::Low {
+            self.diags.push(
+                Diagnostic::info("low-confidence", format!("{}: {}", path, reason))
+                    .at(Some(line), None),
+            );
+        }
+        self.report.items.push(ConfidenceItem {
+            path,
+            confidence,
+            reason,
+            line: Some(line),
+        });
+    }
+
+    fn finish(mut self) -> Option<ImportData> {
+        if self.regs.is_empty() {
+            self.diags.push(Diagnostic::error(
+                "no-registers",
+                "No registers found: expected _OFFSET or _ADDR macros, or struct instances",
+            ));
+            return None;
+        }
+
+        let mut regs = std::mem::take(&mut self.regs);
+        for reg in regs.iter_mut().filter(|r| r.fields.is_empty()) {
+            // a register without fields is one field spanning it
+            reg.fields.push(Field {
+                name: reg.name.clone(),
+                key: String::new(),
+                lsb: 0,
+                width: reg.bits,
+                description: None,
+                enums: Vec::new(),
+                line: reg.line,
+                confidence: Confidence::Medium,
+                reason: "no field macros, one field spans the register".to_string(),
+            });
+        }
+
+        let mut address_blocks = Vec::new();
+        let blocks = std::mem::take(&mut self.blocks);
+        for (b, block) in blocks.iter().enumerate() {
+            let mut inside: Vec<&mut Reg> = regs.iter_mut().filter(|r| r.block == b).collect();
+            if inside.is_empty() {
+                continue;
+            }
+            self.item(
+                block.name.clone(),
+                block.confidence,
+                block.reason.clone(),
+                block.line,
+            );
+            inside.sort_by_key(|r| r.offset);
+            let mut registers = Vec::new();
+            let mut range = block.size;
+            for reg in inside {
+                range = range.max(reg.offset + reg.bits / 8);
+                let path = format!("{}.{}", block.name, reg.name);
+                self.item(path.clone(), reg.confidence, reg.reason.clone(), reg.line);
+                reg.fields.sort_by_key(|f| f.lsb);
+                let mut fields = Vec::new();
+                for f in &reg.fields {
+                    self.item(
+                        format!("{}.{}", path, f.name),
+                        f.confidence,
+                        f.reason.clone(),
+                        f.line,
+                    );
+                    let mask = u64::MAX >> (64 - f.width.clamp(1, 64));
+                    fields.push(ImportField {
+                        name: f.name.clone(),
+                        description: f.description.clone(),
+                        bit_offset: f.lsb as u32,
+                        bit_width: f.width as u32,
+                        access: reg.access.to_string(),
+                        modified_write_value: None,
+                        read_action: None,
+                        reset_value: hex(reg.reset.checked_shr(f.lsb as u32).unwrap_or(0) & mask),
+                        enumerated_values: f.enums.clone(),
+                    });
+                }
+                registers.push(ImportRegister {
+                    name: reg.name.clone(),
+                    address_offset: hex(reg.offset),
+                    size: reg.bits as u32,
+                    description: reg.description.clone(),
+                    fields,
+                });
+            }
+            address_blocks.push(ImportAddressBlock {
+                name: block.name.clone(),
+                base_address: hex(block.base),
+                range: hex(range),
+                width: 32,
+                registers,
+            });
+        }
+
+        let count = |c| self.report.count(c);
+        let summary = format!(
+            "{} high, {} medium and {} low confidence items",
+            count(Confidence::High),
+            count(Confidence::Medium),
+            count(Confidence::Low)
+        );
+        self.diags.push(Diagnostic::info("confidence", summary));
+
+        let name = self
+            .guard()
+            .unwrap_or_else(|| address_blocks[0].name.clone());
+        Some(ImportData {
+            project: ImportProject {
+                name: name.clone(),
+                description: None,
+                vendor: String::new(),
+                library: String::new(),
+                version: "0.1.0".to_string(),
+            },
+            memory_maps: vec![ImportMemoryMap {
+                name,
+                address_blocks,
+            }],
+        })
+    }
+}
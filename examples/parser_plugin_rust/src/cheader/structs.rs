@@ -0,0 +1,552 @@
+//! Register layouts described by C structs (CMSIS `UART_TypeDef` and alike).
+
+use std::collections::HashMap;
+
+use crate::expr::eval;
+
+/// A bit-field of a register typedef (`uint32_t EN : 1;`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitField {
+    pub name: String,
+    pub lsb: u64,
+    pub width: u64,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    /// Plain integer, a register without known fields
+    Scalar,
+    /// Register split into bit-fields
+    Fields(Vec<BitField>),
+    /// Registers at byte offsets
+    Registers(Vec<Member>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    pub size: u64,
+    pub align: u64,
+    pub layout: Layout,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub offset: u64,
+    pub ty: Type,
+    /// Array length
+    pub count: Option<u64>,
+    /// From the CMSIS qualifiers `__I`, `__O`, `__IO` or `const`
+    pub access: Option<&'static str>,
+    pub line: usize,
+}
+
+/// Something in a struct the layout had to guess: the problem and what was
+/// assumed instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Guess {
+    pub line: usize,
+    pub problem: String,
+    pub default: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Number(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    line: usize,
+}
+
+fn tokenize(src: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = src.chars().collect();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c == '#' {
+            // preprocessor lines, with continuations
+            while i < chars.len() && chars[i] != '\n' {
+                if chars[i] == '\\' && chars.get(i + 1) == Some(&'\n') {
+                    line += 1;
+                    i += 1;
+                }
+                i += 1;
+            }
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' || c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let tok = if c.is_ascii_digit() {
+                Tok::Number(text.trim_end_matches(['u', 'U', 'l', 'L']).to_string())
+            } else {
+                Tok::Ident(text)
+            };
+            tokens.push(Token { tok, line });
+        } else {
+            tokens.push(Token {
+                tok: Tok::Punct(c),
+                line,
+            });
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// A struct or typedef'd struct found at the top level.
+#[derive(Debug, Clone)]
+pub struct Definition {
+    /// Typedef name, or the struct tag
+    pub name: String,
+    pub ty: Type,
+    pub line: usize,
+}
+
+const SIZES: [(&str, u64); 22] = [
+    ("uint8_t", 1),
+    ("int8_t", 1),
+    ("u8", 1),
+    ("char", 1),
+    ("uint16_t", 2),
+    ("int16_t", 2),
+    ("u16", 2),
+    ("short", 2),
+    ("uint32_t", 4),
+    ("int32_t", 4),
+    ("u32", 4),
+    ("int", 4),
+    ("unsigned", 4),
+    ("signed", 4),
+    ("long", 4),
+    ("uint64_t", 8),
+    ("int64_t", 8),
+    ("u64", 8),
+    ("__u8", 1),
+    ("__u16", 2),
+    ("__u32", 4),
+    ("__u64", 8),
+];
+
+const QUALIFIERS: [(&str, Option<&str>); 9] = [
+    ("__I", Some("read-only")),
+    ("__IM", Some("read-only")),
+    ("__O", Some("write-only")),
+    ("__OM", Some("write-only")),
+    ("__IO", Some("read-write")),
+    ("__IOM", Some("read-write")),
+    ("const", Some("read-only")),
+    ("volatile", None),
+    ("static", None),
+];
+
+/// Parses the structs and unions of comment-free source. `macros` resolves
+/// array lengths such as `uint32_t FIFO[UART_FIFO_DEPTH]`.
+pub fn parse(
+    src: &str,
+    macros: &HashMap<String, u64>,
+    guesses: &mut Vec<Guess>,
+) -> Vec<Definition> {
+    let tokens = tokenize(src);
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        macros,
+        types: HashMap::new(),
+        guesses,
+    };
+    let mut defs = Vec::new();
+    while parser.pos < tokens.len() {
+        let typedef = parser.eat_ident("typedef");
+        if let Some(kind @ ("struct" | "union")) = parser.peek_ident() {
+            let union = kind == "union";
+            let line = tokens[parser.pos].line;
+            parser.pos += 1;
+            let tag = parser.ident();
+            if parser.eat('{') {
+                let ty = parser.body(union);
+                let name = if typedef { parser.ident().or(tag) } else { tag };
+                if let Some(name) = name {
+                    parser.types.insert(name.clone(), ty.clone());
+                    defs.push(Definition { name, ty, line });
+                }
+                continue;
+            }
+        }
+        parser.pos += 1;
+    }
+    defs
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    macros: &'a HashMap<String, u64>,
+    types: HashMap<String, Type>,
+    guesses: &'a mut Vec<Guess>,
+}
+
+impl Parser<'_> {
+    fn peek_ident(&self) -> Option<&str> {
+        match &self.tokens.get(self.pos)?.tok {
+            Tok::Ident(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn eat_ident(&mut self, word: &str) -> bool {
+        let found = self.peek_ident() == Some(word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        let name = self.peek_ident()?.to_string();
+        self.pos += 1;
+        Some(name)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|t| t.tok == Tok::Punct(c));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(0, |t| t.line)
+    }
+
+    fn guess(&mut self, line: usize, problem: String, default: &str) {
+        self.guesses.push(Guess {
+            line,
+            problem,
+            default: default.to_string(),
+        });
+    }
+
+    /// Members up to the closing brace, laid out with natural alignment.
+    fn body(&mut self, union: bool) -> Type {
+        let mut members: Vec<Member> = Vec::new();
+        let mut bits: Vec<BitField> = Vec::new();
+        let mut offset = 0u64;
+        let mut size = 0;
+        let mut align = 1;
+        // open bit-field storage unit: byte offset, size, next free bit
+        let mut unit: Option<(u64, u64, u64)> = None;
+
+        while self.pos < self.tokens.len() && !self.eat('}') {
+            let line = self.line();
+            let mut access = None;
+            let mut words = Vec::new();
+            let mut nested = None;
+            // type: qualifiers, type words, or a nested struct/union
+            loop {
+                match self.peek_ident() {
+                    Some(kind @ ("struct" | "union")) => {
+                        let is_union = kind == "union";
+                        self.pos += 1;
+                        let tag = self.ident();
+                        if self.eat('{') {
+                            nested = Some(self.body(is_union));
+                        } else if let Some(tag) = tag {
+                            words.push(tag);
+                        }
+                    }
+                    Some(word) => {
+                        if let Some((_, qualifier)) = QUALIFIERS.iter().find(|(q, _)| *q == word) {
+                            access = access.or(*qualifier);
+                            self.pos += 1;
+                        } else if nested.is_none()
+                            && (words.is_empty()
+                                || self.tokens.get(self.pos + 1).is_some_and(|t| {
+                                    matches!(t.tok, Tok::Ident(_)) || t.tok == Tok::Punct('*')
+                                }))
+                        {
+                            words.push(word.to_string());
+                            self.pos += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+            let ty = nested.unwrap_or_else(|| self.scalar(&words, line));
+
+            // declarators: name[N] : bits, ...
+            let mut declared = false;
+            loop {
+                let pointer = self.eat('*');
+                // `uint32_t : 3;` pads a bit-field unit
+                let name = match self.ident() {
+                    Some(name) => name,
+                    None if self.tokens.get(self.pos).map(|t| &t.tok) == Some(&Tok::Punct(':')) => {
+                        String::new()
+                    }
+                    None => break,
+                };
+                declared = true;
+                let mut count = None;
+                while self.eat('[') {
+                    let mut expr = String::new();
+                    while self.pos < self.tokens.len() && !self.eat(']') {
+                        match &self.tokens[self.pos].tok {
+                            Tok::Ident(s) | Tok::Number(s) => expr.push_str(s),
+                            Tok::Punct(c) => expr.push(*c),
+                        }
+                        expr.push(' ');
+                        self.pos += 1;
+                    }
+                    let n = eval(&expr, &|id| self.macros.get(id).copied()).unwrap_or_else(|| {
+                        self.guess(
+                            line,
+                            format!("Array {}[{}] has an unknown length", name, expr.trim()),
+                            "using 1",
+                        );
+                        1
+                    });
+                    count = Some(count.unwrap_or(1) * n);
+                }
+                let ty = if pointer {
+                    self.guess(line, format!("{} is a pointer", name), "assumed 4 bytes");
+                    Type {
+                        size: 4,
+                        align: 4,
+                        layout: Layout::Scalar,
+                    }
+                } else {
+                    ty.clone()
+                };
+
+                if self.eat(':') {
+                    let width = match self.tokens.get(self.pos).map(|t| &t.tok) {
+                        Some(Tok::Number(n)) | Some(Tok::Ident(n)) => {
+                            let n = n.clone();
+                            eval(&n, &|id| self.macros.get(id).copied())
+                        }
+                        _ => None,
+                    }
+                    .unwrap_or(1);
+                    self.pos += 1;
+                    let unit_bits = ty.size * 8;
+                    let (start, unit_size, next) = match unit {
+                        Some((start, unit_size, next)) if next + width <= unit_size * 8 => {
+                            (start, unit_size, next)
+                        }
+                        _ => {
+                            let start = offset.div_ceil(ty.align) * ty.align;
+                            offset = start + ty.size;
+                            (start, ty.size, 0)
+                        }
+                    };
+                    if !name.is_empty() && width > 0 && next + width <= unit_bits.max(unit_size * 8)
+                    {
+                        bits.push(BitField {
+                            name,
+                            lsb: start * 8 + next,
+                            width,
+                            line,
+                        });
+                    }
+                    unit = Some((start, unit_size, next + width));
+                    align = align.max(ty.align);
+                    size = size.max(offset);
+                } else {
+                    unit = None;
+                    let total = ty.size * count.unwrap_or(1);
+                    let at = if union {
+                        0
+                    } else {
+                        offset.div_ceil(ty.align) * ty.align
+                    };
+                    offset = at + total;
+                    align = align.max(ty.align);
+                    size = size.max(offset);
+                    members.push(Member {
+                        name,
+                        offset: at,
+                        ty,
+                        count,
+                        access,
+                        line,
+                    });
+                }
+                if union {
+                    offset = 0;
+                }
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            if !declared {
+                // anonymous struct/union: its members are ours
+                if let Layout::Registers(inner) = ty.layout {
+                    let at = if union {
+                        0
+                    } else {
+                        offset.div_ceil(ty.align) * ty.align
+                    };
+                    members.extend(inner.into_iter().map(|m| Member {
+                        offset: at + m.offset,
+                        ..m
+                    }));
+                    if !union {
+                        offset = at + ty.size;
+                    }
+                    size = size.max(at + ty.size);
+                } else if let Layout::Fields(inner) = ty.layout {
+                    bits.extend(inner);
+                    size = size.max(ty.size);
+                }
+            }
+            // skip to the end of the member
+            while self.pos < self.tokens.len() && !self.eat(';') {
+                if self.tokens[self.pos].tok == Tok::Punct('}') {
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+
+        let size = size.div_ceil(align) * align;
+        let layout = if !bits.is_empty() {
+            // a register: its bit-fields, whatever integer views of it a union holds
+            Layout::Fields(bits)
+        } else if union {
+            // views of one location: the bit-field one if any, else the first
+            if let Some(view) = members
+                .iter()
+                .position(|m| matches!(m.ty.layout, Layout::Fields(_)))
+            {
+                members.swap_remove(view).ty.layout
+            } else if members.len() == 1 {
+                members.swap_remove(0).ty.layout
+            } else if members.iter().all(|m| m.ty.layout == Layout::Scalar) {
+                Layout::Scalar
+            } else {
+                let first = members.swap_remove(0);
+                self.guess(
+                    first.line,
+                    format!("Union member {} overlays other members", first.name),
+                    &format!("only {} is kept", first.name),
+                );
+                Layout::Registers(vec![first])
+            }
+        } else {
+            Layout::Registers(members)
+        };
+        Type {
+            size,
+            align,
+            layout,
+        }
+    }
+
+    /// Size of an integer type or a known typedef.
+    fn scalar(&mut self, words: &[String], line: usize) -> Type {
+        if let Some(ty) = words.iter().find_map(|w| self.types.get(w)) {
+            return ty.clone();
+        }
+        let longs = words.iter().filter(|w| *w == "long").count();
+        let size = if longs >= 2 {
+            Some(8)
+        } else {
+            // `unsigned char` is a char, `unsigned` alone an int
+            words
+                .iter()
+                .rev()
+                .find_map(|w| SIZES.iter().find(|(t, _)| t == w).map(|(_, s)| *s))
+        };
+        let size = size.unwrap_or_else(|| {
+            self.guess(
+                line,
+                format!("Unknown type \"{}\"", words.join(" ")),
+                "assumed 4 bytes",
+            );
+            4
+        });
+        Type {
+            size,
+            align: size,
+            layout: Layout::Scalar,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_cmsis_structs() {
+        let src = "typedef union {\n  struct {\n    uint32_t EN : 1;\n    uint32_t : 3;\n    uint32_t MODE : 2;\n  } b;\n  uint32_t w;\n} CTRL_Type;\n\
+                   typedef struct {\n  __IO CTRL_Type CTRL;\n  __I  uint32_t SR;\n  uint16_t RESERVED0[2];\n  __O  uint8_t DR[N];\n  struct { __IO uint32_t SRC, DST; } CH[2];\n} DMA_TypeDef;\n";
+        let macros = HashMap::from([("N".to_string(), 4)]);
+        let mut guesses = Vec::new();
+        let defs = parse(src, &macros, &mut guesses);
+
+        assert!(guesses.is_empty(), "{:?}", guesses);
+        assert_eq!(defs[0].name, "CTRL_Type");
+        assert_eq!(
+            defs[0].ty.layout,
+            Layout::Fields(vec![
+                BitField {
+                    name: "EN".into(),
+                    lsb: 0,
+                    width: 1,
+                    line: 3
+                },
+                BitField {
+                    name: "MODE".into(),
+                    lsb: 4,
+                    width: 2,
+                    line: 5
+                },
+            ])
+        );
+
+        let Layout::Registers(members) = &defs[1].ty.layout else {
+            panic!("registers expected");
+        };
+        let layout: Vec<_> = members
+            .iter()
+            .map(|m| (m.name.as_str(), m.offset, m.count, m.access))
+            .collect();
+        assert_eq!(
+            layout,
+            [
+                ("CTRL", 0, None, Some("read-write")),
+                ("SR", 4, None, Some("read-only")),
+                ("RESERVED0", 8, Some(2), None),
+                ("DR", 12, Some(4), Some("write-only")),
+                ("CH", 16, Some(2), None),
+            ]
+        );
+        assert_eq!(defs[1].ty.size, 32);
+        assert_eq!((defs[1].line, members[3].line), (9, 13));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+
+use polars::prelude::*;
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::options::ParseOptions;
+
+/// What a sheet describes, which decides the logical columns its headers can map to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SheetRole {
+    Version,
+    AddressMap,
+    Block,
+}
+
+impl SheetRole {
+    /// Logical columns of the role, as used by `parse_register` and `schema`.
+    pub fn columns(self) -> &'static [&'static str] {
+        match self {
+            SheetRole::Version => &["VENDOR", "LIBRARY", "NAME", "VERSION"],
+            SheetRole::AddressMap => &["BLOCK", "INSTANCE", "OFFSET", "RANGE", "WIDTH"],
+            SheetRole::Block => &[
+                "ADDR",
+                "REG",
+                "FIELD",
+                "BIT",
+                "WIDTH",
+                "ATTRIBUTE",
+                "DEFAULT",
+                "DESCRIPTION",
+                "ENUM",
+                "REG_DESCRIPTION",
+            ],
+        }
+    }
+
+    /// Sheet names tried when no alias is configured (block sheets are named by `BLOCK`).
+    pub fn sheet_names(self) -> &'static [&'static str] {
+        match self {
+            SheetRole::Version => &["version"],
+            SheetRole::AddressMap => &["address_map", "address map"],
+            SheetRole::Block => &[],
+        }
+    }
+}
+
+/// Header spellings understood without any configuration, on top of the logical names.
+const BUILTIN_ALIASES: &[(&str, &[&str])] = &[
+    ("ADDR", &["address", "address_offset", "offset"]),
+    ("REG", &["register", "register_name", "reg_name"]),
+    ("FIELD", &["field_name"]),
+    ("BIT", &["bits", "bit_offset", "bit_range"]),
+    ("ATTRIBUTE", &["access", "attr"]),
+    ("DEFAULT", &["reset", "reset_value"]),
+    ("DESCRIPTION", &["desc"]),
+    ("BLOCK", &["block_name"]),
+    ("OFFSET", &["base_address"]),
+];
+
+/// Headers are compared ignoring case, surrounding spaces and `_`/`-`/space separators,
+/// so `Register Name`, `register_name` and `REGISTER-NAME` are the same header.
+fn normalize_header(header: &str) -> String {
+    header
+        .trim()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Resolves workbook headers and sheet names to the logical names the parser works with.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMap {
+    // normalized header -> logical columns it may stand for
+    headers: HashMap<String, Vec<&'static str>>,
+    sheets: HashMap<SheetRole, Vec<String>>,
+}
+
+impl ColumnMap {
+    /// Builds the map from the built-in aliases and the ones configured in `opts`.
+    ///
+    /// Aliases given for an unknown logical column are reported and ignored.
+    pub fn new(opts: &ParseOptions, diags: &mut Vec<Diagnostic>) -> Self {
+        let mut map = Self::default();
+
+        let roles = [SheetRole::Version, SheetRole::AddressMap, SheetRole::Block];
+        for logical in roles.iter().flat_map(|role| role.columns()) {
+            map.add(logical, logical);
+        }
+        for (logical, aliases) in BUILTIN_ALIASES {
+            aliases.iter().for_each(|alias| map.add(logical, alias));
+        }
+
+        let mut configured: Vec<_> = opts.columns.iter().collect();
+        configured.sort();
+        for (name, aliases) in configured {
+            let logical = roles
+                .iter()
+                .flat_map(|role| role.columns())
+                .find(|logical| logical.eq_ignore_ascii_case(name.trim()));
+            match logical {
+                Some(logical) => aliases.iter().for_each(|alias| map.add(logical, alias)),
+                None => diags.push(Diagnostic::warning(
+                    "unknown-column-mapping",
+                    format!("Aliases given for unknown column \"{}\" were ignored", name),
+                )),
+            }
+        }
+
+        for role in roles {
+            let configured = match role {
+                SheetRole::Version => opts.sheets.version.as_slice(),
+                SheetRole::AddressMap => opts.sheets.address_map.as_slice(),
+                SheetRole::Block => &[],
+            };
+            let names = configured
+                .iter()
+                .cloned()
+                .chain(role.sheet_names().iter().map(|s| s.to_string()))
+                .collect();
+            map.sheets.insert(role, names);
+        }
+
+        map
+    }
+
+    fn add(&mut self, logical: &'static str, alias: &str) {
+        let logicals = self.headers.entry(normalize_header(alias)).or_default();
+        if !logicals.contains(&logical) {
+            logicals.push(logical);
+        }
+    }
+
+    /// Sheet names to try, in order, for a sheet of `role`.
+    pub fn sheet_names(&self, role: SheetRole) -> &[String] {
+        self.sheets.get(&role).map_or(&[], Vec::as_slice)
+    }
+
+    /// Logical column a header of a `role` sheet stands for.
+    pub fn resolve(&self, role: SheetRole, header: &str) -> Option<&'static str> {
+        self.headers
+            .get(&normalize_header(header))?
+            .iter()
+            .copied()
+            .find(|logical| role.columns().contains(logical))
+    }
+
+    /// Number of distinct logical columns of `role` found among `cells`.
+    pub fn header_score<S: AsRef<str>>(&self, role: SheetRole, cells: &[S]) -> usize {
+        cells
+            .iter()
+            .filter_map(|cell| self.resolve(role, cell.as_ref()))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Renames the columns of `df` to their logical names.
+    ///
+    /// A logical column is taken by the first header standing for it; later
+    /// ones (e.g. both `REG` and `Register` present) keep their own names.
+    pub fn apply(&self, role: SheetRole, df: &mut DataFrame) -> Result<(), Error> {
+        let headers: Vec<String> = df
+            .get_column_names()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let mut taken: HashSet<&str> = headers
+            .iter()
+            .filter_map(|header| role.columns().iter().find(|l| **l == header.as_str()))
+            .copied()
+            .collect();
+
+        for header in &headers {
+            let Some(logical) = self.resolve(role, header) else {
+                continue;
+            };
+            if header != logical && taken.insert(logical) {
+                df.rename(header, logical.into())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_headers_per_sheet_role() {
+        let mut opts = ParseOptions::default();
+        opts.columns
+            .insert("reg".into(), vec!["寄存器".into(), "Register Name".into()]);
+        opts.columns.insert("FIELD".into(), vec!["位域".into()]);
+        let map = ColumnMap::new(&opts, &mut vec![]);
+
+        assert_eq!(map.resolve(SheetRole::Block, "寄存器"), Some("REG"));
+        assert_eq!(map.resolve(SheetRole::Block, " 位域 "), Some("FIELD"));
+        assert_eq!(map.resolve(SheetRole::Block, "REGISTER_NAME"), Some("REG"));
+        assert_eq!(
+            map.resolve(SheetRole::Block, "Reset Value"),
+            Some("DEFAULT")
+        );
+        // "Offset" is the block base in address_map but the register address in a block sheet
+        assert_eq!(map.resolve(SheetRole::AddressMap, "Offset"), Some("OFFSET"));
+        assert_eq!(map.resolve(SheetRole::Block, "Offset"), Some("ADDR"));
+        assert_eq!(map.resolve(SheetRole::Version, "寄存器"), None);
+    }
+
+    #[test]
+    fn reports_aliases_of_unknown_columns() {
+        let mut opts = ParseOptions::default();
+        opts.columns.insert("COLOR".into(), vec!["颜色".into()]);
+        let mut diags = vec![];
+        ColumnMap::new(&opts, &mut diags);
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].code, "unknown-column-mapping");
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::{Cursor, Read};
+
+use calamine::{Data, Range};
+use zip::ZipArchive;
+
+use crate::error::Error;
+use crate::text::decode;
+
+/// CSV files of a zip bundle, one sheet per file named after it (`uart.csv` -> `uart`).
+pub struct CsvBundle {
+    sheets: Vec<(String, Range<Data>)>,
+}
+
+impl CsvBundle {
+    /// Reads every `.csv` entry of the zip in `data`, in archive order.
+    pub fn from_zip(data: &[u8]) -> Result<Self, Error> {
+        let mut zip = ZipArchive::new(Cursor::new(data)).map_err(anyhow::Error::from)?;
+        let mut sheets = Vec::new();
+
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i).map_err(anyhow::Error::from)?;
+            let Some(name) = csv_sheet_name(file.name()) else {
+                continue;
+            };
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            sheets.push((name, csv_to_range(&bytes)?));
+        }
+
+        Ok(Self { sheets })
+    }
+
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheets.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    pub fn worksheet_range(&self, name: &str) -> Option<Range<Data>> {
+        self.sheets
+            .iter()
+            .find(|(sheet, _)| sheet == name)
+            .map(|(_, range)| range.clone())
+    }
+}
+
+/// Sheet name of a zip entry, `None` for directories, macOS metadata and non-CSV files.
+fn csv_sheet_name(path: &str) -> Option<String> {
+    let file = path.rsplit(['/', '\\']).next()?;
+    let stem = file
+        .strip_suffix(".csv")
+        .or_else(|| file.strip_suffix(".CSV"))?;
+    if path.starts_with("__MACOSX") || file.starts_with('.') || stem.is_empty() {
+        return None;
+    }
+    Some(stem.to_string())
+}
+
+/// Occurrences of `delimiter` in `line`, outside of double quotes.
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut quoted = false;
+    line.bytes()
+        .filter(|&b| {
+            if b == b'"' {
+                quoted = !quoted;
+            }
+            !quoted && b == delimiter
+        })
+        .count()
+}
+
+/// Picks the delimiter splitting the first lines into the same, largest number of cells.
+pub fn detect_delimiter(text: &str) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(10)
+        .collect();
+
+    // reversed so that ties go to the earlier candidate, `max_by_key` keeping the last one
+    [b',', b';', b'\t', b'|']
+        .into_iter()
+        .rev()
+        .max_by_key(|&delimiter| {
+            let counts: Vec<usize> = lines
+                .iter()
+                .map(|line| count_unquoted(line, delimiter))
+                .collect();
+            let first = counts.first().copied().unwrap_or(0);
+            let consistent = counts.iter().filter(|&&c| c == first).count();
+            (first > 0, consistent, first)
+        })
+        .unwrap_or(b',')
+}
+
+/// Parses CSV bytes into a range of string cells starting at `A1`, so that it goes
+/// through the same header detection and DataFrame conversion as a worksheet.
+pub fn csv_to_range(bytes: &[u8]) -> Result<Range<Data>, Error> {
+    let (text, _) = decode(bytes);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(&text))
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(anyhow::Error::from)?;
+        rows.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if rows.is_empty() || width == 0 {
+        return Ok(Range::empty());
+    }
+
+    let mut range = Range::new((0, 0), (rows.len() as u32 - 1, width as u32 - 1));
+    for (r, row) in rows.into_iter().enumerate() {
+        for (c, cell) in row.into_iter().enumerate() {
+            if !cell.trim().is_empty() {
+                range.set_value((r as u32, c as u32), Data::String(cell));
+            }
+        }
+    }
+
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_delimiter() {
+        assert_eq!(detect_delimiter("A,B,C\n1,2,3\n"), b',');
+        assert_eq!(detect_delimiter("A;B;C\n\"1,5\";2;3\n"), b';');
+        assert_eq!(detect_delimiter("A\tB\n1\t2\n"), b'\t');
+    }
+
+    #[test]
+    fn names_sheets_after_csv_files() {
+        assert_eq!(csv_sheet_name("spec/uart.csv").as_deref(), Some("uart"));
+        assert_eq!(csv_sheet_name("version.CSV").as_deref(), Some("version"));
+        assert_eq!(csv_sheet_name("__MACOSX/._uart.csv"), None);
+        assert_eq!(csv_sheet_name("README.md"), None);
+    }
+}
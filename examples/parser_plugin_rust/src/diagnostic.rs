@@ -0,0 +1,162 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A problem found while importing, located as precisely as the source allows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable machine readable identifier, e.g. `missing-column`
+    pub code: String,
+    pub message: String,
+    pub sheet: Option<String>,
+    /// 1-based row number as shown by the spreadsheet application,
+    /// or line number for text sources (XML, SystemRDL, ...)
+    pub row: Option<usize>,
+    /// Column letter, e.g. `C` or `AB`
+    pub column: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code: code.to_string(),
+            message: message.into(),
+            sheet: None,
+            row: None,
+            column: None,
+        }
+    }
+
+    pub fn error(code: &str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    pub fn warning(code: &str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, message)
+    }
+
+    pub fn info(code: &str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Info, code, message)
+    }
+
+    /// A value that cannot be used as written: a warning naming the `default` used
+    /// instead, or an error in strict mode.
+    pub fn fallback(strict: bool, code: &str, problem: String, default: &str) -> Self {
+        if strict {
+            Self::error(code, problem)
+        } else {
+            Self::warning(code, format!("{}, {}", problem, default))
+        }
+    }
+
+    pub fn in_sheet(mut self, sheet: &str) -> Self {
+        self.sheet = Some(sheet.to_string());
+        self
+    }
+
+    pub fn at(mut self, row: Option<usize>, column: Option<String>) -> Self {
+        self.row = row;
+        self.column = column;
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+        write!(f, "{}[{}]", severity, self.code)?;
+
+        if let Some(sheet) = &self.sheet {
+            write!(f, " {}", sheet)?;
+            match (&self.column, self.row) {
+                (Some(column), Some(row)) => write!(f, "!{}{}", column, row)?,
+                (None, Some(row)) => write!(f, " row {}", row)?,
+                (Some(column), None) => write!(f, " column {}", column)?,
+                (None, None) => {}
+            }
+        } else if let Some(line) = self.row {
+            write!(f, " line {}", line)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Converts a 0-based column index into its spreadsheet letter (0 -> `A`, 27 -> `AB`).
+pub fn column_letter(index: usize) -> String {
+    let mut n = index + 1;
+    let mut letters = Vec::new();
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().map(|&b| b as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_letter_matches_spreadsheet_naming() {
+        assert_eq!(column_letter(0), "A");
+        assert_eq!(column_letter(25), "Z");
+        assert_eq!(column_letter(26), "AA");
+        assert_eq!(column_letter(27), "AB");
+        assert_eq!(column_letter(701), "ZZ");
+        assert_eq!(column_letter(702), "AAA");
+    }
+
+    #[test]
+    fn fallback_is_an_error_only_in_strict_mode() {
+        let problem = || "Field R.A: unknown access \"rwx\"".to_string();
+        assert_eq!(
+            Diagnostic::fallback(false, "unknown-access", problem(), "using read-write")
+                .to_string(),
+            "warning[unknown-access]: Field R.A: unknown access \"rwx\", using read-write"
+        );
+        assert_eq!(
+            Diagnostic::fallback(true, "unknown-access", problem(), "using read-write").to_string(),
+            "error[unknown-access]: Field R.A: unknown access \"rwx\""
+        );
+    }
+
+    #[test]
+    fn diagnostic_display_includes_location() {
+        let diag = Diagnostic::error("missing-value", "WIDTH is empty")
+            .in_sheet("uart")
+            .at(Some(12), Some("E".into()));
+        assert_eq!(
+            diag.to_string(),
+            "error[missing-value] uart!E12: WIDTH is empty"
+        );
+
+        let diag = Diagnostic::warning("unknown-access", "bogus").at(Some(7), None);
+        assert_eq!(diag.to_string(), "warning[unknown-access] line 7: bogus");
+
+        let diag = Diagnostic::warning("unreferenced-sheet", "ignored").in_sheet("Notes");
+        assert_eq!(
+            diag.to_string(),
+            "warning[unreferenced-sheet] Notes: ignored"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Register tables of Word documents (.docx), split into sections by their heading
+//! paragraphs and imported like a Markdown document.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use roxmltree::{Document, Node};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::format::detect_format;
+use crate::markup::{self, Heading, Section, Table};
+use crate::options::ParseOptions;
+use crate::types::ImportData;
+use crate::xml::{child, children};
+
+/// Outline level Word gives to body text.
+const BODY_LEVEL: usize = 9;
+
+/// Reads the register tables of a Word document.
+///
+/// Paragraphs with a heading style (`Heading 1`, ..., or any style or paragraph
+/// with an outline level) start a section, the `Title` style above all of them.
+/// Merged cells repeat their value in every cell they cover, as in a workbook.
+/// Rows are numbered within their table, the first one being row 1.
+pub fn read_docx(
+    data: &[u8],
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let unsupported = || Error::Unsupported(detect_format(data));
+    let mut zip = ZipArchive::new(Cursor::new(data)).map_err(|_| unsupported())?;
+    let document = entry(&mut zip, "word/document.xml")?.ok_or_else(unsupported)?;
+    let levels = match entry(&mut zip, "word/styles.xml")? {
+        Some(styles) => style_levels(&Document::parse(&styles)?),
+        None => HashMap::new(),
+    };
+
+    let document = Document::parse(&document)?;
+    let body = child(document.root_element(), "body")
+        .ok_or_else(|| Error::Empty("word/document.xml has no body".into()))?;
+    let mut sections = vec![Section {
+        heading: None,
+        tables: Vec::new(),
+    }];
+    collect(body, &levels, &mut sections);
+
+    markup::read_sections(&sections, opts, diags)
+}
+
+/// Text of the zip entry `name`, `None` when the archive has no such entry.
+fn entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>, Error> {
+    let mut file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(anyhow::Error::from(e).into()),
+    };
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    Ok(Some(text))
+}
+
+/// `w:val` and the other attributes of the WordprocessingML namespace.
+fn attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+fn val<'a>(node: Node<'a, '_>, path: &[&str]) -> Option<&'a str> {
+    let node = path.iter().try_fold(node, |node, name| child(node, name))?;
+    attr(node, "val")
+}
+
+/// Heading level of every paragraph style, following `w:basedOn`.
+fn style_levels(styles: &Document) -> HashMap<String, usize> {
+    // own level or the style it is based on
+    let mut own: HashMap<String, (Option<usize>, Option<String>)> = HashMap::new();
+    for style in children(styles.root_element(), "style") {
+        let Some(id) = attr(style, "styleId") else {
+            continue;
+        };
+        let name = val(style, &["name"]).unwrap_or(id).to_lowercase();
+        let level = val(style, &["pPr", "outlineLvl"])
+            .and_then(|l| l.parse::<usize>().ok())
+            .map(|l| l + 1)
+            .or_else(|| {
+                name.strip_prefix("heading")
+                    .and_then(|n| n.trim().parse().ok())
+            })
+            .or_else(|| (name == "title").then_some(0));
+        let based_on = val(style, &["basedOn"]).map(str::to_string);
+        own.insert(id.to_string(), (level, based_on));
+    }
+
+    own.keys()
+        .filter_map(|id| {
+            let mut style = id;
+            // basedOn chains are short, a cycle is cut after a few steps
+            for _ in 0..8 {
+                let (level, based_on) = own.get(style)?;
+                if let Some(level) = level {
+                    return Some((id.clone(), *level));
+                }
+                style = based_on.as_ref()?;
+            }
+            None
+        })
+        .filter(|(_, level)| *level < BODY_LEVEL)
+        .collect()
+}
+
+/// Appends the headings and tables of `parent` to `sections`, looking into content
+/// controls.
+fn collect(parent: Node, levels: &HashMap<String, usize>, sections: &mut Vec<Section>) {
+    for node in parent.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "p" => {
+                let level = val(node, &["pPr", "outlineLvl"])
+                    .and_then(|l| l.parse::<usize>().ok())
+                    .map(|l| l + 1)
+                    .or_else(|| val(node, &["pPr", "pStyle"]).and_then(|s| levels.get(s).copied()))
+                    .filter(|level| *level < BODY_LEVEL);
+                let text = paragraph_text(node);
+                if let (Some(level), false) = (level, text.trim().is_empty()) {
+                    sections.push(Section {
+                        heading: Some(Heading {
+                            text: text.trim().to_string(),
+                            level,
+                            line: None,
+                        }),
+                        tables: Vec::new(),
+                    });
+                }
+            }
+            "tbl" => {
+                let table = table(node);
+                if !table.rows.is_empty() {
+                    sections.last_mut().unwrap().tables.push(table);
+                }
+            }
+            "sdt" => {
+                if let Some(content) = child(node, "sdtContent") {
+                    collect(content, levels, sections);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Text of the runs of a paragraph, with tabs as spaces and breaks as line feeds.
+fn paragraph_text(paragraph: Node) -> String {
+    let mut text = String::new();
+    for node in paragraph
+        .descendants()
+        .filter(|n| n.is_element() && n.parent().is_some_and(|p| p.tag_name().name() == "r"))
+    {
+        match node.tag_name().name() {
+            "t" => text.push_str(node.text().unwrap_or("")),
+            "tab" => text.push(' '),
+            "br" | "cr" => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Rows of a table, spanned cells repeated and blank rows left out.
+fn table(node: Node) -> Table {
+    let mut rows: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut above: Vec<String> = Vec::new();
+    for (index, tr) in children(node, "tr").enumerate() {
+        let mut cells = Vec::new();
+        for tc in children(tr, "tc") {
+            let span = val(tc, &["tcPr", "gridSpan"])
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(1)
+                .max(1);
+            // `<w:vMerge/>` continues the cell above, `w:val="restart"` starts a new one
+            let continued = child(tc, "tcPr")
+                .and_then(|pr| child(pr, "vMerge"))
+                .is_some_and(|m| attr(m, "val").is_none_or(|v| v == "continue"));
+            let text = if continued {
+                above.get(cells.len()).cloned().unwrap_or_default()
+            } else {
+                children(tc, "p")
+                    .map(paragraph_text)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .trim()
+                    .to_string()
+            };
+            cells.extend(std::iter::repeat_n(text, span));
+        }
+        above = cells.clone();
+        if cells.iter().any(|c| !c.is_empty()) {
+            rows.push((index + 1, cells));
+        }
+    }
+    Table { rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+    #[test]
+    fn reads_styles_and_merged_cells() {
+        let styles = format!(
+            r#"<w:styles {W}>
+<w:style w:type="paragraph" w:styleId="1"><w:name w:val="heading 1"/></w:style>
+<w:style w:type="paragraph" w:styleId="RegHead"><w:name w:val="Reg Head"/><w:basedOn w:val="2"/></w:style>
+<w:style w:type="paragraph" w:styleId="2"><w:name w:val="Custom"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+</w:styles>"#
+        );
+        let levels = style_levels(&Document::parse(&styles).unwrap());
+        assert_eq!(levels.get("1"), Some(&1));
+        assert_eq!(levels.get("RegHead"), Some(&2));
+        assert_eq!(levels.get("Normal"), None);
+
+        let tbl = format!(
+            r#"<w:tbl {W}>
+<w:tr><w:tc><w:tcPr><w:gridSpan w:val="2"/></w:tcPr><w:p><w:r><w:t>Caption</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:tcPr><w:vMerge w:val="restart"/></w:tcPr><w:p><w:r><w:t>0x0</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>A</w:t><w:br/><w:t>B</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:tcPr><w:vMerge/></w:tcPr><w:p/></w:tc><w:tc><w:p><w:r><w:t>C</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p/></w:tc><w:tc><w:p/></w:tc></w:tr>
+</w:tbl>"#
+        );
+        let doc = Document::parse(&tbl).unwrap();
+        let rows = table(doc.root_element()).rows;
+        assert_eq!(
+            rows,
+            [
+                (1, vec!["Caption".to_string(), "Caption".to_string()]),
+                (2, vec!["0x0".to_string(), "A\nB".to_string()]),
+                (3, vec!["0x0".to_string(), "C".to_string()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::number::parse_u64;
+use crate::schema::EnumValue;
+
+/// Extracts enumerated values written as `value: name - description` entries.
+///
+/// Entries are separated by new lines, `;` or `,` (e.g. "0: disabled, 1: enabled").
+/// `=` is accepted instead of `:`, and `-`/`–` or a second `:` separates the name
+/// from an optional description. Entries whose value is not a number are skipped,
+/// which keeps ordinary prose in a `DESCRIPTION` cell from being picked up.
+pub fn parse_enum_values(text: &str) -> Vec<EnumValue> {
+    text.split(['\n', ';'])
+        .flat_map(split_commas)
+        .filter_map(parse_entry)
+        .collect()
+}
+
+// Only split on commas when every piece is an entry of its own, so that commas
+// inside a description stay part of it.
+fn split_commas(chunk: &str) -> Vec<&str> {
+    let pieces: Vec<&str> = chunk.split(',').filter(|p| !p.trim().is_empty()).collect();
+    if pieces.len() > 1 && pieces.iter().all(|p| parse_entry(p).is_some()) {
+        pieces
+    } else {
+        vec![chunk]
+    }
+}
+
+fn parse_entry(entry: &str) -> Option<EnumValue> {
+    let entry = entry
+        .trim()
+        .trim_start_matches(['-', '*', '•'])
+        .trim_start();
+
+    let sep = entry.find([':', '='])?;
+    let value = entry[..sep].trim();
+    parse_u64(value)?;
+
+    let rest = entry[sep + 1..].trim();
+    let (name, desc) = [" - ", " – ", " — ", ":"]
+        .iter()
+        .filter_map(|d| rest.find(d).map(|pos| (pos, d.len())))
+        .min_by_key(|(pos, _)| *pos)
+        .map(|(pos, len)| (rest[..pos].trim(), rest[pos + len..].trim()))
+        .unwrap_or((rest, ""));
+
+    let name = to_identifier(name)?;
+
+    Some(EnumValue {
+        name,
+        value: value.to_string(),
+        desc: (!desc.is_empty()).then(|| desc.to_string()),
+    })
+}
+
+fn to_identifier(name: &str) -> Option<String> {
+    let ident = name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .collect::<String>();
+
+    match ident.chars().next() {
+        None => None,
+        Some(c) if c.is_ascii_digit() => Some(format!("_{}", ident)),
+        Some(_) => Some(ident),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(text: &str) -> Vec<(String, String, Option<String>)> {
+        parse_enum_values(text)
+            .into_iter()
+            .map(|e| (e.value, e.name, e.desc))
+            .collect()
+    }
+
+    #[test]
+    fn parse_enum_values_reads_lines_and_comma_lists() {
+        assert_eq!(
+            pairs("0: disabled, 1: enabled, 2: auto"),
+            vec![
+                ("0".into(), "disabled".into(), None),
+                ("1".into(), "enabled".into(), None),
+                ("2".into(), "auto".into(), None),
+            ]
+        );
+
+        assert_eq!(
+            pairs("Operating mode\n0x0: IDLE - no transfer, bus parked\n0x1 = RUN – active"),
+            vec![
+                (
+                    "0x0".into(),
+                    "IDLE".into(),
+                    Some("no transfer, bus parked".into())
+                ),
+                ("0x1".into(), "RUN".into(), Some("active".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_enum_values_ignores_prose() {
+        assert!(parse_enum_values("Enable bit").is_empty());
+        assert!(parse_enum_values("Note: see spec, section 3").is_empty());
+    }
+}
@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::diagnostic::Diagnostic;
+use crate::format::Format;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Calamine error: {0}")]
@@ -11,6 +14,9 @@ pub enum Error {
     #[error("Polars error: {0}")]
     Polars(#[from] polars::prelude::PolarsError),
 
+    #[error("XML error: {0}")]
+    Xml(#[from] roxmltree::Error),
+
     #[error("Json error: {0}")]
     Json(#[from] serde_json::Error),
 
@@ -25,4 +31,39 @@ pub enum Error {
 
     #[error("Empty Error: {0}")]
     Empty(String),
+
+    #[error("Unsupported file format: {0}")]
+    Unsupported(Format),
+
+    #[error("Cannot read {0}: {1}")]
+    Open(Format, calamine::Error),
+
+    #[error("Header row {1} is outside of sheet \"{0}\"")]
+    HeaderRow(String, usize),
+
+    #[error("{}", summarize(.0))]
+    Diagnostics(Vec<Diagnostic>),
+}
+
+impl Error {
+    /// Describes the error as a diagnostic without location; callers add sheet/row/column.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Error::NotFound(name) => {
+                Diagnostic::error("missing-sheet", format!("Sheet \"{}\" not found", name))
+            }
+            Error::Empty(_) => Diagnostic::error("empty-sheet", "Sheet has no data"),
+            Error::Unsupported(_) => Diagnostic::error("unsupported-format", self.to_string()),
+            Error::HeaderRow(..) => Diagnostic::error("header-row", self.to_string()),
+            _ => Diagnostic::error("read-error", self.to_string()),
+        }
+    }
+}
+
+fn summarize(diags: &[Diagnostic]) -> String {
+    match diags {
+        [] => "Import failed".to_string(),
+        [only] => only.to_string(),
+        [first, rest @ ..] => format!("{} (and {} more)", first, rest.len()),
+    }
 }
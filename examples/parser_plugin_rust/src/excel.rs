@@ -1,21 +1,70 @@
 use std::collections::HashMap;
 use std::fmt::Display;
 
-use calamine::{CellType, DataType, Range};
+use calamine::{CellType, DataType, Dimensions, Range};
 use polars::prelude::*;
 
+use crate::diagnostic::{column_letter, Diagnostic};
 use crate::error::Error;
 
+/// Hidden column carrying the 1-based spreadsheet row of every record.
+pub const ROW_COLUMN: &str = "__ROW__";
+
+/// A worksheet converted into a DataFrame, with what is needed to point back at its cells.
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    pub name: String,
+    pub df: DataFrame,
+    /// 1-based row of the header
+    pub header_row: usize,
+    /// 0-based index of the first column
+    pub first_col: usize,
+}
+
+impl Sheet {
+    pub fn column_letter(&self, column: &str) -> Option<String> {
+        self.df
+            .get_column_index(column)
+            .map(|i| column_letter(self.first_col + i))
+    }
+
+    /// Attaches this sheet and the cell at `row`/`column` to `diag`.
+    pub fn locate(&self, diag: Diagnostic, row: Option<usize>, column: Option<&str>) -> Diagnostic {
+        diag.in_sheet(&self.name)
+            .at(row, column.and_then(|c| self.column_letter(c)))
+    }
+}
+
+/// Copies the value of each merged region into all of its cells, as it is displayed.
+///
+/// Cells outside merged regions are left as they are: a blank cell stays blank.
+pub fn fill_merged<T: CellType>(range: &mut Range<T>, merges: &[Dimensions]) {
+    for merge in merges {
+        let Some(value) = range.get_value(merge.start).cloned() else {
+            continue;
+        };
+        for row in merge.start.0..=merge.end.0 {
+            for col in merge.start.1..=merge.end.1 {
+                if (row, col) != merge.start {
+                    range.set_value((row, col), value.clone());
+                }
+            }
+        }
+    }
+}
+
 pub trait ToDataFrame {
-    fn to_data_frame(&self) -> Result<DataFrame, Error>;
+    /// Converts the rows below the `header`-th row (0-based, within the used range),
+    /// using that row as column names. Rows above it (title banners, legends) are dropped.
+    fn to_data_frame(&self, header: usize) -> Result<DataFrame, Error>;
 }
 
 impl<T> ToDataFrame for Range<T>
 where
     T: DataType + CellType + Display,
 {
-    fn to_data_frame(&self) -> Result<DataFrame, Error> {
-        let all_rows = self.rows().collect::<Vec<_>>();
+    fn to_data_frame(&self, header: usize) -> Result<DataFrame, Error> {
+        let all_rows = self.rows().skip(header).collect::<Vec<_>>();
 
         let mut header_counts = HashMap::<String, usize>::new();
         let headers: Vec<String> = all_rows
@@ -48,12 +97,18 @@ where
             });
         }
 
-        let columns: Vec<Column> = columns
+        let mut columns: Vec<Column> = columns
             .into_iter()
             .zip(headers)
             .map(|(col, name)| Column::new((&name).into(), col))
             .collect();
 
+        let first_row = self.start().map_or(0, |(row, _)| row) + header as u32 + 2;
+        let rows: Vec<u32> = (0..all_rows.len() as u32 - 1)
+            .map(|i| first_row + i)
+            .collect();
+        columns.push(Column::new(ROW_COLUMN.into(), rows));
+
         let df = DataFrame::new(columns)?;
 
         Ok(df)
@@ -0,0 +1,177 @@
+use crate::number::parse_u64;
+
+/// Evaluates the integer expressions found in register descriptions (IP-XACT
+/// `baseAddress`, SystemRDL property values, ...): literals as accepted by
+/// [`parse_u64`], identifiers resolved through `lookup`, parentheses, unary `-`/`~`
+/// and the binary operators `** * / % + - << >> & ^ |` with SystemVerilog precedence.
+///
+/// Returns `None` when the expression cannot be evaluated (unknown identifier,
+/// division by zero, overflow, unsupported syntax).
+pub fn eval(expr: &str, lookup: &dyn Fn(&str) -> Option<u64>) -> Option<u64> {
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        lookup,
+    };
+    let value = parser.expr(0)?;
+    (parser.pos == tokens.len()).then_some(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(u64),
+    Ident(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+const OPS: [&str; 13] = [
+    "**", "<<", ">>", "*", "/", "%", "+", "-", "&", "^", "|", "~", "!",
+];
+
+fn tokenize(expr: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = expr.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c.is_ascii_digit() || c == '\'' {
+            // number, possibly a Verilog literal such as 8'hFF or 'b1010
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '\'')
+            {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(parse_u64(&literal)?));
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let op = OPS.iter().find(|op| rest.starts_with(**op))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+
+    Some(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    lookup: &'a dyn Fn(&str) -> Option<u64>,
+}
+
+/// Binding power of a binary operator, higher binds tighter.
+fn precedence(op: &str) -> Option<u8> {
+    Some(match op {
+        "|" => 1,
+        "^" => 2,
+        "&" => 3,
+        "<<" | ">>" => 4,
+        "+" | "-" => 5,
+        "*" | "/" | "%" => 6,
+        "**" => 7,
+        _ => return None,
+    })
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn expr(&mut self, min: u8) -> Option<u64> {
+        let mut lhs = self.unary()?;
+
+        while let Some(Token::Op(op)) = self.tokens.get(self.pos) {
+            let Some(prec) = precedence(op).filter(|p| *p > min) else {
+                break;
+            };
+            self.pos += 1;
+            // `**` is right associative
+            let rhs = self.expr(if *op == "**" { prec - 1 } else { prec })?;
+            lhs = match *op {
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "<<" => lhs.checked_shl(u32::try_from(rhs).ok()?)?,
+                ">>" => lhs.checked_shr(u32::try_from(rhs).ok()?)?,
+                "+" => lhs.checked_add(rhs)?,
+                "-" => lhs.checked_sub(rhs)?,
+                "*" => lhs.checked_mul(rhs)?,
+                "/" => lhs.checked_div(rhs)?,
+                "%" => lhs.checked_rem(rhs)?,
+                "**" => lhs.checked_pow(u32::try_from(rhs).ok()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(lhs)
+    }
+
+    fn unary(&mut self) -> Option<u64> {
+        match self.next()?.clone() {
+            Token::Number(n) => Some(n),
+            Token::Ident(name) => (self.lookup)(&name),
+            Token::Open => {
+                let value = self.expr(0)?;
+                (self.next()? == &Token::Close).then_some(value)
+            }
+            Token::Op("-") => Some(self.unary()?.wrapping_neg()),
+            Token::Op("+") => self.unary(),
+            Token::Op("~") => Some(!self.unary()?),
+            Token::Op("!") => Some((self.unary()? == 0) as u64),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_literals_operators_and_identifiers() {
+        let lookup = |name: &str| match name {
+            "BASE" => Some(0x4000),
+            "N" => Some(4),
+            _ => None,
+        };
+        let eval = |s: &str| eval(s, &lookup);
+
+        assert_eq!(eval("0x10"), Some(16));
+        assert_eq!(eval("32'h1F"), Some(0x1F));
+        assert_eq!(eval("1 + 2 * 3"), Some(7));
+        assert_eq!(eval("(1 + 2) * 3"), Some(9));
+        assert_eq!(eval("BASE + N * 4"), Some(0x4010));
+        assert_eq!(eval("1 << N | 1"), Some(17));
+        assert_eq!(eval("2 ** 3 ** 2"), Some(512));
+        assert_eq!(eval("~0 & 'hFF"), Some(0xFF));
+        assert_eq!(eval("UNKNOWN + 1"), None);
+        assert_eq!(eval("1 / 0"), None);
+        assert_eq!(eval("1 +"), None);
+        assert_eq!(eval("(1"), None);
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+/// File formats told apart by their leading bytes (and zip entry names).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Xlsx,
+    Xlsb,
+    Xls,
+    Ods,
+    /// Zip of CSV files, one per sheet
+    CsvBundle,
+    /// Recognized, but not a workbook this plugin reads
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Xlsx => write!(f, "Excel workbook (.xlsx)"),
+            Format::Xlsb => write!(f, "Excel binary workbook (.xlsb)"),
+            Format::Xls => write!(f, "Excel 97-2003 workbook (.xls)"),
+            Format::Ods => write!(f, "OpenDocument spreadsheet (.ods)"),
+            Format::CsvBundle => write!(f, "CSV bundle (.zip)"),
+            Format::Unsupported(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+const ZIP: &[u8] = b"PK\x03\x04";
+// OLE2 compound file, the container of .xls (and of legacy .doc/.ppt/.msg)
+const CFB: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|w| w == needle)
+}
+
+/// `text` in UTF-16LE, as compound file directory entries store stream names.
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// Guesses the format of `data` from its signature, without trusting a file extension.
+pub fn detect_format(data: &[u8]) -> Format {
+    if data.starts_with(CFB) {
+        // told apart by their main stream: "Workbook" (Excel 97-2003) or "Book" (Excel 5)
+        return if contains(data, &utf16("Workbook\0")) || contains(data, &utf16("Book\0")) {
+            Format::Xls
+        } else if contains(data, &utf16("WordDocument\0")) {
+            Format::Unsupported("Word 97-2003 document (.doc)")
+        } else if contains(data, &utf16("PowerPoint Document\0")) {
+            Format::Unsupported("PowerPoint 97-2003 presentation (.ppt)")
+        } else if contains(data, &utf16("__substg1.0_")) {
+            Format::Unsupported("Outlook message (.msg)")
+        } else {
+            Format::Unsupported("OLE2 compound file without an Excel workbook")
+        };
+    }
+
+    if data.starts_with(ZIP) {
+        // OpenDocument stores an uncompressed "mimetype" entry first
+        let head = &data[..data.len().min(128)];
+        if contains(head, b"application/vnd.oasis.opendocument.spreadsheet") {
+            return Format::Ods;
+        }
+        if contains(head, b"application/vnd.oasis.opendocument") {
+            return Format::Unsupported("OpenDocument file (not a spreadsheet)");
+        }
+        // entry names are stored uncompressed in the local headers and central directory
+        return if contains(data, b"xl/workbook.bin") {
+            Format::Xlsb
+        } else if contains(data, b"xl/workbook.xml") {
+            Format::Xlsx
+        } else if contains(data, b"word/document.xml") {
+            Format::Unsupported("Word document (.docx)")
+        } else if contains(data, b"ppt/presentation.xml") {
+            Format::Unsupported("PowerPoint presentation (.pptx)")
+        } else if contains(data, b".csv") || contains(data, b".CSV") {
+            Format::CsvBundle
+        } else {
+            Format::Unsupported("ZIP archive")
+        };
+    }
+
+    let text = data.trim_ascii_start();
+    if data.starts_with(b"%PDF") {
+        Format::Unsupported("PDF document")
+    } else if text.starts_with(b"<") {
+        Format::Unsupported("XML document")
+    } else if data.is_empty() {
+        Format::Unsupported("empty file")
+    } else if std::str::from_utf8(data).is_ok_and(|text| text.contains([',', ';', '\t'])) {
+        Format::Unsupported(
+            "single CSV file (zip it together with version.csv, address_map.csv and the block CSVs)",
+        )
+    } else if std::str::from_utf8(data).is_ok() {
+        Format::Unsupported("text file")
+    } else {
+        Format::Unsupported("unknown binary file")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format_from_signature() {
+        let mut ods = b"PK\x03\x04".to_vec();
+        ods.extend_from_slice(&[0; 26]);
+        ods.extend_from_slice(b"mimetypeapplication/vnd.oasis.opendocument.spreadsheet");
+        assert_eq!(detect_format(&ods), Format::Ods);
+
+        assert_eq!(
+            detect_format(b"PK\x03\x04....[Content_Types].xml....xl/workbook.xml"),
+            Format::Xlsx
+        );
+        assert_eq!(
+            detect_format(b"PK\x03\x04....xl/workbook.bin....xl/workbook.xml"),
+            Format::Xlsb
+        );
+        let cfb = |stream: &str| [CFB, &utf16(stream)].concat();
+        assert_eq!(detect_format(&cfb("Workbook\0")), Format::Xls);
+        assert_eq!(detect_format(&cfb("Book\0")), Format::Xls);
+        assert_eq!(
+            detect_format(&cfb("WordDocument\0")).to_string(),
+            "Word 97-2003 document (.doc)"
+        );
+        assert_eq!(
+            detect_format(&cfb("__substg1.0_0037001F")).to_string(),
+            "Outlook message (.msg)"
+        );
+        assert_eq!(
+            detect_format(b"PK\x03\x04....word/document.xml").to_string(),
+            "Word document (.docx)"
+        );
+        assert_eq!(detect_format(b"%PDF-1.7").to_string(), "PDF document");
+        assert_eq!(
+            detect_format(b"PK\x03\x04....version.csv....uart.csv"),
+            Format::CsvBundle
+        );
+        assert!(detect_format(b"ADDR,REG\n")
+            .to_string()
+            .starts_with("single CSV file"));
+        assert_eq!(detect_format(b"hello").to_string(), "text file");
+    }
+}
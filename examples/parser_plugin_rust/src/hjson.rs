@@ -0,0 +1,354 @@
+//! Minimal HJSON reader keeping the line of every value, for diagnostics.
+//!
+//! Supports what register descriptions use: `#`, `//` and `/* */` comments,
+//! optional root braces, unquoted keys, optional commas, single/double quoted,
+//! `'''` multiline and quoteless strings. Numbers are kept as written.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    Str(String),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub value: Value,
+    pub line: usize,
+}
+
+impl Node {
+    /// Member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Scalar as text: strings, numbers and booleans.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::Str(s) | Value::Number(s) => Some(s),
+            Value::Bool(true) => Some("true"),
+            Value::Bool(false) => Some("false"),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Node)]> {
+        match &self.value {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Text of member `key`, trimmed.
+    pub fn text(&self, key: &str) -> Option<String> {
+        self.get(key)
+            .and_then(Node::as_str)
+            .map(|s| s.trim().to_string())
+    }
+}
+
+/// Parses an HJSON document. Returns the line and message of the first error.
+pub fn parse(src: &str) -> Result<Node, (usize, String)> {
+    let mut parser = Parser {
+        chars: src.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    parser.skip_blank();
+    let root = if parser.peek() == Some('{') || parser.peek() == Some('[') {
+        parser.value()?
+    } else {
+        // braces of the root object may be omitted
+        let line = parser.line;
+        Node {
+            value: Value::Object(parser.members(None)?),
+            line,
+        }
+    };
+    parser.skip_blank();
+    match parser.peek() {
+        None => Ok(root),
+        Some(c) => Err((
+            parser.line,
+            format!("Unexpected '{}' after the root value", c),
+        )),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(k, c)| self.chars.get(self.pos + k) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_blank(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '#' || self.starts("//") {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            } else if self.starts("/*") {
+                self.pos += 2;
+                while self.peek().is_some() && !self.starts("*/") {
+                    self.bump();
+                }
+                self.pos += 2;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Object members up to `close` (or the end of input for a braceless root).
+    fn members(&mut self, close: Option<char>) -> Result<Vec<(String, Node)>, (usize, String)> {
+        let mut members = Vec::new();
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None if close.is_none() => return Ok(members),
+                None => return Err((self.line, "Missing '}'".to_string())),
+                Some(c) if Some(c) == close => {
+                    self.bump();
+                    return Ok(members);
+                }
+                Some(',') => {
+                    self.bump();
+                    continue;
+                }
+                _ => {}
+            }
+            let key = self.key()?;
+            self.skip_blank();
+            if self.bump() != Some(':') {
+                return Err((self.line, format!("Expected ':' after \"{}\"", key)));
+            }
+            self.skip_blank();
+            members.push((key, self.value()?));
+        }
+    }
+
+    fn key(&mut self) -> Result<String, (usize, String)> {
+        if let Some(quote @ ('"' | '\'')) = self.peek() {
+            return self.quoted(quote);
+        }
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !",:[]{}".contains(c))
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err((self.line, "Expected a key".to_string()));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn value(&mut self) -> Result<Node, (usize, String)> {
+        let line = self.line;
+        let value = match self.peek() {
+            None => return Err((line, "Expected a value".to_string())),
+            Some('{') => {
+                self.bump();
+                Value::Object(self.members(Some('}'))?)
+            }
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blank();
+                    match self.peek() {
+                        None => return Err((self.line, "Missing ']'".to_string())),
+                        Some(']') => {
+                            self.bump();
+                            break;
+                        }
+                        Some(',') => {
+                            self.bump();
+                        }
+                        _ => items.push(self.value()?),
+                    }
+                }
+                Value::Array(items)
+            }
+            Some(_) if self.starts("'''") => Value::Str(self.multiline()?),
+            Some(quote @ ('"' | '\'')) => Value::Str(self.quoted(quote)?),
+            Some(_) => self.quoteless(),
+        };
+        Ok(Node { value, line })
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, (usize, String)> {
+        let line = self.line;
+        self.bump();
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None => return Err((line, "Unterminated string".to_string())),
+                Some(c) if c == quote => return Ok(text),
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some(c) => text.push(c),
+                    None => return Err((line, "Unterminated string".to_string())),
+                },
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    /// `'''` string; the indentation of the opening quotes is removed from every line.
+    fn multiline(&mut self) -> Result<String, (usize, String)> {
+        let line = self.line;
+        let line_start = self.chars[..self.pos]
+            .iter()
+            .rposition(|&c| c == '\n')
+            .map_or(0, |p| p + 1);
+        let indent = self.pos - line_start;
+        self.pos += 3;
+
+        // text on the line of the opening quotes, if any, is kept as is
+        while self.peek().is_some_and(|c| c != '\n' && c.is_whitespace()) {
+            self.pos += 1;
+        }
+        if self.peek() == Some('\n') {
+            self.bump();
+        }
+
+        let mut lines = vec![String::new()];
+        let mut column = 0;
+        loop {
+            if self.starts("'''") {
+                self.pos += 3;
+                break;
+            }
+            match self.bump() {
+                None => return Err((line, "Unterminated ''' string".to_string())),
+                Some('\n') => {
+                    lines.push(String::new());
+                    column = 0;
+                }
+                Some(c) if c.is_whitespace() && column < indent => column += 1,
+                Some(c) => {
+                    column = indent;
+                    lines.last_mut().unwrap().push(c);
+                }
+            }
+        }
+        // the line holding the closing quotes is not part of the text
+        if lines.len() > 1 && lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Keyword, number or quoteless string running to the end of the line.
+    fn quoteless(&mut self) -> Value {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.pos += 1;
+        }
+        let rest: String = self.chars[start..self.pos].iter().collect();
+
+        // a literal may be followed by a comma, a closing bracket or a comment
+        let end = rest
+            .find(|c: char| c == ',' || c == ']' || c == '}' || c == '#' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let (literal, after) = rest.split_at(end);
+        let tail = after.trim_start();
+        if tail.is_empty()
+            || tail.starts_with([',', ']', '}', '#'])
+            || tail.starts_with("//")
+            || tail.starts_with("/*")
+        {
+            let literal_value = match literal {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                "null" => Some(Value::Null),
+                _ if is_number(literal) => Some(Value::Number(literal.to_string())),
+                _ => None,
+            };
+            if let Some(value) = literal_value {
+                self.pos = start + literal.chars().count();
+                return value;
+            }
+        }
+        Value::Str(rest.trim().to_string())
+    }
+}
+
+/// JSON number syntax.
+fn is_number(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits.parse::<f64>().is_ok()
+        && !digits.contains(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hjson_with_lines() {
+        let src = "# comment\n{\n  name: \"uart\",\n  regwidth: 32\n  list: [ 1, two\n    {bits: \"0\"} ]\n  desc: '''\n    First line\n      indented\n    '''\n  free: a b, c\n}";
+        let root = parse(src).unwrap();
+
+        assert_eq!(root.text("name").as_deref(), Some("uart"));
+        assert_eq!(
+            root.get("regwidth").unwrap().value,
+            Value::Number("32".into())
+        );
+        let list = root.get("list").unwrap().as_array().unwrap();
+        assert_eq!(list[0].value, Value::Number("1".into()));
+        assert_eq!(list[1].value, Value::Str("two".into()));
+        assert_eq!(
+            (list[2].text("bits").as_deref(), list[2].line),
+            (Some("0"), 6)
+        );
+        assert_eq!(root.text("desc").as_deref(), Some("First line\nindented"));
+        assert_eq!(root.text("free").as_deref(), Some("a b, c"));
+
+        let braceless = parse("a: 1\nb: [\"x\"]").unwrap();
+        assert_eq!(braceless.get("b").unwrap().line, 2);
+        assert_eq!(parse("{ a: \"open").unwrap_err().0, 1);
+    }
+}
@@ -0,0 +1,34 @@
+//! IEEE 1685 (IP-XACT) component descriptions.
+
+mod reader;
+mod writer;
+
+pub use reader::read_component;
+pub use writer::write_component;
+
+/// IP-XACT revision, told apart by the namespace of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standard {
+    /// IEEE 1685-2009, `spirit:` elements
+    Ieee2009,
+    /// IEEE 1685-2014, `ipxact:` elements
+    Ieee2014,
+    /// IEEE 1685-2022, access policies instead of plain `access`
+    Ieee2022,
+}
+
+impl Standard {
+    pub fn namespace(self) -> &'static str {
+        match self {
+            Standard::Ieee2009 => "http://www.spiritconsortium.org/XMLSchema/SPIRIT/1685-2009",
+            Standard::Ieee2014 => "http://www.accellera.org/XMLSchema/IPXACT/1685-2014",
+            Standard::Ieee2022 => "http://www.accellera.org/XMLSchema/IPXACT/1685-2022",
+        }
+    }
+
+    pub fn from_namespace(namespace: &str) -> Option<Self> {
+        [Standard::Ieee2009, Standard::Ieee2014, Standard::Ieee2022]
+            .into_iter()
+            .find(|standard| standard.namespace() == namespace.trim_end_matches('/'))
+    }
+}
@@ -0,0 +1,757 @@
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use super::Standard;
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::expr::eval;
+use crate::layout::{elements, FieldBits};
+use crate::number::hex;
+use crate::options::ParseOptions;
+use crate::types::*;
+use crate::xml::{child, children, line, text};
+
+/// Registers a single register or register file may expand to through its arrays
+/// and those of the register files around it.
+const MAX_ELEMENTS: u64 = 1 << 16;
+
+/// Reads an IP-XACT `component` (1685-2009, 2014 or 2022) into `ImportData`.
+///
+/// Register files and register arrays are flattened into the address block:
+/// `rf[2]` holding `ctrl` gives `rf_0_ctrl` and `rf_1_ctrl`. Values are SystemVerilog
+/// expressions that may refer to the component parameters.
+pub fn read_component(
+    xml: &str,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let doc = Document::parse(xml)?;
+    let root = doc.root_element();
+
+    let standard = Standard::from_namespace(root.tag_name().namespace().unwrap_or_default());
+    if standard.is_none() {
+        diags.push(
+            Diagnostic::warning(
+                "unknown-namespace",
+                format!(
+                    "Namespace \"{}\" is not IP-XACT 1685-2009/2014/2022, reading it anyway",
+                    root.tag_name().namespace().unwrap_or_default()
+                ),
+            )
+            .at(Some(line(root)), None),
+        );
+    }
+    if root.tag_name().name() != "component" {
+        diags.push(
+            Diagnostic::error(
+                "not-a-component",
+                format!(
+                    "Root element is \"{}\", expected an IP-XACT component",
+                    root.tag_name().name()
+                ),
+            )
+            .at(Some(line(root)), None),
+        );
+        return Ok(None);
+    }
+
+    let mut reader = Reader {
+        params: HashMap::new(),
+        opts,
+        diags,
+    };
+    reader.read_parameters(root);
+
+    let memory_maps = child(root, "memoryMaps")
+        .map(|maps| {
+            children(maps, "memoryMap")
+                .filter(|map| reader.is_present(*map))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|map| reader.memory_map(map))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(ImportData {
+        project: ImportProject {
+            name: text(root, "name").unwrap_or_default(),
+            description: text(root, "description"),
+            vendor: text(root, "vendor").unwrap_or_default(),
+            library: text(root, "library").unwrap_or_default(),
+            version: text(root, "version")
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "0.1.0".to_string()),
+        },
+        memory_maps,
+    }))
+}
+
+/// Access and side effects of a register or field, as written in the document.
+#[derive(Debug, Clone, Default)]
+struct Access {
+    access: Option<String>,
+    modified_write_value: Option<String>,
+    read_action: Option<String>,
+}
+
+impl Access {
+    /// `access`, `modifiedWriteValue` and `readAction` of `node`, directly below it
+    /// (2009/2014) or in its first access policy (2022).
+    fn of(node: Node) -> Self {
+        let policy = child(node, "fieldAccessPolicies")
+            .or_else(|| child(node, "accessPolicies"))
+            .and_then(|policies| {
+                policies
+                    .children()
+                    .find(|c| c.is_element() && c.tag_name().name().ends_with("ccessPolicy"))
+            });
+        let get = |name: &str| text(node, name).or_else(|| policy.and_then(|p| text(p, name)));
+
+        Self {
+            access: get("access"),
+            modified_write_value: get("modifiedWriteValue"),
+            read_action: get("readAction"),
+        }
+    }
+
+    fn or(self, inherited: &Access) -> Self {
+        Self {
+            access: self.access.or_else(|| inherited.access.clone()),
+            ..self
+        }
+    }
+}
+
+struct Reader<'a> {
+    // parameter id and name -> value
+    params: HashMap<String, u64>,
+    opts: &'a ParseOptions,
+    diags: &'a mut Vec<Diagnostic>,
+}
+
+impl Reader<'_> {
+    /// Evaluates every `parameter` of the document, in order, so that later
+    /// parameters may use earlier ones.
+    fn read_parameters(&mut self, root: Node) {
+        for param in root
+            .descendants()
+            .filter(|n| n.is_element() && n.tag_name().name() == "parameter")
+        {
+            let Some(value) = text(param, "value") else {
+                continue;
+            };
+            let Some(value) = self.eval(&value) else {
+                continue;
+            };
+            if let Some(id) = param.attribute("parameterId") {
+                self.params.insert(id.to_string(), value);
+            }
+            if let Some(name) = text(param, "name") {
+                self.params.insert(name, value);
+            }
+        }
+    }
+
+    fn eval(&self, expr: &str) -> Option<u64> {
+        eval(expr, &|name| self.params.get(name).copied())
+    }
+
+    /// A value that cannot be evaluated: warning naming the default, error in strict mode.
+    fn fallback(&mut self, node: Node, problem: String, default: &str) {
+        let diag = Diagnostic::fallback(self.opts.strict, "invalid-number", problem, default);
+        self.diags.push(diag.at(Some(line(node)), None));
+    }
+
+    /// Value of the `name` child of `node`, `None` when absent.
+    /// An expression that cannot be evaluated is reported and replaced by `default`.
+    fn number(&mut self, node: Node, name: &str, what: &str, default: u64) -> Option<u64> {
+        let expr = text(node, name)?;
+        Some(self.eval(&expr).unwrap_or_else(|| {
+            let at = child(node, name).unwrap_or(node);
+            self.fallback(
+                at,
+                format!("{}: {} \"{}\" cannot be evaluated", what, name, expr),
+                &format!("using {}", default),
+            );
+            default
+        }))
+    }
+
+    /// Address-like value in `unit_bits` units, formatted as hex in bytes; the raw text
+    /// when it cannot be evaluated.
+    fn address(&mut self, node: Node, name: &str, what: &str, unit_bits: u64) -> String {
+        let Some(expr) = text(node, name) else {
+            self.diags.push(
+                Diagnostic::error("missing-value", format!("{} has no {}", what, name))
+                    .at(Some(line(node)), None),
+            );
+            return "0x0".to_string();
+        };
+        match self.eval(&expr) {
+            Some(value) => {
+                let at = child(node, name).unwrap_or(node);
+                self.bytes(at, value, unit_bits, &format!("{}: {}", what, name))
+                    .map_or(expr, hex)
+            }
+            None => {
+                let at = child(node, name).unwrap_or(node);
+                self.fallback(
+                    at,
+                    format!("{}: {} \"{}\" cannot be evaluated", what, name, expr),
+                    "imported as is",
+                );
+                expr
+            }
+        }
+    }
+
+    /// `units` of `unit_bits` bits in bytes, `None` once reported when it overflows.
+    fn bytes(&mut self, node: Node, units: u64, unit_bits: u64, what: &str) -> Option<u64> {
+        let bytes = units.checked_mul(unit_bits / 8);
+        if bytes.is_none() {
+            self.diags.push(
+                Diagnostic::error(
+                    "address-overflow",
+                    format!("{}: address does not fit in 64 bits", what),
+                )
+                .at(Some(line(node)), None),
+            );
+        }
+        bytes
+    }
+
+    /// `base + offset + index * stride`, `None` once reported when it overflows.
+    fn element_address(
+        &mut self,
+        node: Node,
+        (base, offset): (u64, u64),
+        (index, stride): (u64, u64),
+        what: &str,
+    ) -> Option<u64> {
+        let address = base
+            .checked_add(offset)
+            .and_then(|a| a.checked_add(index.checked_mul(stride)?));
+        if address.is_none() {
+            self.diags.push(
+                Diagnostic::error(
+                    "address-overflow",
+                    format!("{}: address does not fit in 64 bits", what),
+                )
+                .at(Some(line(node)), None),
+            );
+        }
+        address
+    }
+
+    /// Number of elements of an array with `dims` inside arrays of `copies` elements,
+    /// `None` once reported when it exceeds [`MAX_ELEMENTS`].
+    fn count(&mut self, node: Node, dims: &[u64], copies: u64, what: &str) -> Option<u64> {
+        let count = dims
+            .iter()
+            .try_fold(copies, |acc, &dim| acc.checked_mul(dim))
+            .filter(|&count| count <= MAX_ELEMENTS);
+        if count.is_none() {
+            self.diags.push(
+                Diagnostic::error(
+                    "too-many-elements",
+                    format!(
+                        "{}: {} expands to more than {} registers",
+                        what,
+                        dims.iter().map(|d| format!("[{}]", d)).collect::<String>(),
+                        MAX_ELEMENTS
+                    ),
+                )
+                .at(Some(line(node)), None),
+            );
+        }
+        count
+    }
+
+    /// `false` when the element carries an `isPresent` expression evaluating to 0.
+    fn is_present(&self, node: Node) -> bool {
+        text(node, "isPresent")
+            .and_then(|expr| self.eval(&expr))
+            .is_none_or(|value| value != 0)
+    }
+
+    /// Array dimensions of a register or register file (`dim` in 2009/2014, `array/dim` in 2022).
+    fn dims(&mut self, node: Node, what: &str) -> Vec<u64> {
+        let array = child(node, "array");
+        let dims: Vec<Node> = children(node, "dim")
+            .chain(array.into_iter().flat_map(|a| children(a, "dim")))
+            .collect();
+        dims.into_iter()
+            .map(|dim| {
+                let expr = dim.text().unwrap_or_default().trim().to_string();
+                self.eval(&expr).unwrap_or_else(|| {
+                    self.fallback(
+                        dim,
+                        format!("{}: dim \"{}\" cannot be evaluated", what, expr),
+                        "using 1",
+                    );
+                    1
+                })
+            })
+            .collect()
+    }
+
+    fn memory_map(&mut self, map: Node) -> ImportMemoryMap {
+        let name = text(map, "name").unwrap_or_default();
+        let mut unit_bits = self
+            .number(map, "addressUnitBits", &format!("Memory map {}", name), 8)
+            .unwrap_or(8)
+            .max(1);
+        // addresses are imported in bytes
+        if !unit_bits.is_multiple_of(8) {
+            let at = child(map, "addressUnitBits").unwrap_or(map);
+            let diag = Diagnostic::fallback(
+                self.opts.strict,
+                "unsupported-unit",
+                format!(
+                    "Memory map {}: addressUnitBits {} is not a whole number of bytes",
+                    name, unit_bits
+                ),
+                "using 8",
+            );
+            self.diags.push(diag.at(Some(line(at)), None));
+            unit_bits = 8;
+        }
+
+        for unsupported in ["memoryRemap", "bank", "subspaceMap"] {
+            for node in children(map, unsupported) {
+                self.diags.push(
+                    Diagnostic::warning(
+                        "unsupported-element",
+                        format!("Memory map {}: {} is not imported", name, unsupported),
+                    )
+                    .at(Some(line(node)), None),
+                );
+            }
+        }
+
+        let address_blocks = children(map, "addressBlock")
+            .filter(|blk| self.is_present(*blk))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|blk| self.address_block(blk, unit_bits))
+            .collect();
+
+        ImportMemoryMap {
+            name,
+            address_blocks,
+        }
+    }
+
+    fn address_block(&mut self, blk: Node, unit_bits: u64) -> ImportAddressBlock {
+        let name = text(blk, "name").unwrap_or_default();
+        let what = format!("Address block {}", name);
+        let base_address = self.address(blk, "baseAddress", &what, unit_bits);
+        let range = self.address(blk, "range", &what, unit_bits);
+        let width = self.number(blk, "width", &what, 32).unwrap_or(32) as u32;
+
+        let mut registers = Vec::new();
+        self.registers(
+            blk,
+            0,
+            "",
+            &Access::of(blk),
+            width,
+            (unit_bits, 1),
+            &mut registers,
+        );
+
+        ImportAddressBlock {
+            name,
+            base_address,
+            range,
+            width,
+            registers,
+        }
+    }
+
+    /// Registers and register files below `parent`, at `base` (in address units) with
+    /// names prefixed by `prefix`; `copies` counts the elements of the enclosing arrays.
+    #[allow(clippy::too_many_arguments)]
+    fn registers(
+        &mut self,
+        parent: Node,
+        base: u64,
+        prefix: &str,
+        access: &Access,
+        width: u32,
+        (unit_bits, copies): (u64, u64),
+        out: &mut Vec<ImportRegister>,
+    ) {
+        for node in parent.children().filter(|n| n.is_element()) {
+            if !self.is_present(node) {
+                continue;
+            }
+            match node.tag_name().name() {
+                "register" => {
+                    self.register(node, base, prefix, access, width, (unit_bits, copies), out)
+                }
+                "registerFile" => {
+                    let name = text(node, "name").unwrap_or_default();
+                    let what = format!("Register file {}{}", prefix, name);
+                    let offset = self.number(node, "addressOffset", &what, 0).unwrap_or(0);
+                    let dims = self.dims(node, &what);
+                    // 2022 gives the stride explicitly, earlier revisions use the range
+                    let stride = child(node, "array")
+                        .and_then(|array| self.number(array, "stride", &what, 0))
+                        .or_else(|| self.number(node, "range", &what, 0))
+                        .unwrap_or(0);
+                    let access = Access::of(node).or(access);
+                    let Some(copies) = self.count(node, &dims, copies, &what) else {
+                        continue;
+                    };
+
+                    for (suffix, index) in elements(&dims) {
+                        let what = format!("{}{}", what, suffix);
+                        let Some(base) =
+                            self.element_address(node, (base, offset), (index, stride), &what)
+                        else {
+                            continue;
+                        };
+                        let prefix = format!("{}{}{}_", prefix, name, suffix);
+                        let units = (unit_bits, copies);
+                        self.registers(node, base, &prefix, &access, width, units, out);
+                    }
+                }
+                "alternateRegisters" => self.diags.push(
+                    Diagnostic::warning(
+                        "unsupported-element",
+                        "Alternate registers are not imported",
+                    )
+                    .at(Some(line(node)), None),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn register(
+        &mut self,
+        reg: Node,
+        base: u64,
+        prefix: &str,
+        access: &Access,
+        width: u32,
+        (unit_bits, copies): (u64, u64),
+        out: &mut Vec<ImportRegister>,
+    ) {
+        let name = format!("{}{}", prefix, text(reg, "name").unwrap_or_default());
+        let what = format!("Register {}", name);
+        let offset = self.number(reg, "addressOffset", &what, 0);
+        if offset.is_none() {
+            self.diags.push(
+                Diagnostic::error("missing-value", format!("{} has no addressOffset", what))
+                    .at(Some(line(reg)), None),
+            );
+        }
+        let offset = offset.unwrap_or(0);
+        let size = self
+            .number(reg, "size", &what, width as u64)
+            .unwrap_or(width as u64);
+        let dims = self.dims(reg, &what);
+        let stride = child(reg, "array")
+            .and_then(|array| self.number(array, "stride", &what, 0))
+            .unwrap_or(size.div_ceil(unit_bits));
+        let access = Access::of(reg).or(access);
+        if self.count(reg, &dims, copies, &what).is_none() {
+            return;
+        }
+
+        // 2009 keeps the reset on the register, later revisions on each field
+        let reset = child(reg, "reset").and_then(|reset| {
+            let value = self.number(reset, "value", &what, 0)?;
+            let mask = self
+                .number(reset, "mask", &what, u64::MAX)
+                .unwrap_or(u64::MAX);
+            Some((value, mask))
+        });
+
+        let mut bits = FieldBits::new(size);
+        let fields: Vec<ImportField> = children(reg, "field")
+            .filter(|f| self.is_present(*f))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter_map(|field| self.field(field, &name, &access, reset, &mut bits))
+            .collect();
+
+        let description = text(reg, "description");
+        let single = dims.is_empty();
+        for (suffix, index) in elements(&dims) {
+            let name = if single {
+                name.clone()
+            } else {
+                format!("{}{}", name, suffix)
+            };
+            let what = format!("Register {}", name);
+            let Some(address) = self
+                .element_address(reg, (base, offset), (index, stride), &what)
+                .and_then(|units| self.bytes(reg, units, unit_bits, &what))
+            else {
+                continue;
+            };
+            out.push(ImportRegister {
+                name,
+                address_offset: hex(address),
+                size: size as u32,
+                description: description.clone(),
+                fields: fields.clone(),
+            });
+        }
+    }
+
+    /// Field `field` of register `reg`, `None` when its bits are out of the register or
+    /// already taken in `bits`.
+    fn field(
+        &mut self,
+        field: Node,
+        reg: &str,
+        access: &Access,
+        reg_reset: Option<(u64, u64)>,
+        bits: &mut FieldBits,
+    ) -> Option<ImportField> {
+        let name = text(field, "name").unwrap_or_default();
+        let what = format!("Field {}.{}", reg, name);
+        let bit_offset = self.number(field, "bitOffset", &what, 0).unwrap_or(0);
+        let bit_width = self.number(field, "bitWidth", &what, 1).unwrap_or(1);
+        if let Err(diag) = bits.take(&what, bit_offset, bit_width) {
+            self.diags.push(diag.at(Some(line(field)), None));
+            return None;
+        }
+        let access = Access::of(field).or(access);
+
+        // hard reset first, then any other reset type
+        let resets: Vec<Node> = child(field, "resets")
+            .map(|r| children(r, "reset").collect())
+            .unwrap_or_default();
+        let reset = resets
+            .iter()
+            .find(|r| r.attribute("resetTypeRef").is_none_or(|t| t == "HARD"))
+            .or(resets.first())
+            .copied();
+        let reset_value = match (reset, reg_reset) {
+            (Some(reset), _) => match text(reset, "value") {
+                Some(expr) => self.eval(&expr).map(hex).unwrap_or_else(|| {
+                    self.fallback(
+                        reset,
+                        format!("{}: reset \"{}\" cannot be evaluated", what, expr),
+                        "imported as is",
+                    );
+                    expr
+                }),
+                None => "0x0".to_string(),
+            },
+            (None, Some((value, mask))) => {
+                let field_mask = 1u64
+                    .checked_shl(bit_width as u32)
+                    .map_or(u64::MAX, |m| m - 1);
+                let shifted = (value & mask).checked_shr(bit_offset as u32).unwrap_or(0);
+                hex(shifted & field_mask)
+            }
+            (None, None) => {
+                self.diags.push(
+                    Diagnostic::info("default-reset", format!("{} has no reset, using 0", what))
+                        .at(Some(line(field)), None),
+                );
+                "0x0".to_string()
+            }
+        };
+
+        let enumerated_values = child(field, "enumeratedValues")
+            .map(|values| {
+                children(values, "enumeratedValue")
+                    .map(|value| ImportEnumeratedValue {
+                        name: text(value, "name").unwrap_or_default(),
+                        value: self.enum_value(value, &what),
+                        description: text(value, "description"),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if access.access.is_none() {
+            self.diags.push(
+                Diagnostic::info(
+                    "default-access",
+                    format!("{} has no access, using read-write", what),
+                )
+                .at(Some(line(field)), None),
+            );
+        }
+
+        Some(ImportField {
+            name,
+            description: text(field, "description"),
+            bit_offset: bit_offset as u32,
+            bit_width: bit_width as u32,
+            access: access.access.unwrap_or_else(|| "read-write".to_string()),
+            modified_write_value: access.modified_write_value,
+            read_action: access.read_action,
+            reset_value,
+            enumerated_values,
+        })
+    }
+
+    /// Value of an `enumeratedValue` in decimal, like the other importers; the raw
+    /// expression when it cannot be evaluated.
+    fn enum_value(&mut self, value: Node, what: &str) -> String {
+        let expr = text(value, "value").unwrap_or_default();
+        match self.eval(&expr) {
+            Some(number) => number.to_string(),
+            None => {
+                let name = text(value, "name").unwrap_or_default();
+                self.fallback(
+                    child(value, "value").unwrap_or(value),
+                    format!(
+                        "{}: value \"{}\" of {} cannot be evaluated",
+                        what, expr, name
+                    ),
+                    "imported as is",
+                );
+                expr
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_register_file_and_register_arrays() {
+        let xml = r#"<?xml version="1.0"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2022">
+  <ipxact:vendor>example.com</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>dma</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>regs</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>dma</ipxact:name>
+        <ipxact:baseAddress>'h4000</ipxact:baseAddress>
+        <ipxact:range>'h100</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:registerFile>
+          <ipxact:name>ch</ipxact:name>
+          <ipxact:array><ipxact:dim>2</ipxact:dim><ipxact:stride>'h10</ipxact:stride></ipxact:array>
+          <ipxact:addressOffset>'h20</ipxact:addressOffset>
+          <ipxact:range>'h10</ipxact:range>
+          <ipxact:register>
+            <ipxact:name>src</ipxact:name>
+            <ipxact:array><ipxact:dim>2</ipxact:dim></ipxact:array>
+            <ipxact:addressOffset>4</ipxact:addressOffset>
+            <ipxact:size>32</ipxact:size>
+            <ipxact:field>
+              <ipxact:name>addr</ipxact:name>
+              <ipxact:bitOffset>0</ipxact:bitOffset>
+              <ipxact:bitWidth>32</ipxact:bitWidth>
+            </ipxact:field>
+          </ipxact:register>
+        </ipxact:registerFile>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>"#;
+
+        let mut diags = vec![];
+        let data = read_component(xml, &ParseOptions::default(), &mut diags)
+            .expect("xml")
+            .expect("component");
+        let regs: Vec<(&str, &str)> = data.memory_maps[0].address_blocks[0]
+            .registers
+            .iter()
+            .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+            .collect();
+        assert_eq!(
+            regs,
+            [
+                ("ch_0_src_0", "0x24"),
+                ("ch_0_src_1", "0x28"),
+                ("ch_1_src_0", "0x34"),
+                ("ch_1_src_1", "0x38"),
+            ]
+        );
+        assert!(diags.iter().all(|d| !d.is_error()));
+    }
+
+    #[test]
+    fn converts_address_units_to_bytes_and_bounds_arrays() {
+        let xml = r#"<?xml version="1.0"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2022">
+  <ipxact:vendor>example.com</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>dsp</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>regs</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>dsp</ipxact:name>
+        <ipxact:baseAddress>'h800</ipxact:baseAddress>
+        <ipxact:range>'h10</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>coef</ipxact:name>
+          <ipxact:array><ipxact:dim>2</ipxact:dim></ipxact:array>
+          <ipxact:addressOffset>4</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+        </ipxact:register>
+        <ipxact:register>
+          <ipxact:name>huge</ipxact:name>
+          <ipxact:array><ipxact:dim>'h10000</ipxact:dim><ipxact:dim>2</ipxact:dim></ipxact:array>
+          <ipxact:addressOffset>0</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+        </ipxact:register>
+        <ipxact:register>
+          <ipxact:name>far</ipxact:name>
+          <ipxact:array><ipxact:dim>2</ipxact:dim><ipxact:stride>'h8000000000000000</ipxact:stride></ipxact:array>
+          <ipxact:addressOffset>'h8000000000000000</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+        </ipxact:register>
+      </ipxact:addressBlock>
+      <ipxact:addressUnitBits>16</ipxact:addressUnitBits>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>"#;
+
+        let mut diags = vec![];
+        let data = read_component(xml, &ParseOptions::default(), &mut diags)
+            .expect("xml")
+            .expect("component");
+        let blk = &data.memory_maps[0].address_blocks[0];
+        assert_eq!(
+            (blk.base_address.as_str(), blk.range.as_str()),
+            ("0x1000", "0x20")
+        );
+        let regs: Vec<(&str, &str)> = blk
+            .registers
+            .iter()
+            .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+            .collect();
+        // 16-bit units: a 32-bit register takes 2 units, 4 bytes
+        assert_eq!(regs, [("coef_0", "0x8"), ("coef_1", "0xC")]);
+
+        let errors: Vec<String> = diags
+            .iter()
+            .filter(|d| d.is_error())
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "error[too-many-elements] line 21: Register huge: [65536][2] expands to more than 65536 registers",
+                "error[address-overflow] line 27: Register far_0: address does not fit in 64 bits",
+                "error[address-overflow] line 27: Register far_1: address does not fit in 64 bits",
+            ]
+        );
+    }
+}
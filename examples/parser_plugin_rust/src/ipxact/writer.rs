@@ -0,0 +1,251 @@
+use super::Standard;
+use crate::number::parse_u64;
+use crate::types::*;
+
+const XSI: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// Writes `data` as an IEEE 1685-2022 `component`.
+///
+/// Elements follow the order of the 2022 schema (`resets` between `bitOffset` and
+/// `bitWidth`, access in a `fieldAccessPolicy`). Numbers are written as
+/// SystemVerilog literals, `0x4000_0000` as `'h40000000`; other values, such as
+/// parameter expressions, are written as is. A register without
+/// fields gets one spanning it, the schema requiring at least one. For the same
+/// reason an empty vendor or library is written as `unknown` and an empty version
+/// as `1.0`, spaces in the VLNV become `_`, and every other name is made a valid
+/// `xs:Name` (`timer regs` gives `timer_regs`, `0_cfg` gives `_0_cfg`).
+pub fn write_component(data: &ImportData) -> String {
+    let namespace = Standard::Ieee2022.namespace();
+    let mut xml = Writer::default();
+    xml.out
+        .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.out.push_str(&format!(
+        "<ipxact:component xmlns:ipxact=\"{0}\" xmlns:xsi=\"{1}\" xsi:schemaLocation=\"{0} {0}/index.xsd\">\n",
+        namespace, XSI
+    ));
+    xml.depth = 1;
+
+    let project = &data.project;
+    xml.leaf("vendor", &vlnv(&project.vendor, "unknown"));
+    xml.leaf("library", &vlnv(&project.library, "unknown"));
+    xml.leaf("name", &vlnv(&project.name, "component"));
+    xml.leaf("version", &vlnv(&project.version, "1.0"));
+    xml.description(project.description.as_deref());
+
+    if !data.memory_maps.is_empty() {
+        xml.open("memoryMaps");
+        for map in &data.memory_maps {
+            memory_map(&mut xml, map);
+        }
+        xml.close("memoryMaps");
+    }
+
+    xml.out.push_str("</ipxact:component>\n");
+    xml.out
+}
+
+fn memory_map(xml: &mut Writer, map: &ImportMemoryMap) {
+    xml.open("memoryMap");
+    xml.leaf("name", &name(&map.name, "map"));
+    for block in &map.address_blocks {
+        xml.open("addressBlock");
+        xml.leaf("name", &name(&block.name, "block"));
+        xml.leaf("baseAddress", &literal(&block.base_address));
+        xml.leaf("range", &literal(&block.range));
+        xml.leaf("width", &block.width.to_string());
+        xml.leaf("usage", "register");
+        for reg in &block.registers {
+            register(xml, reg);
+        }
+        xml.close("addressBlock");
+    }
+    xml.leaf("addressUnitBits", "8");
+    xml.close("memoryMap");
+}
+
+fn register(xml: &mut Writer, reg: &ImportRegister) {
+    xml.open("register");
+    xml.leaf("name", &name(&reg.name, "register"));
+    xml.description(reg.description.as_deref());
+    xml.leaf("addressOffset", &literal(&reg.address_offset));
+    xml.leaf("size", &reg.size.to_string());
+    if reg.fields.is_empty() {
+        field(
+            xml,
+            &ImportField {
+                name: reg.name.clone(),
+                description: None,
+                bit_offset: 0,
+                bit_width: reg.size,
+                access: "read-write".to_string(),
+                modified_write_value: None,
+                read_action: None,
+                reset_value: "0".to_string(),
+                enumerated_values: vec![],
+            },
+        );
+    }
+    for f in &reg.fields {
+        field(xml, f);
+    }
+    xml.close("register");
+}
+
+fn field(xml: &mut Writer, field: &ImportField) {
+    xml.open("field");
+    xml.leaf("name", &name(&field.name, "field"));
+    xml.description(field.description.as_deref());
+    xml.leaf("bitOffset", &field.bit_offset.to_string());
+    if !field.reset_value.trim().is_empty() {
+        xml.open("resets");
+        xml.open("reset");
+        xml.leaf("value", &literal(&field.reset_value));
+        xml.close("reset");
+        xml.close("resets");
+    }
+    xml.leaf("bitWidth", &field.bit_width.to_string());
+
+    let policy = [
+        ("access", Some(field.access.as_str())),
+        ("modifiedWriteValue", field.modified_write_value.as_deref()),
+        ("readAction", field.read_action.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(tag, value)| Some((tag, value.filter(|v| !v.trim().is_empty())?)))
+    .collect::<Vec<_>>();
+    if !policy.is_empty() {
+        xml.open("fieldAccessPolicies");
+        xml.open("fieldAccessPolicy");
+        for (tag, value) in policy {
+            xml.leaf(tag, value);
+        }
+        xml.close("fieldAccessPolicy");
+        xml.close("fieldAccessPolicies");
+    }
+
+    if !field.enumerated_values.is_empty() {
+        xml.open("enumeratedValues");
+        for value in &field.enumerated_values {
+            xml.open("enumeratedValue");
+            xml.leaf("name", &name(&value.name, "value"));
+            xml.description(value.description.as_deref());
+            xml.leaf("value", &literal(&value.value));
+            xml.close("enumeratedValue");
+        }
+        xml.close("enumeratedValues");
+    }
+    xml.close("field");
+}
+
+/// `value` with a C `0x` prefix turned into a SystemVerilog literal.
+fn literal(value: &str) -> String {
+    let value = value.trim();
+    match parse_u64(value) {
+        Some(n) if value.starts_with("0x") || value.starts_with("0X") => format!("'h{:X}", n),
+        _ => value.to_string(),
+    }
+}
+
+/// A part of the VLNV as a schema name, `placeholder` when it is empty.
+fn vlnv(value: &str, placeholder: &str) -> String {
+    match value.split_whitespace().collect::<Vec<_>>() {
+        words if words.is_empty() => placeholder.to_string(),
+        words => words.join("_"),
+    }
+}
+
+/// `value` as an `xs:Name`, like [`vlnv`] with every character a name cannot hold
+/// turned into `_`, and `_` put before a leading digit, `.` or `-`.
+fn name(value: &str, placeholder: &str) -> String {
+    let name: String = vlnv(value, placeholder)
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_alphabetic() || matches!(c, '_' | ':')) {
+        name
+    } else {
+        format!("_{}", name)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `ipxact:` elements, indented by two spaces.
+#[derive(Default)]
+struct Writer {
+    out: String,
+    depth: usize,
+}
+
+impl Writer {
+    fn indent(&mut self) {
+        self.out.push_str(&"  ".repeat(self.depth));
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.indent();
+        self.out.push_str(&format!("<ipxact:{}>\n", tag));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(&format!("</ipxact:{}>\n", tag));
+    }
+
+    fn leaf(&mut self, tag: &str, text: &str) {
+        self.indent();
+        self.out.push_str(&format!(
+            "<ipxact:{0}>{1}</ipxact:{0}>\n",
+            tag,
+            escape(text)
+        ));
+    }
+
+    fn description(&mut self, text: Option<&str>) {
+        if let Some(text) = text.filter(|t| !t.trim().is_empty()) {
+            self.leaf("description", text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_numbers_as_literals() {
+        assert_eq!(literal("0x4000_0000"), "'h40000000");
+        assert_eq!(literal("16"), "16");
+        assert_eq!(literal("2'b01"), "2'b01");
+        assert_eq!(literal("UART_BASE + 'h10"), "UART_BASE + 'h10");
+        assert_eq!(escape("a < b & c"), "a &lt; b &amp; c");
+    }
+
+    #[test]
+    fn fills_in_empty_vlnv_parts() {
+        assert_eq!(vlnv("", "unknown"), "unknown");
+        assert_eq!(vlnv(" Timer SoC ", "component"), "Timer_SoC");
+        assert_eq!(vlnv("acme.com", "unknown"), "acme.com");
+    }
+
+    #[test]
+    fn writes_names_as_schema_names() {
+        assert_eq!(name(" timer regs ", "block"), "timer_regs");
+        assert_eq!(name("", "field"), "field");
+        assert_eq!(name("0_cfg", "register"), "_0_cfg");
+        assert_eq!(name("CTRL/STATUS (rw)", "register"), "CTRL_STATUS__rw_");
+        assert_eq!(name("uart.ctrl-0", "register"), "uart.ctrl-0");
+    }
+}
@@ -0,0 +1,115 @@
+//! Register layout helpers shared by the importers of text formats (IP-XACT,
+//! SystemRDL, CMSIS-SVD, reggen, RALF, C headers) and the RTL generation.
+
+use crate::diagnostic::Diagnostic;
+
+/// `(suffix, index)` of every element of an array with `dims`, row-major:
+/// `[2][2]` gives `_0_0`, `_0_1`, `_1_0`, `_1_1`. A single element without suffix
+/// when `dims` is empty.
+pub fn elements(dims: &[u64]) -> Vec<(String, u64)> {
+    dims.iter().fold(vec![(String::new(), 0)], |acc, &dim| {
+        acc.into_iter()
+            .flat_map(|(suffix, index)| {
+                (0..dim).map(move |i| (format!("{}_{}", suffix, i), index * dim + i))
+            })
+            .collect()
+    })
+}
+
+/// Whether `name` marks reserved bits: `reserved`, `rsvd`, `pad` or `unused`,
+/// maybe numbered (`RESERVED1`, `_rsvd_2`).
+pub fn is_reserved(name: &str) -> bool {
+    let lower = name.trim_start_matches('_').to_ascii_lowercase();
+    ["reserved", "rsvd", "pad", "unused"].iter().any(|p| {
+        lower
+            .strip_prefix(p)
+            .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit() || c == '_'))
+    })
+}
+
+/// Bits of a register taken by the fields placed so far.
+pub struct FieldBits {
+    width: u64,
+    /// `(lsb, end)` of each placed field
+    taken: Vec<(u64, u64)>,
+}
+
+impl FieldBits {
+    pub fn new(width: u64) -> Self {
+        Self {
+            width,
+            taken: Vec::new(),
+        }
+    }
+
+    /// Takes `bits` bits from `lsb` for field `what`. When they are empty, past the
+    /// register width or overlap a field placed before, nothing is taken and the
+    /// error, not yet located, is returned.
+    pub fn take(&mut self, what: &str, lsb: u64, bits: u64) -> Result<(), Diagnostic> {
+        let end = lsb.saturating_add(bits);
+        if bits == 0 || end > self.width {
+            return Err(Diagnostic::error(
+                "field-out-of-range",
+                format!(
+                    "{} [{}:{}] does not fit in {} bits",
+                    what,
+                    lsb.saturating_add(bits.max(1) - 1),
+                    lsb,
+                    self.width
+                ),
+            ));
+        }
+        if self.taken.iter().any(|&(l, e)| l < end && lsb < e) {
+            return Err(Diagnostic::error(
+                "field-overlap",
+                format!("{} overlaps another field", what),
+            ));
+        }
+        self.taken.push((lsb, end));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_fields_past_the_width_or_overlapping() {
+        let mut bits = FieldBits::new(32);
+        assert!(bits.take("Field R.A", 0, 8).is_ok());
+        assert!(bits.take("Field R.B", 8, 24).is_ok());
+
+        let err = bits.take("Field R.C", 4, 2).expect_err("overlap");
+        assert_eq!(
+            err.to_string(),
+            "error[field-overlap]: Field R.C overlaps another field"
+        );
+        let err = bits.take("Field R.D", 64, 1).expect_err("out of range");
+        assert_eq!(
+            err.to_string(),
+            "error[field-out-of-range]: Field R.D [64:64] does not fit in 32 bits"
+        );
+        let err = bits
+            .take("Field R.E", u64::MAX, 2)
+            .expect_err("out of range");
+        assert_eq!(err.code, "field-out-of-range");
+
+        assert_eq!(elements(&[]), [(String::new(), 0)]);
+        assert_eq!(
+            elements(&[2, 2]),
+            [
+                ("_0_0".into(), 0),
+                ("_0_1".into(), 1),
+                ("_1_0".into(), 2),
+                ("_1_1".into(), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn tells_reserved_names() {
+        assert!(is_reserved("_RESERVED1") && is_reserved("rsvd_2"));
+        assert!(!is_reserved("PADDING") && !is_reserved("unused_en"));
+    }
+}
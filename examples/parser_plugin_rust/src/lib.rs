@@ -1,77 +1,516 @@
-use calamine::{open_workbook_from_rs, Reader, Xlsx};
 use std::collections::HashMap;
-use std::io::Cursor;
 
+mod access;
+mod cheader;
+mod columns;
+mod csv_bundle;
+mod diagnostic;
+mod docx;
+mod enums;
 mod error;
 mod excel;
+mod expr;
+mod format;
+mod hjson;
+mod ipxact;
+mod layout;
+mod markup;
+mod number;
+mod ods;
+mod options;
 mod parser;
+mod ralf;
+mod reggen;
+mod rtl;
 mod schema;
+mod svd;
+mod systemrdl;
+mod text;
 mod types;
+mod workbook;
+mod xlsb;
+mod xml;
 
+pub use crate::cheader::{Confidence, ConfidenceItem, ConfidenceReport};
+pub use crate::diagnostic::{Diagnostic, Severity};
 use crate::error::Error;
-use excel::ToDataFrame;
+pub use crate::options::{ParseOptions, SheetAliases};
+pub use crate::rtl::{Bus, RtlFile, RtlOptions};
+pub use crate::types::{
+    ImportAddressBlock, ImportData, ImportEnumeratedValue, ImportField, ImportMemoryMap,
+    ImportPluginResult, ImportProject, ImportRegister,
+};
+use access::{parse_access, AccessSpec};
+use columns::{ColumnMap, SheetRole};
 use parser::parse_register;
-use schema::{df_to_blks, df_to_compo, df_to_regs, Component};
-use types::*;
+use schema::{
+    check_columns, df_to_blks, df_to_compo, df_to_regs, Component, Register, REGISTER_COLUMNS,
+};
+use workbook::{open_workbook, SheetLoader};
 
-pub fn parse_excel_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
-    let cursor = Cursor::new(data);
-    let mut wb: Xlsx<_> = open_workbook_from_rs(cursor)?;
-
-    // Load all sheets into DataFrames
-    let sheets = wb.worksheets();
-    let mut df_map: HashMap<String, polars::prelude::DataFrame> = sheets
-        .iter()
-        .map(|(sheet_name, range_data)| {
-            // Normalize key to lowercase for case-insensitivity
-            range_data
-                .to_data_frame()
-                .map(|df| (sheet_name.trim().to_lowercase(), df))
+/// Import result together with everything worth telling the user about it.
+///
+/// `data` is `None` as soon as one diagnostic is an error.
+#[derive(Debug)]
+pub struct ParseOutcome {
+    pub data: Option<ImportData>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ParseOutcome {
+    /// Outcome of a front end that reported into `diagnostics` and either produced
+    /// data or stopped with an error.
+    fn new(result: Result<Option<ImportData>, Error>, mut diagnostics: Vec<Diagnostic>) -> Self {
+        let data = result.unwrap_or_else(|e| {
+            diagnostics.push(e.to_diagnostic());
+            None
+        });
+
+        ParseOutcome {
+            data: data.filter(|_| !diagnostics.iter().any(Diagnostic::is_error)),
+            diagnostics,
+        }
+    }
+
+    /// The data, or every error diagnostic.
+    pub fn into_result(self) -> Result<ImportData, Error> {
+        self.data.ok_or_else(|| {
+            Error::Diagnostics(
+                self.diagnostics
+                    .into_iter()
+                    .filter(Diagnostic::is_error)
+                    .collect(),
+            )
         })
-        .collect::<Result<HashMap<_, _>, _>>()
-        .map_err(Error::from)?;
-
-    // Closure to find sheet by name (already lowercased keys)
-    let mut get_df = |name: &str| -> Result<polars::prelude::DataFrame, Error> {
-        df_map
-            .remove(&name.to_lowercase())
-            .ok_or_else(|| Error::NotFound(name.into()))
-    };
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+
+    /// Full result for the import dialog: data when successful, the error summary
+    /// otherwise, and every warning (e.g. silently defaulted values) either way.
+    pub fn into_report(self) -> ImportPluginResult {
+        let warnings = self.warnings().map(|d| d.to_string()).collect();
+        let error = self.has_errors().then(|| {
+            Error::Diagnostics(
+                self.diagnostics
+                    .iter()
+                    .filter(|d| d.is_error())
+                    .cloned()
+                    .collect(),
+            )
+            .to_string()
+        });
+
+        ImportPluginResult {
+            success: self.data.is_some(),
+            data: self.data,
+            error,
+            warnings,
+            diagnostics: self.diagnostics,
+            confidence: None,
+        }
+    }
+}
+
+impl From<ParseOutcome> for ImportPluginResult {
+    fn from(outcome: ParseOutcome) -> Self {
+        outcome.into_report()
+    }
+}
+
+/// The report of a C header import, carrying its confidence report.
+impl From<(ParseOutcome, ConfidenceReport)> for ImportPluginResult {
+    fn from((outcome, confidence): (ParseOutcome, ConfidenceReport)) -> Self {
+        ImportPluginResult {
+            confidence: Some(confidence),
+            ..outcome.into_report()
+        }
+    }
+}
+
+pub fn parse_excel_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_excel_with_diagnostics(data).into_result()
+}
+
+/// Parses the workbook, collecting as many diagnostics as possible instead of
+/// stopping at the first problem.
+pub fn parse_excel_with_diagnostics(data: &[u8]) -> ParseOutcome {
+    parse_excel_with_options(data, &ParseOptions::default())
+}
+
+pub fn parse_excel_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let result = parse_workbook(data, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Reads an IP-XACT (IEEE 1685-2009/2014/2022) `component.xml`.
+pub fn parse_ipxact_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_ipxact_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_ipxact_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (xml, _) = text::decode_xml(data);
+    let result = ipxact::read_component(&xml, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Writes `data` as an IP-XACT (IEEE 1685-2022) `component.xml`, e.g. the result of
+/// `parse_excel_to_import_data`.
+pub fn export_ipxact(data: &ImportData) -> String {
+    ipxact::write_component(data)
+}
+
+/// Generates a SystemVerilog register block, `<block>_csr.sv`, for every address
+/// block of `data`. Fails with an error diagnostic per register or field that
+/// cannot be generated, e.g. overlapping fields.
+pub fn generate_sv(data: &ImportData) -> Result<Vec<RtlFile>, Error> {
+    generate_sv_with_options(data, &RtlOptions::default())
+}
+
+/// Like `generate_sv`, adding after each register block the bus adapter
+/// `<block>_<bus>.sv` and its testbench `<block>_<bus>_tb.sv` when `opts.bus` is set.
+pub fn generate_sv_with_options(
+    data: &ImportData,
+    opts: &RtlOptions,
+) -> Result<Vec<RtlFile>, Error> {
+    rtl::generate_sv_files(data, opts)
+}
+
+/// Generates the VHDL-2008 twin of every `generate_sv` register block,
+/// `<block>_csr.vhd`, with its GHDL testbench `<block>_csr_tb.vhd`.
+pub fn generate_vhdl(data: &ImportData) -> Result<Vec<RtlFile>, Error> {
+    rtl::generate_vhdl_files(data)
+}
+
+/// Reads a CMSIS-SVD device description.
+pub fn parse_svd_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_svd_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_svd_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (xml, _) = text::decode_xml(data);
+    let result = svd::read_device(&xml, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Parses and elaborates a SystemRDL 2.0 description.
+pub fn parse_rdl_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_rdl_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_rdl_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (src, _) = text::decode(data);
+    let result = systemrdl::read_rdl(&src, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Reads an OpenTitan reggen `.hjson` register description.
+pub fn parse_hjson_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_hjson_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_hjson_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (src, _) = text::decode(data);
+    let result = reggen::read_reggen(&src, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Parses a Synopsys RALF description.
+pub fn parse_ralf_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_ralf_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_ralf_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (src, _) = text::decode(data);
+    let result = ralf::read_ralf(&src, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Reads the register tables of a Markdown document, one address block per heading.
+pub fn parse_markdown_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_markdown_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_markdown_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (src, _) = text::decode(data);
+    let result = markup::read_tables(&src, markup::Syntax::Markdown, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Reads the register tables of an AsciiDoc document, one address block per heading.
+pub fn parse_asciidoc_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_asciidoc_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_asciidoc_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let (src, _) = text::decode(data);
+    let result = markup::read_tables(&src, markup::Syntax::AsciiDoc, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Reads the register tables of a Word document, one address block per heading.
+pub fn parse_docx_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_docx_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_docx_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    let mut diagnostics = Vec::new();
+    let result = docx::read_docx(data, opts, &mut diagnostics);
+    ParseOutcome::new(result, diagnostics)
+}
+
+/// Recovers registers from a C header's `#define`s and peripheral structs.
+pub fn parse_header_to_import_data(data: &[u8]) -> Result<ImportData, Error> {
+    parse_header_with_options(data, &ParseOptions::default()).into_result()
+}
+
+pub fn parse_header_with_options(data: &[u8], opts: &ParseOptions) -> ParseOutcome {
+    parse_header_with_confidence(data, opts).0
+}
+
+/// `parse_header_with_options` together with how sure the import is about each
+/// block, register and field it recovered.
+pub fn parse_header_with_confidence(
+    data: &[u8],
+    opts: &ParseOptions,
+) -> (ParseOutcome, ConfidenceReport) {
+    let mut diagnostics = Vec::new();
+    let mut report = ConfidenceReport::default();
+    let (src, _) = text::decode(data);
+    let result = cheader::read_header(&src, opts, &mut diagnostics, &mut report);
+    (ParseOutcome::new(result, diagnostics), report)
+}
+
+fn parse_workbook(
+    data: &[u8],
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    // .xlsx, .xlsb, .xls, .ods or a zip of CSV files, told apart by their signature
+    let wb = open_workbook(data)?;
+
+    // Sheets are only converted when `version`, `address_map` (or their aliases)
+    // or a `BLOCK` entry names them
+    let mut loader = SheetLoader::new(wb, ColumnMap::new(opts, diags), opts);
 
     let compo = {
-        let compo_df = get_df("version")?;
+        let compo_sheet = loader.load_role(SheetRole::Version, diags)?;
+
+        df_to_compo(&compo_sheet, diags, |diags| {
+            let blks_sheet = loader.load_role(SheetRole::AddressMap, diags)?;
 
-        df_to_compo(compo_df, || {
-            let blks_df = get_df("address_map")?;
+            // Block type sheets are parsed once and shared by every instance
+            let mut block_types: HashMap<String, Vec<Register>> = HashMap::new();
 
-            df_to_blks(blks_df, |s| {
-                // If sheet not found directly, try case insensitive lookup (already done by map keys)
-                // But `s` comes from "BLOCK" column in address_map.
-                let regs_df = get_df(s)?;
-                let parsered_df = parse_register(regs_df)?;
-                df_to_regs(parsered_df)
+            df_to_blks(&blks_sheet, opts, diags, |s, diags| {
+                // `s` comes from "BLOCK" column in address_map.
+                let key = s.trim().to_lowercase();
+                if let Some(regs) = block_types.get(&key) {
+                    return Ok(regs.clone());
+                }
+
+                let regs_sheet = loader.load(s, SheetRole::Block, diags)?;
+                let regs = if check_columns(&regs_sheet, &REGISTER_COLUMNS, diags) {
+                    let parsered_df = parse_register(regs_sheet.df.clone())?;
+                    df_to_regs(parsered_df, &regs_sheet, opts, diags)?
+                } else {
+                    vec![]
+                };
+                block_types.insert(key, regs.clone());
+                Ok(regs)
             })
-        })
-        .map_err(Error::from)?
+        })?
     };
 
-    // Convert internal Component to ImportData logic
-    let import_data = convert_component_to_import_data(compo);
+    diags.extend(loader.unreferenced().into_iter().map(|name| {
+        Diagnostic::warning(
+            "unreferenced-sheet",
+            "Sheet is not referenced by version/address_map and was ignored",
+        )
+        .in_sheet(&name)
+    }));
 
-    Ok(import_data)
+    // Convert internal Component to ImportData logic
+    Ok(compo.map(convert_component_to_import_data))
 }
 
 #[cfg(target_arch = "wasm32")]
 mod wasm_exports {
     use super::*;
-    use serde_wasm_bindgen::to_value;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_wasm_bindgen::{from_value, to_value};
     use wasm_bindgen::prelude::*;
 
+    fn serialize<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+        to_value(value).map_err(|e| JsError::new(&format!("Serialization error: {}", e)))
+    }
+
+    /// `options` of type `T`, its default when `undefined` or `null`.
+    fn options<T: DeserializeOwned + Default>(options: JsValue) -> Result<T, JsError> {
+        if options.is_undefined() || options.is_null() {
+            return Ok(T::default());
+        }
+        from_value(options).map_err(|e| JsError::new(&format!("Invalid options: {}", e)))
+    }
+
+    fn import_data(data: JsValue) -> Result<ImportData, JsError> {
+        from_value(data).map_err(|e| JsError::new(&format!("Invalid data: {}", e)))
+    }
+
+    /// For every source format, `name(data)` returning its `ImportData` through
+    /// `to_data`, and `report(data)` returning its `ImportPluginResult` made from
+    /// what `with_options` returns.
+    macro_rules! import_exports {
+        ($(
+            $(#[$doc:meta])* $name:ident($to_data:ident),
+            $(#[$report_doc:meta])* $report:ident($with_options:ident);
+        )*) => {$(
+            $(#[$doc])*
+            #[wasm_bindgen]
+            pub fn $name(data: &[u8]) -> Result<JsValue, JsError> {
+                let import_data = super::$to_data(data)
+                    .map_err(|e| JsError::new(&format!("Parsing error: {}", e)))?;
+                serialize(&import_data)
+            }
+
+            $(#[$report_doc])*
+            #[wasm_bindgen]
+            pub fn $report(data: &[u8]) -> Result<JsValue, JsError> {
+                let outcome = super::$with_options(data, &ParseOptions::default());
+                serialize(&ImportPluginResult::from(outcome))
+            }
+        )*};
+    }
+
+    import_exports! {
+        parse_excel(parse_excel_to_import_data),
+        /// Returns `{ success, data, error, warnings, diagnostics }` (`ImportPluginResult`),
+        /// so the problems that did not block the import can be shown as well.
+        parse_excel_with_report(parse_excel_with_options);
+
+        /// Reads an IP-XACT `component.xml`, same result as `parse_excel`.
+        parse_ipxact(parse_ipxact_to_import_data),
+        /// `parse_excel_with_report` for an IP-XACT `component.xml`.
+        parse_ipxact_with_report(parse_ipxact_with_options);
+
+        /// Reads a CMSIS-SVD device, same result as `parse_excel`.
+        parse_svd(parse_svd_to_import_data),
+        /// `parse_excel_with_report` for a CMSIS-SVD file.
+        parse_svd_with_report(parse_svd_with_options);
+
+        /// Elaborates a SystemRDL source, same result as `parse_excel`.
+        parse_rdl(parse_rdl_to_import_data),
+        /// `parse_excel_with_report` for a SystemRDL source, diagnostics carry line numbers.
+        parse_rdl_with_report(parse_rdl_with_options);
+
+        /// Reads an OpenTitan reggen description, same result as `parse_excel`.
+        parse_hjson(parse_hjson_to_import_data),
+        /// `parse_excel_with_report` for a reggen `.hjson`, diagnostics carry line numbers.
+        parse_hjson_with_report(parse_hjson_with_options);
+
+        /// Reads a Synopsys RALF description, same result as `parse_excel`.
+        parse_ralf(parse_ralf_to_import_data),
+        /// `parse_excel_with_report` for a `.ralf` file, diagnostics carry line numbers.
+        parse_ralf_with_report(parse_ralf_with_options);
+
+        /// Reads the register tables of a Markdown document, same result as `parse_excel`.
+        parse_markdown(parse_markdown_to_import_data),
+        /// `parse_excel_with_report` for a Markdown document; diagnostics name the heading
+        /// as sheet and carry line numbers as rows.
+        parse_markdown_with_report(parse_markdown_with_options);
+
+        /// Reads the register tables of an AsciiDoc document, same result as `parse_excel`.
+        parse_asciidoc(parse_asciidoc_to_import_data),
+        /// `parse_markdown_with_report` for an AsciiDoc document.
+        parse_asciidoc_with_report(parse_asciidoc_with_options);
+
+        /// Reads the register tables of a Word document, same result as `parse_excel`.
+        parse_docx(parse_docx_to_import_data),
+        /// `parse_markdown_with_report` for a Word document, rows numbered within each table.
+        parse_docx_with_report(parse_docx_with_options);
+
+        /// Recovers registers from a C header, same result as `parse_excel`.
+        parse_header(parse_header_to_import_data),
+        /// `parse_excel_with_report` for a C header, diagnostics carry line numbers. Its
+        /// `confidence` is `{ items: [{ path, confidence, reason, line }] }`, one item per
+        /// block, register and field (`confidence` is `high`, `medium` or `low`).
+        parse_header_with_report(parse_header_with_confidence);
+    }
+
+    /// Same result as `parse_excel_with_report`, honouring `options`
+    /// (`{ strict: true }` turns every silently defaulted value into an error).
+    #[wasm_bindgen]
+    pub fn parse_excel_with_options(data: &[u8], options: JsValue) -> Result<JsValue, JsError> {
+        let opts: ParseOptions = self::options(options)?;
+        serialize(&super::parse_excel_with_options(data, &opts).into_report())
+    }
+
+    /// Same as `parse_excel_with_options`, with the options given as a JSON string,
+    /// e.g. `{"columns": {"REG": ["寄存器"]}, "sheets": {"addressMap": ["地址映射"]}}`.
     #[wasm_bindgen]
-    pub fn parse_excel(data: &[u8]) -> Result<JsValue, JsError> {
-        let import_data = parse_excel_to_import_data(data)
-            .map_err(|e| JsError::new(&format!("Parsing error: {}", e)))?;
-        to_value(&import_data).map_err(|e| JsError::new(&format!("Serialization error: {}", e)))
+    pub fn parse_excel_with_config(data: &[u8], config: &str) -> Result<JsValue, JsError> {
+        let opts: ParseOptions = serde_json::from_str(config)
+            .map_err(|e| JsError::new(&format!("Invalid config: {}", e)))?;
+        serialize(&super::parse_excel_with_options(data, &opts).into_report())
+    }
+
+    /// Returns every diagnostic of the workbook as an array of
+    /// `{ severity, code, message, sheet, row, column }` objects.
+    #[wasm_bindgen]
+    pub fn validate_excel(data: &[u8]) -> Result<JsValue, JsError> {
+        serialize(&parse_excel_with_diagnostics(data).diagnostics)
+    }
+
+    /// Writes the `data` of an import (an `ImportData` object) as an IP-XACT 2022
+    /// `component.xml`.
+    #[wasm_bindgen]
+    pub fn export_ipxact(data: JsValue) -> Result<String, JsError> {
+        Ok(super::export_ipxact(&import_data(data)?))
+    }
+
+    /// Generates the SystemVerilog register blocks of `data` (an `ImportData`
+    /// object) as an array of `{ name, content }` files.
+    #[wasm_bindgen]
+    pub fn generate_sv(data: JsValue) -> Result<JsValue, JsError> {
+        let files =
+            super::generate_sv(&import_data(data)?).map_err(|e| JsError::new(&e.to_string()))?;
+        serialize(&files)
+    }
+
+    /// `generate_sv` with `RtlOptions`, e.g. `{ bus: "axi4-lite" }` for the AXI4-Lite
+    /// adapters and their testbenches.
+    #[wasm_bindgen]
+    pub fn generate_sv_with_options(data: JsValue, options: JsValue) -> Result<JsValue, JsError> {
+        let opts: RtlOptions = self::options(options)?;
+        let files = super::generate_sv_with_options(&import_data(data)?, &opts)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        serialize(&files)
+    }
+
+    /// Generates the VHDL register blocks of `data` (an `ImportData` object) and
+    /// their testbenches as an array of `{ name, content }` files.
+    #[wasm_bindgen]
+    pub fn generate_vhdl(data: JsValue) -> Result<JsValue, JsError> {
+        let files =
+            super::generate_vhdl(&import_data(data)?).map_err(|e| JsError::new(&e.to_string()))?;
+        serialize(&files)
     }
 }
 
@@ -109,37 +548,40 @@ fn convert_component_to_import_data(compo: Component) -> ImportData {
                             name: reg.name,
                             address_offset: reg.offset, // Parser ensures hex string
                             size: reg.size.parse().unwrap_or(32),
-                            description: None, // schema::Register doesn't capture description?
-                            // schema::Register def in base.rs: name, offset, size, fields.
-                            // FIELDS capture description.
-                            // Register description??
-                            // parser.rs agg includes "DESCRIPTION".
-                            // But `df_to_regs` DOES NOT extract Register Description!
-                            // checking base.rs df_to_regs:
-                            // let name = extract_str("REG")?; ...
-                            // It DOES NOT extract DESCRIPTION for the register itself.
-                            // irgen seems to miss register description or I missed it in base.rs?
-                            // base.rs lines 115-186: no generic description extraction.
-                            // But fields have description.
+                            // REG_DESCRIPTION column or the DESCRIPTION of a register-only row
+                            description: reg.desc,
                             fields: reg
                                 .fields
                                 .into_iter()
                                 .map(|f| {
+                                    // unknown values were reported by `df_to_regs`,
+                                    // default fallback better than "WO" via frontend logic
+                                    let spec = parse_access(&f.attr).unwrap_or(AccessSpec {
+                                        access: "read-write",
+                                        modified_write_value: None,
+                                        read_action: None,
+                                    });
+
                                     ImportField {
                                         name: f.name,
                                         description: Some(f.desc),
                                         bit_offset: f.offset.parse().unwrap_or(0),
                                         bit_width: f.width.parse().unwrap_or(1),
-                                        access: match f.attr.to_uppercase().as_str() {
-                                            "RW" | "READ-WRITE" => "read-write".to_string(),
-                                            "RO" | "READ-ONLY" => "read-only".to_string(),
-                                            "WO" | "WRITE-ONLY" => "write-only".to_string(),
-                                            "W1C" | "WRITE-1-TO-CLEAR" => {
-                                                "access-unused".to_string()
-                                            } // Or handle appropriately if schema supports
-                                            _ => "read-write".to_string(), // Default fallback better than "WO" via frontend logic
-                                        },
+                                        access: spec.access.to_string(),
+                                        modified_write_value: spec
+                                            .modified_write_value
+                                            .map(String::from),
+                                        read_action: spec.read_action.map(String::from),
                                         reset_value: f.reset,
+                                        enumerated_values: f
+                                            .enums
+                                            .into_iter()
+                                            .map(|e| ImportEnumeratedValue {
+                                                name: e.name,
+                                                value: e.value,
+                                                description: e.desc,
+                                            })
+                                            .collect(),
                                     }
                                 })
                                 .collect(),
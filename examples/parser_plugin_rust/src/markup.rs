@@ -0,0 +1,738 @@
+//! Register tables of Markdown and AsciiDoc documents, read with the columns of
+//! the Excel sheets. Word documents go through the same sections once `docx` has
+//! extracted them.
+
+use calamine::{Data, Range};
+use polars::prelude::*;
+
+use crate::columns::{ColumnMap, SheetRole};
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::excel::{Sheet, ToDataFrame, ROW_COLUMN};
+use crate::number::{hex, parse_u64};
+use crate::options::ParseOptions;
+use crate::parser::parse_register;
+use crate::schema::{
+    check_columns, df_to_blks, df_to_compo, df_to_regs, Block, Component, Register,
+    REGISTER_COLUMNS,
+};
+use crate::types::ImportData;
+
+/// Rows of a table searched for its header.
+const HEADER_SEARCH_ROWS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Markdown,
+    AsciiDoc,
+}
+
+pub struct Heading {
+    pub text: String,
+    /// 1 for the document title or a top-level heading
+    pub level: usize,
+    pub line: Option<usize>,
+}
+
+/// Rows of a table with their line, the header first.
+pub struct Table {
+    pub rows: Vec<(usize, Vec<String>)>,
+}
+
+/// A heading and the tables up to the next one; the tables before the first
+/// heading have none.
+pub struct Section {
+    pub heading: Option<Heading>,
+    pub tables: Vec<Table>,
+}
+
+/// Registers found under a heading, with the base address the heading ends with.
+struct HeadingBlock {
+    name: String,
+    base: Option<u64>,
+    line: Option<usize>,
+    regs: Vec<Register>,
+}
+
+/// Reads the register tables of a Markdown or AsciiDoc document.
+///
+/// Every heading with a register table under it is an address block named after
+/// the heading, its rows going through `parse_register` like a block sheet. A
+/// table with the `address_map` columns places the blocks (its `BLOCK` names the
+/// heading), one with the `version` columns gives the VLNV. Without them, a block
+/// sits at the address its heading ends with (`## UART (0x4000_0000)`), and the
+/// project is named after the document title.
+pub fn read_tables(
+    src: &str,
+    syntax: Syntax,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    read_sections(&scan(src, syntax), opts, diags)
+}
+
+/// Imports the register tables of `sections`, see [`read_tables`].
+///
+/// A table without `ADDR` and `REG` columns lists the fields of one register: its
+/// heading names the register and gives its offset (`### CTRL (0x04)`), the
+/// closest heading of a lower level names the block. A register heading without
+/// an offset follows the registers placed before it in the block.
+pub fn read_sections(
+    sections: &[Section],
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let columns = ColumnMap::new(opts, diags);
+    let title = sections
+        .iter()
+        .find_map(|s| s.heading.as_ref())
+        .map(|h| heading_base(&h.text).0);
+
+    let mut version = None;
+    let mut address_map = None;
+    let mut blocks: Vec<HeadingBlock> = Vec::new();
+
+    for (index, section) in sections.iter().enumerate() {
+        for table in &section.tables {
+            let classify = |header: &[String]| {
+                [SheetRole::Block, SheetRole::AddressMap, SheetRole::Version]
+                    .into_iter()
+                    .map(|role| (role, columns.header_score(role, header)))
+                    .fold((SheetRole::Block, 0), |best, next| {
+                        if next.1 > best.1 {
+                            next
+                        } else {
+                            best
+                        }
+                    })
+            };
+            // the header may sit below a caption row spanning the table
+            let (start, role) = (0..table.rows.len().min(HEADER_SEARCH_ROWS))
+                .map(|k| (k, classify(&table.rows[k].1)))
+                .find(|(_, role)| role.1 > 0)
+                .unwrap_or((0, (SheetRole::Block, 0)));
+            let below_caption = (start > 0).then(|| Table {
+                rows: table.rows[start..].to_vec(),
+            });
+            let table = below_caption.as_ref().unwrap_or(table);
+            let (line, header) = &table.rows[0];
+            let name = section
+                .heading
+                .as_ref()
+                .map_or(String::new(), |h| identifier(&heading_base(&h.text).0));
+            match role {
+                (_, 0) => diags.push(
+                    Diagnostic::info("ignored-table", "Table names no known column, ignored")
+                        .at(Some(*line), None),
+                ),
+                (SheetRole::Version, _) => {
+                    version = Some(to_sheet(table, &name, SheetRole::Version, &columns)?)
+                }
+                (SheetRole::AddressMap, _) => {
+                    address_map = Some(to_sheet(table, &name, SheetRole::AddressMap, &columns)?)
+                }
+                (SheetRole::Block, _) => {
+                    let Some(heading) = &section.heading else {
+                        diags.push(
+                            Diagnostic::warning(
+                                "ignored-table",
+                                "Register table before the first heading is not imported",
+                            )
+                            .at(Some(*line), None),
+                        );
+                        continue;
+                    };
+                    let names_registers = header.iter().any(|cell| {
+                        matches!(
+                            columns.resolve(SheetRole::Block, cell),
+                            Some("ADDR" | "REG")
+                        )
+                    });
+                    let (block, field_table) = if names_registers {
+                        (heading, None)
+                    } else {
+                        // the fields of the register named by the heading
+                        let (text, offset) = heading_base(&heading.text);
+                        let text = text
+                            .strip_suffix(" register")
+                            .or_else(|| text.strip_suffix(" Register"))
+                            .unwrap_or(&text);
+                        let register = identifier(text);
+                        renamed(diags, "register", text, &register, heading.line);
+                        let parent = sections[..index]
+                            .iter()
+                            .rev()
+                            .filter_map(|s| s.heading.as_ref())
+                            .find(|h| h.level < heading.level);
+                        let Some(parent) = parent else {
+                            diags.push(
+                                Diagnostic::warning(
+                                    "ignored-table",
+                                    "Table names no register and its heading is under no block heading, ignored",
+                                )
+                                .in_sheet(&name)
+                                .at(Some(*line), None),
+                            );
+                            continue;
+                        };
+                        let offset = offset.unwrap_or_else(|| {
+                            let parent_name = identifier(&heading_base(&parent.text).0);
+                            let regs = blocks
+                                .iter()
+                                .find(|b| b.name == parent_name)
+                                .map_or(&[][..], |b| &b.regs[..]);
+                            // on the next word, 32 bits unless the block is wider
+                            let next = range(regs).next_multiple_of(width(regs).max(32) / 8);
+                            diags.push(
+                                Diagnostic::fallback(
+                                    opts.strict,
+                                    "missing-offset",
+                                    format!("Register heading {} gives no offset", register),
+                                    &format!("placed at {}", hex(next)),
+                                )
+                                .in_sheet(&name)
+                                .at(Some(*line), None),
+                            );
+                            next
+                        });
+                        (parent, Some(with_register(table, &register, offset)))
+                    };
+                    let table = field_table.as_ref().unwrap_or(table);
+                    let sheet = to_sheet(table, &name, SheetRole::Block, &columns)?;
+                    let regs = if check_columns(&sheet, &REGISTER_COLUMNS, diags) {
+                        let parsed = parse_register(sheet.df.clone())?;
+                        df_to_regs(parsed, &sheet, opts, diags)?
+                    } else {
+                        vec![]
+                    };
+                    // several tables under a heading make up one block
+                    let (text, base) = heading_base(&block.text);
+                    let block_name = identifier(&text);
+                    match blocks.iter_mut().find(|b| b.name == block_name) {
+                        Some(block) => block.regs.extend(regs),
+                        None => {
+                            renamed(diags, "block", &text, &block_name, block.line);
+                            blocks.push(HeadingBlock {
+                            name: block_name,
+                            base,
+                                line: block.line,
+                                regs,
+                            })
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if blocks.is_empty() {
+        diags.push(Diagnostic::error(
+            "missing-table",
+            "No register table found under a heading",
+        ));
+        return Ok(None);
+    }
+
+    let extract_blocks = |diags: &mut Vec<Diagnostic>| -> Result<Vec<Block>, Error> {
+        if let Some(map) = &address_map {
+            let mut unused: Vec<&str> = blocks.iter().map(|b| b.name.as_str()).collect();
+            let blks = df_to_blks(map, opts, diags, |name, _| {
+                // BLOCK may repeat the heading as written
+                let key = identifier(name).to_lowercase();
+                unused.retain(|b| b.to_lowercase() != key);
+                blocks
+                    .iter()
+                    .find(|b| b.name.to_lowercase() == key)
+                    .map(|b| b.regs.clone())
+                    .ok_or_else(|| Error::NotFound(name.into()))
+            })?;
+            for name in unused {
+                let line = blocks.iter().find(|b| b.name == name).map(|b| b.line);
+                diags.push(
+                    Diagnostic::warning(
+                        "unreferenced-sheet",
+                        "Heading is not referenced by the address map and was ignored",
+                    )
+                    .in_sheet(name)
+                    .at(line.flatten(), None),
+                );
+            }
+            return Ok(blks);
+        }
+
+        Ok(blocks
+            .iter()
+            .map(
+                |HeadingBlock {
+                     name,
+                     base,
+                     line,
+                     regs,
+                 }| {
+                    if base.is_none() {
+                        diags.push(
+                            Diagnostic::info(
+                                "default-offset",
+                                "Heading gives no base address, block placed at 0x0",
+                            )
+                            .in_sheet(name)
+                            .at(*line, None),
+                        );
+                    }
+                    Block {
+                        name: name.clone(),
+                        offset: hex(base.unwrap_or(0)),
+                        range: hex(range(regs)),
+                        size: width(regs).to_string(),
+                        regs: regs.clone(),
+                    }
+                },
+            )
+            .collect())
+    };
+
+    let compo = match &version {
+        Some(sheet) => df_to_compo(sheet, diags, extract_blocks)?,
+        None => {
+            let blks = extract_blocks(diags)?;
+            Some(Component {
+                vendor: String::new(),
+                library: String::new(),
+                name: title.unwrap_or_else(|| blocks[0].name.clone()),
+                version: String::new(),
+                blks,
+            })
+        }
+    };
+    Ok(compo.map(crate::convert_component_to_import_data))
+}
+
+/// Bytes spanned by `regs`, from 0.
+fn range(regs: &[Register]) -> u64 {
+    regs.iter()
+        .filter_map(|r| {
+            let offset = parse_u64(&r.offset)?;
+            let bits: u64 = r.size.trim().parse().ok()?;
+            Some(offset + bits.div_ceil(8))
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Register width of a block: the narrowest of 8, 16, 32 and 64 bits holding
+/// every register of `regs` and its fields, 32 without any.
+fn width(regs: &[Register]) -> u64 {
+    let bits =
+        regs.iter()
+            .flat_map(|r| {
+                let ends = r.fields.iter().filter_map(|f| {
+                    Some(parse_u64(&f.offset)?.saturating_add(parse_u64(&f.width)?))
+                });
+                parse_u64(&r.size).into_iter().chain(ends)
+            })
+            .max();
+    match bits {
+        None => 32,
+        Some(bits) => bits.next_power_of_two().max(8),
+    }
+}
+
+/// Heading text without the base address it may end with: `UART (0x4000_0000)`
+/// or `UART @ 0x40000000`.
+fn heading_base(text: &str) -> (String, Option<u64>) {
+    let split = if let Some(open) = text.strip_suffix(')').and_then(|t| t.rfind('(')) {
+        Some((&text[..open], &text[open + 1..text.len() - 1]))
+    } else {
+        text.rfind('@').map(|at| (&text[..at], &text[at + 1..]))
+    };
+    match split.and_then(|(name, base)| Some((name, parse_u64(base.trim())?))) {
+        Some((name, base)) if !name.trim().is_empty() => (name.trim().to_string(), Some(base)),
+        _ => (text.trim().to_string(), None),
+    }
+}
+
+/// `text` as a block or register name: every run of characters other than letters,
+/// digits and `_` becomes one `_` (`Timer Registers` gives `Timer_Registers`), and a
+/// leading digit is prefixed with `_`.
+fn identifier(text: &str) -> String {
+    let mut name = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_end_matches('_');
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Reports a heading `text` that had to be turned into the identifier `name`.
+fn renamed(diags: &mut Vec<Diagnostic>, what: &str, text: &str, name: &str, line: Option<usize>) {
+    if text.trim() != name {
+        diags.push(
+            Diagnostic::info(
+                "renamed-heading",
+                format!(
+                    "Heading \"{}\" is not an identifier, {} named {}",
+                    text.trim(),
+                    what,
+                    name
+                ),
+            )
+            .at(line, None),
+        );
+    }
+}
+
+/// A field table with `ADDR` and `REG` columns added, set to the register of its
+/// heading on every row.
+fn with_register(table: &Table, register: &str, offset: u64) -> Table {
+    let width = table.rows[0].1.len();
+    let rows = table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, (line, cells))| {
+            let mut cells = cells.clone();
+            cells.resize(width, String::new());
+            if i == 0 {
+                cells.extend(["ADDR".to_string(), "REG".to_string()]);
+            } else {
+                cells.extend([hex(offset), register.to_string()]);
+            }
+            (*line, cells)
+        })
+        .collect();
+    Table { rows }
+}
+
+/// A table as a block, address map or version sheet named `name`, its rows
+/// numbered with their line.
+fn to_sheet(
+    table: &Table,
+    name: &str,
+    role: SheetRole,
+    columns: &ColumnMap,
+) -> Result<Sheet, Error> {
+    let width = table.rows.iter().map(|(_, r)| r.len()).max().unwrap_or(0);
+    let mut range = Range::new(
+        (0, 0),
+        (table.rows.len() as u32 - 1, width.max(1) as u32 - 1),
+    );
+    for (r, (_, row)) in table.rows.iter().enumerate() {
+        for (c, cell) in row.iter().enumerate() {
+            if !cell.is_empty() {
+                range.set_value((r as u32, c as u32), Data::String(cell.clone()));
+            }
+        }
+    }
+    let mut df = range.to_data_frame(0)?;
+    columns.apply(role, &mut df)?;
+    let lines: Vec<u32> = table.rows[1..]
+        .iter()
+        .map(|(line, _)| *line as u32)
+        .collect();
+    df.with_column(Column::new(ROW_COLUMN.into(), lines))?;
+
+    Ok(Sheet {
+        name: name.to_string(),
+        df,
+        header_row: table.rows[0].0,
+        first_col: 0,
+    })
+}
+
+/// Splits the document into sections, skipping code and comment blocks.
+fn scan(src: &str, syntax: Syntax) -> Vec<Section> {
+    let lines: Vec<&str> = src.lines().collect();
+    let mut sections = vec![Section {
+        heading: None,
+        tables: Vec::new(),
+    }];
+    let mut fence: Option<String> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let next = lines.get(i + 1).map_or("", |l| l.trim());
+
+        if let Some(open) = &fence {
+            if line.starts_with(open.as_str()) {
+                fence = None;
+            }
+            i += 1;
+            continue;
+        }
+        if let Some(open) = fence_open(line, syntax) {
+            fence = Some(open);
+            i += 1;
+            continue;
+        }
+
+        if let Some((level, text)) = heading(line, syntax) {
+            sections.push(Section {
+                heading: Some(Heading {
+                    text,
+                    level,
+                    line: Some(i + 1),
+                }),
+                tables: Vec::new(),
+            });
+            i += 1;
+            continue;
+        }
+        // setext heading, underlined with `===` or `---`
+        if syntax == Syntax::Markdown
+            && !line.is_empty()
+            && !line.contains('|')
+            && !next.is_empty()
+            && (next.chars().all(|c| c == '=') || next.chars().all(|c| c == '-'))
+        {
+            sections.push(Section {
+                heading: Some(Heading {
+                    text: inline(line),
+                    level: if next.starts_with('=') { 1 } else { 2 },
+                    line: Some(i + 1),
+                }),
+                tables: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+
+        let table = match syntax {
+            Syntax::Markdown if line.contains('|') && is_delimiter_row(next) => {
+                Some(markdown_table(&lines, &mut i))
+            }
+            Syntax::AsciiDoc if line == "|===" => Some(asciidoc_table(&lines, &mut i)),
+            _ => None,
+        };
+        match table {
+            Some(table) if !table.rows.is_empty() => {
+                sections.last_mut().unwrap().tables.push(table)
+            }
+            Some(_) => {}
+            None => i += 1,
+        }
+    }
+    sections
+}
+
+/// The closing line of a code or comment block opened by `line`.
+fn fence_open(line: &str, syntax: Syntax) -> Option<String> {
+    match syntax {
+        Syntax::Markdown => ["```", "~~~"]
+            .iter()
+            .find(|f| line.starts_with(*f))
+            .map(|f| f.to_string()),
+        Syntax::AsciiDoc => {
+            let first = line.chars().next()?;
+            (line.len() >= 4 && "-./+".contains(first) && line.chars().all(|c| c == first))
+                .then(|| line.to_string())
+        }
+    }
+}
+
+/// Level and text of a `#` heading, or a `=` one in AsciiDoc.
+fn heading(line: &str, syntax: Syntax) -> Option<(usize, String)> {
+    let marks: &[char] = match syntax {
+        Syntax::Markdown => &['#'],
+        Syntax::AsciiDoc => &['=', '#'],
+    };
+    let mark = marks.iter().find(|m| line.starts_with(**m))?;
+    let level = line.chars().take_while(|c| c == mark).count();
+    let text = &line[level..];
+    if level > 6 || !text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let text = text.trim().trim_end_matches('#').trim();
+    (!text.is_empty()).then(|| (level, inline(text)))
+}
+
+fn is_delimiter_row(line: &str) -> bool {
+    let cells = split_cells(line);
+    line.contains('-')
+        && !cells.is_empty()
+        && cells.iter().all(|c| {
+            let c = c.trim();
+            !c.is_empty() && c.trim_matches(':').chars().all(|ch| ch == '-')
+        })
+}
+
+/// Cells of a Markdown row, split on unescaped `|` outside code spans.
+fn split_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut code = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => cell.push(chars.next().unwrap()),
+            '`' => {
+                code = !code;
+                cell.push(c);
+            }
+            '|' if !code => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    // a row without trailing `|` ends with a cell
+    if !cell.trim().is_empty() {
+        cells.push(cell);
+    }
+    cells
+}
+
+fn markdown_table(lines: &[&str], i: &mut usize) -> Table {
+    let mut rows = vec![(*i + 1, cells(split_cells(lines[*i])))];
+    *i += 2;
+    while *i < lines.len() && lines[*i].contains('|') && !lines[*i].trim().is_empty() {
+        rows.push((*i + 1, cells(split_cells(lines[*i]))));
+        *i += 1;
+    }
+    Table { rows }
+}
+
+fn cells(raw: Vec<String>) -> Vec<String> {
+    raw.iter().map(|c| inline(c)).collect()
+}
+
+/// Cell or heading text without inline markup; `<br>` and AsciiDoc's ` +` break lines.
+fn inline(text: &str) -> String {
+    let text = text
+        .replace("<br />", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br>", "\n")
+        .replace(" +\n", "\n")
+        .replace('`', "");
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            // bold or italic around the whole text
+            ["**", "__", "*", "_"]
+                .iter()
+                .find_map(|m| {
+                    line.strip_prefix(m)
+                        .and_then(|l| l.strip_suffix(m))
+                        .filter(|l| !l.is_empty())
+                })
+                .unwrap_or(line)
+                .to_string()
+        })
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Column count set by a `[cols="1,2,3"]` or `[cols="3*"]` attribute line.
+fn cols_attribute(line: &str) -> Option<usize> {
+    let start = line.find("cols=")? + 5;
+    let value = line[start..].trim_start_matches('"');
+    let value = &value[..value.find('"').unwrap_or(value.len())];
+    if let Some((count, _)) = value.split_once('*') {
+        return count.trim().parse().ok();
+    }
+    Some(value.split([',', ';']).count())
+}
+
+/// A cell specifier in front of `|`: span, alignment or style (`2+`, `.^`, `a`).
+fn is_cell_spec(text: &str) -> bool {
+    !text.is_empty()
+        && text.len() <= 8
+        && text.chars().all(|c| "0123456789.+*<^>adehlmsv".contains(c))
+}
+
+fn asciidoc_table(lines: &[&str], i: &mut usize) -> Table {
+    let columns = (0..*i)
+        .rev()
+        .map(|k| lines[k].trim())
+        .find(|l| !l.is_empty())
+        .filter(|l| l.starts_with('['))
+        .and_then(cols_attribute);
+
+    // cells with the line they start on
+    let mut found: Vec<(usize, String)> = Vec::new();
+    let mut first_line_cells = 0;
+    *i += 1;
+    while *i < lines.len() && lines[*i].trim() != "|===" {
+        let line = lines[*i];
+        let mut parts = line.split('|');
+        let lead = parts.next().unwrap_or_default();
+        let rest: Vec<&str> = parts.collect();
+        if !is_cell_spec(lead.trim()) && !lead.trim().is_empty() {
+            // continuation of the previous cell
+            if let Some(last) = found.last_mut() {
+                last.1.push('\n');
+                last.1.push_str(lead.trim());
+            }
+        }
+        if first_line_cells == 0 {
+            first_line_cells = rest.len();
+        }
+        for part in rest {
+            found.push((*i + 1, part.trim().to_string()));
+        }
+        *i += 1;
+    }
+    *i += 1;
+
+    let width = columns.unwrap_or(first_line_cells).max(1);
+    let rows = found
+        .chunks(width)
+        .map(|row| {
+            let line = row[0].0;
+            (line, row.iter().map(|(_, c)| inline(c)).collect())
+        })
+        .collect();
+    Table { rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_tables_under_headings() {
+        let md = "# Chip\n\n## UART (0x4000_0000)\n\n| ADDR | REG |\n|:-----|-----|\n| 0x0  | `CTRL` \\| x |\n\n```\n# not a heading\n```\nUART2\n-----\n";
+        let sections = scan(md, Syntax::Markdown);
+        let headings: Vec<_> = sections
+            .iter()
+            .map(|s| {
+                s.heading
+                    .as_ref()
+                    .map(|h| (h.text.as_str(), h.level, h.line))
+            })
+            .collect();
+        assert_eq!(
+            headings,
+            [
+                None,
+                Some(("Chip", 1, Some(1))),
+                Some(("UART (0x4000_0000)", 2, Some(3))),
+                Some(("UART2", 2, Some(12)))
+            ]
+        );
+        let rows = &sections[2].tables[0].rows;
+        assert_eq!(
+            rows[1],
+            (7, vec!["0x0".to_string(), "CTRL | x".to_string()])
+        );
+        assert_eq!(
+            heading_base("UART (0x4000_0000)"),
+            ("UART".to_string(), Some(0x4000_0000))
+        );
+
+        let adoc = "= Chip\n\n== DMA @ 0x100\n\n[cols=\"3*\", options=\"header\"]\n|===\n|ADDR |REG |FIELD\n\n|0x0\n|CTRL\n|EN +\nenable\n|===\n";
+        let sections = scan(adoc, Syntax::AsciiDoc);
+        let rows = &sections[2].tables[0].rows;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].0, 9);
+        assert_eq!(rows[1].1, ["0x0", "CTRL", "EN\nenable"]);
+    }
+}
@@ -0,0 +1,59 @@
+/// Parses the integer spellings found in register specs: decimal, `0x`/`0b`/`0o`
+/// prefixed values and Verilog sized/unsized literals (`8'hFF`, `'b101`).
+/// Underscores are accepted as digit separators.
+pub fn parse_u64(s: &str) -> Option<u64> {
+    let s = s.trim().replace('_', "");
+    if s.is_empty() {
+        return None;
+    }
+
+    let (digits, radix) = if let Some(pos) = s.find('\'') {
+        let literal = &s[pos + 1..];
+        let literal = literal.strip_prefix(['s', 'S']).unwrap_or(literal);
+        let mut chars = literal.chars();
+        let radix = match chars.next()?.to_ascii_lowercase() {
+            'h' => 16,
+            'd' => 10,
+            'o' => 8,
+            'b' => 2,
+            _ => return None,
+        };
+        (chars.as_str().to_string(), radix)
+    } else {
+        let lower = s.to_lowercase();
+        if let Some(hex) = lower.strip_prefix("0x") {
+            (hex.to_string(), 16)
+        } else if let Some(bin) = lower.strip_prefix("0b") {
+            (bin.to_string(), 2)
+        } else if let Some(oct) = lower.strip_prefix("0o") {
+            (oct.to_string(), 8)
+        } else {
+            (lower, 10)
+        }
+    };
+
+    u64::from_str_radix(&digits, radix).ok()
+}
+
+/// `value` as the importers write addresses and reset values: `0x` and uppercase digits.
+pub fn hex(value: u64) -> String {
+    format!("0x{:X}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_u64_handles_common_spellings() {
+        assert_eq!(parse_u64("42"), Some(42));
+        assert_eq!(parse_u64(" 0x1F "), Some(31));
+        assert_eq!(parse_u64("0XFF"), Some(255));
+        assert_eq!(parse_u64("0b101"), Some(5));
+        assert_eq!(parse_u64("8'hA5"), Some(0xA5));
+        assert_eq!(parse_u64("'b11"), Some(3));
+        assert_eq!(parse_u64("32'h0000_1000"), Some(0x1000));
+        assert_eq!(parse_u64("abc"), None);
+        assert_eq!(parse_u64(""), None);
+    }
+}
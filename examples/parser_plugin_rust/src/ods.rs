@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use calamine::Dimensions;
+use roxmltree::{Document, Node};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use crate::error::Error;
+
+const TABLE_NS: &str = "urn:oasis:names:tc:opendocument:xmlns:table:1.0";
+
+/// Merged regions of every table of the .ods in `data`, by sheet name.
+///
+/// calamine reads the cells of an .ods but not their `table:number-rows-spanned` and
+/// `table:number-columns-spanned` attributes, which mark merged cells in `content.xml`.
+pub fn merge_cells(data: &[u8]) -> Result<HashMap<String, Vec<Dimensions>>, Error> {
+    let mut zip = ZipArchive::new(Cursor::new(data)).map_err(anyhow::Error::from)?;
+    let mut file = match zip.by_name("content.xml") {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(HashMap::new()),
+        Err(e) => return Err(anyhow::Error::from(e).into()),
+    };
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let content = Document::parse(&content)?;
+
+    let merges = content
+        .descendants()
+        .filter(|n| n.has_tag_name((TABLE_NS, "table")))
+        .filter_map(|table| {
+            let name = table.attribute((TABLE_NS, "name"))?;
+            Some((name.to_string(), table_merges(table)))
+        })
+        .collect();
+    Ok(merges)
+}
+
+/// Regions spanned by the cells of `table`; rows may sit in header-row or row groups
+/// and both rows and cells may be repeated.
+fn table_merges(table: Node) -> Vec<Dimensions> {
+    let rows = table.descendants().filter(|n| {
+        n.has_tag_name((TABLE_NS, "table-row"))
+            // rows of a table nested in a cell belong to that table
+            && n.ancestors()
+                .find(|a| a.has_tag_name((TABLE_NS, "table")))
+                == Some(table)
+    });
+
+    let mut regions = Vec::new();
+    let mut row: u32 = 0;
+    for node in rows {
+        let mut col: u32 = 0;
+        for cell in node.children().filter(|c| {
+            c.has_tag_name((TABLE_NS, "table-cell"))
+                || c.has_tag_name((TABLE_NS, "covered-table-cell"))
+        }) {
+            let spanned = |attr| {
+                cell.attribute((TABLE_NS, attr))
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .unwrap_or(1)
+            };
+            let (rows_spanned, cols_spanned) = (
+                spanned("number-rows-spanned"),
+                spanned("number-columns-spanned"),
+            );
+            if rows_spanned > 1 || cols_spanned > 1 {
+                regions.push(Dimensions::new(
+                    (row, col),
+                    (
+                        row.saturating_add(rows_spanned - 1),
+                        col.saturating_add(cols_spanned - 1),
+                    ),
+                ));
+            }
+            col = col.saturating_add(spanned("number-columns-repeated").max(1));
+        }
+        row = row.saturating_add(
+            node.attribute((TABLE_NS, "number-rows-repeated"))
+                .and_then(|n| n.parse::<u32>().ok())
+                .unwrap_or(1)
+                .max(1),
+        );
+    }
+    regions
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Knobs of an import, deserialized from the JSON/JS object given to the wasm exports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ParseOptions {
+    /// Turn every fallback to a default value (unparseable `WIDTH`, unknown
+    /// `ATTRIBUTE`, non-numeric `OFFSET`, ...) into an error. Meant for sign-off
+    /// imports; the lenient default suits exploratory ones.
+    pub strict: bool,
+    /// Extra header spellings per logical column, e.g. `{ "REG": ["寄存器"], "FIELD": ["位域"] }`.
+    pub columns: HashMap<String, Vec<String>>,
+    /// Extra sheet names per sheet role.
+    pub sheets: SheetAliases,
+    /// 1-based row holding the headers of every sheet; detected when not set.
+    pub header_row: Option<usize>,
+    /// How many rows from the top are scanned for the header row.
+    pub header_scan_rows: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            columns: HashMap::new(),
+            sheets: SheetAliases::default(),
+            header_row: None,
+            header_scan_rows: 10,
+        }
+    }
+}
+
+/// Names tried, before the built-in ones, for the sheets that are not named by `BLOCK`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SheetAliases {
+    pub version: Vec<String>,
+    pub address_map: Vec<String>,
+}
@@ -1,9 +1,45 @@
 use crate::error::Error;
+use crate::excel::ROW_COLUMN;
 use polars::prelude::*;
 
-pub fn parse_register(df: DataFrame) -> anyhow::Result<DataFrame, Error> {
+pub fn parse_register(mut df: DataFrame) -> anyhow::Result<DataFrame, Error> {
+    // optional columns
+    for name in ["ENUM", "REG_DESCRIPTION"] {
+        if df.column(name).is_err() {
+            let height = df.height();
+            df.with_column(Column::full_null(name.into(), height, &DataType::String))?;
+        }
+    }
+    if df.column(ROW_COLUMN).is_err() {
+        // header on row 1
+        let rows: Vec<u32> = (2..df.height() as u32 + 2).collect();
+        df.with_column(Column::new(ROW_COLUMN.into(), rows))?;
+    }
+
+    // columns describing a single field, blank on a row that only names a register
+    let field_cols = [
+        "FIELD",
+        "BIT",
+        "WIDTH",
+        "ATTRIBUTE",
+        "DEFAULT",
+        "DESCRIPTION",
+        "ENUM",
+    ];
+
     let parsed_df = df
         .lazy()
+        // a row carrying REG but no FIELD describes the register itself
+        .with_columns(&[
+            col("FIELD")
+                .fill_null(lit(""))
+                .neq(lit(""))
+                .alias("HAS_FIELD"),
+            when(col("FIELD").fill_null(lit("")).eq(lit("")))
+                .then(col("DESCRIPTION"))
+                .otherwise(lit(NULL).cast(DataType::String))
+                .alias("REG_ROW_DESC"),
+        ])
         // fullfill empty description
         .with_column(
             when(col("DESCRIPTION").is_null())
@@ -11,8 +47,49 @@ pub fn parse_register(df: DataFrame) -> anyhow::Result<DataFrame, Error> {
                 .otherwise(col("DESCRIPTION"))
                 .alias("DESCRIPTION"),
         )
-        // Unmerge cells and distribute content to each cell
-        .select([col("*").fill_null_with_strategy(FillNullStrategy::Forward(None))])
+        // a row naming its own register without ADDR would silently join the register above,
+        // remember it so `df_to_regs` reports the inherited ADDR
+        .with_column(
+            col("ADDR")
+                .is_not_null()
+                .or(col("REG").is_null())
+                .alias("ADDR_GIVEN"),
+        )
+        // Merged cells were already unmerged by the sheet loader, only ADDR and REG may be
+        // left blank for the following fields of a register; field values are never inherited
+        .with_column(col("ADDR").fill_null_with_strategy(FillNullStrategy::Forward(None)))
+        .with_column(
+            col("REG")
+                .fill_null_with_strategy(FillNullStrategy::Forward(None))
+                .over(&[col("ADDR")]),
+        )
+        // a register-only row carries no field
+        .with_columns(
+            field_cols
+                .iter()
+                .map(|c| {
+                    when(col("HAS_FIELD"))
+                        .then(col(*c))
+                        .otherwise(lit(NULL).cast(DataType::String))
+                        .alias(*c)
+                })
+                .collect::<Vec<_>>(),
+        )
+        .with_column(
+            // register description: REG_DESCRIPTION column first, then the register-only row
+            coalesce(&[
+                col("REG_DESCRIPTION")
+                    .drop_nulls()
+                    .first()
+                    .over(&[col("ADDR")]),
+                col("REG_ROW_DESC")
+                    .drop_nulls()
+                    .first()
+                    .over(&[col("ADDR")]),
+            ])
+            .alias("REG_DESC"),
+        )
+        .with_column(col(ROW_COLUMN).min().over(&[col("ADDR")]).alias("REG_ROW"))
         .with_columns(&[
             // caculate reg width by sum field width
             // "32"
@@ -100,12 +177,11 @@ pub fn parse_register(df: DataFrame) -> anyhow::Result<DataFrame, Error> {
         )
         .explode(by_name(["N_SERIES"], true))
         .filter(
-            col("IS_EXPANDABLE")
-                .and(col("N_SERIES").is_not_null())
-                .or(col("IS_EXPANDABLE")
-                    .not()
-                    .and(col("FIELD").is_not_null())
-                    .and(col("FIELD").neq(lit("")))),
+            col("HAS_FIELD").and(
+                col("IS_EXPANDABLE")
+                    .and(col("N_SERIES").is_not_null())
+                    .or(col("IS_EXPANDABLE").not()),
+            ),
         )
         .with_columns(&[
             when(col("IS_EXPANDABLE"))
@@ -151,18 +227,23 @@ pub fn parse_register(df: DataFrame) -> anyhow::Result<DataFrame, Error> {
         .agg([
             col("ADDR").first(),
             col("REG_WIDTH").first(),
+            col("REG_DESC").first(),
+            col("REG_ROW").first(),
+            col("ADDR_GIVEN").first(),
             col("FIELD"),
-            // col("BIT"),
+            col("BIT"),
             col("WIDTH"),
             col("ATTRIBUTE"),
             // col("BYTES"),
             col("BIT_OFFSET"),
             col("DEFAULT"),
             col("DESCRIPTION"),
+            col("ENUM"),
             // col("BASE_REG"),
             // col("IS_EXPANDABLE"),
             // col("BASE_ADDR"),
             col("N_SERIES"),
+            col(ROW_COLUMN),
         ])
         .collect()?;
 
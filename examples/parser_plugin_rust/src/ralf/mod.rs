@@ -0,0 +1,637 @@
+//! Synopsys RALF register descriptions.
+
+mod parser;
+
+use std::collections::HashMap;
+
+use crate::access::parse_access;
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::expr::eval;
+use crate::layout::FieldBits;
+use crate::number::hex;
+use crate::options::ParseOptions;
+use crate::types::*;
+use parser::{statements, tokenize, word, Stmt, Tok, Token};
+
+/// Properties without an import equivalent that do not change the layout.
+const IGNORED: [&str; 11] = [
+    "shared",
+    "volatile",
+    "soft_reset",
+    "hdl_path",
+    "endian",
+    "noise",
+    "attributes",
+    "initial",
+    "left_to_right",
+    "doc",
+    "bytes",
+];
+
+/// Parses RALF source into `ImportData`.
+///
+/// The top is the last `system` no other system instantiates: each block it
+/// holds (nested systems flattened, their name prefixing the blocks) becomes an
+/// address block. Without a system, every `block` is an address block at 0.
+/// Offsets are taken as written, as byte addresses.
+pub fn read_ralf(
+    src: &str,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let chars: Vec<char> = src.chars().collect();
+    let parsed = tokenize(&chars).and_then(|tokens| {
+        let mut elaborator = Elaborator {
+            src: &chars,
+            opts,
+            diags,
+            defs: HashMap::new(),
+        };
+        let top = statements(&tokens)?;
+        elaborator.define(&top)?;
+        Ok(elaborator.elaborate(&top))
+    });
+    match parsed {
+        Ok(data) => Ok(data),
+        Err((line, message)) => {
+            diags.push(Diagnostic::error("syntax-error", message).at(Some(line), None));
+            Ok(None)
+        }
+    }
+}
+
+/// `register NAME[=RENAME][[N]] [(hdl_path)] [@offset] [+incr]`
+struct Instance {
+    def: String,
+    name: String,
+    count: Option<u64>,
+    at: Option<u64>,
+    incr: Option<u64>,
+}
+
+/// A register laid out in its block.
+struct Placed {
+    offset: u64,
+    bytes: u64,
+    register: ImportRegister,
+}
+
+struct Elaborator<'a, 't> {
+    src: &'a [char],
+    opts: &'a ParseOptions,
+    diags: &'a mut Vec<Diagnostic>,
+    /// Top-level definitions by keyword and name
+    defs: HashMap<(String, String), Stmt<'t>>,
+}
+
+impl<'t> Elaborator<'_, 't> {
+    /// Reports `problem` at `line`: an error in strict mode, otherwise a warning
+    /// saying which `default` is used instead.
+    fn fallback(&mut self, code: &str, line: usize, problem: String, default: &str) {
+        let diag = Diagnostic::fallback(self.opts.strict, code, problem, default);
+        self.diags.push(diag.at(Some(line), None));
+    }
+
+    fn unsupported(&mut self, what: String, line: usize) {
+        self.diags.push(
+            Diagnostic::warning("unsupported-element", format!("{} is not imported", what))
+                .at(Some(line), None),
+        );
+    }
+
+    fn number(&mut self, text: &str, line: usize, what: &str, default: u64) -> u64 {
+        eval(text, &|_| None).unwrap_or_else(|| {
+            self.fallback(
+                "invalid-number",
+                line,
+                format!("{}: \"{}\" is not a number", what, text),
+                &format!("using {}", default),
+            );
+            default
+        })
+    }
+
+    /// Records the top-level definitions, reporting what cannot be imported.
+    fn define(&mut self, top: &[Stmt<'t>]) -> Result<(), (usize, String)> {
+        for stmt in top {
+            match stmt.keyword() {
+                Some(kind @ ("field" | "register" | "regfile" | "block" | "system")) => {
+                    let name = stmt
+                        .arg()
+                        .ok_or((stmt.line, format!("{} without a name", kind)))?;
+                    if stmt.body.is_none() {
+                        return Err((stmt.line, format!("Expected '{{' after {} {}", kind, name)));
+                    }
+                    self.defs.insert((kind.to_string(), name), stmt.clone());
+                }
+                Some(other) => self.unsupported(
+                    format!("{} {}", other, stmt.arg().unwrap_or_default()),
+                    stmt.line,
+                ),
+                None => return Err((stmt.line, "Expected a definition".to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    fn elaborate(&mut self, top: &[Stmt<'t>]) -> Option<ImportData> {
+        let instantiated: Vec<String> = top
+            .iter()
+            .filter(|s| s.keyword() == Some("system"))
+            .flat_map(|s| self.body(s))
+            .filter(|s| s.keyword() == Some("system") && s.body.is_none())
+            .filter_map(|s| s.arg())
+            .collect();
+        let system = top
+            .iter()
+            .filter(|s| s.keyword() == Some("system"))
+            .rfind(|s| !instantiated.contains(&s.arg().unwrap_or_default()));
+
+        let (name, description, address_blocks) = if let Some(system) = system {
+            let name = system.arg().unwrap_or_default();
+            let mut blocks = Vec::new();
+            self.system(system, 0, "", &mut blocks);
+            (name, self.doc(system), blocks)
+        } else {
+            let blocks: Vec<Stmt> = top
+                .iter()
+                .filter(|s| s.keyword() == Some("block"))
+                .cloned()
+                .collect();
+            let Some(first) = blocks.first() else {
+                self.diags.push(Diagnostic::error(
+                    "missing-block",
+                    "No block or system to import: define one at the top level",
+                ));
+                return None;
+            };
+            let name = first.arg().unwrap_or_default();
+            let description = self.doc(first);
+            let address_blocks = blocks
+                .iter()
+                .map(|block| {
+                    let name = block.arg().unwrap_or_default();
+                    self.address_block(block, &name, 0).0
+                })
+                .collect();
+            (name, description, address_blocks)
+        };
+
+        Some(ImportData {
+            project: ImportProject {
+                name: name.clone(),
+                description,
+                vendor: String::new(),
+                library: String::new(),
+                version: "0.1.0".to_string(),
+            },
+            memory_maps: vec![ImportMemoryMap {
+                name,
+                address_blocks,
+            }],
+        })
+    }
+
+    fn body(&self, stmt: &Stmt<'t>) -> Vec<Stmt<'t>> {
+        // bodies were split once already while tokenizing the top level, they are balanced
+        stmt.body
+            .map(|body| statements(body).unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Text of `doc { ... }` in the body of `stmt`.
+    fn doc(&self, stmt: &Stmt<'t>) -> Option<String> {
+        let doc = self
+            .body(stmt)
+            .into_iter()
+            .find(|s| s.keyword() == Some("doc"))?;
+        let text: String = match doc.body {
+            Some([first, .., last]) => self.src[first.start..last.end].iter().collect(),
+            Some([only]) => word(only).unwrap_or_default(),
+            _ => doc.arg().unwrap_or_default(),
+        };
+        let text = text.trim().to_string();
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn instance(&mut self, stmt: &Stmt<'t>) -> Instance {
+        let def = stmt.arg().unwrap_or_default();
+        let mut inst = Instance {
+            name: def.clone(),
+            def,
+            count: None,
+            at: None,
+            incr: None,
+        };
+        let words = stmt.words;
+        let mut i = 2;
+        while i < words.len() {
+            let next = words.get(i + 1).and_then(word);
+            match words[i].tok {
+                Tok::Punct('=') => {
+                    inst.name = next.unwrap_or_default();
+                    i += 2;
+                }
+                Tok::Punct('[') => {
+                    let n = next.unwrap_or_default();
+                    inst.count = Some(self.number(&n, stmt.line, &inst.name, 1));
+                    i += 3;
+                }
+                Tok::Punct('(') => {
+                    while i < words.len() && words[i].tok != Tok::Punct(')') {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                Tok::Punct('@') => {
+                    let at = next.unwrap_or_default();
+                    if at != "none" {
+                        inst.at = Some(self.number(&at, stmt.line, &inst.name, 0));
+                    }
+                    i += 2;
+                }
+                Tok::Punct('+') => {
+                    let incr = next.unwrap_or_default();
+                    inst.incr = Some(self.number(&incr, stmt.line, &inst.name, 0));
+                    i += 2;
+                }
+                _ => i += 1,
+            }
+        }
+        inst
+    }
+
+    /// Inline body of an instance, or the body of the definition it refers to.
+    fn definition(&mut self, kind: &str, stmt: &Stmt<'t>, inst: &Instance) -> Option<Stmt<'t>> {
+        if stmt.body.is_some() {
+            return Some(stmt.clone());
+        }
+        let def = self
+            .defs
+            .get(&(kind.to_string(), inst.def.clone()))
+            .cloned();
+        if def.is_none() {
+            self.diags.push(
+                Diagnostic::error(
+                    "unknown-definition",
+                    format!("{} {} is not defined", kind, inst.def),
+                )
+                .at(Some(stmt.line), None),
+            );
+        }
+        def
+    }
+
+    /// Integer property `name` (e.g. `bytes 4;`) of a body.
+    fn property(&mut self, body: &[Stmt<'t>], name: &str, what: &str) -> Option<u64> {
+        let stmt = body.iter().find(|s| s.keyword() == Some(name))?;
+        let text = stmt.arg().unwrap_or_default();
+        eval(&text, &|_| None).or_else(|| {
+            self.fallback(
+                "invalid-number",
+                stmt.line,
+                format!("{}: {} \"{}\" is not a number", what, name, text),
+                "ignored",
+            );
+            None
+        })
+    }
+
+    /// Blocks of `system` at `base`, nested systems prefixing their blocks.
+    fn system(
+        &mut self,
+        system: &Stmt<'t>,
+        base: u64,
+        prefix: &str,
+        blocks: &mut Vec<ImportAddressBlock>,
+    ) -> u64 {
+        let mut cursor = 0;
+        let mut end = 0;
+        for stmt in self.body(system) {
+            let kind = match stmt.keyword() {
+                Some(kind @ ("block" | "system")) => kind,
+                Some(keyword) if IGNORED.contains(&keyword) => continue,
+                _ => {
+                    self.unsupported(
+                        format!(
+                            "{} {}",
+                            stmt.keyword().unwrap_or_default(),
+                            stmt.arg().unwrap_or_default()
+                        ),
+                        stmt.line,
+                    );
+                    continue;
+                }
+            };
+            let inst = self.instance(&stmt);
+            let Some(def) = self.definition(kind, &stmt, &inst) else {
+                continue;
+            };
+            let offset = inst.at.unwrap_or(cursor);
+            let mut size = 0;
+            for (index, name) in names(&inst).into_iter().enumerate() {
+                let at = offset + index as u64 * inst.incr.unwrap_or(size);
+                let name = format!("{}{}", prefix, name);
+                size = if kind == "block" {
+                    let (block, size) = self.address_block(&def, &name, base + at);
+                    blocks.push(block);
+                    size
+                } else {
+                    self.system(&def, base + at, &format!("{}_", name), blocks)
+                };
+                cursor = at + size;
+                end = end.max(cursor);
+            }
+        }
+        end
+    }
+
+    /// Block `name` placed at `base`, and its size in bytes.
+    fn address_block(
+        &mut self,
+        block: &Stmt<'t>,
+        name: &str,
+        base: u64,
+    ) -> (ImportAddressBlock, u64) {
+        let mut body = self.body(block);
+        // only the first domain of a multi-interface block is imported
+        let domains: Vec<Stmt> = body
+            .iter()
+            .filter(|s| s.keyword() == Some("domain"))
+            .cloned()
+            .collect();
+        if let Some(first) = domains.first() {
+            for other in &domains[1..] {
+                self.unsupported(
+                    format!(
+                        "domain {} of block {}",
+                        other.arg().unwrap_or_default(),
+                        name
+                    ),
+                    other.line,
+                );
+            }
+            let mut inner = self.body(first);
+            inner.extend(body.into_iter().filter(|s| s.keyword() == Some("bytes")));
+            body = inner;
+        }
+
+        let bytes = self.property(&body, "bytes", name).unwrap_or_else(|| {
+            self.fallback(
+                "missing-value",
+                block.line,
+                format!("Block {} has no bytes", name),
+                "using 4",
+            );
+            4
+        });
+        let placed = self.registers(&body, bytes, "");
+        let range = placed.iter().map(|p| p.offset + p.bytes).max().unwrap_or(0);
+        let block = ImportAddressBlock {
+            name: name.to_string(),
+            base_address: hex(base),
+            range: hex(range),
+            width: (bytes * 8) as u32,
+            registers: placed
+                .into_iter()
+                .map(|p| ImportRegister {
+                    address_offset: hex(p.offset),
+                    ..p.register
+                })
+                .collect(),
+        };
+        (block, range)
+    }
+
+    /// Registers and regfiles of a block or regfile body, unplaced ones following
+    /// the previous one.
+    fn registers(&mut self, body: &[Stmt<'t>], bytes: u64, prefix: &str) -> Vec<Placed> {
+        let mut placed = Vec::new();
+        let mut cursor = 0;
+        for stmt in body {
+            let kind = match stmt.keyword() {
+                Some(kind @ ("register" | "regfile")) => kind,
+                Some(keyword) if IGNORED.contains(&keyword) => continue,
+                Some("memory") => {
+                    self.unsupported(
+                        format!("memory {}", stmt.arg().unwrap_or_default()),
+                        stmt.line,
+                    );
+                    continue;
+                }
+                Some("virtual") => {
+                    self.unsupported(
+                        format!(
+                            "virtual register {}",
+                            stmt.words.get(2).and_then(word).unwrap_or_default()
+                        ),
+                        stmt.line,
+                    );
+                    continue;
+                }
+                other => {
+                    self.unsupported(other.unwrap_or_default().to_string(), stmt.line);
+                    continue;
+                }
+            };
+            let inst = self.instance(stmt);
+            let Some(def) = self.definition(kind, stmt, &inst) else {
+                continue;
+            };
+            let offset = inst.at.unwrap_or(cursor);
+            for (index, name) in names(&inst).into_iter().enumerate() {
+                let name = format!("{}{}", prefix, name);
+                if kind == "register" {
+                    let Some(register) = self.register(&def, &name, bytes) else {
+                        break;
+                    };
+                    let at = offset + index as u64 * inst.incr.unwrap_or(register.1);
+                    cursor = at + register.1;
+                    placed.push(Placed {
+                        offset: at,
+                        bytes: register.1,
+                        register: register.0,
+                    });
+                } else {
+                    let inner = self.body(&def);
+                    let regs = self.registers(&inner, bytes, &format!("{}_", name));
+                    let size = regs.iter().map(|p| p.offset + p.bytes).max().unwrap_or(0);
+                    let at = offset + index as u64 * inst.incr.unwrap_or(size);
+                    cursor = at + size;
+                    placed.extend(regs.into_iter().map(|p| Placed {
+                        offset: at + p.offset,
+                        ..p
+                    }));
+                }
+            }
+        }
+        placed
+    }
+
+    /// Register `name` and its size in bytes; `None` when it has no usable fields.
+    fn register(
+        &mut self,
+        def: &Stmt<'t>,
+        name: &str,
+        block_bytes: u64,
+    ) -> Option<(ImportRegister, u64)> {
+        let body = self.body(def);
+        let bytes = self.property(&body, "bytes", name).unwrap_or(block_bytes);
+        let width = bytes * 8;
+        let left_to_right = body.iter().any(|s| s.keyword() == Some("left_to_right"));
+
+        let mut fields = Vec::new();
+        let mut taken = FieldBits::new(width);
+        // next free bit: from the lsb, or from the msb with `left_to_right`
+        let mut cursor = if left_to_right { width } else { 0 };
+        for stmt in &body {
+            match stmt.keyword() {
+                Some("field") => {}
+                Some(keyword) if IGNORED.contains(&keyword) => continue,
+                other => {
+                    self.unsupported(
+                        format!("{} in register {}", other.unwrap_or_default(), name),
+                        stmt.line,
+                    );
+                    continue;
+                }
+            }
+            let inst = self.instance(stmt);
+            let Some(field_def) = self.definition("field", stmt, &inst) else {
+                continue;
+            };
+            let field_body = self.body(&field_def);
+            for (index, field_name) in names(&inst).into_iter().enumerate() {
+                let what = format!("Field {}.{}", name, field_name);
+                let bits = self
+                    .property(&field_body, "bits", &what)
+                    .unwrap_or_else(|| {
+                        self.fallback(
+                            "missing-value",
+                            field_def.line,
+                            format!("{} has no bits", what),
+                            "using 1",
+                        );
+                        1
+                    });
+                let lsb = match inst.at {
+                    Some(at) => {
+                        at.saturating_add((index as u64).saturating_mul(inst.incr.unwrap_or(bits)))
+                    }
+                    None if left_to_right => cursor.saturating_sub(bits),
+                    None => cursor,
+                };
+                cursor = if left_to_right {
+                    lsb
+                } else {
+                    lsb.saturating_add(bits)
+                };
+
+                if let Err(diag) = taken.take(&what, lsb, bits) {
+                    self.diags.push(diag.at(Some(stmt.line), None));
+                    continue;
+                }
+                fields.push(self.field(&field_def, &field_body, field_name, &what, lsb, bits));
+            }
+        }
+
+        if fields.is_empty() {
+            self.diags.push(
+                Diagnostic::warning("empty-register", format!("Register {} has no fields", name))
+                    .at(Some(def.line), None),
+            );
+            return None;
+        }
+        fields.sort_by_key(|f| f.bit_offset);
+        Some((
+            ImportRegister {
+                name: name.to_string(),
+                address_offset: String::new(),
+                size: width as u32,
+                description: self.doc(def),
+                fields,
+            },
+            bytes,
+        ))
+    }
+
+    fn field(
+        &mut self,
+        def: &Stmt<'t>,
+        body: &[Stmt<'t>],
+        name: String,
+        what: &str,
+        lsb: u64,
+        bits: u64,
+    ) -> ImportField {
+        let access_stmt = body.iter().find(|s| s.keyword() == Some("access"));
+        let access = access_stmt
+            .and_then(|s| s.arg())
+            .unwrap_or("rw".to_string());
+        let spec = parse_access(&access).unwrap_or_else(|| {
+            self.fallback(
+                "unknown-access",
+                access_stmt.map_or(def.line, |s| s.line),
+                format!("{}: unknown access \"{}\"", what, access),
+                "using read-write",
+            );
+            parse_access("rw").unwrap()
+        });
+
+        let reset = self
+            .property(body, "reset", what)
+            .or_else(|| self.property(body, "hard_reset", what))
+            .unwrap_or(0);
+
+        let mut enumerated_values = Vec::new();
+        if let Some(enum_stmt) = body.iter().find(|s| s.keyword() == Some("enum")) {
+            enumerated_values = self.enums(enum_stmt, what);
+        }
+
+        ImportField {
+            name,
+            description: self.doc(def),
+            bit_offset: lsb as u32,
+            bit_width: bits as u32,
+            access: spec.access.to_string(),
+            modified_write_value: spec.modified_write_value.map(str::to_string),
+            read_action: spec.read_action.map(str::to_string),
+            reset_value: hex(reset),
+            enumerated_values,
+        }
+    }
+
+    /// `enum { A, B = 2, C }`: values count up from the previous one.
+    fn enums(&mut self, stmt: &Stmt<'t>, what: &str) -> Vec<ImportEnumeratedValue> {
+        let tokens: &[Token] = stmt.body.unwrap_or_default();
+        let mut values = Vec::new();
+        let mut next = 0;
+        for entry in tokens
+            .split(|t| t.tok == Tok::Punct(',') || t.tok == Tok::Newline)
+            .filter(|e| !e.is_empty())
+        {
+            let Some(name) = word(&entry[0]) else {
+                continue;
+            };
+            if let Some(value) = entry.get(2).and_then(word) {
+                next = self.number(&value, entry[0].line, what, next);
+            }
+            values.push(ImportEnumeratedValue {
+                name,
+                value: next.to_string(),
+                description: None,
+            });
+            next = next.saturating_add(1);
+        }
+        values
+    }
+}
+
+/// Instance names of an array (`R[4]` gives `R_0` ... `R_3`) or a single instance.
+fn names(inst: &Instance) -> Vec<String> {
+    match inst.count {
+        Some(n) => (0..n).map(|i| format!("{}_{}", inst.name, i)).collect(),
+        None => vec![inst.name.clone()],
+    }
+}
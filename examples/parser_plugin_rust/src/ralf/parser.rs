@@ -0,0 +1,229 @@
+//! Tokens and statement tree of RALF's Tcl-like syntax.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    /// Name, keyword or number as written (`'h10`, `4'b1`, `0x3F`)
+    Word(String),
+    Str(String),
+    Punct(char),
+    Newline,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub tok: Tok,
+    pub line: usize,
+    /// Character span in the source, to recover `doc { ... }` text
+    pub start: usize,
+    pub end: usize,
+}
+
+const PUNCTS: &str = "{}[]()@+=;,:";
+
+/// Splits RALF source into tokens, dropping `#` and `//` comments.
+/// Returns the line and message of the first error.
+pub fn tokenize(src: &[char]) -> Result<Vec<Token>, (usize, String)> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < src.len() {
+        let c = src[i];
+        let start = i;
+        if c == '\n' {
+            tokens.push(Token {
+                tok: Tok::Newline,
+                line,
+                start,
+                end: i + 1,
+            });
+            line += 1;
+            i += 1;
+        } else if c == '\\' && src.get(i + 1) == Some(&'\n') {
+            // line continuation
+            line += 1;
+            i += 2;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' || (c == '/' && src.get(i + 1) == Some(&'/')) {
+            while i < src.len() && src[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '"' {
+            let start_line = line;
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match src.get(i) {
+                    None => return Err((start_line, "Unterminated string".to_string())),
+                    Some('"') => break,
+                    Some('\\') if src.get(i + 1).is_some() => {
+                        text.push(src[i + 1]);
+                        i += 1;
+                    }
+                    Some(&ch) => {
+                        if ch == '\n' {
+                            line += 1;
+                        }
+                        text.push(ch);
+                    }
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token {
+                tok: Tok::Str(text),
+                line: start_line,
+                start,
+                end: i,
+            });
+        } else if PUNCTS.contains(c) {
+            tokens.push(Token {
+                tok: Tok::Punct(c),
+                line,
+                start,
+                end: i + 1,
+            });
+            i += 1;
+        } else {
+            while i < src.len() && !src[i].is_whitespace() && !PUNCTS.contains(src[i]) {
+                i += 1;
+            }
+            tokens.push(Token {
+                tok: Tok::Word(src[start..i].iter().collect()),
+                line,
+                start,
+                end: i,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// A command: its words up to `;` or the end of the line, and the tokens of the
+/// `{ ... }` body that may close it.
+#[derive(Debug, Clone)]
+pub struct Stmt<'t> {
+    pub words: &'t [Token],
+    pub body: Option<&'t [Token]>,
+    pub line: usize,
+}
+
+impl Stmt<'_> {
+    /// The first word, e.g. `register` or `bits`.
+    pub fn keyword(&self) -> Option<&str> {
+        match &self.words.first()?.tok {
+            Tok::Word(w) => Some(w),
+            _ => None,
+        }
+    }
+
+    /// Text of the word after the keyword, e.g. `8` in `bits 8;`.
+    pub fn arg(&self) -> Option<String> {
+        self.words.get(1).and_then(word)
+    }
+}
+
+pub fn word(token: &Token) -> Option<String> {
+    match &token.tok {
+        Tok::Word(w) | Tok::Str(w) => Some(w.clone()),
+        _ => None,
+    }
+}
+
+/// Splits tokens into statements; braces nest.
+pub fn statements(tokens: &[Token]) -> Result<Vec<Stmt<'_>>, (usize, String)> {
+    let mut stmts = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if matches!(tokens[i].tok, Tok::Newline | Tok::Punct(';')) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let line = tokens[i].line;
+        // brackets and parentheses may span lines
+        let mut depth = 0;
+        let mut body = None;
+        while i < tokens.len() {
+            match tokens[i].tok {
+                Tok::Punct('[' | '(') => depth += 1,
+                Tok::Punct(']' | ')') => depth -= 1,
+                Tok::Newline | Tok::Punct(';') if depth <= 0 => break,
+                Tok::Punct('}') => return Err((tokens[i].line, "Unexpected '}'".to_string())),
+                Tok::Punct('{') => {
+                    let close = matching_brace(tokens, i)?;
+                    body = Some(&tokens[i + 1..close]);
+                    break;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        let words = &tokens[start..i];
+        if let Some(body) = body {
+            i += body.len() + 2;
+        }
+        stmts.push(Stmt {
+            words: strip_newlines(words),
+            body,
+            line,
+        });
+    }
+    Ok(stmts)
+}
+
+fn strip_newlines(words: &[Token]) -> &[Token] {
+    let end = words
+        .iter()
+        .rposition(|t| t.tok != Tok::Newline)
+        .map_or(0, |p| p + 1);
+    &words[..end]
+}
+
+fn matching_brace(tokens: &[Token], open: usize) -> Result<usize, (usize, String)> {
+    let mut depth = 0;
+    for (k, token) in tokens.iter().enumerate().skip(open) {
+        match token.tok {
+            Tok::Punct('{') => depth += 1,
+            Tok::Punct('}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(k);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err((tokens[open].line, "Missing '}'".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_statements_and_bodies() {
+        let src: Vec<char> = "# uart\nregister CTRL @'h10 {\n  bytes 4; field EN { bits 1; }\n}\nblock b { bytes 4\n}"
+            .chars()
+            .collect();
+        let tokens = tokenize(&src).unwrap();
+        let stmts = statements(&tokens).unwrap();
+
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(stmts[0].keyword(), Some("register"));
+        assert_eq!(stmts[0].arg().as_deref(), Some("CTRL"));
+        assert_eq!(stmts[0].words[3].tok, Tok::Word("'h10".into()));
+        assert_eq!(stmts[0].line, 2);
+
+        let inner = statements(stmts[0].body.unwrap()).unwrap();
+        let keywords: Vec<_> = inner.iter().map(|s| s.keyword()).collect();
+        assert_eq!(keywords, [Some("bytes"), Some("field")]);
+        assert_eq!(inner[1].line, 3);
+        assert_eq!(stmts[1].line, 5);
+
+        let unbalanced: Vec<char> = "block b {\n  bytes 4;\n".chars().collect();
+        let tokens = tokenize(&unbalanced).unwrap();
+        assert_eq!(statements(&tokens).unwrap_err().0, 1);
+    }
+}
@@ -0,0 +1,573 @@
+//! OpenTitan reggen register descriptions (`*.hjson`).
+
+use std::collections::HashMap;
+
+use crate::access::{parse_access, AccessSpec};
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::expr::eval;
+use crate::hjson::{self, Node, Value};
+use crate::layout::FieldBits;
+use crate::number::hex;
+use crate::options::ParseOptions;
+use crate::types::*;
+
+const HW_ACCESS: [&str; 4] = ["hro", "hrw", "hwo", "none"];
+
+/// Reads a reggen IP description into `ImportData`.
+///
+/// Registers are placed one after the other (`skipto` and `reserved` move the
+/// offset), `multireg` is expanded into `NAME_0`, `NAME_1`, ... with its single
+/// field packed into as few registers as possible unless `compact: false`, and
+/// each `window` becomes an address block of its own. The `INTR_*` and
+/// `ALERT_TEST` registers reggen derives from `interrupt_list`/`alert_list` are
+/// generated too. When `registers` is keyed by bus interface, every interface
+/// gets its own memory map.
+pub fn read_reggen(
+    src: &str,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let root = match hjson::parse(src) {
+        Ok(root) => root,
+        Err((line, message)) => {
+            diags.push(Diagnostic::error("syntax-error", message).at(Some(line), None));
+            return Ok(None);
+        }
+    };
+    let Some(name) = root.text("name") else {
+        diags.push(
+            Diagnostic::error("missing-value", "The IP has no name").at(Some(root.line), None),
+        );
+        return Ok(None);
+    };
+
+    let mut reader = Reader {
+        opts,
+        diags,
+        params: HashMap::new(),
+        regwidth: 32,
+    };
+    reader.params(&root);
+    reader.regwidth = reader.number(&root, "regwidth", &name, 32) as u32;
+
+    let interfaces: Vec<(Option<&str>, &[Node])> = match root.get("registers").map(|r| &r.value) {
+        Some(Value::Array(items)) => vec![(None, items)],
+        Some(Value::Object(members)) => members
+            .iter()
+            .map(|(interface, items)| (Some(interface.as_str()), items.as_array().unwrap_or(&[])))
+            .collect(),
+        _ => {
+            reader.diags.push(
+                Diagnostic::error("missing-value", format!("{} has no registers", name))
+                    .at(Some(root.line), None),
+            );
+            return Ok(None);
+        }
+    };
+
+    let mut memory_maps = Vec::new();
+    for (index, (interface, items)) in interfaces.into_iter().enumerate() {
+        let mut block = Block::default();
+        if index == 0 {
+            reader.auto_registers(&root, &mut block);
+        }
+        for item in items {
+            reader.item(item, &mut block);
+        }
+
+        let mut address_blocks = vec![ImportAddressBlock {
+            name: name.clone(),
+            base_address: "0x0".to_string(),
+            range: hex(block.regs_end),
+            width: reader.regwidth,
+            registers: block.registers,
+        }];
+        address_blocks.extend(block.windows);
+        memory_maps.push(ImportMemoryMap {
+            name: interface.unwrap_or(&name).to_string(),
+            address_blocks,
+        });
+    }
+
+    Ok(Some(ImportData {
+        project: ImportProject {
+            name,
+            description: root
+                .text("one_line_desc")
+                .or_else(|| root.text("human_name")),
+            vendor: String::new(),
+            library: String::new(),
+            version: root.text("version").unwrap_or_else(|| "0.1.0".to_string()),
+        },
+        memory_maps,
+    }))
+}
+
+/// Registers and windows of one bus interface, in address order.
+#[derive(Default)]
+struct Block {
+    registers: Vec<ImportRegister>,
+    windows: Vec<ImportAddressBlock>,
+    /// Next free offset
+    offset: u64,
+    /// End of the last register
+    regs_end: u64,
+}
+
+struct Reader<'a> {
+    opts: &'a ParseOptions,
+    diags: &'a mut Vec<Diagnostic>,
+    /// Defaults of `param_list`, usable in counts and bit ranges
+    params: HashMap<String, u64>,
+    regwidth: u32,
+}
+
+impl Reader<'_> {
+    /// Reports `problem`: an error in strict mode, otherwise a warning saying
+    /// which `default` is used instead.
+    fn fallback(&mut self, code: &str, line: usize, problem: String, default: &str) {
+        let diag = Diagnostic::fallback(self.opts.strict, code, problem, default);
+        self.diags.push(diag.at(Some(line), None));
+    }
+
+    fn eval(&self, text: &str) -> Option<u64> {
+        eval(text, &|name| self.params.get(name).copied())
+    }
+
+    fn params(&mut self, root: &Node) {
+        for param in root
+            .get("param_list")
+            .and_then(Node::as_array)
+            .unwrap_or_default()
+        {
+            let (Some(name), Some(default)) = (param.text("name"), param.text("default")) else {
+                continue;
+            };
+            let value = match default.as_str() {
+                "true" => Some(1),
+                "false" => Some(0),
+                _ => self.eval(&default),
+            };
+            if let Some(value) = value {
+                self.params.insert(name, value);
+            }
+        }
+    }
+
+    /// Integer member `key` of `node`, `default` when absent or invalid.
+    fn number(&mut self, node: &Node, key: &str, what: &str, default: u64) -> u64 {
+        let Some(member) = node.get(key) else {
+            return default;
+        };
+        let text = member.as_str().unwrap_or_default().trim().to_string();
+        self.eval(&text).unwrap_or_else(|| {
+            self.fallback(
+                "invalid-number",
+                member.line,
+                format!("{}: {} \"{}\" is not a number", what, key, text),
+                &format!("using {}", default),
+            );
+            default
+        })
+    }
+
+    /// `swaccess` of `node`, `inherited` when absent; `hwaccess` is only checked.
+    fn access(&mut self, node: &Node, what: &str, inherited: AccessSpec) -> AccessSpec {
+        if let Some(hw) = node.get("hwaccess") {
+            let value = hw.as_str().unwrap_or_default().trim();
+            if !HW_ACCESS.contains(&value) {
+                self.fallback(
+                    "unknown-access",
+                    hw.line,
+                    format!("{}: unknown hwaccess \"{}\"", what, value),
+                    "ignored",
+                );
+            }
+        }
+
+        let Some(sw) = node.get("swaccess") else {
+            return inherited;
+        };
+        let value = sw.as_str().unwrap_or_default().trim();
+        let spec = match value {
+            // reads as 0, writing 1 clears
+            "r0w1c" => Some(AccessSpec {
+                access: "write-only",
+                modified_write_value: Some("oneToClear"),
+                read_action: None,
+            }),
+            "ro" | "rc" | "rw" | "rw1s" | "rw1c" | "rw0c" | "wo" => parse_access(value),
+            _ => None,
+        };
+        spec.unwrap_or_else(|| {
+            self.fallback(
+                "unknown-access",
+                sw.line,
+                format!("{}: unknown swaccess \"{}\"", what, value),
+                "using read-write",
+            );
+            parse_access("rw").unwrap()
+        })
+    }
+
+    /// The `INTR_STATE`, `INTR_ENABLE`, `INTR_TEST` and `ALERT_TEST` registers reggen
+    /// adds in front of the declared ones.
+    fn auto_registers(&mut self, root: &Node, block: &mut Block) {
+        let flag = |key: &str| root.text(key).is_some_and(|v| v == "true");
+        let interrupts = root
+            .get("interrupt_list")
+            .and_then(Node::as_array)
+            .unwrap_or_default();
+        if !interrupts.is_empty() && !flag("no_auto_intr_regs") {
+            let status = |irq: &Node| irq.text("type").is_some_and(|t| t == "status");
+            self.auto_register(
+                block,
+                "INTR_STATE",
+                "Interrupt State Register",
+                interrupts,
+                |irq| {
+                    let spec = if status(irq) { "ro" } else { "rw1c" };
+                    (parse_access(spec).unwrap(), irq.text("desc"))
+                },
+            );
+            self.auto_register(
+                block,
+                "INTR_ENABLE",
+                "Interrupt Enable Register",
+                interrupts,
+                |irq| {
+                    let desc = format!(
+                        "Enable interrupt when !!INTR_STATE.{} is set.",
+                        irq.text("name").unwrap_or_default()
+                    );
+                    (parse_access("rw").unwrap(), Some(desc))
+                },
+            );
+            self.auto_register(
+                block,
+                "INTR_TEST",
+                "Interrupt Test Register",
+                interrupts,
+                |irq| {
+                    let desc = format!(
+                        "Write 1 to force !!INTR_STATE.{} to 1.",
+                        irq.text("name").unwrap_or_default()
+                    );
+                    (parse_access("wo").unwrap(), Some(desc))
+                },
+            );
+        }
+
+        let alerts = root
+            .get("alert_list")
+            .and_then(Node::as_array)
+            .unwrap_or_default();
+        if !alerts.is_empty() && !flag("no_auto_alert_regs") {
+            self.auto_register(block, "ALERT_TEST", "Alert Test Register", alerts, |_| {
+                (
+                    parse_access("wo").unwrap(),
+                    Some("Write 1 to trigger one alert event of this kind.".to_string()),
+                )
+            });
+        }
+    }
+
+    /// One register with a field per interrupt or alert, `width` bits each.
+    fn auto_register(
+        &mut self,
+        block: &mut Block,
+        name: &str,
+        description: &str,
+        sources: &[Node],
+        spec: impl Fn(&Node) -> (AccessSpec, Option<String>),
+    ) {
+        let mut fields = Vec::new();
+        let mut offset = 0;
+        for source in sources {
+            let field_name = source.text("name").unwrap_or_default();
+            let width = self.number(source, "width", &field_name, 1) as u32;
+            let (sw, desc) = spec(source);
+            fields.push(field(&field_name, desc, offset, width, sw, 0, Vec::new()));
+            offset += width;
+        }
+        self.place(
+            block,
+            ImportRegister {
+                name: name.to_string(),
+                address_offset: String::new(),
+                size: self.regwidth,
+                description: Some(description.to_string()),
+                fields,
+            },
+        );
+    }
+
+    /// Places `register` at the current offset.
+    fn place(&mut self, block: &mut Block, mut register: ImportRegister) {
+        let bytes = (self.regwidth / 8) as u64;
+        register.address_offset = hex(block.offset);
+        block.offset += bytes;
+        block.regs_end = block.offset;
+        block.registers.push(register);
+    }
+
+    /// One entry of a `registers` list.
+    fn item(&mut self, item: &Node, block: &mut Block) {
+        let bytes = (self.regwidth / 8) as u64;
+        if let Some(skipto) = item.get("skipto") {
+            let text = skipto.as_str().unwrap_or_default().trim().to_string();
+            match self.eval(&text) {
+                Some(offset) if offset >= block.offset => block.offset = offset,
+                Some(offset) => self.diags.push(
+                    Diagnostic::error(
+                        "address-overlap",
+                        format!(
+                            "skipto {} is below the next free offset {}",
+                            hex(offset),
+                            hex(block.offset)
+                        ),
+                    )
+                    .at(Some(skipto.line), None),
+                ),
+                None => self.fallback(
+                    "invalid-number",
+                    skipto.line,
+                    format!("skipto \"{}\" is not a number", text),
+                    "ignored",
+                ),
+            }
+        } else if item.get("reserved").is_some() {
+            block.offset += self.number(item, "reserved", "reserved", 1) * bytes;
+        } else if let Some(window) = item.get("window") {
+            self.window(window, block);
+        } else if let Some(multireg) = item.get("multireg") {
+            self.multireg(multireg, block);
+        } else if item.get("sameaddr").is_some() {
+            self.diags.push(
+                Diagnostic::warning("unsupported-element", "sameaddr is not imported")
+                    .at(Some(item.line), None),
+            );
+        } else if item.get("name").is_some() {
+            if let Some(register) = self.register(item) {
+                self.place(block, register);
+            }
+        } else {
+            let keys: Vec<&str> = item
+                .as_object()
+                .unwrap_or_default()
+                .iter()
+                .map(|(k, _)| k.as_str())
+                .collect();
+            self.diags.push(
+                Diagnostic::warning(
+                    "unsupported-element",
+                    format!(
+                        "Register list entry {{{}}} is not imported",
+                        keys.join(", ")
+                    ),
+                )
+                .at(Some(item.line), None),
+            );
+        }
+    }
+
+    /// A memory window, aligned to its size rounded up to a power of two.
+    fn window(&mut self, window: &Node, block: &mut Block) {
+        let name = window.text("name").unwrap_or_default();
+        let bytes = (self.regwidth / 8) as u64;
+        let items = self.number(window, "items", &name, 1);
+        let validbits = self.number(window, "validbits", &name, self.regwidth as u64) as u32;
+        let size = items * bytes;
+        let align = size.next_power_of_two();
+        block.offset = block.offset.div_ceil(align) * align;
+        block.windows.push(ImportAddressBlock {
+            name,
+            base_address: hex(block.offset),
+            range: hex(size),
+            width: validbits,
+            registers: Vec::new(),
+        });
+        block.offset += size;
+    }
+
+    /// `count` copies of the register described by `multireg`, named `NAME_0`,
+    /// `NAME_1`, ... at consecutive offsets. A single field is packed `regwidth /
+    /// width` times per register (named `FIELD_0`, `FIELD_1`, ...) unless
+    /// `compact: false`.
+    fn multireg(&mut self, multireg: &Node, block: &mut Block) {
+        let Some(template) = self.register(multireg) else {
+            return;
+        };
+        let count = match multireg.get("count") {
+            Some(_) => self.number(multireg, "count", &template.name, 1),
+            None => {
+                self.diags.push(
+                    Diagnostic::error(
+                        "missing-value",
+                        format!("Multireg {} has no count", template.name),
+                    )
+                    .at(Some(multireg.line), None),
+                );
+                return;
+            }
+        };
+        let compact = multireg.text("compact").is_none_or(|c| c != "false");
+
+        let per_reg = match template.fields.as_slice() {
+            [only] if compact && only.bit_offset == 0 => {
+                ((self.regwidth / only.bit_width) as u64).clamp(1, count.max(1))
+            }
+            _ => 1,
+        };
+        for index in 0..count.div_ceil(per_reg) {
+            let mut register = template.clone();
+            register.name = format!("{}_{}", template.name, index);
+            if per_reg > 1 {
+                let first = index * per_reg;
+                let field = &template.fields[0];
+                register.fields = (first..count.min(first + per_reg))
+                    .map(|n| ImportField {
+                        name: format!("{}_{}", field.name, n),
+                        bit_offset: field.bit_width * (n - first) as u32,
+                        ..field.clone()
+                    })
+                    .collect();
+            }
+            self.place(block, register);
+        }
+    }
+
+    /// A register without its offset; `None` when it cannot be laid out.
+    fn register(&mut self, reg: &Node) -> Option<ImportRegister> {
+        let name = reg.text("name").unwrap_or_default();
+        let access = self.access(reg, &name, parse_access("rw").unwrap());
+        let reg_reset = self.number(reg, "resval", &name, 0);
+
+        let Some(fields) = reg.get("fields").and_then(Node::as_array) else {
+            self.diags.push(
+                Diagnostic::error("missing-value", format!("Register {} has no fields", name))
+                    .at(Some(reg.line), None),
+            );
+            return None;
+        };
+
+        let mut bits = FieldBits::new(self.regwidth as u64);
+        let mut imported = Vec::new();
+        for node in fields {
+            let field_name = node
+                .text("name")
+                .or_else(|| (fields.len() == 1).then(|| name.clone()))
+                .unwrap_or_default();
+            let what = format!("Field {}.{}", name, field_name);
+            let Some((msb, lsb)) = self.bits(node, &what) else {
+                continue;
+            };
+            let width = msb - lsb + 1;
+            if let Err(diag) = bits.take(&what, lsb, width) {
+                self.diags.push(diag.at(Some(node.line), None));
+                continue;
+            }
+
+            let sw = self.access(node, &what, access);
+            let reset = match node.text("resval").as_deref() {
+                // unknown reset
+                Some("x") => 0,
+                Some(_) => self.number(node, "resval", &what, 0),
+                None => {
+                    let mask = 1u64.checked_shl(width as u32).map_or(u64::MAX, |m| m - 1);
+                    reg_reset.checked_shr(lsb as u32).unwrap_or(0) & mask
+                }
+            };
+            let enums = self.enums(node, &what);
+            imported.push(field(
+                &field_name,
+                node.text("desc"),
+                lsb as u32,
+                width as u32,
+                sw,
+                reset,
+                enums,
+            ));
+        }
+        imported.sort_by_key(|f| f.bit_offset);
+
+        Some(ImportRegister {
+            name,
+            address_offset: String::new(),
+            size: self.regwidth,
+            description: reg.text("desc"),
+            fields: imported,
+        })
+    }
+
+    /// `bits: "msb:lsb"` or `bits: "n"`.
+    fn bits(&mut self, field: &Node, what: &str) -> Option<(u64, u64)> {
+        let text = field.text("bits").unwrap_or_default();
+        let parsed = match text.split_once(':') {
+            Some((msb, lsb)) => self.eval(msb).zip(self.eval(lsb)),
+            None => self.eval(&text).map(|bit| (bit, bit)),
+        };
+        match parsed {
+            Some((msb, lsb)) if msb >= lsb && msb < 128 => Some((msb, lsb)),
+            _ => {
+                self.diags.push(
+                    Diagnostic::error(
+                        "invalid-bits",
+                        format!("{}: bits \"{}\" is not a bit range", what, text),
+                    )
+                    .at(Some(field.line), None),
+                );
+                None
+            }
+        }
+    }
+
+    fn enums(&mut self, field: &Node, what: &str) -> Vec<ImportEnumeratedValue> {
+        let mut values = Vec::new();
+        for entry in field
+            .get("enum")
+            .and_then(Node::as_array)
+            .unwrap_or_default()
+        {
+            let name = entry.text("name").unwrap_or_default();
+            let text = entry.text("value").unwrap_or_default();
+            match self.eval(&text) {
+                Some(value) => values.push(ImportEnumeratedValue {
+                    name,
+                    value: value.to_string(),
+                    description: entry.text("desc"),
+                }),
+                None => self.fallback(
+                    "invalid-number",
+                    entry.line,
+                    format!("{}: value \"{}\" of {} is not a number", what, text, name),
+                    "not imported",
+                ),
+            }
+        }
+        values
+    }
+}
+
+fn field(
+    name: &str,
+    description: Option<String>,
+    bit_offset: u32,
+    bit_width: u32,
+    sw: AccessSpec,
+    reset: u64,
+    enumerated_values: Vec<ImportEnumeratedValue>,
+) -> ImportField {
+    ImportField {
+        name: name.to_string(),
+        description,
+        bit_offset,
+        bit_width,
+        access: sw.access.to_string(),
+        modified_write_value: sw.modified_write_value.map(str::to_string),
+        read_action: sw.read_action.map(str::to_string),
+        reset_value: hex(reset),
+        enumerated_values,
+    }
+}
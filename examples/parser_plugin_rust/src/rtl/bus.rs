@@ -0,0 +1,329 @@
+use std::fmt::Write;
+
+use super::sv::{comment, hw_port, module_header, port};
+use super::{Bus, CsrBlock, HwPort, RtlFile};
+
+/// Clock and reset of the bus: names and whether the reset is active low.
+pub fn clock_reset(bus: Bus) -> (&'static str, &'static str, bool) {
+    match bus {
+        Bus::Apb3 | Bus::Apb4 => ("pclk", "presetn", true),
+        Bus::Axi4Lite => ("aclk", "aresetn", true),
+        Bus::AhbLite => ("hclk", "hresetn", true),
+        Bus::Wishbone => ("clk_i", "rst_i", false),
+    }
+}
+
+/// Ports of the bus other than clock and reset. Addresses are byte addresses within
+/// the block, the interconnect having decoded the block itself.
+pub fn bus_ports(bus: Bus, block: &CsrBlock) -> Vec<HwPort> {
+    let aw = block.addr_width;
+    let dw = block.data_width;
+    let p = |input: bool, width: u32, name: &str| HwPort {
+        input,
+        width,
+        name: name.to_string(),
+        element: name.to_string(),
+    };
+    match bus {
+        Bus::Apb3 | Bus::Apb4 => {
+            let mut ports = vec![
+                p(true, 1, "psel"),
+                p(true, 1, "penable"),
+                p(true, 1, "pwrite"),
+                p(true, aw, "paddr"),
+                p(true, dw, "pwdata"),
+            ];
+            if bus == Bus::Apb4 {
+                ports.push(p(true, dw / 8, "pstrb"));
+                ports.push(p(true, 3, "pprot"));
+            }
+            ports.extend([
+                p(false, dw, "prdata"),
+                p(false, 1, "pready"),
+                p(false, 1, "pslverr"),
+            ]);
+            ports
+        }
+        Bus::Axi4Lite => vec![
+            p(true, 1, "awvalid"),
+            p(false, 1, "awready"),
+            p(true, aw, "awaddr"),
+            p(true, 3, "awprot"),
+            p(true, 1, "wvalid"),
+            p(false, 1, "wready"),
+            p(true, dw, "wdata"),
+            p(true, dw / 8, "wstrb"),
+            p(false, 1, "bvalid"),
+            p(true, 1, "bready"),
+            p(false, 2, "bresp"),
+            p(true, 1, "arvalid"),
+            p(false, 1, "arready"),
+            p(true, aw, "araddr"),
+            p(true, 3, "arprot"),
+            p(false, 1, "rvalid"),
+            p(true, 1, "rready"),
+            p(false, dw, "rdata"),
+            p(false, 2, "rresp"),
+        ],
+        Bus::AhbLite => vec![
+            p(true, 1, "hsel"),
+            p(true, aw, "haddr"),
+            p(true, 2, "htrans"),
+            p(true, 1, "hwrite"),
+            p(true, 3, "hsize"),
+            p(true, dw, "hwdata"),
+            p(true, 1, "hready"),
+            p(false, 1, "hreadyout"),
+            p(false, 1, "hresp"),
+            p(false, dw, "hrdata"),
+        ],
+        Bus::Wishbone => vec![
+            p(true, 1, "cyc_i"),
+            p(true, 1, "stb_i"),
+            p(true, 1, "we_i"),
+            p(true, aw, "adr_i"),
+            p(true, dw, "dat_i"),
+            p(true, dw / 8, "sel_i"),
+            p(false, dw, "dat_o"),
+            p(false, 1, "ack_o"),
+            p(false, 1, "err_o"),
+        ],
+    }
+}
+
+/// `<block>_<bus>.sv`: the register block of `block` behind a `bus` slave, with the
+/// hardware side ports passed through.
+pub fn generate_adapter(block: &CsrBlock, bus: Bus) -> RtlFile {
+    let module = format!("{}_{}", block.name, bus.suffix());
+    let (clk, rst, active_low) = clock_reset(bus);
+    let dw = block.data_width;
+    let aw = block.addr_width;
+    let lanes = dw / 8;
+    let mut out = String::new();
+
+    // output to a String cannot fail
+    let _ = writeln!(
+        out,
+        "// Generated by register_manager, do not edit.\n\
+         //\n\
+         // {} registers, {} slave: {}-bit data, {}-bit byte address.\n",
+        block.name,
+        protocol(bus),
+        dw,
+        aw
+    );
+
+    let mut ports = vec![port("input", 1, clk), port("input", 1, rst)];
+    ports.extend(bus_ports(bus, block).iter().map(hw_port));
+    for reg in &block.regs {
+        ports.push(comment(&reg.name, reg.desc.as_deref()));
+        ports.extend(reg.hw_ports().iter().map(hw_port));
+    }
+    module_header(&mut out, &module, &ports);
+
+    let _ = writeln!(out, "  logic req, req_write, rsp_error;");
+    let _ = writeln!(out, "  logic [{}:0] req_addr;", aw - 1);
+    let _ = writeln!(out, "  logic [{}:0] req_wdata, rsp_rdata;", dw - 1);
+    let _ = writeln!(out, "  logic [{}:0] req_wstrb;\n", lanes - 1);
+
+    let body = match bus {
+        Bus::Apb3 | Bus::Apb4 => {
+            let strobes = if bus == Bus::Apb4 { "pstrb" } else { "'1" };
+            format!(
+                "  // the access phase is the transfer, without wait states
+  assign req = psel && penable;
+  assign req_write = pwrite;
+  assign req_addr = paddr;
+  assign req_wdata = pwdata;
+  assign req_wstrb = {strobes};
+  assign prdata = rsp_rdata;
+  assign pready = 1'b1;
+  assign pslverr = rsp_error;
+"
+            )
+        }
+        Bus::Axi4Lite => format!(
+            "  // one transfer at a time: address and data of a write are taken in any order,
+  // a write wins over a read presented in the same cycle
+  logic aw_full, w_full, wr_go, rd_go;
+  logic [{a}:0] aw_addr;
+  logic [{d}:0] w_data;
+  logic [{s}:0] w_strb;
+
+  assign awready = !aw_full;
+  assign wready = !w_full;
+  assign wr_go = aw_full && w_full && !bvalid;
+  assign rd_go = !wr_go && arvalid && !rvalid;
+  assign arready = rd_go;
+
+  assign req = wr_go || rd_go;
+  assign req_write = wr_go;
+  assign req_addr = wr_go ? aw_addr : araddr;
+  assign req_wdata = w_data;
+  assign req_wstrb = w_strb;
+
+  always_ff @(posedge aclk or negedge aresetn) begin
+    if (!aresetn) begin
+      aw_full <= 1'b0;
+      w_full <= 1'b0;
+      aw_addr <= '0;
+      w_data <= '0;
+      w_strb <= '0;
+      bvalid <= 1'b0;
+      bresp <= 2'b00;
+      rvalid <= 1'b0;
+      rresp <= 2'b00;
+      rdata <= '0;
+    end else begin
+      if (awvalid && awready) begin
+        aw_full <= 1'b1;
+        aw_addr <= awaddr;
+      end
+      if (wvalid && wready) begin
+        w_full <= 1'b1;
+        w_data <= wdata;
+        w_strb <= wstrb;
+      end
+      if (wr_go) begin
+        aw_full <= 1'b0;
+        w_full <= 1'b0;
+        bvalid <= 1'b1;
+        bresp <= rsp_error ? 2'b10 : 2'b00;
+      end else if (bready) begin
+        bvalid <= 1'b0;
+      end
+      if (rd_go) begin
+        rvalid <= 1'b1;
+        rresp <= rsp_error ? 2'b10 : 2'b00;
+        rdata <= rsp_rdata;
+      end else if (rready) begin
+        rvalid <= 1'b0;
+      end
+    end
+  end
+",
+            a = aw - 1,
+            d = dw - 1,
+            s = lanes - 1
+        ),
+        Bus::AhbLite => {
+            let lanes_of = if lanes == 1 {
+                "  assign a_strb = 1'b1;\n".to_string()
+            } else {
+                let lsb = block.addr_lsb();
+                format!(
+                    "  always_comb begin
+    a_strb = '0;
+    for (int i = 0; i < {lanes}; i++)
+      if ((i >> hsize) == ({{{pad}'d0, haddr[{top}:0]}} >> hsize)) a_strb[i] = 1'b1;
+  end
+",
+                    pad = 32 - lsb,
+                    top = lsb - 1
+                )
+            };
+            format!(
+                "  // the register access takes the first cycle of the data phase, an error
+  // response a second one
+  logic dphase, d_write, d_error;
+  logic [{a}:0] d_addr;
+  logic [{s}:0] a_strb, d_strb;
+
+  // byte lanes of the transfer in its address phase
+{lanes_of}
+  always_ff @(posedge hclk or negedge hresetn) begin
+    if (!hresetn) begin
+      dphase <= 1'b0;
+      d_write <= 1'b0;
+      d_addr <= '0;
+      d_strb <= '0;
+      d_error <= 1'b0;
+    end else begin
+      if (hready) begin
+        dphase <= hsel && htrans[1];
+        d_write <= hwrite;
+        d_addr <= haddr;
+        d_strb <= a_strb;
+      end
+      d_error <= req && rsp_error;
+    end
+  end
+
+  assign req = dphase && !d_error;
+  assign req_write = d_write;
+  assign req_addr = d_addr;
+  assign req_wdata = hwdata;
+  assign req_wstrb = d_strb;
+  assign hrdata = rsp_rdata;
+  assign hreadyout = !(req && rsp_error);
+  assign hresp = (req && rsp_error) || d_error;
+",
+                a = aw - 1,
+                s = lanes - 1
+            )
+        }
+        Bus::Wishbone => "  // classic cycles acknowledged one clock later
+  assign req = cyc_i && stb_i && !ack_o && !err_o;
+  assign req_write = we_i;
+  assign req_addr = adr_i;
+  assign req_wdata = dat_i;
+  assign req_wstrb = sel_i;
+
+  always_ff @(posedge clk_i) begin
+    if (rst_i) begin
+      ack_o <= 1'b0;
+      err_o <= 1'b0;
+      dat_o <= '0;
+    end else begin
+      ack_o <= req && !rsp_error;
+      err_o <= req && rsp_error;
+      dat_o <= rsp_rdata;
+    end
+  end
+"
+        .to_string(),
+    };
+    let _ = writeln!(out, "{}", body);
+
+    let reset = if active_low {
+        rst.to_string()
+    } else {
+        format!("!{}", rst)
+    };
+    let mut connections = vec![format!(".clk({})", clk), format!(".rst_n({})", reset)];
+    for name in [
+        "req",
+        "req_write",
+        "req_addr",
+        "req_wdata",
+        "req_wstrb",
+        "rsp_rdata",
+        "rsp_error",
+    ] {
+        connections.push(format!(".{0}({0})", name));
+    }
+    for reg in &block.regs {
+        for p in reg.hw_ports() {
+            connections.push(format!(".{0}({0})", p.name));
+        }
+    }
+    let _ = writeln!(out, "  {}_csr u_csr (", block.name);
+    let _ = writeln!(out, "    {}", connections.join(",\n    "));
+    let _ = writeln!(out, "  );\n");
+    let _ = writeln!(out, "endmodule");
+
+    RtlFile {
+        name: format!("{}.sv", module),
+        content: out,
+    }
+}
+
+pub fn protocol(bus: Bus) -> &'static str {
+    match bus {
+        Bus::Apb3 => "APB3",
+        Bus::Apb4 => "APB4",
+        Bus::Axi4Lite => "AXI4-Lite",
+        Bus::AhbLite => "AHB-Lite",
+        Bus::Wishbone => "Wishbone B4 classic",
+    }
+}
@@ -0,0 +1,932 @@
+//! Register block RTL generated from the import data: every address block becomes
+//! a CSR module with its flops, side effects and address decoder.
+
+mod bus;
+mod sv;
+mod testbench;
+mod vhdl;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+pub use bus::generate_adapter;
+pub use sv::generate_sv;
+pub use testbench::generate_testbench;
+pub use vhdl::{generate_vhdl, generate_vhdl_testbench};
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::layout::is_reserved;
+use crate::number::parse_u64;
+use crate::types::*;
+
+/// A generated source file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RtlFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// Knobs of the RTL generation, deserialized from the JSON/JS object given to the
+/// wasm exports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RtlOptions {
+    /// Bus front end wrapped around every register block, with its testbench; only
+    /// the bus-agnostic access port when not set.
+    pub bus: Option<Bus>,
+}
+
+/// Bus protocol of a register block adapter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Bus {
+    Apb3,
+    /// APB3 with byte strobes and `PPROT`
+    Apb4,
+    #[serde(rename = "axi4-lite")]
+    Axi4Lite,
+    AhbLite,
+    /// Wishbone B4 classic cycles
+    Wishbone,
+}
+
+impl Bus {
+    /// Suffix of the adapter module and file names.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Bus::Apb3 => "apb3",
+            Bus::Apb4 => "apb4",
+            Bus::Axi4Lite => "axi4lite",
+            Bus::AhbLite => "ahblite",
+            Bus::Wishbone => "wb",
+        }
+    }
+
+    /// Whether writes can be narrower than the data width.
+    pub fn has_strobes(self) -> bool {
+        self != Bus::Apb3
+    }
+}
+
+/// Software access of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sw {
+    ReadWrite,
+    ReadOnly,
+    WriteOnly,
+    /// read-writeOnce: only the first write after reset takes effect
+    ReadWriteOnce,
+    WriteOnce,
+}
+
+/// `modifiedWriteValue` of a field: what a software write does to its bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnWrite {
+    Store,
+    OneToClear,
+    OneToSet,
+    OneToToggle,
+    ZeroToClear,
+    ZeroToSet,
+    ZeroToToggle,
+    Clear,
+    Set,
+}
+
+/// `readAction` of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnRead {
+    Keep,
+    Clear,
+    Set,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsrField {
+    pub name: String,
+    pub lsb: u32,
+    pub width: u32,
+    pub reset: u64,
+    pub sw: Sw,
+    pub on_write: OnWrite,
+    pub on_read: OnRead,
+    pub desc: Option<String>,
+}
+
+impl CsrField {
+    pub fn readable(&self) -> bool {
+        matches!(self.sw, Sw::ReadWrite | Sw::ReadOnly | Sw::ReadWriteOnce)
+    }
+
+    pub fn writable(&self) -> bool {
+        !matches!(self.sw, Sw::ReadOnly)
+    }
+
+    pub fn write_once(&self) -> bool {
+        matches!(self.sw, Sw::ReadWriteOnce | Sw::WriteOnce)
+    }
+
+    /// Whether the field has a flop; a read-only field without read side effect
+    /// shows a hardware input instead.
+    pub fn stored(&self) -> bool {
+        self.sw != Sw::ReadOnly || self.on_read != OnRead::Keep
+    }
+
+    /// Hardware sets bits that software clears (interrupt status, `W1C`, `RC`).
+    pub fn hw_set(&self) -> bool {
+        matches!(
+            self.on_write,
+            OnWrite::OneToClear | OnWrite::ZeroToClear | OnWrite::Clear
+        ) && self.writable()
+            || self.on_read == OnRead::Clear
+    }
+
+    /// Hardware clears bits that software sets (`W1S` start bits, `RS`).
+    pub fn hw_clear(&self) -> bool {
+        matches!(
+            self.on_write,
+            OnWrite::OneToSet | OnWrite::ZeroToSet | OnWrite::Set
+        ) && self.writable()
+            || self.on_read == OnRead::Set
+    }
+
+    /// Pulse telling hardware that software wrote the field, for write-only fields
+    /// that hardware cannot watch through their value.
+    pub fn write_strobe(&self) -> bool {
+        matches!(self.sw, Sw::WriteOnly | Sw::WriteOnce)
+    }
+
+    pub fn msb(&self) -> u32 {
+        self.lsb + self.width - 1
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CsrRegister {
+    pub name: String,
+    pub offset: u64,
+    pub desc: Option<String>,
+    pub fields: Vec<CsrField>,
+}
+
+impl CsrRegister {
+    /// Hardware side ports of the fields, in field order.
+    pub fn hw_ports(&self) -> Vec<HwPort> {
+        let mut ports = Vec::new();
+        for f in &self.fields {
+            let mut port = |input: bool, width: u32, suffix: &str| {
+                let element = match suffix {
+                    "q" | "d" => f.name.clone(),
+                    _ => format!("{}_{}", f.name, suffix),
+                };
+                ports.push(HwPort {
+                    input,
+                    width,
+                    name: format!("{}_{}_{}", self.name, f.name, suffix),
+                    element,
+                })
+            };
+            if f.stored() {
+                port(false, f.width, "q");
+            } else {
+                port(true, f.width, "d");
+            }
+            if f.write_strobe() {
+                port(false, 1, "we");
+            }
+            if f.hw_set() {
+                port(true, f.width, "set");
+            }
+            if f.hw_clear() {
+                port(true, f.width, "clr");
+            }
+        }
+        ports
+    }
+}
+
+/// A port between a register block and the hardware it controls.
+#[derive(Debug, Clone)]
+pub struct HwPort {
+    pub input: bool,
+    pub width: u32,
+    pub name: String,
+    /// Name within the record of its register, e.g. `en` for `ctrl_en_q`
+    pub element: String,
+}
+
+/// An address block ready for generation, with identifiers usable in
+/// SystemVerilog and VHDL and numbers checked.
+#[derive(Debug, Clone)]
+pub struct CsrBlock {
+    pub name: String,
+    pub data_width: u32,
+    /// Width of the byte address within the block
+    pub addr_width: u32,
+    pub regs: Vec<CsrRegister>,
+}
+
+impl CsrBlock {
+    /// Low address bits selecting a byte within a data word.
+    pub fn addr_lsb(&self) -> u32 {
+        (self.data_width / 8).trailing_zeros()
+    }
+}
+
+/// Reserved words of SystemVerilog (IEEE 1800-2017, Annex B) and VHDL (IEEE 1076-2008,
+/// 15.10) that a register or field may be named after.
+const KEYWORDS: &[&str] = &[
+    "abs",
+    "accept_on",
+    "access",
+    "after",
+    "alias",
+    "all",
+    "always",
+    "always_comb",
+    "always_ff",
+    "always_latch",
+    "and",
+    "architecture",
+    "array",
+    "assert",
+    "assign",
+    "assume",
+    "assume_guarantee",
+    "attribute",
+    "automatic",
+    "before",
+    "begin",
+    "bind",
+    "bins",
+    "binsof",
+    "bit",
+    "block",
+    "body",
+    "break",
+    "buf",
+    "buffer",
+    "bufif0",
+    "bufif1",
+    "bus",
+    "byte",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "chandle",
+    "checker",
+    "class",
+    "clocking",
+    "cmos",
+    "component",
+    "config",
+    "configuration",
+    "const",
+    "constant",
+    "constraint",
+    "context",
+    "continue",
+    "cover",
+    "covergroup",
+    "coverpoint",
+    "cross",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "disconnect",
+    "dist",
+    "do",
+    "downto",
+    "edge",
+    "else",
+    "elsif",
+    "end",
+    "endcase",
+    "endchecker",
+    "endclass",
+    "endclocking",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endgroup",
+    "endinterface",
+    "endmodule",
+    "endpackage",
+    "endprimitive",
+    "endprogram",
+    "endproperty",
+    "endsequence",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "entity",
+    "enum",
+    "event",
+    "eventually",
+    "exit",
+    "expect",
+    "export",
+    "extends",
+    "extern",
+    "fairness",
+    "file",
+    "final",
+    "first_match",
+    "for",
+    "force",
+    "foreach",
+    "forever",
+    "fork",
+    "forkjoin",
+    "function",
+    "generate",
+    "generic",
+    "genvar",
+    "global",
+    "group",
+    "guarded",
+    "highz0",
+    "highz1",
+    "if",
+    "iff",
+    "ifnone",
+    "ignore_bins",
+    "illegal_bins",
+    "implements",
+    "implies",
+    "import",
+    "impure",
+    "in",
+    "incdir",
+    "include",
+    "inertial",
+    "initial",
+    "inout",
+    "input",
+    "inside",
+    "instance",
+    "int",
+    "integer",
+    "interconnect",
+    "interface",
+    "intersect",
+    "is",
+    "join",
+    "join_any",
+    "join_none",
+    "label",
+    "large",
+    "let",
+    "liblist",
+    "library",
+    "linkage",
+    "literal",
+    "local",
+    "localparam",
+    "logic",
+    "longint",
+    "loop",
+    "macromodule",
+    "map",
+    "matches",
+    "medium",
+    "mod",
+    "modport",
+    "module",
+    "nand",
+    "negedge",
+    "nettype",
+    "new",
+    "next",
+    "nexttime",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "null",
+    "of",
+    "on",
+    "open",
+    "or",
+    "others",
+    "out",
+    "output",
+    "package",
+    "packed",
+    "parameter",
+    "pmos",
+    "port",
+    "posedge",
+    "postponed",
+    "primitive",
+    "priority",
+    "procedure",
+    "process",
+    "program",
+    "property",
+    "protected",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_ondetect",
+    "pulsestyle_onevent",
+    "pure",
+    "rand",
+    "randc",
+    "randcase",
+    "randsequence",
+    "range",
+    "rcmos",
+    "real",
+    "realtime",
+    "record",
+    "ref",
+    "reg",
+    "register",
+    "reject",
+    "reject_on",
+    "release",
+    "rem",
+    "repeat",
+    "report",
+    "restrict",
+    "restrict_guarantee",
+    "return",
+    "rnmos",
+    "rol",
+    "ror",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "s_always",
+    "s_eventually",
+    "s_nexttime",
+    "s_until",
+    "s_until_with",
+    "scalared",
+    "select",
+    "sequence",
+    "severity",
+    "shared",
+    "shortint",
+    "shortreal",
+    "showcancelled",
+    "signal",
+    "signed",
+    "sla",
+    "sll",
+    "small",
+    "soft",
+    "solve",
+    "specify",
+    "specparam",
+    "sra",
+    "srl",
+    "static",
+    "string",
+    "strong",
+    "strong0",
+    "strong1",
+    "struct",
+    "subtype",
+    "super",
+    "supply0",
+    "supply1",
+    "sync_accept_on",
+    "sync_reject_on",
+    "table",
+    "tagged",
+    "task",
+    "then",
+    "this",
+    "throughout",
+    "time",
+    "timeprecision",
+    "timeunit",
+    "to",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "transport",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "type",
+    "typedef",
+    "unaffected",
+    "union",
+    "unique",
+    "unique0",
+    "units",
+    "unsigned",
+    "until",
+    "until_with",
+    "untyped",
+    "use",
+    "uwire",
+    "var",
+    "variable",
+    "vectored",
+    "virtual",
+    "vmode",
+    "void",
+    "vprop",
+    "vunit",
+    "wait",
+    "wait_order",
+    "wand",
+    "weak",
+    "weak0",
+    "weak1",
+    "when",
+    "while",
+    "wildcard",
+    "wire",
+    "with",
+    "within",
+    "wor",
+    "xnor",
+    "xor",
+];
+
+/// Lower-case identifier valid in both SystemVerilog and VHDL: letters, digits and
+/// single underscores, starting with a letter.
+pub fn identifier(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let mut out = out.trim_matches('_').to_string();
+    if out.is_empty() {
+        out = "unnamed".to_string();
+    }
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out = format!("r_{}", out);
+    }
+    if KEYWORDS.contains(&out.as_str()) {
+        out.push_str("_x");
+    }
+    out
+}
+
+/// SystemVerilog of every address block of `data`: `<block>_csr.sv`, then the
+/// adapter and testbench of `opts.bus` when set.
+pub fn generate_sv_files(data: &ImportData, opts: &RtlOptions) -> Result<Vec<RtlFile>, Error> {
+    let mut files = Vec::new();
+    for block in lower(data)? {
+        files.push(generate_sv(&block));
+        if let Some(bus) = opts.bus {
+            files.push(generate_adapter(&block, bus));
+            files.push(generate_testbench(&block, bus));
+        }
+    }
+    Ok(files)
+}
+
+/// VHDL of every address block of `data`: `<block>_csr.vhd` and its testbench
+/// `<block>_csr_tb.vhd`.
+pub fn generate_vhdl_files(data: &ImportData) -> Result<Vec<RtlFile>, Error> {
+    let mut files = Vec::new();
+    for block in lower(data)? {
+        files.push(generate_vhdl(&block));
+        files.push(generate_vhdl_testbench(&block));
+    }
+    Ok(files)
+}
+
+/// Address blocks of `data` checked for generation, or an error diagnostic for
+/// every problem found.
+pub fn lower(data: &ImportData) -> Result<Vec<CsrBlock>, Error> {
+    let mut diags = Vec::new();
+    let mut blocks: Vec<CsrBlock> = Vec::new();
+    for block in data.memory_maps.iter().flat_map(|map| &map.address_blocks) {
+        let Some(lowered) = lower_block(block, &mut diags) else {
+            continue;
+        };
+        // one module and file per block
+        if blocks.iter().any(|b| b.name == lowered.name) {
+            diags.push(
+                Diagnostic::error(
+                    "duplicate-name",
+                    format!("Module name {}_csr is already taken", lowered.name),
+                )
+                .in_sheet(&block.name),
+            );
+        }
+        blocks.push(lowered);
+    }
+    if diags.is_empty() {
+        Ok(blocks)
+    } else {
+        Err(Error::Diagnostics(diags))
+    }
+}
+
+fn lower_block(block: &ImportAddressBlock, diags: &mut Vec<Diagnostic>) -> Option<CsrBlock> {
+    let errors = diags.len();
+    let mut error = |code: &str, message: String| {
+        diags.push(Diagnostic::error(code, message).in_sheet(&block.name));
+    };
+
+    let data_width = block.width;
+    if ![8, 16, 32, 64].contains(&data_width) {
+        error(
+            "unsupported-width",
+            format!("Data width {} is not 8, 16, 32 or 64 bits", data_width),
+        );
+        return None;
+    }
+    let bytes = (data_width / 8) as u64;
+
+    let mut regs = Vec::new();
+    let mut end = 0;
+    for reg in &block.registers {
+        let Some(offset) = parse_u64(&reg.address_offset) else {
+            error(
+                "invalid-number",
+                format!(
+                    "Register {}: offset \"{}\" is not a number",
+                    reg.name, reg.address_offset
+                ),
+            );
+            continue;
+        };
+        if offset % bytes != 0 {
+            error(
+                "unaligned-register",
+                format!(
+                    "Register {}: offset 0x{:X} is not aligned to the {}-bit data width",
+                    reg.name, offset, data_width
+                ),
+            );
+        }
+        if reg.size > data_width {
+            error(
+                "register-too-wide",
+                format!(
+                    "Register {}: {} bits do not fit the {}-bit data width",
+                    reg.name, reg.size, data_width
+                ),
+            );
+        }
+        if let Some(other) = regs.iter().find(|r: &&CsrRegister| r.offset == offset) {
+            error(
+                "register-overlap",
+                format!(
+                    "Register {}: offset 0x{:X} is already taken by {}",
+                    reg.name, offset, other.name
+                ),
+            );
+        }
+        end = end.max(offset.saturating_add(bytes));
+
+        let name = identifier(&reg.name);
+        if let Some(other) = regs.iter().find(|r: &&CsrRegister| r.name == name) {
+            error(
+                "duplicate-name",
+                format!(
+                    "Register {}: name {} is already taken by the register at 0x{:X}",
+                    reg.name, name, other.offset
+                ),
+            );
+        }
+
+        // like the IP-XACT export, a register without fields is one read-write field
+        let spanning = [ImportField {
+            name: reg.name.clone(),
+            description: None,
+            bit_offset: 0,
+            bit_width: reg.size,
+            access: "read-write".to_string(),
+            modified_write_value: None,
+            read_action: None,
+            reset_value: "0".to_string(),
+            enumerated_values: vec![],
+        }];
+        let reg_fields = if reg.fields.is_empty() {
+            &spanning[..]
+        } else {
+            &reg.fields[..]
+        };
+
+        let mut fields: Vec<CsrField> = Vec::new();
+        for field in reg_fields {
+            let what = format!("Field {}.{}", reg.name, field.name);
+            // in 64 bits, an offset and width near u32::MAX must not wrap into the register
+            let lsb = u64::from(field.bit_offset);
+            if field.bit_width == 0 || lsb + u64::from(field.bit_width) > u64::from(reg.size) {
+                error(
+                    "field-out-of-range",
+                    format!(
+                        "{}: bits [{}:{}] are outside the {}-bit register",
+                        what,
+                        lsb + u64::from(field.bit_width.max(1)) - 1,
+                        field.bit_offset,
+                        reg.size
+                    ),
+                );
+                continue;
+            }
+            let Some(lowered) = lower_field(field, &what, &mut error) else {
+                continue;
+            };
+            // reserved bits read as zero and need no port
+            if !lowered.stored() && is_reserved(&field.name) {
+                continue;
+            }
+            if let Some(other) = fields
+                .iter()
+                .find(|f| f.lsb <= lowered.msb() && lowered.lsb <= f.msb())
+            {
+                error(
+                    "field-overlap",
+                    format!("{}: bits overlap field {}", what, other.name),
+                );
+            } else if fields.iter().any(|f| f.name == lowered.name) {
+                error(
+                    "duplicate-name",
+                    format!("{}: name {} is already taken", what, lowered.name),
+                );
+            }
+            fields.push(lowered);
+        }
+        fields.sort_by_key(|f| f.lsb);
+
+        regs.push(CsrRegister {
+            name,
+            offset,
+            desc: reg.description.clone(),
+            fields,
+        });
+    }
+    regs.sort_by_key(|r| r.offset);
+
+    // names joined with `_` may meet: register `a_b` with field `c` and register `a`
+    // with field `b_c`, or field `en` with a set input and field `en_set`
+    let mut ports: HashMap<String, &str> = HashMap::new();
+    for reg in &regs {
+        let mut elements = HashSet::new();
+        for port in reg.hw_ports() {
+            if !elements.insert(port.element.clone()) {
+                error(
+                    "duplicate-name",
+                    format!(
+                        "Register {}: record element {} is already taken",
+                        reg.name, port.element
+                    ),
+                );
+            }
+            if let Some(other) = ports.insert(port.name.clone(), &reg.name) {
+                error(
+                    "duplicate-name",
+                    format!(
+                        "Register {}: port {} is already taken by register {}",
+                        reg.name, port.name, other
+                    ),
+                );
+            }
+        }
+    }
+
+    let range = parse_u64(&block.range).unwrap_or(0).max(end).max(bytes);
+    let addr_width = (64 - (range - 1).leading_zeros()).max(bytes.trailing_zeros() + 1);
+
+    (diags.len() == errors).then_some(CsrBlock {
+        name: identifier(&block.name),
+        data_width,
+        addr_width,
+        regs,
+    })
+}
+
+fn lower_field(
+    field: &ImportField,
+    what: &str,
+    error: &mut impl FnMut(&str, String),
+) -> Option<CsrField> {
+    let sw = match field.access.as_str() {
+        "read-write" => Sw::ReadWrite,
+        "read-only" => Sw::ReadOnly,
+        "write-only" => Sw::WriteOnly,
+        "read-writeOnce" => Sw::ReadWriteOnce,
+        "writeOnce" => Sw::WriteOnce,
+        other => {
+            error(
+                "unsupported-access",
+                format!("{}: access \"{}\" cannot be generated", what, other),
+            );
+            return None;
+        }
+    };
+    let on_write = match field.modified_write_value.as_deref() {
+        None => OnWrite::Store,
+        Some("oneToClear") => OnWrite::OneToClear,
+        Some("oneToSet") => OnWrite::OneToSet,
+        Some("oneToToggle") => OnWrite::OneToToggle,
+        Some("zeroToClear") => OnWrite::ZeroToClear,
+        Some("zeroToSet") => OnWrite::ZeroToSet,
+        Some("zeroToToggle") => OnWrite::ZeroToToggle,
+        Some("clear") => OnWrite::Clear,
+        Some("set") => OnWrite::Set,
+        Some(other) => {
+            error(
+                "unsupported-access",
+                format!(
+                    "{}: modifiedWriteValue \"{}\" cannot be generated",
+                    what, other
+                ),
+            );
+            return None;
+        }
+    };
+    let on_read = match field.read_action.as_deref() {
+        None => OnRead::Keep,
+        Some("clear") => OnRead::Clear,
+        Some("set") => OnRead::Set,
+        Some(other) => {
+            error(
+                "unsupported-access",
+                format!("{}: readAction \"{}\" cannot be generated", what, other),
+            );
+            return None;
+        }
+    };
+
+    let reset = if field.reset_value.trim().is_empty() {
+        0
+    } else if let Some(reset) = parse_u64(&field.reset_value) {
+        reset
+    } else {
+        error(
+            "invalid-number",
+            format!(
+                "{}: reset value \"{}\" is not a number",
+                what, field.reset_value
+            ),
+        );
+        return None;
+    };
+    let mask = u64::MAX >> (64 - field.bit_width.min(64));
+    if reset & !mask != 0 {
+        error(
+            "reset-too-wide",
+            format!(
+                "{}: reset value 0x{:X} does not fit in {} bits",
+                what, reset, field.bit_width
+            ),
+        );
+        return None;
+    }
+
+    Some(CsrField {
+        name: identifier(&field.name),
+        lsb: field.bit_offset,
+        width: field.bit_width,
+        reset,
+        sw,
+        on_write,
+        on_read,
+        desc: field.description.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn makes_identifiers_for_both_languages() {
+        assert_eq!(identifier("CTRL"), "ctrl");
+        assert_eq!(identifier("  Tx  Data__reg "), "tx_data_reg");
+        assert_eq!(identifier("3V3_EN"), "r_3v3_en");
+        assert_eq!(identifier("Signal"), "signal_x");
+        assert_eq!(identifier("_"), "unnamed");
+        // reserved in only one of the languages
+        for word in [
+            "interface",
+            "task",
+            "string",
+            "edge",
+            "protected",
+            "context",
+        ] {
+            assert_eq!(identifier(word), format!("{}_x", word));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::fmt::Write;
+
+use super::{CsrBlock, CsrField, CsrRegister, HwPort, OnRead, OnWrite, RtlFile};
+
+/// `<block>_csr.sv`: the registers of `block` behind a bus-agnostic access port.
+///
+/// A transfer is a cycle with `req` high. Its response (`rsp_rdata`, `rsp_error`)
+/// is combinational in that cycle and its effects, write or read side effect, take
+/// place on the next rising `clk`. `rsp_error` flags an address hitting no register.
+pub fn generate_sv(block: &CsrBlock) -> RtlFile {
+    let module = format!("{}_csr", block.name);
+    let mut out = String::new();
+    let dw = block.data_width;
+    let aw = block.addr_width;
+    let lsb = block.addr_lsb();
+
+    // output to a String cannot fail
+    let _ = writeln!(
+        out,
+        "// Generated by register_manager, do not edit.\n\
+         //\n\
+         // {} registers: {}-bit data, {}-bit byte address.\n\
+         // A transfer is a cycle with req high; rsp_rdata and rsp_error answer it in\n\
+         // the same cycle, writes and read side effects take place on the next clk edge.\n",
+        block.name, dw, aw
+    );
+
+    let mut ports = vec![
+        port("input", 1, "clk"),
+        port("input", 1, "rst_n"),
+        port("input", 1, "req"),
+        port("input", 1, "req_write"),
+        // vectors even when one bit wide, as they are indexed
+        format!("input  logic [{}:0] req_addr", aw - 1),
+        format!("input  logic [{}:0] req_wdata", dw - 1),
+        format!("input  logic [{}:0] req_wstrb", dw / 8 - 1),
+        format!("output logic [{}:0] rsp_rdata", dw - 1),
+        port("output", 1, "rsp_error"),
+    ];
+    for reg in &block.regs {
+        ports.push(comment(&reg.name, reg.desc.as_deref()));
+        ports.extend(reg.hw_ports().iter().map(hw_port));
+    }
+    module_header(&mut out, &module, &ports);
+
+    // byte lane enables as a bit mask
+    let lanes = (0..dw / 8)
+        .rev()
+        .map(|i| format!("{{8{{req_wstrb[{}]}}}}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = writeln!(out, "  logic [{}:0] wmask;", dw - 1);
+    let _ = writeln!(out, "  assign wmask = {{{}}};\n", lanes);
+
+    // address decoder
+    for reg in &block.regs {
+        let _ = writeln!(out, "  logic {0}_hit, {0}_wr, {0}_rd;", reg.name);
+        let _ = writeln!(
+            out,
+            "  assign {}_hit = req_addr[{}:{}] == {}'h{:X};",
+            reg.name,
+            aw - 1,
+            lsb,
+            aw - lsb,
+            reg.offset >> lsb
+        );
+        let _ = writeln!(
+            out,
+            "  assign {0}_wr = req && req_write && {0}_hit;",
+            reg.name
+        );
+        let _ = writeln!(
+            out,
+            "  assign {0}_rd = req && !req_write && {0}_hit;",
+            reg.name
+        );
+    }
+    let hits = block
+        .regs
+        .iter()
+        .map(|r| format!("{}_hit", r.name))
+        .collect::<Vec<_>>();
+    if hits.is_empty() {
+        let _ = writeln!(out, "  assign rsp_error = req;\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "  assign rsp_error = req && !({});\n",
+            hits.join(" || ")
+        );
+    }
+
+    for reg in &block.regs {
+        register(&mut out, reg);
+    }
+
+    // read data
+    let _ = writeln!(out, "  always_comb begin");
+    let _ = writeln!(out, "    rsp_rdata = '0;");
+    for reg in &block.regs {
+        let readable = reg
+            .fields
+            .iter()
+            .filter(|f| f.readable())
+            .collect::<Vec<_>>();
+        if readable.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "    if ({}_hit) begin", reg.name);
+        for f in readable {
+            let value = if f.stored() { "q" } else { "d" };
+            let _ = writeln!(
+                out,
+                "      rsp_rdata{} = {}_{};",
+                slice(f),
+                signal(reg, f),
+                value
+            );
+        }
+        let _ = writeln!(out, "    end");
+    }
+    let _ = writeln!(out, "  end\n");
+    let _ = writeln!(out, "endmodule");
+
+    RtlFile {
+        name: format!("{}.sv", module),
+        content: out,
+    }
+}
+
+/// Next state and flops of the stored fields of `reg`.
+fn register(out: &mut String, reg: &CsrRegister) {
+    let stored = reg.fields.iter().filter(|f| f.stored()).collect::<Vec<_>>();
+    if stored.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "  // {} at 0x{:X}", reg.name, reg.offset);
+
+    for f in &stored {
+        let name = signal(reg, f);
+        let width = range(f.width);
+        if f.desc.is_some() {
+            let _ = writeln!(out, "  {}", comment(&name, f.desc.as_deref()));
+        }
+        let _ = writeln!(out, "  logic {}{}_next;", width, name);
+        if f.writable() {
+            // a port when hardware is told about writes
+            if !f.write_strobe() {
+                let _ = writeln!(out, "  logic {}_we;", name);
+            }
+            let once = if f.write_once() {
+                let _ = writeln!(out, "  logic {}_written;", name);
+                format!(" && !{}_written", name)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                out,
+                "  assign {}_we = {}_wr && |wmask{}{};",
+                name,
+                reg.name,
+                slice(f),
+                once
+            );
+        }
+
+        // hardware clear, then software, then hardware set: an event is never
+        // lost to a simultaneous software clear
+        let _ = writeln!(out, "  always_comb begin");
+        let _ = writeln!(out, "    {0}_next = {0}_q;", name);
+        if f.hw_clear() {
+            let _ = writeln!(out, "    {0}_next = {0}_next & ~{0}_clr;", name);
+        }
+        if f.writable() {
+            let _ = writeln!(
+                out,
+                "    if ({}_we) {}_next = {};",
+                name,
+                name,
+                write_value(f, &format!("{}_next", name))
+            );
+        }
+        match f.on_read {
+            OnRead::Keep => {}
+            OnRead::Clear => {
+                let _ = writeln!(out, "    if ({}_rd) {}_next = '0;", reg.name, name);
+            }
+            OnRead::Set => {
+                let _ = writeln!(out, "    if ({}_rd) {}_next = '1;", reg.name, name);
+            }
+        }
+        if f.hw_set() {
+            let _ = writeln!(out, "    {0}_next = {0}_next | {0}_set;", name);
+        }
+        let _ = writeln!(out, "  end");
+    }
+
+    let _ = writeln!(out, "  always_ff @(posedge clk or negedge rst_n) begin");
+    let _ = writeln!(out, "    if (!rst_n) begin");
+    for f in &stored {
+        let name = signal(reg, f);
+        let _ = writeln!(out, "      {}_q <= {}'h{:X};", name, f.width, f.reset);
+        if f.write_once() {
+            let _ = writeln!(out, "      {}_written <= 1'b0;", name);
+        }
+    }
+    let _ = writeln!(out, "    end else begin");
+    for f in &stored {
+        let name = signal(reg, f);
+        let _ = writeln!(out, "      {0}_q <= {0}_next;", name);
+        if f.write_once() {
+            let _ = writeln!(out, "      if ({0}_we) {0}_written <= 1'b1;", name);
+        }
+    }
+    let _ = writeln!(out, "    end");
+    let _ = writeln!(out, "  end\n");
+}
+
+/// Value of field `f` after a software write on top of `q`.
+fn write_value(f: &CsrField, q: &str) -> String {
+    let wd = format!("req_wdata{}", slice(f));
+    let wm = format!("wmask{}", slice(f));
+    match f.on_write {
+        OnWrite::Store => format!("({} & ~{}) | ({} & {})", q, wm, wd, wm),
+        OnWrite::OneToClear => format!("{} & ~({} & {})", q, wd, wm),
+        OnWrite::OneToSet => format!("{} | ({} & {})", q, wd, wm),
+        OnWrite::OneToToggle => format!("{} ^ ({} & {})", q, wd, wm),
+        OnWrite::ZeroToClear => format!("{} & ~(~{} & {})", q, wd, wm),
+        OnWrite::ZeroToSet => format!("{} | (~{} & {})", q, wd, wm),
+        OnWrite::ZeroToToggle => format!("{} ^ (~{} & {})", q, wd, wm),
+        OnWrite::Clear => format!("{} & ~{}", q, wm),
+        OnWrite::Set => format!("{} | {}", q, wm),
+    }
+}
+
+/// `module name (` with the ANSI `ports`, comment lines taking no comma.
+pub(super) fn module_header(out: &mut String, module: &str, ports: &[String]) {
+    let last = ports.iter().rposition(|p| !p.starts_with("//"));
+    let _ = writeln!(out, "module {} (", module);
+    for (i, p) in ports.iter().enumerate() {
+        let sep = if p.starts_with("//") || Some(i) == last {
+            ""
+        } else {
+            ","
+        };
+        let _ = writeln!(out, "  {}{}", p, sep);
+    }
+    let _ = writeln!(out, ");\n");
+}
+
+/// `// name: first line of the description`
+pub(super) fn comment(name: &str, desc: Option<&str>) -> String {
+    match desc.and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty())) {
+        Some(line) => format!("// {}: {}", name, line),
+        None => format!("// {}", name),
+    }
+}
+
+fn signal(reg: &CsrRegister, f: &CsrField) -> String {
+    format!("{}_{}", reg.name, f.name)
+}
+
+pub(super) fn hw_port(p: &HwPort) -> String {
+    port(if p.input { "input" } else { "output" }, p.width, &p.name)
+}
+
+pub(super) fn port(dir: &str, width: u32, name: &str) -> String {
+    format!("{:<6} logic {}{}", dir, range(width), name)
+}
+
+pub(super) fn range(width: u32) -> String {
+    if width == 1 {
+        String::new()
+    } else {
+        format!("[{}:0] ", width - 1)
+    }
+}
+
+fn slice(f: &CsrField) -> String {
+    if f.width == 1 {
+        format!("[{}]", f.lsb)
+    } else {
+        format!("[{}:{}]", f.msb(), f.lsb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Sw;
+    use super::*;
+
+    fn field(name: &str, sw: Sw, on_write: OnWrite, on_read: OnRead) -> CsrField {
+        CsrField {
+            name: name.to_string(),
+            lsb: 4,
+            width: 4,
+            reset: 0,
+            sw,
+            on_write,
+            on_read,
+            desc: None,
+        }
+    }
+
+    #[test]
+    fn writes_side_effects_within_the_strobes() {
+        let f = field("f", Sw::ReadWrite, OnWrite::Store, OnRead::Keep);
+        assert_eq!(
+            write_value(&f, "q"),
+            "(q & ~wmask[7:4]) | (req_wdata[7:4] & wmask[7:4])"
+        );
+        let f = field("f", Sw::ReadWrite, OnWrite::ZeroToClear, OnRead::Keep);
+        assert_eq!(write_value(&f, "q"), "q & ~(~req_wdata[7:4] & wmask[7:4])");
+        let f = field("f", Sw::ReadOnly, OnWrite::Store, OnRead::Clear);
+        assert!(f.stored() && f.hw_set() && !f.hw_clear() && !f.writable());
+    }
+}
@@ -0,0 +1,486 @@
+use std::fmt::Write;
+
+use super::bus::{bus_ports, clock_reset, protocol};
+use super::sv::range;
+use super::{Bus, CsrBlock, OnRead, OnWrite, RtlFile, Sw};
+
+/// One bus transfer of a testbench with the response it expects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Access {
+    pub write: bool,
+    pub addr: u64,
+    /// Written data, or read data expected under `mask`
+    pub data: u64,
+    /// Byte lanes of a write
+    pub strb: u64,
+    pub mask: u64,
+    pub error: bool,
+    pub what: String,
+}
+
+/// Transfers checking `block` through its access port, hardware inputs held at zero:
+/// reset values, the error response of an unmapped address, clearing side effects
+/// and write/read back of the plain read-write fields, byte lane by byte lane when
+/// `strobes`.
+pub fn vectors(block: &CsrBlock, strobes: bool) -> Vec<Access> {
+    let bytes = (block.data_width / 8) as u64;
+    let lanes = (1u64 << bytes) - 1;
+    let ones = u64::MAX >> (64 - block.data_width);
+    let mut out = Vec::new();
+    let read = |addr: u64, data: u64, mask: u64, what: String| Access {
+        write: false,
+        addr,
+        data,
+        strb: 0,
+        mask,
+        error: false,
+        what,
+    };
+    let write = |addr: u64, data: u64, strb: u64, what: String| Access {
+        write: true,
+        addr,
+        data,
+        strb,
+        mask: 0,
+        error: false,
+        what,
+    };
+
+    for reg in &block.regs {
+        let readable = reg.fields.iter().filter(|f| f.readable());
+        let (mut mask, mut reset) = (0, 0);
+        for f in readable {
+            mask |= field_mask(f.lsb, f.width);
+            if f.stored() {
+                reset |= f.reset << f.lsb;
+            }
+        }
+        if mask != 0 {
+            out.push(read(reg.offset, reset, mask, format!("{} reset", reg.name)));
+        }
+    }
+
+    // one more word than there are registers holds a hole if the block has any
+    let size = 1u64.checked_shl(block.addr_width).unwrap_or(u64::MAX);
+    if let Some(hole) = (0..size)
+        .step_by(bytes as usize)
+        .take(block.regs.len() + 1)
+        .find(|a| block.regs.iter().all(|r| r.offset != *a))
+    {
+        let what = format!("unmapped 0x{:X}", hole);
+        out.push(Access {
+            error: true,
+            ..write(hole, ones, lanes, what.clone())
+        });
+        out.push(Access {
+            error: true,
+            ..read(hole, 0, 0, what)
+        });
+    }
+
+    for reg in &block.regs {
+        // the reset reads above already cleared these
+        let cleared_on_read = reg
+            .fields
+            .iter()
+            .filter(|f| f.readable() && f.on_read == OnRead::Clear && f.reset != 0)
+            .fold(0, |m, f| m | field_mask(f.lsb, f.width));
+        if cleared_on_read != 0 {
+            let what = format!("{} clear on read", reg.name);
+            out.push(read(reg.offset, 0, cleared_on_read, what));
+        }
+
+        let one_to_clear = reg
+            .fields
+            .iter()
+            .filter(|f| f.writable() && f.on_write == OnWrite::OneToClear && f.reset != 0)
+            .collect::<Vec<_>>();
+        if !one_to_clear.is_empty() {
+            let mask = one_to_clear
+                .iter()
+                .fold(0, |m, f| m | field_mask(f.lsb, f.width));
+            let what = format!("{} write 1 to clear", reg.name);
+            out.push(write(reg.offset, mask, lanes, what.clone()));
+            let readable = one_to_clear
+                .iter()
+                .filter(|f| f.readable())
+                .fold(0, |m, f| m | field_mask(f.lsb, f.width));
+            if readable != 0 {
+                out.push(read(reg.offset, 0, readable, what));
+            }
+        }
+
+        let plain = reg
+            .fields
+            .iter()
+            .filter(|f| {
+                f.sw == Sw::ReadWrite && f.on_write == OnWrite::Store && f.on_read == OnRead::Keep
+            })
+            .fold(0, |m, f| m | field_mask(f.lsb, f.width));
+        if plain != 0 {
+            let what = format!("{} write", reg.name);
+            out.push(write(reg.offset, plain, lanes, what.clone()));
+            out.push(read(reg.offset, plain, plain, what));
+            if strobes && bytes > 1 {
+                let what = format!("{} byte 0 write", reg.name);
+                out.push(write(reg.offset, 0, 1, what.clone()));
+                out.push(read(reg.offset, plain & !0xFF, plain, what));
+            }
+            let what = format!("{} clear", reg.name);
+            out.push(write(reg.offset, 0, lanes, what.clone()));
+            out.push(read(reg.offset, 0, plain, what));
+        }
+    }
+    out
+}
+
+fn field_mask(lsb: u32, width: u32) -> u64 {
+    (u64::MAX >> (64 - width)) << lsb
+}
+
+/// `<block>_<bus>_tb.sv`: a self-checking testbench of the `bus` adapter of `block`
+/// running the `vectors`, for Icarus Verilog or Verilator.
+pub fn generate_testbench(block: &CsrBlock, bus: Bus) -> RtlFile {
+    let module = format!("{}_{}", block.name, bus.suffix());
+    let tb = format!("{}_tb", module);
+    let (clk, rst, active_low) = clock_reset(bus);
+    let dw = block.data_width;
+    let aw = block.addr_width;
+    let mut out = String::new();
+
+    // output to a String cannot fail
+    let _ = writeln!(
+        out,
+        "// Generated by register_manager, do not edit.\n\
+         //\n\
+         // Self-checking testbench of the {protocol} slave of {block}, printing PASS or\n\
+         // every mismatch followed by FAIL:\n\
+         //   iverilog -g2012 -o {tb} {tb}.sv {module}.sv {block}_csr.sv && vvp {tb}\n\
+         //   verilator --binary --timing -Wno-fatal --top-module {tb} {tb}.sv {module}.sv {block}_csr.sv && obj_dir/V{tb}\n\
+         \n\
+         `timescale 1ns / 1ps\n\
+         \n\
+         module {tb};\n\
+         \x20 localparam int AW = {aw};\n\
+         \x20 localparam int DW = {dw};\n\
+         \x20 localparam int NB = {nb};\n\
+         \n\
+         \x20 logic clk = 1'b0;\n\
+         \x20 logic rst_n = 1'b0;\n\
+         \x20 int errors = 0;\n\
+         \x20 always #5 clk = ~clk;\n",
+        protocol = protocol(bus),
+        block = block.name,
+        nb = dw / 8,
+    );
+
+    let ports = bus_ports(bus, block);
+    let _ = writeln!(out, "  // bus");
+    for p in &ports {
+        if p.name == "hready" {
+            // a single slave: its own ready closes the data phase
+            let _ = writeln!(out, "  logic hready;\n  assign hready = hreadyout;");
+        } else {
+            declare(&mut out, p.input, p.width, &p.name);
+        }
+    }
+    let _ = writeln!(out, "\n  // hardware side, inputs held at zero");
+    let mut connections = vec![
+        format!(".{}(clk)", clk),
+        format!(".{}({})", rst, if active_low { "rst_n" } else { "!rst_n" }),
+    ];
+    connections.extend(ports.iter().map(|p| format!(".{0}({0})", p.name)));
+    for reg in &block.regs {
+        for p in reg.hw_ports() {
+            declare(&mut out, p.input, p.width, &p.name);
+            connections.push(format!(".{0}({0})", p.name));
+        }
+    }
+    let _ = writeln!(out, "\n  {} dut (", module);
+    let _ = writeln!(out, "    {}", connections.join(",\n    "));
+    let _ = writeln!(out, "  );\n");
+
+    out.push_str(CHECK);
+    out.push_str(&tasks(bus, block));
+
+    let _ = writeln!(out, "  initial begin");
+    let _ = writeln!(out, "    repeat (2) @(negedge clk);");
+    let _ = writeln!(out, "    rst_n = 1'b1;");
+    let _ = writeln!(out, "    @(negedge clk);");
+    for a in vectors(block, bus.has_strobes()) {
+        let hex = |n: u64, bits: u32| format!("{}'h{:X}", bits, n);
+        let error = if a.error { "1'b1" } else { "1'b0" };
+        if a.write {
+            let _ = writeln!(
+                out,
+                "    write(\"{}\", {}, {}, {}, {});",
+                a.what,
+                hex(a.addr, aw),
+                hex(a.data, dw),
+                hex(a.strb, dw / 8),
+                error
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                "    read(\"{}\", {}, {}, {}, {});",
+                a.what,
+                hex(a.addr, aw),
+                hex(a.data, dw),
+                hex(a.mask, dw),
+                error
+            );
+        }
+    }
+    let _ = writeln!(out, "    if (errors == 0) $display(\"PASS\");");
+    let _ = writeln!(out, "    else $display(\"FAIL: %0d mismatches\", errors);");
+    let _ = writeln!(out, "    $finish;");
+    let _ = writeln!(out, "  end\n");
+    let _ = writeln!(out, "endmodule");
+
+    RtlFile {
+        name: format!("{}.sv", tb),
+        content: out,
+    }
+}
+
+/// Testbench signal of a DUT port, inputs starting at zero.
+fn declare(out: &mut String, input: bool, width: u32, name: &str) {
+    let init = if input { " = '0" } else { "" };
+    let _ = writeln!(out, "  logic {}{}{};", range(width), name, init);
+}
+
+const CHECK: &str = "  task automatic check(input string what, input logic [DW-1:0] data,
+                       input logic [DW-1:0] expected, input logic [DW-1:0] mask,
+                       input logic error, input logic expected_error);
+    if (error !== expected_error) begin
+      errors++;
+      $display(\"%s: error response %0b, expected %0b\", what, error, expected_error);
+    end else if (((data ^ expected) & mask) !== '0) begin
+      errors++;
+      $display(\"%s: read 0x%h, expected 0x%h under mask 0x%h\", what, data, expected, mask);
+    end
+  endtask
+
+";
+
+/// `write` and `read` tasks of `bus`, starting and ending on a falling clock edge.
+fn tasks(bus: Bus, block: &CsrBlock) -> String {
+    let head = "  task automatic write(input string what, input logic [AW-1:0] addr,
+                       input logic [DW-1:0] data, input logic [NB-1:0] strb,
+                       input logic expected_error);
+";
+    let read_head = "  task automatic read(input string what, input logic [AW-1:0] addr,
+                      input logic [DW-1:0] expected, input logic [DW-1:0] mask,
+                      input logic expected_error);
+";
+    let (write, read) = match bus {
+        Bus::Apb3 | Bus::Apb4 => {
+            let strb = if bus == Bus::Apb4 {
+                "\n    pstrb = strb;"
+            } else {
+                ""
+            };
+            (
+                format!(
+                    "    psel = 1'b1;
+    penable = 1'b0;
+    pwrite = 1'b1;
+    paddr = addr;
+    pwdata = data;{strb}
+    @(negedge clk);
+    penable = 1'b1;
+    #1;
+    while (!pready) begin @(negedge clk); #1; end
+    check(what, '0, '0, '0, pslverr, expected_error);
+    @(negedge clk);
+    psel = 1'b0;
+    penable = 1'b0;
+"
+                ),
+                "    psel = 1'b1;
+    penable = 1'b0;
+    pwrite = 1'b0;
+    paddr = addr;
+    @(negedge clk);
+    penable = 1'b1;
+    #1;
+    while (!pready) begin @(negedge clk); #1; end
+    check(what, prdata, expected, mask, pslverr, expected_error);
+    @(negedge clk);
+    psel = 1'b0;
+    penable = 1'b0;
+"
+                .to_string(),
+            )
+        }
+        Bus::Axi4Lite => (
+            "    logic aw_done, w_done;
+    awvalid = 1'b1;
+    awaddr = addr;
+    wvalid = 1'b1;
+    wdata = data;
+    wstrb = strb;
+    do begin
+      #1;
+      aw_done = awvalid && awready;
+      w_done = wvalid && wready;
+      @(negedge clk);
+      if (aw_done) awvalid = 1'b0;
+      if (w_done) wvalid = 1'b0;
+    end while (awvalid || wvalid);
+    bready = 1'b1;
+    #1;
+    while (!bvalid) begin @(negedge clk); #1; end
+    check(what, '0, '0, '0, bresp != 2'b00, expected_error);
+    @(negedge clk);
+    bready = 1'b0;
+"
+            .to_string(),
+            "    arvalid = 1'b1;
+    araddr = addr;
+    #1;
+    while (!arready) begin @(negedge clk); #1; end
+    @(negedge clk);
+    arvalid = 1'b0;
+    rready = 1'b1;
+    #1;
+    while (!rvalid) begin @(negedge clk); #1; end
+    check(what, rdata, expected, mask, rresp != 2'b00, expected_error);
+    @(negedge clk);
+    rready = 1'b0;
+"
+            .to_string(),
+        ),
+        Bus::AhbLite => {
+            let size = (block.data_width / 8).trailing_zeros();
+            let address = |write: &str| {
+                format!(
+                    "    hsel = 1'b1;
+    htrans = 2'b10;
+    hwrite = 1'b{write};
+    haddr = addr;
+    #1;
+    while (!hreadyout) begin @(negedge clk); #1; end
+    @(negedge clk);
+    hsel = 1'b0;
+    htrans = 2'b00;
+"
+                )
+            };
+            (
+                format!(
+                    "    hsize = strb == '1 ? 3'd{size} : 3'd0;
+{}    hwdata = data;
+    #1;
+    while (!hreadyout) begin @(negedge clk); #1; end
+    check(what, '0, '0, '0, hresp, expected_error);
+    @(negedge clk);
+",
+                    address("1")
+                ),
+                format!(
+                    "    hsize = 3'd{size};
+{}    #1;
+    while (!hreadyout) begin @(negedge clk); #1; end
+    check(what, hrdata, expected, mask, hresp, expected_error);
+    @(negedge clk);
+",
+                    address("0")
+                ),
+            )
+        }
+        Bus::Wishbone => (
+            "    cyc_i = 1'b1;
+    stb_i = 1'b1;
+    we_i = 1'b1;
+    adr_i = addr;
+    dat_i = data;
+    sel_i = strb;
+    #1;
+    while (!(ack_o || err_o)) begin @(negedge clk); #1; end
+    check(what, '0, '0, '0, err_o, expected_error);
+    cyc_i = 1'b0;
+    stb_i = 1'b0;
+    @(negedge clk);
+"
+            .to_string(),
+            "    cyc_i = 1'b1;
+    stb_i = 1'b1;
+    we_i = 1'b0;
+    adr_i = addr;
+    #1;
+    while (!(ack_o || err_o)) begin @(negedge clk); #1; end
+    check(what, dat_o, expected, mask, err_o, expected_error);
+    cyc_i = 1'b0;
+    stb_i = 1'b0;
+    @(negedge clk);
+"
+            .to_string(),
+        ),
+    };
+    format!(
+        "{}{}  endtask\n\n{}{}  endtask\n\n",
+        head, write, read_head, read
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CsrField, CsrRegister};
+    use super::*;
+
+    #[test]
+    fn checks_clearing_side_effects_from_reset() {
+        let field = |name: &str, lsb: u32, on_write: OnWrite, on_read: OnRead| CsrField {
+            name: name.to_string(),
+            lsb,
+            width: 1,
+            reset: 1,
+            sw: if on_read == OnRead::Clear {
+                Sw::ReadOnly
+            } else {
+                Sw::ReadWrite
+            },
+            on_write,
+            on_read,
+            desc: None,
+        };
+        let block = CsrBlock {
+            name: "irq".to_string(),
+            data_width: 8,
+            addr_width: 1,
+            regs: vec![CsrRegister {
+                name: "stat".to_string(),
+                offset: 0,
+                desc: None,
+                fields: vec![
+                    field("done", 0, OnWrite::OneToClear, OnRead::Keep),
+                    field("ovf", 1, OnWrite::Store, OnRead::Clear),
+                ],
+            }],
+        };
+        let what: Vec<_> = vectors(&block, true)
+            .iter()
+            .map(|a| (a.write, a.addr, a.data, a.mask, a.error, a.what.clone()))
+            .collect();
+        assert_eq!(
+            what,
+            [
+                (false, 0, 0b11, 0b11, false, "stat reset".to_string()),
+                (true, 1, 0xFF, 0, true, "unmapped 0x1".to_string()),
+                (false, 1, 0, 0, true, "unmapped 0x1".to_string()),
+                (false, 0, 0, 0b10, false, "stat clear on read".to_string()),
+                (true, 0, 0b01, 0, false, "stat write 1 to clear".to_string()),
+                (
+                    false,
+                    0,
+                    0,
+                    0b01,
+                    false,
+                    "stat write 1 to clear".to_string()
+                ),
+            ]
+        );
+    }
+}
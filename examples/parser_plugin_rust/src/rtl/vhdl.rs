@@ -0,0 +1,522 @@
+use std::fmt::Write;
+
+use super::testbench::vectors;
+use super::{CsrBlock, CsrField, CsrRegister, OnRead, OnWrite, RtlFile};
+
+/// `<block>_csr.vhd`: the VHDL-2008 twin of `generate_sv`, with the same access
+/// port and behaviour. The hardware side ports are gathered in the records
+/// `<block>_in_t` and `<block>_out_t` of the package `<block>_csr_pkg`, one element
+/// per register holding one per field, e.g. `hw_out.ctrl.en` for `ctrl_en_q`.
+pub fn generate_vhdl(block: &CsrBlock) -> RtlFile {
+    let mut out = String::new();
+    let dw = block.data_width;
+    let aw = block.addr_width;
+    let lsb = block.addr_lsb();
+
+    // output to a String cannot fail
+    let _ = writeln!(
+        out,
+        "-- Generated by register_manager, do not edit.\n\
+         --\n\
+         -- {} registers: {}-bit data, {}-bit byte address.\n\
+         -- A transfer is a cycle with req high; rsp_rdata and rsp_error answer it in\n\
+         -- the same cycle, writes and read side effects take place on the next clk edge.\n",
+        block.name, dw, aw
+    );
+    package(&mut out, block);
+
+    let _ = writeln!(
+        out,
+        "library ieee;\n\
+         use ieee.std_logic_1164.all;\n\
+         use work.{0}_csr_pkg.all;\n\
+         \n\
+         entity {0}_csr is\n\
+         \x20 port (\n\
+         \x20   clk       : in  std_logic;\n\
+         \x20   rst_n     : in  std_logic;\n\
+         \x20   req       : in  std_logic;\n\
+         \x20   req_write : in  std_logic;\n\
+         \x20   req_addr  : in  std_logic_vector({1} downto 0);\n\
+         \x20   req_wdata : in  std_logic_vector({2} downto 0);\n\
+         \x20   req_wstrb : in  std_logic_vector({3} downto 0);\n\
+         \x20   rsp_rdata : out std_logic_vector({2} downto 0);\n\
+         \x20   rsp_error : out std_logic;\n\
+         \x20   hw_in     : in  {0}_in_t;\n\
+         \x20   hw_out    : out {0}_out_t\n\
+         \x20 );\n\
+         end entity;\n\
+         \n\
+         architecture rtl of {0}_csr is\n\
+         \x20 signal wmask : std_logic_vector({2} downto 0);",
+        block.name,
+        aw - 1,
+        dw - 1,
+        dw / 8 - 1
+    );
+    for reg in &block.regs {
+        let _ = writeln!(
+            out,
+            "  signal {0}_hit, {0}_wr, {0}_rd : std_logic;",
+            reg.name
+        );
+        for f in reg.fields.iter().filter(|f| f.stored()) {
+            let name = signal(reg, f);
+            let _ = writeln!(out, "  signal {0}_q, {0}_next : {1};", name, ty(f.width));
+            if f.writable() {
+                let _ = writeln!(out, "  signal {}_we : std_logic;", name);
+            }
+            if f.write_once() {
+                let _ = writeln!(out, "  signal {}_written : std_logic;", name);
+            }
+        }
+    }
+    let _ = writeln!(out, "begin");
+
+    // byte lane enables as a bit mask
+    let lanes = (0..dw / 8)
+        .rev()
+        .map(|i| format!("{} downto {} => req_wstrb({})", i * 8 + 7, i * 8, i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = writeln!(out, "  wmask <= ({});\n", lanes);
+
+    // address decoder
+    for reg in &block.regs {
+        let _ = writeln!(
+            out,
+            "  {}_hit <= '1' when req_addr({} downto {}) = {} else '0';",
+            reg.name,
+            aw - 1,
+            lsb,
+            vector(reg.offset >> lsb, aw - lsb)
+        );
+        let _ = writeln!(out, "  {0}_wr <= req and req_write and {0}_hit;", reg.name);
+        let _ = writeln!(
+            out,
+            "  {0}_rd <= req and not req_write and {0}_hit;",
+            reg.name
+        );
+    }
+    let hits = block
+        .regs
+        .iter()
+        .map(|r| format!("{}_hit", r.name))
+        .collect::<Vec<_>>();
+    if hits.is_empty() {
+        let _ = writeln!(out, "  rsp_error <= req;\n");
+    } else {
+        let _ = writeln!(out, "  rsp_error <= req and not ({});\n", hits.join(" or "));
+    }
+
+    for reg in &block.regs {
+        register(&mut out, reg);
+    }
+
+    // read data
+    let _ = writeln!(
+        out,
+        "  process (all)\n  begin\n    rsp_rdata <= (others => '0');"
+    );
+    for reg in &block.regs {
+        let readable = reg
+            .fields
+            .iter()
+            .filter(|f| f.readable())
+            .collect::<Vec<_>>();
+        if readable.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "    if {}_hit = '1' then", reg.name);
+        for f in readable {
+            let value = if f.stored() {
+                format!("{}_q", signal(reg, f))
+            } else {
+                format!("hw_in.{}.{}", reg.name, f.name)
+            };
+            let _ = writeln!(out, "      rsp_rdata{} <= {};", slice(f), value);
+        }
+        let _ = writeln!(out, "    end if;");
+    }
+    let _ = writeln!(out, "  end process;\n");
+
+    // hardware outputs
+    let outputs = block.regs.iter().flat_map(|reg| {
+        reg.hw_ports()
+            .into_iter()
+            .filter(|p| !p.input)
+            .map(move |p| (reg, p))
+    });
+    let mut any = false;
+    for (reg, p) in outputs {
+        let _ = writeln!(out, "  hw_out.{}.{} <= {};", reg.name, p.element, p.name);
+        any = true;
+    }
+    if !any {
+        let _ = writeln!(out, "  hw_out.none <= '0';");
+    }
+    let _ = writeln!(out, "end architecture;");
+
+    RtlFile {
+        name: format!("{}_csr.vhd", block.name),
+        content: out,
+    }
+}
+
+/// `<block>_csr_pkg`: the hardware side records, and `<block>_in_zero` tying every
+/// input to zero.
+fn package(out: &mut String, block: &CsrBlock) {
+    let _ = writeln!(
+        out,
+        "library ieee;\n\
+         use ieee.std_logic_1164.all;\n\
+         \n\
+         package {}_csr_pkg is",
+        block.name
+    );
+    for (input, kind) in [(true, "in"), (false, "out")] {
+        let regs = block
+            .regs
+            .iter()
+            .map(|reg| {
+                let ports = reg
+                    .hw_ports()
+                    .into_iter()
+                    .filter(|p| p.input == input)
+                    .collect::<Vec<_>>();
+                (reg, ports)
+            })
+            .filter(|(_, ports)| !ports.is_empty())
+            .collect::<Vec<_>>();
+        for (reg, ports) in &regs {
+            let _ = writeln!(
+                out,
+                "  type {}_{}_{}_t is record",
+                block.name, reg.name, kind
+            );
+            for p in ports {
+                let _ = writeln!(out, "    {} : {};", p.element, ty(p.width));
+            }
+            let _ = writeln!(out, "  end record;");
+        }
+        let _ = writeln!(out, "  type {}_{}_t is record", block.name, kind);
+        if regs.is_empty() {
+            // a record needs an element
+            let _ = writeln!(out, "    none : std_logic;");
+        }
+        for (reg, _) in &regs {
+            let _ = writeln!(out, "    {0} : {1}_{0}_{2}_t;", reg.name, block.name, kind);
+        }
+        let _ = writeln!(out, "  end record;\n");
+
+        if input {
+            let value = if regs.is_empty() {
+                "(none => '0')".to_string()
+            } else {
+                aggregate(regs.iter().map(|(reg, ports)| {
+                    let elements = aggregate(
+                        ports
+                            .iter()
+                            .map(|p| format!("{} => {}", p.element, zero(p.width))),
+                    );
+                    format!("{} => {}", reg.name, elements)
+                }))
+            };
+            let _ = writeln!(
+                out,
+                "  constant {0}_in_zero : {0}_in_t := {1};\n",
+                block.name, value
+            );
+        }
+    }
+    let _ = writeln!(out, "end package;\n");
+}
+
+/// Next state and flops of the stored fields of `reg`.
+fn register(out: &mut String, reg: &CsrRegister) {
+    let stored = reg.fields.iter().filter(|f| f.stored()).collect::<Vec<_>>();
+    if stored.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "  -- {} at 0x{:X}", reg.name, reg.offset);
+
+    for f in &stored {
+        let name = signal(reg, f);
+        if let Some(desc) = first_line(f.desc.as_deref()) {
+            let _ = writeln!(out, "  -- {}: {}", name, desc);
+        }
+        if f.writable() {
+            let strobe = if f.width == 1 {
+                format!("wmask({})", f.lsb)
+            } else {
+                format!("(or wmask({} downto {}))", f.msb(), f.lsb)
+            };
+            let once = if f.write_once() {
+                format!(" and not {}_written", name)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                out,
+                "  {}_we <= {}_wr and {}{};",
+                name, reg.name, strobe, once
+            );
+        }
+
+        // hardware clear, software write, read action, then hardware set, as in the
+        // SystemVerilog
+        let _ = writeln!(out, "  process (all)");
+        let _ = writeln!(out, "    variable v : {};", ty(f.width));
+        let _ = writeln!(out, "  begin");
+        let _ = writeln!(out, "    v := {}_q;", name);
+        let hw = format!("hw_in.{}.{}", reg.name, f.name);
+        if f.hw_clear() {
+            let _ = writeln!(out, "    v := v and not {}_clr;", hw);
+        }
+        if f.writable() {
+            let _ = writeln!(out, "    if {}_we = '1' then", name);
+            let _ = writeln!(out, "      v := {};", write_value(f, "v"));
+            let _ = writeln!(out, "    end if;");
+        }
+        let on_read = match f.on_read {
+            OnRead::Keep => None,
+            OnRead::Clear => Some(zero(f.width)),
+            OnRead::Set => Some(if f.width == 1 {
+                "'1'".to_string()
+            } else {
+                "(others => '1')".to_string()
+            }),
+        };
+        if let Some(value) = on_read {
+            let _ = writeln!(out, "    if {}_rd = '1' then", reg.name);
+            let _ = writeln!(out, "      v := {};", value);
+            let _ = writeln!(out, "    end if;");
+        }
+        if f.hw_set() {
+            let _ = writeln!(out, "    v := v or {}_set;", hw);
+        }
+        let _ = writeln!(out, "    {}_next <= v;", name);
+        let _ = writeln!(out, "  end process;");
+    }
+
+    let _ = writeln!(out, "  process (clk, rst_n)");
+    let _ = writeln!(out, "  begin");
+    let _ = writeln!(out, "    if rst_n = '0' then");
+    for f in &stored {
+        let name = signal(reg, f);
+        let _ = writeln!(out, "      {}_q <= {};", name, literal(f.reset, f.width));
+        if f.write_once() {
+            let _ = writeln!(out, "      {}_written <= '0';", name);
+        }
+    }
+    let _ = writeln!(out, "    elsif rising_edge(clk) then");
+    for f in &stored {
+        let name = signal(reg, f);
+        let _ = writeln!(out, "      {0}_q <= {0}_next;", name);
+        if f.write_once() {
+            let _ = writeln!(out, "      if {0}_we = '1' then", name);
+            let _ = writeln!(out, "        {}_written <= '1';", name);
+            let _ = writeln!(out, "      end if;");
+        }
+    }
+    let _ = writeln!(out, "    end if;");
+    let _ = writeln!(out, "  end process;\n");
+}
+
+/// Value of field `f` after a software write on top of `q`.
+fn write_value(f: &CsrField, q: &str) -> String {
+    let wd = format!("req_wdata{}", slice(f));
+    let wm = format!("wmask{}", slice(f));
+    match f.on_write {
+        OnWrite::Store => format!("({} and not {}) or ({} and {})", q, wm, wd, wm),
+        OnWrite::OneToClear => format!("{} and not ({} and {})", q, wd, wm),
+        OnWrite::OneToSet => format!("{} or ({} and {})", q, wd, wm),
+        OnWrite::OneToToggle => format!("{} xor ({} and {})", q, wd, wm),
+        OnWrite::ZeroToClear => format!("{} and not (not {} and {})", q, wd, wm),
+        OnWrite::ZeroToSet => format!("{} or (not {} and {})", q, wd, wm),
+        OnWrite::ZeroToToggle => format!("{} xor (not {} and {})", q, wd, wm),
+        OnWrite::Clear => format!("{} and not {}", q, wm),
+        OnWrite::Set => format!("{} or {}", q, wm),
+    }
+}
+
+/// `<block>_csr_tb.vhd`: a self-checking testbench of `<block>_csr` running the
+/// transfers of the SystemVerilog testbenches on the access port, for GHDL.
+pub fn generate_vhdl_testbench(block: &CsrBlock) -> RtlFile {
+    let tb = format!("{}_csr_tb", block.name);
+    let dw = block.data_width;
+    let aw = block.addr_width;
+    let nb = dw / 8;
+    let mut out = String::new();
+
+    // output to a String cannot fail
+    let _ = writeln!(
+        out,
+        "-- Generated by register_manager, do not edit.\n\
+         --\n\
+         -- Self-checking testbench of {block}_csr, reporting PASS or every mismatch\n\
+         -- followed by FAIL:\n\
+         --   ghdl -a --std=08 {block}_csr.vhd {tb}.vhd && ghdl -r --std=08 {tb}\n\
+         \n\
+         library ieee;\n\
+         use ieee.std_logic_1164.all;\n\
+         use work.{block}_csr_pkg.all;\n\
+         \n\
+         entity {tb} is\n\
+         end entity;\n\
+         \n\
+         architecture sim of {tb} is\n\
+         \x20 signal clk       : std_logic := '0';\n\
+         \x20 signal rst_n     : std_logic := '0';\n\
+         \x20 signal done      : boolean := false;\n\
+         \x20 signal req       : std_logic := '0';\n\
+         \x20 signal req_write : std_logic := '0';\n\
+         \x20 signal req_addr  : std_logic_vector({a} downto 0) := (others => '0');\n\
+         \x20 signal req_wdata : std_logic_vector({d} downto 0) := (others => '0');\n\
+         \x20 signal req_wstrb : std_logic_vector({s} downto 0) := (others => '0');\n\
+         \x20 signal rsp_rdata : std_logic_vector({d} downto 0);\n\
+         \x20 signal rsp_error : std_logic;\n\
+         \x20 -- hardware inputs held at zero\n\
+         \x20 signal hw_in     : {block}_in_t := {block}_in_zero;\n\
+         \x20 signal hw_out    : {block}_out_t;\n\
+         begin\n\
+         \x20 clk <= not clk after 5 ns when not done else '0';\n\
+         \n\
+         \x20 dut : entity work.{block}_csr\n\
+         \x20   port map (\n\
+         \x20     clk       => clk,\n\
+         \x20     rst_n     => rst_n,\n\
+         \x20     req       => req,\n\
+         \x20     req_write => req_write,\n\
+         \x20     req_addr  => req_addr,\n\
+         \x20     req_wdata => req_wdata,\n\
+         \x20     req_wstrb => req_wstrb,\n\
+         \x20     rsp_rdata => rsp_rdata,\n\
+         \x20     rsp_error => rsp_error,\n\
+         \x20     hw_in     => hw_in,\n\
+         \x20     hw_out    => hw_out\n\
+         \x20   );\n\
+         \n\
+         \x20 process\n\
+         \x20   variable errors : natural := 0;\n\
+         \n\
+         \x20   -- one transfer from a falling clock edge to the next\n\
+         \x20   procedure transfer(what : string; wr : std_logic;\n\
+         \x20                      addr : std_logic_vector({a} downto 0);\n\
+         \x20                      data : std_logic_vector({d} downto 0);\n\
+         \x20                      strb : std_logic_vector({s} downto 0);\n\
+         \x20                      mask : std_logic_vector({d} downto 0);\n\
+         \x20                      err : std_logic) is\n\
+         \x20   begin\n\
+         \x20     req <= '1';\n\
+         \x20     req_write <= wr;\n\
+         \x20     req_addr <= addr;\n\
+         \x20     req_wdata <= data;\n\
+         \x20     req_wstrb <= strb;\n\
+         \x20     wait for 1 ns;\n\
+         \x20     if rsp_error /= err then\n\
+         \x20       report what & \": error response \" & std_logic'image(rsp_error) &\n\
+         \x20              \", expected \" & std_logic'image(err) severity error;\n\
+         \x20       errors := errors + 1;\n\
+         \x20     elsif ((rsp_rdata xor data) and mask) /= (mask'range => '0') then\n\
+         \x20       report what & \": read 0x\" & to_hstring(rsp_rdata) & \", expected 0x\" &\n\
+         \x20              to_hstring(data) & \" under mask 0x\" & to_hstring(mask) severity error;\n\
+         \x20       errors := errors + 1;\n\
+         \x20     end if;\n\
+         \x20     wait until falling_edge(clk);\n\
+         \x20     req <= '0';\n\
+         \x20     wait until falling_edge(clk);\n\
+         \x20   end procedure;\n\
+         \x20 begin\n\
+         \x20   wait until falling_edge(clk);\n\
+         \x20   wait until falling_edge(clk);\n\
+         \x20   rst_n <= '1';\n\
+         \x20   wait until falling_edge(clk);",
+        block = block.name,
+        a = aw - 1,
+        d = dw - 1,
+        s = nb - 1,
+    );
+    for a in vectors(block, true) {
+        let bit = |b: bool| if b { "'1'" } else { "'0'" };
+        let _ = writeln!(
+            out,
+            "    transfer(\"{}\", {}, {}, {}, {}, {}, {});",
+            a.what,
+            bit(a.write),
+            vector(a.addr, aw),
+            vector(a.data, dw),
+            vector(a.strb, nb),
+            vector(a.mask, dw),
+            bit(a.error)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "    if errors = 0 then\n\
+         \x20     report \"PASS\";\n\
+         \x20   else\n\
+         \x20     report \"FAIL: \" & integer'image(errors) & \" mismatches\" severity failure;\n\
+         \x20   end if;\n\
+         \x20   done <= true;\n\
+         \x20   wait;\n\
+         \x20 end process;\n\
+         end architecture;"
+    );
+
+    RtlFile {
+        name: format!("{}.vhd", tb),
+        content: out,
+    }
+}
+
+fn signal(reg: &CsrRegister, f: &CsrField) -> String {
+    format!("{}_{}", reg.name, f.name)
+}
+
+fn first_line(desc: Option<&str>) -> Option<&str> {
+    desc.and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+}
+
+fn ty(width: u32) -> String {
+    if width == 1 {
+        "std_logic".to_string()
+    } else {
+        format!("std_logic_vector({} downto 0)", width - 1)
+    }
+}
+
+fn zero(width: u32) -> String {
+    if width == 1 {
+        "'0'".to_string()
+    } else {
+        "(others => '0')".to_string()
+    }
+}
+
+/// `value` as a `std_logic` or sized VHDL-2008 bit string, `8x"A5"`.
+fn literal(value: u64, width: u32) -> String {
+    if width == 1 {
+        format!("'{}'", value & 1)
+    } else {
+        vector(value, width)
+    }
+}
+
+/// `value` as a sized bit string even when one bit wide.
+fn vector(value: u64, width: u32) -> String {
+    format!("{}x\"{:X}\"", width, value)
+}
+
+/// `(a => x, b => y)`: named, as a one element aggregate must be.
+fn aggregate(elements: impl Iterator<Item = String>) -> String {
+    format!("({})", elements.collect::<Vec<_>>().join(", "))
+}
+
+fn slice(f: &CsrField) -> String {
+    if f.width == 1 {
+        format!("({})", f.lsb)
+    } else {
+        format!("({} downto {})", f.msb(), f.lsb)
+    }
+}
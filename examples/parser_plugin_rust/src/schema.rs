@@ -1,8 +1,17 @@
+use std::collections::HashMap;
+
 use polars::error::PolarsError;
 use polars::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::access::parse_access;
+use crate::diagnostic::Diagnostic;
+use crate::enums::parse_enum_values;
 use crate::error::Error;
+use crate::excel::{Sheet, ROW_COLUMN};
+use crate::layout::FieldBits;
+use crate::number::parse_u64;
+use crate::options::ParseOptions;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Component {
@@ -27,6 +36,7 @@ pub struct Register {
     pub name: String,
     pub offset: String,
     pub size: String,
+    pub desc: Option<String>,
     pub fields: Vec<Field>,
 }
 
@@ -38,144 +48,552 @@ pub struct Field {
     pub attr: String,
     pub reset: String,
     pub desc: String,
+    pub enums: Vec<EnumValue>,
 }
 
-pub fn df_to_regs(df: DataFrame) -> anyhow::Result<Vec<Register>, Error> {
-    (0..df.height())
-        .map(|i| {
-            let extract_str = |col_name: &str| -> anyhow::Result<String, Error> {
-                Ok(df
-                    .column(col_name)?
-                    .str()?
-                    .get(i)
-                    .map(|s| s.into())
-                    .ok_or_else(|| {
-                        PolarsError::NoData(format!("No data in column {}", col_name).into())
-                    })?)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnumValue {
+    pub name: String,
+    pub value: String,
+    pub desc: Option<String>,
+}
+
+/// Columns a block sheet must provide.
+pub const REGISTER_COLUMNS: [&str; 8] = [
+    "ADDR",
+    "REG",
+    "FIELD",
+    "BIT",
+    "WIDTH",
+    "ATTRIBUTE",
+    "DEFAULT",
+    "DESCRIPTION",
+];
+
+/// Reports every column of `required` missing from `sheet`, returns `false` if any is.
+pub fn check_columns(sheet: &Sheet, required: &[&str], diags: &mut Vec<Diagnostic>) -> bool {
+    let missing: Vec<_> = required
+        .iter()
+        .filter(|name| sheet.df.column(name).is_err())
+        .collect();
+
+    for name in &missing {
+        diags.push(
+            Diagnostic::error("missing-column", format!("Column \"{}\" not found", name))
+                .in_sheet(&sheet.name)
+                .at(Some(sheet.header_row), None),
+        );
+    }
+
+    missing.is_empty()
+}
+
+fn missing_value(sheet: &Sheet, row: Option<usize>, column: &str, what: String) -> Diagnostic {
+    sheet.locate(Diagnostic::error("missing-value", what), row, Some(column))
+}
+
+/// Turns the output of [`crate::parser::parse_register`] into registers.
+///
+/// Problems are reported against the cells of `sheet`; registers and fields that
+/// cannot be built are skipped so the rest of the sheet is still checked.
+pub fn df_to_regs(
+    df: DataFrame,
+    sheet: &Sheet,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> anyhow::Result<Vec<Register>, Error> {
+    let extract_str = |col_name: &str, i: usize| -> anyhow::Result<Option<String>, Error> {
+        Ok(df.column(col_name)?.str()?.get(i).map(String::from))
+    };
+
+    // not consume df
+    let extract_list =
+        |col_name: &str, idx: usize| -> anyhow::Result<Vec<Option<String>>, PolarsError> {
+            Ok(df
+                .column(col_name)?
+                .list()?
+                .get_as_series(idx)
+                .ok_or_else(|| PolarsError::NoData("No data at index".into()))?
+                .str()?
+                .into_iter()
+                .map(|opt_s| opt_s.map(String::from))
+                .collect())
+        };
+
+    let mut regs = Vec::with_capacity(df.height());
+    // offset -> name of the registers read so far
+    let mut offsets: HashMap<u64, String> = HashMap::new();
+
+    for i in 0..df.height() {
+        let reg_row = df.column("REG_ROW")?.u32()?.get(i).map(|r| r as usize);
+        let rows: Vec<Option<usize>> = df
+            .column(ROW_COLUMN)?
+            .list()?
+            .get_as_series(i)
+            .ok_or_else(|| PolarsError::NoData("No data at index".into()))?
+            .u32()?
+            .into_iter()
+            .map(|r| r.map(|r| r as usize))
+            .collect();
+
+        let Some(name) = extract_str("REG", i)? else {
+            diags.push(missing_value(
+                sheet,
+                reg_row,
+                "REG",
+                "Register name is empty".into(),
+            ));
+            continue;
+        };
+        let Some(offset) = extract_str("ADDR", i)? else {
+            diags.push(missing_value(
+                sheet,
+                reg_row,
+                "ADDR",
+                format!("Register {} has no address", name),
+            ));
+            continue;
+        };
+        if df.column("ADDR_GIVEN")?.bool()?.get(i) == Some(false) {
+            diags.push(missing_value(
+                sheet,
+                rows.first().copied().flatten(),
+                "ADDR",
+                format!(
+                    "Register {} has no ADDR, it would share {} with the register above",
+                    name, offset
+                ),
+            ));
+            continue;
+        }
+        let Some(size) = extract_str("REG_WIDTH", i)? else {
+            diags.push(missing_value(
+                sheet,
+                reg_row,
+                "WIDTH",
+                format!("Register {} has no field with a valid WIDTH", name),
+            ));
+            continue;
+        };
+        let desc = extract_str("REG_DESC", i)?;
+
+        match parse_u64(&offset) {
+            Some(value) => {
+                if let Some(other) = offsets.get(&value) {
+                    diags.push(sheet.locate(
+                        Diagnostic::error(
+                            "register-overlap",
+                            format!(
+                                "Register {}: offset 0x{:X} is already taken by {}",
+                                name, value, other
+                            ),
+                        ),
+                        rows.first().copied().flatten(),
+                        Some("ADDR"),
+                    ));
+                } else {
+                    offsets.insert(value, name.clone());
+                }
+            }
+            None => diags.push(sheet.locate(
+                Diagnostic::fallback(
+                    opts.strict,
+                    "invalid-number",
+                    format!("Register {}: ADDR \"{}\" is not a number", name, offset),
+                    "imported as is",
+                ),
+                reg_row,
+                Some("ADDR"),
+            )),
+        }
+        if size.trim().parse::<u32>().is_err() {
+            diags.push(sheet.locate(
+                Diagnostic::fallback(
+                    opts.strict,
+                    "invalid-number",
+                    format!("Register {}: width \"{}\" is not a number", name, size),
+                    "using 32",
+                ),
+                reg_row,
+                Some("WIDTH"),
+            ));
+        }
+
+        let name_array = extract_list("FIELD", i)?;
+        let bit_array = extract_list("BIT", i)?;
+        let offset_array = extract_list("BIT_OFFSET", i)?;
+        let width_array = extract_list("WIDTH", i)?;
+        let attribute_array = extract_list("ATTRIBUTE", i)?;
+        let default_array = extract_list("DEFAULT", i)?;
+        let description_array = extract_list("DESCRIPTION", i)?;
+        // ENUM cells are optional, a blank one falls back to the description
+        let enum_array = extract_list("ENUM", i)?;
+
+        let mut fields = Vec::with_capacity(name_array.len());
+        // REG_WIDTH sums the field widths, leaving out reserved gaps, so it cannot bound
+        // the fields; they must at least fit the 64 bits reset values are read into
+        let mut bits = FieldBits::new(64);
+
+        for (j, row) in rows.into_iter().enumerate() {
+            let field_name = name_array[j].clone().unwrap_or_default();
+            let mut require = |value: &Option<String>, column: &str, what: &str| {
+                if value.is_none() {
+                    diags.push(missing_value(
+                        sheet,
+                        row,
+                        column,
+                        format!("Field {}.{}: {}", name, field_name, what),
+                    ));
+                }
+                value.clone()
             };
 
-            let name = extract_str("REG")?;
-            let offset = extract_str("ADDR")?;
-            let size = extract_str("REG_WIDTH")?;
-
-            // not consume df
-            let extract_list = |col_name: &str,
-                                idx: usize|
-             -> anyhow::Result<Vec<String>, PolarsError> {
-                df.column(col_name)?
-                    .list()?
-                    .get_as_series(idx)
-                    .ok_or_else(|| PolarsError::NoData("No data at index".into()))?
-                    .str()?
-                    .into_iter()
-                    .map(|opt_s| {
-                        opt_s.map(|s| s.into()).ok_or_else(|| {
-                            PolarsError::NoData(
-                                format!("No data found in column '{}' at index {}", col_name, idx)
-                                    .into(),
-                            )
-                        })
-                    })
-                    .collect()
+            let offset = match &bit_array[j] {
+                None => require(&None, "BIT", "BIT is empty"),
+                Some(bit) => require(
+                    &offset_array[j],
+                    "BIT",
+                    &format!("bit position \"{}\" cannot be parsed", bit),
+                ),
             };
+            let width = require(&width_array[j], "WIDTH", "WIDTH is empty");
+            let attr = require(&attribute_array[j], "ATTRIBUTE", "ATTRIBUTE is empty");
+            let reset = require(&default_array[j], "DEFAULT", "DEFAULT is empty");
+
+            let (Some(offset), Some(width), Some(attr), Some(reset)) = (offset, width, attr, reset)
+            else {
+                continue;
+            };
+
+            if offset.trim().parse::<u32>().is_err() {
+                diags.push(sheet.locate(
+                    Diagnostic::fallback(
+                        opts.strict,
+                        "invalid-number",
+                        format!(
+                            "Field {}.{}: bit offset \"{}\" is not a number",
+                            name, field_name, offset
+                        ),
+                        "using 0",
+                    ),
+                    row,
+                    Some("BIT"),
+                ));
+            }
+            if width.trim().parse::<u32>().is_err() {
+                diags.push(sheet.locate(
+                    Diagnostic::fallback(
+                        opts.strict,
+                        "invalid-number",
+                        format!(
+                            "Field {}.{}: WIDTH \"{}\" is not a number",
+                            name, field_name, width
+                        ),
+                        "using 1",
+                    ),
+                    row,
+                    Some("WIDTH"),
+                ));
+            }
+            if let (Ok(lsb), Ok(bit_width)) =
+                (offset.trim().parse::<u64>(), width.trim().parse::<u64>())
+            {
+                let what = format!("Field {}.{}", name, field_name);
+                if let Err(diag) = bits.take(&what, lsb, bit_width) {
+                    diags.push(sheet.locate(diag, row, Some("BIT")));
+                }
+            }
+            if parse_u64(&reset).is_none() {
+                diags.push(sheet.locate(
+                    Diagnostic::fallback(
+                        opts.strict,
+                        "invalid-number",
+                        format!(
+                            "Field {}.{}: DEFAULT \"{}\" is not a number",
+                            name, field_name, reset
+                        ),
+                        "imported as is",
+                    ),
+                    row,
+                    Some("DEFAULT"),
+                ));
+            }
+            if parse_access(&attr).is_none() {
+                diags.push(sheet.locate(
+                    Diagnostic::fallback(
+                        opts.strict,
+                        "unknown-access",
+                        format!("Field {}.{}: unknown access \"{}\"", name, field_name, attr),
+                        "defaulting to read-write",
+                    ),
+                    row,
+                    Some("ATTRIBUTE"),
+                ));
+            }
 
-            let name_array = extract_list("FIELD", i)?;
-            let offset_array = extract_list("BIT_OFFSET", i)?;
-            let width_array = extract_list("WIDTH", i)?;
-            let attribute_array = extract_list("ATTRIBUTE", i)?;
-            let default_array = extract_list("DEFAULT", i)?;
-            let description_array = extract_list("DESCRIPTION", i)?;
-
-            let fields = name_array
-                .iter()
-                .zip(offset_array.iter())
-                .zip(width_array.iter())
-                .zip(attribute_array.iter())
-                .zip(default_array.iter())
-                .zip(description_array.iter())
-                .map(|(((((name, offset), width), attr), reset), desc)| Field {
-                    name: name.into(),
-                    offset: offset.into(),
-                    width: width.into(),
-                    attr: attr.into(),
-                    reset: reset.into(),
-                    desc: desc.into(),
-                })
-                .collect();
-
-            Ok(Register {
-                name,
+            let desc = description_array[j].clone().unwrap_or_default();
+            let enums = match enum_array[j].as_deref().map(str::trim) {
+                Some(cell) if !cell.is_empty() => {
+                    let enums = parse_enum_values(cell);
+                    if enums.is_empty() {
+                        diags.push(sheet.locate(
+                            Diagnostic::fallback(
+                                opts.strict,
+                                "invalid-enum",
+                                format!(
+                                    "Field {}.{}: ENUM \"{}\" has no \"value: name\" entry",
+                                    name, field_name, cell
+                                ),
+                                "reading the description instead",
+                            ),
+                            row,
+                            Some("ENUM"),
+                        ));
+                        parse_enum_values(&desc)
+                    } else {
+                        enums
+                    }
+                }
+                _ => parse_enum_values(&desc),
+            };
+
+            fields.push(Field {
+                name: field_name,
                 offset,
-                size,
-                fields,
-            })
-        })
-        .collect()
+                width,
+                attr,
+                reset,
+                desc,
+                enums,
+            });
+        }
+
+        regs.push(Register {
+            name,
+            offset,
+            size,
+            desc,
+            fields,
+        });
+    }
+
+    Ok(regs)
 }
 
-pub fn df_to_blks<F>(df: DataFrame, mut registers_extractor: F) -> anyhow::Result<Vec<Block>, Error>
+/// Reads the `address_map` sheet, calling `registers_extractor` with the block type
+/// (the `BLOCK` column) of every row.
+pub fn df_to_blks<F>(
+    sheet: &Sheet,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+    mut registers_extractor: F,
+) -> anyhow::Result<Vec<Block>, Error>
 where
-    F: FnMut(&str) -> anyhow::Result<Vec<Register>, Error>,
+    F: FnMut(&str, &mut Vec<Diagnostic>) -> anyhow::Result<Vec<Register>, Error>,
 {
-    (0..df.height())
-        .map(|i| {
-            let name: String = df
-                .column("BLOCK")?
-                .str()?
-                .get(i)
-                .map(|s| s.into())
-                .ok_or_else(|| Error::Polars(PolarsError::NoData("No data in DataFrame".into())))?;
-            let offset: String = df
-                .column("OFFSET")?
-                .str()?
-                .get(i)
-                .map(|s| s.into())
-                .ok_or_else(|| Error::Polars(PolarsError::NoData("No data in DataFrame".into())))?;
-            let range: String = df
-                .column("RANGE")?
-                .str()?
-                .get(i)
-                .map(|s| s.into())
-                .ok_or_else(|| Error::Polars(PolarsError::NoData("No data in DataFrame".into())))?;
-            let size = "32".to_string();
-            let regs = registers_extractor(&name)?;
-
-            Ok(Block {
-                name,
-                offset,
-                range,
-                size,
-                regs,
-            })
-        })
-        .collect()
+    if !check_columns(sheet, &["BLOCK", "OFFSET", "RANGE"], diags) {
+        return Ok(vec![]);
+    }
+
+    let df = &sheet.df;
+    let extract_str = |col_name: &str, i: usize| -> anyhow::Result<Option<String>, Error> {
+        Ok(df.column(col_name)?.str()?.get(i).map(String::from))
+    };
+
+    let has_width = df.column("WIDTH").is_ok();
+    if !has_width {
+        // outside strict mode an info only, most address maps leave the width to the default
+        let diag = if opts.strict {
+            Diagnostic::error("default-width", "No WIDTH column")
+        } else {
+            Diagnostic::info(
+                "default-width",
+                "No WIDTH column, every block uses 32-bit registers",
+            )
+        };
+        diags.push(diag.in_sheet(&sheet.name).at(Some(sheet.header_row), None));
+    }
+
+    let mut blks = Vec::with_capacity(df.height());
+    // lowercased block name -> its row, instances sharing a type sheet need an INSTANCE
+    let mut rows_of_names: HashMap<String, Option<usize>> = HashMap::new();
+
+    for i in 0..df.height() {
+        let row = df.column(ROW_COLUMN)?.u32()?.get(i).map(|r| r as usize);
+
+        // "BLOCK" names the sheet describing the block type, "INSTANCE" (optional)
+        // names this particular instance so one type sheet can be mapped many times.
+        let type_name = extract_str("BLOCK", i)?;
+        let offset = extract_str("OFFSET", i)?;
+        let range = extract_str("RANGE", i)?;
+
+        let Some(type_name) = type_name else {
+            if offset.is_some() || range.is_some() {
+                diags.push(missing_value(sheet, row, "BLOCK", "BLOCK is empty".into()));
+            }
+            continue;
+        };
+        let Some(offset) = offset else {
+            diags.push(missing_value(
+                sheet,
+                row,
+                "OFFSET",
+                format!("Block {} has no OFFSET", type_name),
+            ));
+            continue;
+        };
+        let Some(range) = range else {
+            diags.push(missing_value(
+                sheet,
+                row,
+                "RANGE",
+                format!("Block {} has no RANGE", type_name),
+            ));
+            continue;
+        };
+
+        let name = df
+            .column("INSTANCE")
+            .ok()
+            .and_then(|c| c.str().ok())
+            .and_then(|c| c.get(i))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .unwrap_or_else(|| type_name.clone());
+        if let Some(first) = rows_of_names.insert(name.to_lowercase(), row) {
+            let at = first.map(|r| format!(" on row {}", r)).unwrap_or_default();
+            diags.push(sheet.locate(
+                Diagnostic::error(
+                    "duplicate-block",
+                    format!(
+                        "Block {} is already mapped{}, name each instance in an INSTANCE column",
+                        name, at
+                    ),
+                ),
+                row,
+                Some("BLOCK"),
+            ));
+            continue;
+        }
+        for (column, value) in [("OFFSET", &offset), ("RANGE", &range)] {
+            if parse_u64(value).is_none() {
+                diags.push(sheet.locate(
+                    Diagnostic::fallback(
+                        opts.strict,
+                        "invalid-number",
+                        format!("Block {}: {} \"{}\" is not a number", name, column, value),
+                        "imported as is",
+                    ),
+                    row,
+                    Some(column),
+                ));
+            }
+        }
+
+        // register width of the block, optional "WIDTH" column
+        let size = match extract_str("WIDTH", i).ok().flatten() {
+            Some(width) if width.trim().parse::<u32>().is_ok() => width.trim().to_string(),
+            Some(width) => {
+                diags.push(sheet.locate(
+                    Diagnostic::fallback(
+                        opts.strict,
+                        "invalid-number",
+                        format!("Block {}: WIDTH \"{}\" is not a number", name, width),
+                        "using 32",
+                    ),
+                    row,
+                    Some("WIDTH"),
+                ));
+                "32".to_string()
+            }
+            None => {
+                if has_width {
+                    diags.push(sheet.locate(
+                        Diagnostic::fallback(
+                            opts.strict,
+                            "default-width",
+                            format!("Block {} has no WIDTH", name),
+                            "using 32",
+                        ),
+                        row,
+                        Some("WIDTH"),
+                    ));
+                }
+                "32".to_string()
+            }
+        };
+
+        let regs = match registers_extractor(&type_name, diags) {
+            Ok(regs) => regs,
+            Err(e) => {
+                diags.push(sheet.locate(e.to_diagnostic(), row, Some("BLOCK")));
+                continue;
+            }
+        };
+
+        blks.push(Block {
+            name,
+            offset,
+            range,
+            size,
+            regs,
+        });
+    }
+
+    Ok(blks)
 }
 
-pub fn df_to_compo<F>(df: DataFrame, mut blocks_extractor: F) -> anyhow::Result<Component, Error>
+/// Reads the `version` sheet; returns `None` when the VLNV is incomplete.
+pub fn df_to_compo<F>(
+    sheet: &Sheet,
+    diags: &mut Vec<Diagnostic>,
+    mut blocks_extractor: F,
+) -> anyhow::Result<Option<Component>, Error>
 where
-    F: FnMut() -> anyhow::Result<Vec<Block>, Error>,
+    F: FnMut(&mut Vec<Diagnostic>) -> anyhow::Result<Vec<Block>, Error>,
 {
-    let extract_str = |tag: &str| -> anyhow::Result<String, Error> {
-        Ok(df
-            .column(tag)?
-            .str()?
+    let has_columns = check_columns(sheet, &["VENDOR", "LIBRARY", "NAME", "VERSION"], diags);
+
+    let row = Some(sheet.header_row + 1);
+    let mut extract_str = |tag: &str| -> Option<String> {
+        let value = sheet
+            .df
+            .column(tag)
+            .ok()?
+            .str()
+            .ok()?
             .get(0)
-            .map(|s| s.into())
-            .ok_or_else(|| PolarsError::NoData("No data in DataFrame for tag".into()))?)
+            .map(String::from);
+        if value.is_none() {
+            diags.push(missing_value(sheet, row, tag, format!("{} is empty", tag)));
+        }
+        value
     };
 
-    let vendor = extract_str("VENDOR")?;
-    let library = extract_str("LIBRARY")?;
-    let name = extract_str("NAME")?;
-    let version = extract_str("VERSION")?;
+    let vlnv = if has_columns {
+        (
+            extract_str("VENDOR"),
+            extract_str("LIBRARY"),
+            extract_str("NAME"),
+            extract_str("VERSION"),
+        )
+    } else {
+        (None, None, None, None)
+    };
+
+    // keep going so the block sheets get checked as well
+    let blks = blocks_extractor(diags)?;
 
-    let blks = blocks_extractor()?;
+    let (Some(vendor), Some(library), Some(name), Some(version)) = vlnv else {
+        return Ok(None);
+    };
 
-    Ok(Component {
+    Ok(Some(Component {
         vendor,
         library,
         name,
         version,
         blks,
-    })
+    }))
 }
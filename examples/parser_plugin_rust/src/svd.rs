@@ -0,0 +1,644 @@
+//! CMSIS-SVD device descriptions.
+
+use roxmltree::{Document, Node};
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::layout::FieldBits;
+use crate::number::{hex, parse_u64};
+use crate::options::ParseOptions;
+use crate::types::*;
+use crate::xml::{child, children, line, text};
+
+/// Elements the importer has no equivalent for, reported once per occurrence.
+const UNSUPPORTED: [&str; 5] = [
+    "alternatePeripheral",
+    "alternateGroup",
+    "alternateCluster",
+    "alternateRegister",
+    "writeConstraint",
+];
+
+/// Reads an SVD `device` into `ImportData`, one address block per peripheral.
+///
+/// `derivedFrom` is resolved element by element: whatever the derived element
+/// does not specify (including its whole register or field list) comes from the
+/// element it derives from. Clusters are flattened into the peripheral, their name
+/// prefixing the registers; `dim` arrays are expanded with `%s` replaced by each
+/// `dimIndex` (`[%s]` by `_0`, `_1`, ...).
+pub fn read_device(
+    xml: &str,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let doc = Document::parse(xml)?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "device" {
+        diags.push(
+            Diagnostic::error(
+                "not-a-device",
+                format!(
+                    "Root element is \"{}\", expected a CMSIS-SVD device",
+                    root.tag_name().name()
+                ),
+            )
+            .at(Some(line(root)), None),
+        );
+        return Ok(None);
+    }
+
+    for node in root.descendants().filter(|n| n.is_element()) {
+        let tag = node.tag_name().name();
+        if UNSUPPORTED.contains(&tag) {
+            diags.push(
+                Diagnostic::warning("unsupported-element", format!("{} is not imported", tag))
+                    .at(Some(line(node)), None),
+            );
+        }
+    }
+
+    let mut reader = Reader { root, opts, diags };
+    let device = Derived { nodes: vec![root] };
+    let defaults = Defaults {
+        size: 32,
+        access: None,
+        reset_value: 0,
+        reset_mask: u64::MAX,
+    }
+    .with(&mut reader, &device, "Device");
+    let width = reader.number(&device, "width", "Device", 32) as u32;
+
+    let mut address_blocks = Vec::new();
+    for peripheral in child(root, "peripherals")
+        .map(|p| children(p, "peripheral").collect::<Vec<_>>())
+        .unwrap_or_default()
+    {
+        reader.peripheral(peripheral, &defaults, width, &mut address_blocks);
+    }
+
+    let name = text(root, "name").unwrap_or_default();
+    Ok(Some(ImportData {
+        project: ImportProject {
+            name: name.clone(),
+            description: text(root, "description"),
+            vendor: text(root, "vendor")
+                .or_else(|| text(root, "vendorID"))
+                .unwrap_or_default(),
+            library: text(root, "series").unwrap_or_default(),
+            version: text(root, "version").unwrap_or_else(|| "0.1.0".to_string()),
+        },
+        memory_maps: vec![ImportMemoryMap {
+            name,
+            address_blocks,
+        }],
+    }))
+}
+
+/// `scaledNonNegativeInteger`: decimal, `0x` hex, `#` binary, optionally scaled
+/// by a `k`, `M`, `G` or `T` suffix.
+fn svd_number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Some(bits) = text.strip_prefix('#') {
+        return u64::from_str_radix(&bits.replace('_', ""), 2).ok();
+    }
+    let (digits, scale) = match text.chars().last()? {
+        'k' | 'K' => (&text[..text.len() - 1], 1 << 10),
+        'm' | 'M' => (&text[..text.len() - 1], 1 << 20),
+        'g' | 'G' => (&text[..text.len() - 1], 1 << 30),
+        't' | 'T' => (&text[..text.len() - 1], 1 << 40),
+        _ => (text, 1),
+    };
+    parse_u64(digits)?.checked_mul(scale)
+}
+
+/// Substitutes the `dimIndex` of an array element in a name or description.
+fn expand(name: &str, index: &str) -> String {
+    if index.is_empty() {
+        name.to_string()
+    } else if name.contains("%s") {
+        name.replace("[%s]", &format!("_{}", index))
+            .replace("%s", index)
+    } else {
+        format!("{}_{}", name, index)
+    }
+}
+
+/// An element followed by the elements it derives from, nearest first.
+struct Derived<'a, 'input> {
+    nodes: Vec<Node<'a, 'input>>,
+}
+
+impl<'a, 'input> Derived<'a, 'input> {
+    fn node(&self) -> Node<'a, 'input> {
+        self.nodes[0]
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.nodes.iter().find_map(|n| text(*n, name))
+    }
+
+    fn child(&self, name: &str) -> Option<Node<'a, 'input>> {
+        self.nodes.iter().find_map(|n| child(*n, name))
+    }
+
+    /// `tags` children of the `container` child (or of the element itself), taken
+    /// from the first element of the chain that has any.
+    fn list(&self, container: Option<&str>, tags: &[&str]) -> Vec<Node<'a, 'input>> {
+        self.nodes
+            .iter()
+            .filter_map(|n| match container {
+                Some(name) => child(*n, name),
+                None => Some(*n),
+            })
+            .map(|c| {
+                c.children()
+                    .filter(|n| n.is_element() && tags.contains(&n.tag_name().name()))
+                    .collect::<Vec<_>>()
+            })
+            .find(|list| !list.is_empty())
+            .unwrap_or_default()
+    }
+}
+
+/// Register properties inherited from the device, peripheral and clusters.
+#[derive(Debug, Clone)]
+struct Defaults {
+    size: u64,
+    access: Option<String>,
+    reset_value: u64,
+    reset_mask: u64,
+}
+
+impl Defaults {
+    fn with(&self, reader: &mut Reader, elem: &Derived, what: &str) -> Self {
+        Self {
+            size: reader.number(elem, "size", what, self.size),
+            access: elem.text("access").or_else(|| self.access.clone()),
+            reset_value: reader.number(elem, "resetValue", what, self.reset_value),
+            reset_mask: reader.number(elem, "resetMask", what, self.reset_mask),
+        }
+    }
+}
+
+struct Reader<'a, 'input> {
+    root: Node<'a, 'input>,
+    opts: &'a ParseOptions,
+    diags: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a, 'input> Reader<'a, 'input> {
+    /// A value that cannot be read: warning naming the default, error in strict mode.
+    fn fallback(&mut self, node: Node, problem: String, default: &str) {
+        let diag = Diagnostic::fallback(self.opts.strict, "invalid-number", problem, default);
+        self.diags.push(diag.at(Some(line(node)), None));
+    }
+
+    /// Number in the `name` child, `default` when absent or not a number.
+    fn number(&mut self, elem: &Derived, name: &str, what: &str, default: u64) -> u64 {
+        self.optional(elem, name, what).unwrap_or(default)
+    }
+
+    fn optional(&mut self, elem: &Derived, name: &str, what: &str) -> Option<u64> {
+        let value = elem.text(name)?;
+        svd_number(&value).or_else(|| {
+            let at = elem.child(name).unwrap_or(elem.node());
+            self.fallback(
+                at,
+                format!("{}: {} \"{}\" is not a number", what, name, value),
+                "ignored",
+            );
+            None
+        })
+    }
+
+    fn required(&mut self, elem: &Derived, name: &str, what: &str) -> u64 {
+        if elem.text(name).is_none() {
+            self.diags.push(
+                Diagnostic::error("missing-value", format!("{} has no {}", what, name))
+                    .at(Some(line(elem.node())), None),
+            );
+        }
+        self.number(elem, name, what, 0)
+    }
+
+    /// Follows `derivedFrom`, looking the name up with `find` (same scope) or as a
+    /// dotted path from the device (`PERIPH.REG.FIELD`).
+    fn derive(
+        &mut self,
+        node: Node<'a, 'input>,
+        find: impl Fn(Node<'a, 'input>, &str) -> Option<Node<'a, 'input>>,
+    ) -> Derived<'a, 'input> {
+        let mut nodes = vec![node];
+        while let Some(base) = nodes.last().unwrap().attribute("derivedFrom") {
+            let current = *nodes.last().unwrap();
+            let found = find(current, base).or_else(|| self.find_path(base));
+            match found {
+                Some(found) if !nodes.contains(&found) => nodes.push(found),
+                Some(_) => {
+                    self.diags.push(
+                        Diagnostic::error(
+                            "circular-derivation",
+                            format!("derivedFrom=\"{}\" derives from itself", base),
+                        )
+                        .at(Some(line(current)), None),
+                    );
+                    break;
+                }
+                None => {
+                    self.diags.push(
+                        Diagnostic::warning(
+                            "unresolved-derivation",
+                            format!("derivedFrom=\"{}\" not found, ignored", base),
+                        )
+                        .at(Some(line(current)), None),
+                    );
+                    break;
+                }
+            }
+        }
+        Derived { nodes }
+    }
+
+    /// Element at `PERIPH.CLUSTER.REG.FIELD`, through `registers` and `fields`.
+    fn find_path(&self, path: &str) -> Option<Node<'a, 'input>> {
+        let mut segments = path.split('.');
+        let first = segments.next()?;
+        let mut node = child(self.root, "peripherals").and_then(|p| {
+            children(p, "peripheral").find(|n| text(*n, "name").as_deref() == Some(first))
+        })?;
+        for segment in segments {
+            node = sibling_named(node, segment)?;
+        }
+        Some(node)
+    }
+
+    /// `(index, address or bit increment)` of every element of a `dim` array, a
+    /// single unnamed element otherwise.
+    fn dims(&mut self, elem: &Derived, what: &str) -> Vec<(String, u64)> {
+        let Some(dim) = self.optional(elem, "dim", what) else {
+            return vec![(String::new(), 0)];
+        };
+        let increment = self.number(elem, "dimIncrement", what, 0);
+        let names: Vec<String> = match elem.text("dimIndex") {
+            Some(index) => dim_index(&index).unwrap_or_else(|| {
+                self.fallback(
+                    elem.child("dimIndex").unwrap_or(elem.node()),
+                    format!("{}: dimIndex \"{}\" cannot be read", what, index),
+                    "using 0, 1, ...",
+                );
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        if !names.is_empty() && names.len() as u64 != dim {
+            self.diags.push(
+                Diagnostic::warning(
+                    "dim-mismatch",
+                    format!(
+                        "{}: dim is {} but dimIndex has {} entries",
+                        what,
+                        dim,
+                        names.len()
+                    ),
+                )
+                .at(Some(line(elem.node())), None),
+            );
+        }
+
+        (0..dim)
+            .map(|i| {
+                let index = names
+                    .get(i as usize)
+                    .cloned()
+                    .unwrap_or_else(|| i.to_string());
+                (index, i * increment)
+            })
+            .collect()
+    }
+
+    fn peripheral(
+        &mut self,
+        node: Node<'a, 'input>,
+        device: &Defaults,
+        width: u32,
+        out: &mut Vec<ImportAddressBlock>,
+    ) {
+        let elem = self.derive(node, |_, name| {
+            node.parent().and_then(|p| {
+                children(p, "peripheral").find(|n| text(*n, "name").as_deref() == Some(name))
+            })
+        });
+        let name = elem.text("name").unwrap_or_default();
+        let what = format!("Peripheral {}", name);
+        let base = self.required(&elem, "baseAddress", &what);
+        let defaults = device.with(self, &elem, &what);
+
+        let mut registers = Vec::new();
+        let items = elem.list(Some("registers"), &["register", "cluster"]);
+        self.registers(items, 0, "", &defaults, &mut registers);
+
+        // the address blocks give the size, the registers otherwise
+        let blocks: Vec<Node> = elem
+            .nodes
+            .iter()
+            .map(|n| children(*n, "addressBlock").collect::<Vec<_>>())
+            .find(|b| !b.is_empty())
+            .unwrap_or_default();
+        let range = if blocks.is_empty() {
+            registers
+                .iter()
+                .filter_map(|r| Some(svd_number(&r.address_offset)? + (r.size as u64).div_ceil(8)))
+                .max()
+                .unwrap_or(0)
+        } else {
+            blocks
+                .into_iter()
+                .map(|b| {
+                    let block = Derived { nodes: vec![b] };
+                    self.number(&block, "offset", &what, 0) + self.number(&block, "size", &what, 0)
+                })
+                .max()
+                .unwrap_or(0)
+        };
+
+        for (index, increment) in self.dims(&elem, &what) {
+            out.push(ImportAddressBlock {
+                name: expand(&name, &index),
+                base_address: hex(base + increment),
+                range: hex(range),
+                width,
+                registers: registers.clone(),
+            });
+        }
+    }
+
+    /// Registers and clusters of a peripheral or cluster, at `base` with `prefix`.
+    fn registers(
+        &mut self,
+        items: Vec<Node<'a, 'input>>,
+        base: u64,
+        prefix: &str,
+        defaults: &Defaults,
+        out: &mut Vec<ImportRegister>,
+    ) {
+        for node in items {
+            let elem = self.derive(node, |n, name| {
+                n.parent().and_then(|p| sibling_named(p, name))
+            });
+            let name = elem.text("name").unwrap_or_default();
+            let is_cluster = node.tag_name().name() == "cluster";
+            let what = format!(
+                "{} {}{}",
+                if is_cluster { "Cluster" } else { "Register" },
+                prefix,
+                name
+            );
+            let offset = base + self.required(&elem, "addressOffset", &what);
+            let defaults = defaults.with(self, &elem, &what);
+            let dims = self.dims(&elem, &what);
+
+            if is_cluster {
+                let items = elem.list(None, &["register", "cluster"]);
+                for (index, increment) in dims {
+                    let prefix = format!("{}{}_", prefix, expand(&name, &index));
+                    self.registers(items.clone(), offset + increment, &prefix, &defaults, out);
+                }
+                continue;
+            }
+
+            let mut fields = Vec::new();
+            let mut bits = FieldBits::new(defaults.size);
+            for field in elem.list(Some("fields"), &["field"]) {
+                self.field(field, &what, &elem, &defaults, &mut bits, &mut fields);
+            }
+            if fields.is_empty() {
+                // a register without fields is one field spanning it
+                fields.push(ImportField {
+                    name: name.replace("[%s]", "").replace("%s", ""),
+                    description: None,
+                    bit_offset: 0,
+                    bit_width: defaults.size as u32,
+                    access: access(&defaults.access),
+                    modified_write_value: elem.text("modifiedWriteValues"),
+                    read_action: elem.text("readAction"),
+                    reset_value: hex(defaults.reset_value & defaults.reset_mask),
+                    enumerated_values: Vec::new(),
+                });
+            }
+            fields.sort_by_key(|f| f.bit_offset);
+
+            let description = elem.text("description");
+            for (index, increment) in dims {
+                out.push(ImportRegister {
+                    name: format!("{}{}", prefix, expand(&name, &index)),
+                    address_offset: hex(offset + increment),
+                    size: defaults.size as u32,
+                    description: description.as_deref().map(|d| expand(d, &index)),
+                    fields: fields.clone(),
+                });
+            }
+        }
+    }
+
+    fn field(
+        &mut self,
+        node: Node<'a, 'input>,
+        reg: &str,
+        reg_elem: &Derived,
+        defaults: &Defaults,
+        bits: &mut FieldBits,
+        out: &mut Vec<ImportField>,
+    ) {
+        let elem = self.derive(node, |n, name| {
+            n.parent().and_then(|p| sibling_named(p, name))
+        });
+        let name = elem.text("name").unwrap_or_default();
+        let what = format!("Field {}.{}", reg.trim_start_matches("Register "), name);
+
+        let (lsb, width) = if let Some(offset) = self.optional(&elem, "bitOffset", &what) {
+            (offset, self.number(&elem, "bitWidth", &what, 1))
+        } else if let Some(lsb) = self.optional(&elem, "lsb", &what) {
+            let msb = self.number(&elem, "msb", &what, lsb);
+            (lsb, msb.saturating_sub(lsb) + 1)
+        } else if let Some(range) = elem.text("bitRange") {
+            match bit_range(&range) {
+                Some((msb, lsb)) => (lsb, msb.saturating_sub(lsb) + 1),
+                None => {
+                    self.fallback(
+                        elem.child("bitRange").unwrap_or(node),
+                        format!("{}: bitRange \"{}\" cannot be read", what, range),
+                        "using [0:0]",
+                    );
+                    (0, 1)
+                }
+            }
+        } else {
+            self.diags.push(
+                Diagnostic::error("missing-value", format!("{} has no bit position", what))
+                    .at(Some(line(node)), None),
+            );
+            (0, 1)
+        };
+
+        let field_mask = 1u64.checked_shl(width as u32).map_or(u64::MAX, |m| m - 1);
+        let access_text = elem.text("access").or_else(|| defaults.access.clone());
+        let enumerated_values = self.enumerated_values(&elem, &what);
+        let description = elem.text("description");
+
+        for (index, increment) in self.dims(&elem, &what) {
+            let offset = lsb.saturating_add(increment);
+            let name = expand(&name, &index);
+            let what = format!("Field {}.{}", reg.trim_start_matches("Register "), name);
+            if let Err(diag) = bits.take(&what, offset, width) {
+                self.diags.push(diag.at(Some(line(node)), None));
+                continue;
+            }
+            out.push(ImportField {
+                name,
+                description: description.as_deref().map(|d| expand(d, &index)),
+                bit_offset: offset as u32,
+                bit_width: width as u32,
+                access: access(&access_text),
+                modified_write_value: elem
+                    .text("modifiedWriteValues")
+                    .or_else(|| reg_elem.text("modifiedWriteValues")),
+                read_action: elem
+                    .text("readAction")
+                    .or_else(|| reg_elem.text("readAction")),
+                reset_value: hex((defaults.reset_value & defaults.reset_mask)
+                    .checked_shr(offset as u32)
+                    .unwrap_or(0)
+                    & field_mask),
+                enumerated_values: enumerated_values.clone(),
+            });
+        }
+    }
+
+    /// Values of every `enumeratedValues` of the field (read and write usages
+    /// merged), `derivedFrom` naming another `enumeratedValues` of the device.
+    fn enumerated_values(
+        &mut self,
+        field: &Derived<'a, 'input>,
+        what: &str,
+    ) -> Vec<ImportEnumeratedValue> {
+        let mut values: Vec<ImportEnumeratedValue> = Vec::new();
+        let root = self.root;
+        let lists: Vec<Node> = field
+            .nodes
+            .iter()
+            .map(|n| children(*n, "enumeratedValues").collect::<Vec<_>>())
+            .find(|l| !l.is_empty())
+            .unwrap_or_default();
+
+        for list in lists {
+            let list = self.derive(list, |_, name| {
+                let name = name.rsplit('.').next().unwrap_or(name);
+                root.descendants().find(|n| {
+                    n.tag_name().name() == "enumeratedValues"
+                        && text(*n, "name").as_deref() == Some(name)
+                })
+            });
+            for value in list.list(None, &["enumeratedValue"]) {
+                let name = text(value, "name").unwrap_or_default();
+                let Some(written) = text(value, "value") else {
+                    self.diags.push(
+                        Diagnostic::warning(
+                            "unsupported-element",
+                            format!("{}: isDefault value {} is not imported", what, name),
+                        )
+                        .at(Some(line(value)), None),
+                    );
+                    continue;
+                };
+                let Some(number) = svd_number(&written) else {
+                    self.diags.push(
+                        Diagnostic::warning(
+                            "unsupported-element",
+                            format!(
+                                "{}: value \"{}\" of {} has don't care bits or is not a number, not imported",
+                                what, written, name
+                            ),
+                        )
+                        .at(Some(line(value)), None),
+                    );
+                    continue;
+                };
+                if values.iter().any(|v| v.name == name) {
+                    continue;
+                }
+                values.push(ImportEnumeratedValue {
+                    name,
+                    // binary `#0101` is not understood downstream
+                    value: if written.starts_with('#') {
+                        number.to_string()
+                    } else {
+                        written
+                    },
+                    description: text(value, "description"),
+                });
+            }
+        }
+
+        values
+    }
+}
+
+/// Register, cluster or field called `name` in `node`, or in its `registers`/`fields`.
+fn sibling_named<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    let containers = [Some(node), child(node, "registers"), child(node, "fields")];
+    containers.into_iter().flatten().find_map(|c| {
+        c.children().find(|n| {
+            n.is_element()
+                && ["register", "cluster", "field"].contains(&n.tag_name().name())
+                && text(*n, "name").as_deref() == Some(name)
+        })
+    })
+}
+
+/// `[msb:lsb]`
+fn bit_range(range: &str) -> Option<(u64, u64)> {
+    let inner = range.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (msb, lsb) = inner.split_once(':')?;
+    Some((msb.trim().parse().ok()?, lsb.trim().parse().ok()?))
+}
+
+/// `dimIndex` as a list (`A,B,C`) or a numeric or letter range (`0-3`, `A-D`).
+fn dim_index(index: &str) -> Option<Vec<String>> {
+    if index.contains(',') {
+        return Some(index.split(',').map(|s| s.trim().to_string()).collect());
+    }
+    let (first, last) = index.split_once('-')?;
+    let (first, last) = (first.trim(), last.trim());
+    if let (Ok(first), Ok(last)) = (first.parse::<u64>(), last.parse::<u64>()) {
+        return Some((first..=last).map(|i| i.to_string()).collect());
+    }
+    let (first, last) = (first.chars().next()?, last.chars().next()?);
+    (first.is_ascii_uppercase() && last.is_ascii_uppercase())
+        .then(|| (first..=last).map(|c| c.to_string()).collect())
+}
+
+/// SVD access values are spelled as in IP-XACT; unspecified means read-write.
+fn access(access: &Option<String>) -> String {
+    access.clone().unwrap_or_else(|| "read-write".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_svd_numbers_and_dim_indices() {
+        assert_eq!(svd_number("0x40"), Some(0x40));
+        assert_eq!(svd_number("#1010"), Some(10));
+        assert_eq!(svd_number("4k"), Some(4096));
+        assert_eq!(svd_number("#1x0"), None);
+
+        assert_eq!(dim_index("0-3").unwrap(), ["0", "1", "2", "3"]);
+        assert_eq!(dim_index("A-C").unwrap(), ["A", "B", "C"]);
+        assert_eq!(dim_index("TX, RX").unwrap(), ["TX", "RX"]);
+
+        assert_eq!(expand("CH%s_CTRL", "A"), "CHA_CTRL");
+        assert_eq!(expand("DATA[%s]", "2"), "DATA_2");
+        assert_eq!(bit_range("[7:4]"), Some((7, 4)));
+    }
+}
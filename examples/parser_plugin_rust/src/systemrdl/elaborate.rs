@@ -0,0 +1,722 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::parser::{ComponentDef, EnumDef, Kind, Property, Value};
+use crate::diagnostic::Diagnostic;
+use crate::expr::eval;
+use crate::layout::{elements, FieldBits};
+use crate::number::hex;
+use crate::options::ParseOptions;
+use crate::types::*;
+
+/// Parameter values in effect in a component.
+type Env = HashMap<String, u64>;
+
+/// A property assignment together with the parameters it is evaluated with:
+/// those of the component itself, or of the parent for `inst->prop` assignments.
+#[derive(Clone)]
+struct Assigned {
+    value: Value,
+    env: Rc<Env>,
+    line: usize,
+}
+
+type Props = HashMap<String, Assigned>;
+
+/// `a.b->prop = value` seen from the component the path starts in.
+#[derive(Clone)]
+struct Override {
+    path: Vec<String>,
+    prop: Property,
+    env: Rc<Env>,
+}
+
+#[derive(Clone)]
+struct Reg {
+    name: String,
+    offset: u64,
+    size: u32,
+    description: Option<String>,
+    fields: Vec<ImportField>,
+}
+
+/// Registers of an elaborated component, with offsets relative to it.
+#[derive(Clone)]
+struct Elaborated {
+    regs: Vec<Reg>,
+    /// bytes spanned, the end of the last register
+    size: u64,
+    /// alignment in `compact` addressing, the access width of its registers
+    align: u64,
+}
+
+struct Block {
+    name: String,
+    base: u64,
+    elaborated: Elaborated,
+}
+
+/// Elaborates the `top` addrmap into one memory map.
+///
+/// Every addrmap instantiated in `top` becomes an address block; registers placed
+/// directly in `top` go into a block named after it. Regfiles and deeper addrmaps
+/// are flattened into their block, their instance name prefixing the registers
+/// (`rf[2]` holding `ctrl` gives `rf_0_ctrl` and `rf_1_ctrl`).
+pub fn elaborate(
+    top: &ComponentDef,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> ImportData {
+    let mut elaborator = Elaborator { opts, diags };
+    let name = top.name.clone().unwrap_or_default();
+    let env = elaborator.params(top, &Rc::new(Env::new()), &[]);
+    let props = elaborator.props(top, &env, &[]);
+
+    let mut blocks = Vec::new();
+    let own = elaborator.container(top, &name, &env, &[], Some(&mut blocks));
+    if !own.regs.is_empty() {
+        let size = own
+            .regs
+            .iter()
+            .map(|r| r.offset + (r.size as u64).div_ceil(8))
+            .max()
+            .unwrap_or(0);
+        blocks.insert(
+            0,
+            Block {
+                name: name.clone(),
+                base: 0,
+                elaborated: Elaborated { size, ..own },
+            },
+        );
+    }
+
+    let address_blocks = blocks
+        .into_iter()
+        .map(|block| ImportAddressBlock {
+            name: block.name,
+            base_address: hex(block.base),
+            range: hex(block.elaborated.size),
+            width: block
+                .elaborated
+                .regs
+                .iter()
+                .map(|r| r.size)
+                .max()
+                .unwrap_or(32),
+            registers: block
+                .elaborated
+                .regs
+                .into_iter()
+                .map(|r| ImportRegister {
+                    name: r.name,
+                    address_offset: hex(r.offset),
+                    size: r.size,
+                    description: r.description,
+                    fields: r.fields,
+                })
+                .collect(),
+        })
+        .collect();
+
+    ImportData {
+        project: ImportProject {
+            name: name.clone(),
+            description: elaborator.text(&props, "desc"),
+            vendor: String::new(),
+            library: String::new(),
+            version: "0.1.0".to_string(),
+        },
+        memory_maps: vec![ImportMemoryMap {
+            name,
+            address_blocks,
+        }],
+    }
+}
+
+struct Elaborator<'a> {
+    opts: &'a ParseOptions,
+    diags: &'a mut Vec<Diagnostic>,
+}
+
+impl Elaborator<'_> {
+    /// A value that cannot be used: warning naming the default, error in strict mode.
+    fn fallback(&mut self, code: &str, line: usize, problem: String, default: &str) {
+        let diag = Diagnostic::fallback(self.opts.strict, code, problem, default);
+        self.diags.push(diag.at(Some(line), None));
+    }
+
+    fn eval(expr: &str, env: &Env) -> Option<u64> {
+        eval(expr, &|name| match name {
+            "true" => Some(1),
+            "false" => Some(0),
+            _ => env.get(name).copied(),
+        })
+    }
+
+    /// Evaluates `expr` of an instance, reporting it when it is not a number.
+    fn number(&mut self, expr: &str, env: &Env, line: usize, what: &str, default: u64) -> u64 {
+        Self::eval(expr, env).unwrap_or_else(|| {
+            self.fallback(
+                "invalid-number",
+                line,
+                format!("{} \"{}\" cannot be evaluated", what, expr),
+                &format!("using {}", default),
+            );
+            default
+        })
+    }
+
+    /// Numeric property, `None` when it is not assigned.
+    fn number_prop(&mut self, props: &Props, name: &str, what: &str, default: u64) -> Option<u64> {
+        let assigned = props.get(name)?;
+        let (value, written) = match &assigned.value {
+            Value::Expr(expr) => (Self::eval(expr, &assigned.env), expr.as_str()),
+            Value::Str(text) => (None, text.as_str()),
+            Value::Enum(_) => (None, "an enum"),
+        };
+        Some(value.unwrap_or_else(|| {
+            self.fallback(
+                "invalid-number",
+                assigned.line,
+                format!("{}: {} \"{}\" cannot be evaluated", what, name, written),
+                &format!("using {}", default),
+            );
+            default
+        }))
+    }
+
+    /// Property holding a keyword such as `rw` or `woclr`.
+    fn word(props: &Props, name: &str) -> Option<(String, usize)> {
+        match &props.get(name)?.value {
+            Value::Expr(expr) => Some((expr.clone(), props[name].line)),
+            _ => None,
+        }
+    }
+
+    fn flag(props: &Props, name: &str) -> bool {
+        props.get(name).is_some_and(
+            |p| matches!(&p.value, Value::Expr(e) if Self::eval(e, &p.env).is_some_and(|v| v != 0)),
+        )
+    }
+
+    fn text(&self, props: &Props, name: &str) -> Option<String> {
+        match &props.get(name)?.value {
+            Value::Str(text) => {
+                // descriptions are often indented with the source
+                let text = text.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+                Some(text.trim().to_string()).filter(|t| !t.is_empty())
+            }
+            _ => None,
+        }
+    }
+
+    /// Parameters of an instance of `def`: the defaults, then `#(.NAME(value))`
+    /// evaluated with the parameters of the parent. Parameters of enclosing
+    /// components stay visible, as nested definitions may use them.
+    fn params(
+        &mut self,
+        def: &ComponentDef,
+        parent: &Rc<Env>,
+        overrides: &[(String, String)],
+    ) -> Rc<Env> {
+        let mut env = (**parent).clone();
+        for param in &def.params {
+            if let Some(value) = param.default.as_deref().and_then(|d| Self::eval(d, &env)) {
+                env.insert(param.name.clone(), value);
+            }
+        }
+        for (name, expr) in overrides {
+            if !def.params.iter().any(|p| &p.name == name) {
+                self.diags.push(
+                    Diagnostic::warning(
+                        "unknown-parameter",
+                        format!(
+                            "{} has no parameter {}",
+                            def.name.as_deref().unwrap_or("Component"),
+                            name
+                        ),
+                    )
+                    .at(Some(def.line), None),
+                );
+                continue;
+            }
+            if let Some(value) = Self::eval(expr, parent) {
+                env.insert(name.clone(), value);
+            } else {
+                self.fallback(
+                    "invalid-number",
+                    def.line,
+                    format!("Parameter {} \"{}\" cannot be evaluated", name, expr),
+                    "using its default",
+                );
+            }
+        }
+        Rc::new(env)
+    }
+
+    /// Properties of a component: enclosing defaults, then its own assignments,
+    /// then `inst->prop` assignments of the components around it.
+    fn props(&self, def: &ComponentDef, env: &Rc<Env>, overrides: &[Override]) -> Props {
+        let mut props = Props::new();
+        for prop in def.defaults.iter().chain(&def.props) {
+            props.insert(
+                prop.name.clone(),
+                Assigned {
+                    value: prop.value.clone(),
+                    env: env.clone(),
+                    line: prop.line,
+                },
+            );
+        }
+        for o in overrides.iter().filter(|o| o.path.is_empty()) {
+            props.insert(
+                o.prop.name.clone(),
+                Assigned {
+                    value: o.prop.value.clone(),
+                    env: o.env.clone(),
+                    line: o.prop.line,
+                },
+            );
+        }
+        props
+    }
+
+    /// Assignments targeting `inst` or below it, with `inst` removed from the path.
+    fn overrides_for(
+        def: &ComponentDef,
+        env: &Rc<Env>,
+        inherited: &[Override],
+        inst: &str,
+    ) -> Vec<Override> {
+        let own = def.dynamic.iter().map(|d| Override {
+            path: d.path.clone(),
+            prop: d.prop.clone(),
+            env: env.clone(),
+        });
+        inherited
+            .iter()
+            .filter(|o| !o.path.is_empty())
+            .cloned()
+            .chain(own)
+            .filter(|o| o.path[0] == inst)
+            .map(|o| Override {
+                path: o.path[1..].to_vec(),
+                ..o
+            })
+            .collect()
+    }
+
+    /// Places the instances of an addrmap or regfile. With `blocks`, the instances
+    /// that are addrmaps are collected there instead (the top-level addrmap).
+    fn container(
+        &mut self,
+        def: &ComponentDef,
+        name: &str,
+        env: &Rc<Env>,
+        overrides: &[Override],
+        mut blocks: Option<&mut Vec<Block>>,
+    ) -> Elaborated {
+        let props = self.props(def, env, overrides);
+        let what = format!(
+            "{} {}",
+            if def.kind == Kind::Addrmap {
+                "Addrmap"
+            } else {
+                "Regfile"
+            },
+            name
+        );
+        let addressing = match Self::word(&props, "addressing") {
+            Some((mode, _)) if mode == "compact" || mode == "fullalign" => mode,
+            Some((mode, line)) if mode != "regalign" => {
+                self.fallback(
+                    "invalid-property",
+                    line,
+                    format!("{}: unknown addressing \"{}\"", what, mode),
+                    "using regalign",
+                );
+                "regalign".to_string()
+            }
+            _ => "regalign".to_string(),
+        };
+        let alignment = self.number_prop(&props, "alignment", &what, 1);
+
+        let mut regs = Vec::new();
+        let mut next = 0u64;
+        let mut end = 0u64;
+        let mut align = 1u64;
+
+        for inst in &def.instances {
+            let child = &inst.def;
+            match child.kind {
+                Kind::Reg | Kind::Regfile | Kind::Addrmap => {}
+                Kind::Signal => continue,
+                Kind::Mem => {
+                    self.unsupported(format!("Memory {} is not imported", inst.name), inst.line);
+                    continue;
+                }
+                Kind::Field => {
+                    self.diags.push(
+                        Diagnostic::error(
+                            "misplaced-field",
+                            format!("Field {} is not inside a reg", inst.name),
+                        )
+                        .at(Some(inst.line), None),
+                    );
+                    continue;
+                }
+            }
+
+            let child_env = self.params(child, env, &inst.overrides);
+            let child_overrides = Self::overrides_for(def, env, overrides, &inst.name);
+            let elaborated = if child.kind == Kind::Reg {
+                self.reg(child, &inst.name, &child_env, &child_overrides)
+            } else {
+                self.container(child, &inst.name, &child_env, &child_overrides, None)
+            };
+
+            let dims: Vec<u64> = inst
+                .dims
+                .iter()
+                .map(|d| self.number(d, env, inst.line, &format!("{}: array size", inst.name), 1))
+                .collect();
+            let count = dims.iter().product::<u64>();
+            let stride = match &inst.stride {
+                Some(s) => self.number(
+                    s,
+                    env,
+                    inst.line,
+                    &format!("{}: stride", inst.name),
+                    elaborated.size,
+                ),
+                None => elaborated.size,
+            }
+            .max(1);
+            let addr = match &inst.at {
+                Some(at) => {
+                    self.number(at, env, inst.line, &format!("{}: address", inst.name), next)
+                }
+                None => {
+                    let natural = match addressing.as_str() {
+                        "compact" => elaborated.align,
+                        "fullalign" if count > 1 => (stride * count).next_power_of_two(),
+                        _ => elaborated.size.next_power_of_two(),
+                    };
+                    let align = match &inst.align {
+                        Some(a) => self.number(
+                            a,
+                            env,
+                            inst.line,
+                            &format!("{}: alignment", inst.name),
+                            natural,
+                        ),
+                        None => alignment.unwrap_or(natural),
+                    };
+                    next.next_multiple_of(align.max(1))
+                }
+            };
+            next = addr + stride * count;
+            end = end.max(next);
+            align = align.max(elaborated.align);
+
+            for (suffix, index) in elements(&dims) {
+                let inst_name = format!("{}{}", inst.name, suffix);
+                let base = addr + index * stride;
+                match (&mut blocks, child.kind) {
+                    (Some(blocks), Kind::Addrmap) => blocks.push(Block {
+                        name: inst_name,
+                        base,
+                        elaborated: elaborated.clone(),
+                    }),
+                    _ => regs.extend(elaborated.regs.iter().map(|r| Reg {
+                        name: if child.kind == Kind::Reg {
+                            inst_name.clone()
+                        } else {
+                            format!("{}_{}", inst_name, r.name)
+                        },
+                        offset: base + r.offset,
+                        ..r.clone()
+                    })),
+                }
+            }
+        }
+
+        Elaborated {
+            regs,
+            size: end,
+            align,
+        }
+    }
+
+    fn unsupported(&mut self, what: String, line: usize) {
+        self.diags
+            .push(Diagnostic::warning("unsupported-element", what).at(Some(line), None));
+    }
+
+    fn reg(
+        &mut self,
+        def: &ComponentDef,
+        name: &str,
+        env: &Rc<Env>,
+        overrides: &[Override],
+    ) -> Elaborated {
+        let props = self.props(def, env, overrides);
+        let what = format!("Register {}", name);
+
+        let mut width = self
+            .number_prop(&props, "regwidth", &what, 32)
+            .unwrap_or(32);
+        if width < 8 || !width.is_power_of_two() {
+            self.fallback(
+                "invalid-property",
+                props["regwidth"].line,
+                format!(
+                    "{}: regwidth {} is not a power of two of at least 8",
+                    what, width
+                ),
+                "using 32",
+            );
+            width = 32;
+        }
+        let access_width = self
+            .number_prop(&props, "accesswidth", &what, width)
+            .unwrap_or(width)
+            .clamp(8, width);
+        let msb0 = Self::flag(&props, "msb0");
+
+        let mut fields = Vec::new();
+        let mut bits_taken = FieldBits::new(width);
+        // next free bit: counting up from 0 (lsb0) or down from the msb (msb0)
+        let mut next = if msb0 { width as i64 - 1 } else { 0 };
+
+        for inst in &def.instances {
+            if inst.def.kind != Kind::Field {
+                self.unsupported(
+                    format!(
+                        "{}: only fields are imported, {} is ignored",
+                        what, inst.name
+                    ),
+                    inst.line,
+                );
+                continue;
+            }
+            let field_env = self.params(&inst.def, env, &inst.overrides);
+            // `f = value` is the reset of this instance, `inst->reset` of enclosing
+            // components still take precedence
+            let inline = inst.reset.iter().map(|expr| Override {
+                path: Vec::new(),
+                prop: Property {
+                    name: "reset".to_string(),
+                    value: Value::Expr(expr.clone()),
+                    line: inst.line,
+                },
+                env: env.clone(),
+            });
+            let field_overrides: Vec<Override> = inline
+                .chain(Self::overrides_for(def, env, overrides, &inst.name))
+                .collect();
+            let field_props = self.props(&inst.def, &field_env, &field_overrides);
+            let field_what = format!("Field {}.{}", name, inst.name);
+
+            let (lsb, bits) = match &inst.range {
+                Some((a, b)) => {
+                    let a = self.number(a, env, inst.line, &format!("{}: bit", field_what), 0);
+                    let b = self.number(b, env, inst.line, &format!("{}: bit", field_what), 0);
+                    (a.min(b), a.abs_diff(b) + 1)
+                }
+                None => {
+                    let bits = match inst.dims.first() {
+                        Some(d) => {
+                            self.number(d, env, inst.line, &format!("{}: width", field_what), 1)
+                        }
+                        None => self
+                            .number_prop(&field_props, "fieldwidth", &field_what, 1)
+                            .unwrap_or(1),
+                    };
+                    let lsb = if msb0 { next + 1 - bits as i64 } else { next };
+                    (lsb.max(0) as u64, bits)
+                }
+            };
+            next = if msb0 {
+                lsb as i64 - 1
+            } else {
+                (lsb + bits) as i64
+            };
+
+            if let Err(diag) = bits_taken.take(&field_what, lsb, bits) {
+                self.diags.push(diag.at(Some(inst.line), None));
+                continue;
+            }
+
+            let reset = self
+                .number_prop(&field_props, "reset", &field_what, 0)
+                .unwrap_or(0);
+
+            let (access, modified_write_value, read_action) =
+                self.access(&field_props, &field_what, inst.line);
+            let enumerated_values = match field_props.get("encode").map(|p| &p.value) {
+                Some(Value::Enum(def)) => enum_values(def),
+                _ => Vec::new(),
+            };
+
+            fields.push(ImportField {
+                name: inst.name.clone(),
+                description: self
+                    .text(&field_props, "desc")
+                    .or_else(|| self.text(&field_props, "name")),
+                bit_offset: lsb as u32,
+                bit_width: bits as u32,
+                access: access.to_string(),
+                modified_write_value: modified_write_value.map(str::to_string),
+                read_action: read_action.map(str::to_string),
+                reset_value: hex(reset),
+                enumerated_values,
+            });
+        }
+
+        if fields.is_empty() {
+            self.diags.push(
+                Diagnostic::warning("empty-register", format!("{} has no fields", what))
+                    .at(Some(def.line), None),
+            );
+        }
+        fields.sort_by_key(|f| f.bit_offset);
+
+        Elaborated {
+            regs: vec![Reg {
+                name: String::new(),
+                offset: 0,
+                size: width as u32,
+                description: self
+                    .text(&props, "desc")
+                    .or_else(|| self.text(&props, "name")),
+                fields,
+            }],
+            size: width / 8,
+            align: access_width / 8,
+        }
+    }
+
+    /// IP-XACT access, modifiedWriteValue and readAction of a field from its `sw`,
+    /// `onread`/`onwrite` and the `rclr`/`rset`/`woclr`/`woset` shorthands. `hw`
+    /// is only checked, the import has no hardware side.
+    fn access(
+        &mut self,
+        props: &Props,
+        what: &str,
+        line: usize,
+    ) -> (&'static str, Option<&'static str>, Option<&'static str>) {
+        let access = match Self::word(props, "sw") {
+            None => "read-write",
+            Some((sw, line)) => match sw.as_str() {
+                "rw" | "wr" => "read-write",
+                "r" => "read-only",
+                "w" => "write-only",
+                "rw1" => "read-writeOnce",
+                "w1" => "writeOnce",
+                "na" => {
+                    self.unsupported(
+                        format!(
+                            "{}: sw = na has no IP-XACT equivalent, imported as read-only",
+                            what
+                        ),
+                        line,
+                    );
+                    "read-only"
+                }
+                _ => {
+                    self.fallback(
+                        "unknown-access",
+                        line,
+                        format!("{}: unknown sw access \"{}\"", what, sw),
+                        "using read-write",
+                    );
+                    "read-write"
+                }
+            },
+        };
+        if let Some((hw, line)) = Self::word(props, "hw") {
+            if !["rw", "wr", "r", "w", "rw1", "w1", "na"].contains(&hw.as_str()) {
+                self.fallback(
+                    "unknown-access",
+                    line,
+                    format!("{}: unknown hw access \"{}\"", what, hw),
+                    "ignored",
+                );
+            }
+        }
+
+        let read_action = match Self::word(props, "onread") {
+            Some((onread, line)) => match onread.as_str() {
+                "rclr" => Some("clear"),
+                "rset" => Some("set"),
+                "ruser" => Some("modify"),
+                _ => {
+                    self.fallback(
+                        "unknown-access",
+                        line,
+                        format!("{}: unknown onread \"{}\"", what, onread),
+                        "ignored",
+                    );
+                    None
+                }
+            },
+            None if Self::flag(props, "rclr") => Some("clear"),
+            None if Self::flag(props, "rset") => Some("set"),
+            None => None,
+        };
+
+        let modified_write_value = match Self::word(props, "onwrite") {
+            Some((onwrite, line)) => match onwrite.as_str() {
+                "woset" => Some("oneToSet"),
+                "woclr" => Some("oneToClear"),
+                "wot" => Some("oneToToggle"),
+                "wzs" => Some("zeroToSet"),
+                "wzc" => Some("zeroToClear"),
+                "wzt" => Some("zeroToToggle"),
+                "wclr" => Some("clear"),
+                "wset" => Some("set"),
+                "wuser" => Some("modify"),
+                _ => {
+                    self.fallback(
+                        "unknown-access",
+                        line,
+                        format!("{}: unknown onwrite \"{}\"", what, onwrite),
+                        "ignored",
+                    );
+                    None
+                }
+            },
+            None if Self::flag(props, "woclr") => Some("oneToClear"),
+            None if Self::flag(props, "woset") => Some("oneToSet"),
+            None => None,
+        };
+
+        if access == "read-only" && modified_write_value.is_some() {
+            self.diags.push(
+                Diagnostic::warning(
+                    "inconsistent-access",
+                    format!(
+                        "{}: write side effect on a field software cannot write",
+                        what
+                    ),
+                )
+                .at(Some(line), None),
+            );
+        }
+
+        (access, modified_write_value, read_action)
+    }
+}
+
+fn enum_values(def: &EnumDef) -> Vec<ImportEnumeratedValue> {
+    def.entries
+        .iter()
+        .map(|entry| ImportEnumeratedValue {
+            name: entry.name.clone(),
+            value: entry.value.to_string(),
+            description: entry.desc.clone(),
+        })
+        .collect()
+}
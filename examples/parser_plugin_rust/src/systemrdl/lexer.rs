@@ -0,0 +1,178 @@
+use crate::diagnostic::Diagnostic;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    Ident(String),
+    /// Number as written (`12`, `0x1F`, `4'hF`), evaluated later
+    Number(String),
+    Str(String),
+    Punct(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub tok: Tok,
+    pub line: usize,
+}
+
+// longest first, so that `->` is not read as `-` `>`
+const PUNCTS: [&str; 36] = [
+    "::", "->", "+=", "%=", "**", "<<", ">>", "==", "!=", "<=", ">=", "&&", "||", "'{", "{", "}",
+    "[", "]", "(", ")", ";", ",", ".", "=", ":", "?", "#", "@", "+", "-", "*", "/", "%", "&", "|",
+    "^",
+];
+const SINGLE: [&str; 4] = ["~", "!", "<", ">"];
+
+/// Splits SystemRDL source into tokens, dropping comments.
+///
+/// Preprocessor directives (`` `include ``, `` `define ``, ...) and embedded Perl
+/// (`<% %>`) cannot be expanded here: they are skipped with an `unsupported-element`
+/// warning. Returns the line and message of the first character that is not valid.
+pub fn tokenize(src: &str, diags: &mut Vec<Diagnostic>) -> Result<Vec<Token>, (usize, String)> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    let starts = |i: usize, s: &str| {
+        s.chars()
+            .enumerate()
+            .all(|(k, c)| chars.get(i + k) == Some(&c))
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if starts(i, "//") {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if starts(i, "/*") {
+            i += 2;
+            while i < chars.len() && !starts(i, "*/") {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+        } else if c == '`' {
+            let start = i;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            let directive: String = chars[start..i].iter().collect();
+            diags.push(
+                Diagnostic::warning(
+                    "unsupported-element",
+                    format!("Preprocessor directive \"{}\" is ignored", directive.trim()),
+                )
+                .at(Some(line), None),
+            );
+        } else if starts(i, "<%") {
+            diags.push(
+                Diagnostic::warning("unsupported-element", "Embedded Perl (<% %>) is ignored")
+                    .at(Some(line), None),
+            );
+            while i < chars.len() && !starts(i, "%>") {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' {
+            let start_line = line;
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err((start_line, "Unterminated string".to_string())),
+                    Some('"') => break,
+                    Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                        text.push('"');
+                        i += 1;
+                    }
+                    Some(&ch) => {
+                        if ch == '\n' {
+                            line += 1;
+                        }
+                        text.push(ch);
+                    }
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token {
+                tok: Tok::Str(text),
+                line: start_line,
+            });
+        } else if c.is_ascii_digit()
+            || (c == '\'' && chars.get(i + 1).is_some_and(|n| n.is_ascii_alphabetic()))
+        {
+            // 12, 0x1F, 4'hF, 'b1
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '\'')
+            {
+                i += 1;
+            }
+            tokens.push(Token {
+                tok: Tok::Number(chars[start..i].iter().collect()),
+                line,
+            });
+        } else if c.is_alphabetic() || c == '_' || c == '\\' {
+            // `\name` escapes a keyword used as identifier
+            let start = if c == '\\' { i + 1 } else { i };
+            i = start;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token {
+                tok: Tok::Ident(chars[start..i].iter().collect()),
+                line,
+            });
+        } else if let Some(p) = PUNCTS.iter().chain(SINGLE.iter()).find(|p| starts(i, p)) {
+            tokens.push(Token {
+                tok: Tok::Punct(p),
+                line,
+            });
+            i += p.len();
+        } else {
+            return Err((line, format!("Unexpected character '{}'", c)));
+        }
+    }
+
+    tokens.push(Token {
+        tok: Tok::Eof,
+        line,
+    });
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_literals_comments_and_directives() {
+        let mut diags = Vec::new();
+        let src = "`include \"x.rdl\"\nreg /* a\nb */ r { f[3:0] = 4'hA; }; // done\nr->desc = \"say \\\"hi\\\"\";";
+        let tokens = tokenize(src, &mut diags).unwrap();
+        let toks: Vec<_> = tokens.iter().map(|t| (t.tok.clone(), t.line)).collect();
+
+        assert_eq!(toks[0], (Tok::Ident("reg".into()), 2));
+        assert_eq!(toks[1], (Tok::Ident("r".into()), 3));
+        assert_eq!(toks[6], (Tok::Punct(":"), 3));
+        assert_eq!(toks[10], (Tok::Number("4'hA".into()), 3));
+        assert_eq!(toks[15], (Tok::Punct("->"), 4));
+        assert_eq!(toks[18], (Tok::Str("say \"hi\"".into()), 4));
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].code, "unsupported-element");
+    }
+}
@@ -0,0 +1,51 @@
+//! SystemRDL 2.0 register descriptions.
+
+mod elaborate;
+mod lexer;
+mod parser;
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::options::ParseOptions;
+use crate::types::ImportData;
+
+/// Parses and elaborates SystemRDL source into `ImportData`.
+///
+/// The top of the design is the last root-level addrmap that is never
+/// instantiated. Syntax errors are reported as `syntax-error` with their line.
+pub fn read_rdl(
+    src: &str,
+    opts: &ParseOptions,
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Option<ImportData>, Error> {
+    let parsed = lexer::tokenize(src, diags).and_then(|tokens| parser::parse(&tokens, diags));
+    let tops = match parsed {
+        Ok(tops) => tops,
+        Err((line, message)) => {
+            diags.push(Diagnostic::error("syntax-error", message).at(Some(line), None));
+            return Ok(None);
+        }
+    };
+
+    let Some(top) = tops.last() else {
+        diags.push(Diagnostic::error(
+            "missing-addrmap",
+            "No addrmap to elaborate: define one at the top level and do not instantiate it",
+        ));
+        return Ok(None);
+    };
+    if tops.len() > 1 {
+        diags.push(
+            Diagnostic::info(
+                "top-addrmap",
+                format!(
+                    "Several addrmaps are never instantiated, elaborating the last one ({})",
+                    top.name.as_deref().unwrap_or_default()
+                ),
+            )
+            .at(Some(top.line), None),
+        );
+    }
+
+    Ok(Some(elaborate::elaborate(top, opts, diags)))
+}
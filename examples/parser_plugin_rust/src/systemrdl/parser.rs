@@ -0,0 +1,658 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use super::lexer::{Tok, Token};
+use crate::diagnostic::Diagnostic;
+use crate::expr::eval;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Addrmap,
+    Regfile,
+    Reg,
+    Field,
+    Mem,
+    Signal,
+}
+
+impl Kind {
+    fn from_keyword(word: &str) -> Option<Self> {
+        Some(match word {
+            "addrmap" => Kind::Addrmap,
+            "regfile" => Kind::Regfile,
+            "reg" => Kind::Reg,
+            "field" => Kind::Field,
+            "mem" => Kind::Mem,
+            "signal" => Kind::Signal,
+            _ => return None,
+        })
+    }
+}
+
+/// Right-hand side of a property assignment. Expressions are kept as text and
+/// evaluated during elaboration, once parameter values are known.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Expr(String),
+    Str(String),
+    Enum(Rc<EnumDef>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+/// `path->prop = value;`, assigning a property of a (nested) instance.
+#[derive(Debug, Clone)]
+pub struct Dynamic {
+    pub path: Vec<String>,
+    pub prop: Property,
+}
+
+#[derive(Debug)]
+pub struct EnumDef {
+    pub entries: Vec<EnumEntry>,
+}
+
+#[derive(Debug)]
+pub struct EnumEntry {
+    pub name: String,
+    pub value: u64,
+    pub desc: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Param {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+/// A component definition, named or anonymous.
+#[derive(Debug)]
+pub struct ComponentDef {
+    pub kind: Kind,
+    pub name: Option<String>,
+    pub params: Vec<Param>,
+    /// `default` assignments of the enclosing scopes, in effect where the definition starts
+    pub defaults: Vec<Property>,
+    pub props: Vec<Property>,
+    pub dynamic: Vec<Dynamic>,
+    pub instances: Vec<Instance>,
+    pub line: usize,
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub def: Rc<ComponentDef>,
+    pub name: String,
+    /// `#(.NAME(expr))` parameter values
+    pub overrides: Vec<(String, String)>,
+    pub dims: Vec<String>,
+    /// `[msb:lsb]` of a field
+    pub range: Option<(String, String)>,
+    pub reset: Option<String>,
+    pub at: Option<String>,
+    pub stride: Option<String>,
+    pub align: Option<String>,
+    pub line: usize,
+}
+
+#[derive(Default)]
+struct Body {
+    props: Vec<Property>,
+    dynamic: Vec<Dynamic>,
+    instances: Vec<Instance>,
+}
+
+#[derive(Default)]
+struct Scope {
+    types: HashMap<String, Rc<ComponentDef>>,
+    enums: HashMap<String, Rc<EnumDef>>,
+    defaults: Vec<Property>,
+}
+
+pub type ParseError = (usize, String);
+
+/// Parses a SystemRDL description and returns the root-level addrmaps that are
+/// never instantiated, in source order: the candidates for the top of the design.
+///
+/// Component types and enums are resolved while parsing, in the lexical scope
+/// they are used in, and so are `default` assignments.
+pub fn parse(
+    tokens: &[Token],
+    diags: &mut Vec<Diagnostic>,
+) -> Result<Vec<Rc<ComponentDef>>, ParseError> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        scopes: vec![Scope::default()],
+        roots: Vec::new(),
+        instantiated: HashSet::new(),
+        diags,
+    };
+
+    let mut body = Body::default();
+    parser.items(&mut body)?;
+    if parser.peek() != &Tok::Eof {
+        return Err((parser.line(), "Unexpected '}'".to_string()));
+    }
+    for inst in body.instances.iter().filter(|i| i.def.kind != Kind::Signal) {
+        parser.diags.push(
+            Diagnostic::warning(
+                "unsupported-element",
+                format!("Instance {} outside of a component is ignored", inst.name),
+            )
+            .at(Some(inst.line), None),
+        );
+    }
+
+    let instantiated = parser.instantiated;
+    Ok(parser
+        .roots
+        .into_iter()
+        .filter(|def| !instantiated.contains(&Rc::as_ptr(def)))
+        .collect())
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    scopes: Vec<Scope>,
+    roots: Vec<Rc<ComponentDef>>,
+    instantiated: HashSet<*const ComponentDef>,
+    diags: &'a mut Vec<Diagnostic>,
+}
+
+const MODIFIERS: [&str; 5] = ["posedge", "negedge", "bothedge", "level", "nonsticky"];
+
+impl Parser<'_> {
+    fn peek(&self) -> &Tok {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, n: usize) -> &Tok {
+        let last = self.tokens.len() - 1;
+        &self.tokens[(self.pos + n).min(last)].tok
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos.min(self.tokens.len() - 1)].line
+    }
+
+    fn next(&mut self) -> Tok {
+        let tok = self.peek().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Tok::Punct(p) if *p == punct)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), ParseError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", punct)))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        match self.peek().clone() {
+            Tok::Ident(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        let found = match self.peek() {
+            Tok::Ident(name) => format!("\"{}\"", name),
+            Tok::Number(n) => n.clone(),
+            Tok::Str(s) => format!("\"{}\"", s),
+            Tok::Punct(p) => format!("'{}'", p),
+            Tok::Eof => "end of file".to_string(),
+        };
+        (
+            self.line(),
+            format!("Expected {}, found {}", expected, found),
+        )
+    }
+
+    fn unsupported(&mut self, what: String, line: usize) {
+        self.diags
+            .push(Diagnostic::warning("unsupported-element", what).at(Some(line), None));
+    }
+
+    fn find_type(&self, name: &str) -> Option<Rc<ComponentDef>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.types.get(name).cloned())
+    }
+
+    fn find_enum(&self, name: &str) -> Option<Rc<EnumDef>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.enums.get(name).cloned())
+    }
+
+    /// `default` assignments visible here, an inner scope overriding an outer one.
+    fn defaults(&self) -> Vec<Property> {
+        let mut defaults: Vec<Property> = Vec::new();
+        for prop in self.scopes.iter().flat_map(|s| &s.defaults) {
+            defaults.retain(|p| p.name != prop.name);
+            defaults.push(prop.clone());
+        }
+        defaults
+    }
+
+    /// Component body (or the whole file), up to the closing `}`.
+    fn items(&mut self, body: &mut Body) -> Result<(), ParseError> {
+        loop {
+            let line = self.line();
+            let word = match self.peek().clone() {
+                Tok::Punct("}") | Tok::Eof => return Ok(()),
+                Tok::Punct(";") => {
+                    self.next();
+                    continue;
+                }
+                Tok::Ident(word) => word,
+                _ => return Err(self.unexpected("a component, instance or property")),
+            };
+
+            match word.as_str() {
+                "default" => {
+                    self.next();
+                    let prop = self.property()?;
+                    self.scopes.last_mut().unwrap().defaults.push(prop);
+                }
+                "external" | "internal" => {
+                    self.next();
+                    if matches!(self.peek(), Tok::Ident(w) if Kind::from_keyword(w).is_some()) {
+                        self.component(body)?;
+                    } else {
+                        self.instantiation(body)?;
+                    }
+                }
+                w if Kind::from_keyword(w).is_some() => self.component(body)?,
+                "enum" => self.enum_def()?,
+                "property" | "constraint" | "struct" | "abstract" | "alias" => {
+                    self.unsupported(format!("\"{}\" is not supported and ignored", word), line);
+                    self.skip_statement();
+                }
+                w if MODIFIERS.contains(&w) => {
+                    self.next();
+                    body.props.push(self.property()?);
+                }
+                _ => match self.peek_at(1) {
+                    Tok::Punct("=") | Tok::Punct(";") => body.props.push(self.property()?),
+                    Tok::Punct("->") | Tok::Punct(".") | Tok::Punct("[") => {
+                        if let Some(dynamic) = self.dynamic()? {
+                            body.dynamic.push(dynamic);
+                        }
+                    }
+                    Tok::Ident(_) | Tok::Punct("#") => self.instantiation(body)?,
+                    _ => {
+                        self.next();
+                        return Err(self.unexpected("'=', ';' or an instance name"));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Skips a statement the importer does not model, braces included.
+    fn skip_statement(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Tok::Punct("{") => depth += 1,
+                Tok::Punct("}") => depth -= 1,
+                Tok::Punct(";") if depth <= 0 => return,
+                Tok::Eof => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// `name;` or `name = value;`, a bare name meaning `true`.
+    fn property(&mut self) -> Result<Property, ParseError> {
+        let line = self.line();
+        let name = self.ident()?;
+        let value = if self.eat("=") {
+            match (self.peek().clone(), self.peek_at(1)) {
+                (Tok::Str(text), Tok::Punct(";")) => {
+                    self.next();
+                    Value::Str(text)
+                }
+                (Tok::Ident(ident), Tok::Punct(";")) if self.find_enum(&ident).is_some() => {
+                    self.next();
+                    Value::Enum(self.find_enum(&ident).unwrap())
+                }
+                _ => Value::Expr(self.expr()?),
+            }
+        } else {
+            Value::Expr("true".to_string())
+        };
+        self.expect(";")?;
+        Ok(Property { name, value, line })
+    }
+
+    /// `a.b->prop = value;`; indexed paths are reported and skipped.
+    fn dynamic(&mut self) -> Result<Option<Dynamic>, ParseError> {
+        let line = self.line();
+        let mut path = vec![self.ident()?];
+        let mut indexed = false;
+        loop {
+            if self.eat("[") {
+                self.expr()?;
+                self.expect("]")?;
+                indexed = true;
+            } else if self.eat(".") {
+                path.push(self.ident()?);
+            } else {
+                break;
+            }
+        }
+        self.expect("->")?;
+        let prop = self.property()?;
+
+        if indexed {
+            self.unsupported(
+                format!(
+                    "Assignment of {} to a single array element of {} is ignored",
+                    prop.name,
+                    path.join(".")
+                ),
+                line,
+            );
+            return Ok(None);
+        }
+        Ok(Some(Dynamic { path, prop }))
+    }
+
+    /// `[external] type name [#(params)] { body } [instances];`
+    fn component(&mut self, body: &mut Body) -> Result<(), ParseError> {
+        let line = self.line();
+        let Tok::Ident(word) = self.next() else {
+            unreachable!("caller checked the keyword");
+        };
+        let kind = Kind::from_keyword(&word).unwrap();
+        let name = match self.peek() {
+            Tok::Ident(_) => Some(self.ident()?),
+            _ => None,
+        };
+        let params = if self.eat("#") {
+            self.param_defs()?
+        } else {
+            Vec::new()
+        };
+
+        let defaults = self.defaults();
+        self.expect("{")?;
+        self.scopes.push(Scope::default());
+        let mut inner = Body::default();
+        let parsed = self.items(&mut inner);
+        self.scopes.pop();
+        parsed?;
+        self.expect("}")?;
+
+        let def = Rc::new(ComponentDef {
+            kind,
+            name: name.clone(),
+            params,
+            defaults,
+            props: inner.props,
+            dynamic: inner.dynamic,
+            instances: inner.instances,
+            line,
+        });
+        if let Some(name) = name {
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .types
+                .insert(name, def.clone());
+            if self.scopes.len() == 1 && kind == Kind::Addrmap {
+                self.roots.push(def.clone());
+            }
+        }
+
+        if matches!(self.peek(), Tok::Ident(_)) {
+            self.instances(def, Vec::new(), body)?;
+        } else if def.name.is_none() {
+            return Err((line, format!("Anonymous {} is never instantiated", word)));
+        }
+        self.expect(";")
+    }
+
+    /// `#(longint unsigned WIDTH = 32, ...)`, only the name and default are kept.
+    fn param_defs(&mut self) -> Result<Vec<Param>, ParseError> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        loop {
+            // type keywords, then the name
+            let mut name = self.ident()?;
+            while let Tok::Ident(next) = self.peek().clone() {
+                self.next();
+                name = next;
+            }
+            if self.eat("[") {
+                self.expect("]")?;
+            }
+            let default = if self.eat("=") {
+                Some(self.expr()?)
+            } else {
+                None
+            };
+            params.push(Param { name, default });
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(params)
+    }
+
+    /// `type [#(.NAME(value), ...)] inst, ...;`
+    fn instantiation(&mut self, body: &mut Body) -> Result<(), ParseError> {
+        let line = self.line();
+        let type_name = self.ident()?;
+        let def = self
+            .find_type(&type_name)
+            .ok_or_else(|| (line, format!("Unknown component type \"{}\"", type_name)))?;
+
+        let mut overrides = Vec::new();
+        if self.eat("#") {
+            self.expect("(")?;
+            loop {
+                self.expect(".")?;
+                let name = self.ident()?;
+                self.expect("(")?;
+                overrides.push((name, self.expr()?));
+                self.expect(")")?;
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(")")?;
+        }
+
+        self.instances(def, overrides, body)?;
+        self.expect(";")
+    }
+
+    fn instances(
+        &mut self,
+        def: Rc<ComponentDef>,
+        overrides: Vec<(String, String)>,
+        body: &mut Body,
+    ) -> Result<(), ParseError> {
+        self.instantiated.insert(Rc::as_ptr(&def));
+        loop {
+            let mut inst = Instance {
+                def: def.clone(),
+                line: self.line(),
+                name: self.ident()?,
+                overrides: overrides.clone(),
+                dims: Vec::new(),
+                range: None,
+                reset: None,
+                at: None,
+                stride: None,
+                align: None,
+            };
+            while self.eat("[") {
+                let first = self.expr()?;
+                if self.eat(":") {
+                    inst.range = Some((first, self.expr()?));
+                } else {
+                    inst.dims.push(first);
+                }
+                self.expect("]")?;
+            }
+            loop {
+                let slot = if self.eat("=") {
+                    &mut inst.reset
+                } else if self.eat("@") {
+                    &mut inst.at
+                } else if self.eat("+=") {
+                    &mut inst.stride
+                } else if self.eat("%=") {
+                    &mut inst.align
+                } else {
+                    break;
+                };
+                *slot = Some(self.expr()?);
+            }
+            body.instances.push(inst);
+            if !self.eat(",") {
+                return Ok(());
+            }
+        }
+    }
+
+    /// `enum name { ENTRY [= value] [{ desc = "..."; }]; ... };`
+    fn enum_def(&mut self) -> Result<(), ParseError> {
+        self.next();
+        let name = self.ident()?;
+        self.expect("{")?;
+
+        let mut entries = Vec::new();
+        let mut next = 0;
+        while !self.eat("}") {
+            let line = self.line();
+            let entry = self.ident()?;
+            if self.eat("=") {
+                let expr = self.expr()?;
+                next = eval(&expr, &|_| None).ok_or_else(|| {
+                    (
+                        line,
+                        format!("Value of {}::{} cannot be evaluated", name, entry),
+                    )
+                })?;
+            }
+            let mut desc = None;
+            let mut display = None;
+            if self.eat("{") {
+                while !self.eat("}") {
+                    let prop = self.property()?;
+                    if let Value::Str(text) = prop.value {
+                        match prop.name.as_str() {
+                            "desc" => desc = Some(text),
+                            "name" => display = Some(text),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            self.expect(";")?;
+            entries.push(EnumEntry {
+                name: entry,
+                value: next,
+                desc: desc.or(display),
+            });
+            next += 1;
+        }
+        self.expect(";")?;
+
+        let def = Rc::new(EnumDef { entries });
+        self.scopes.last_mut().unwrap().enums.insert(name, def);
+        Ok(())
+    }
+
+    /// Expression text up to the next `;`, `,`, `]`, `)`, `:`, `{`, `}` or
+    /// instance address operator outside of brackets. `Enum::ENTRY` is replaced by
+    /// the value of the entry.
+    fn expr(&mut self) -> Result<String, ParseError> {
+        let start = self.line();
+        let mut parts: Vec<String> = Vec::new();
+        let mut depth = 0;
+        let mut ternary = 0;
+
+        loop {
+            match self.peek().clone() {
+                Tok::Punct(";" | "," | "{" | "}" | "@" | "+=" | "%=") | Tok::Eof => break,
+                Tok::Punct("]" | ")") if depth == 0 => break,
+                Tok::Punct(":") if ternary == 0 => break,
+                Tok::Punct(p) => {
+                    match p {
+                        "(" | "[" => depth += 1,
+                        ")" | "]" => depth -= 1,
+                        "?" => ternary += 1,
+                        ":" => ternary -= 1,
+                        _ => {}
+                    }
+                    parts.push(p.to_string());
+                }
+                Tok::Ident(name) => {
+                    if let (Tok::Punct("::"), Tok::Ident(entry)) =
+                        (self.peek_at(1).clone(), self.peek_at(2).clone())
+                    {
+                        let value = self
+                            .find_enum(&name)
+                            .and_then(|e| {
+                                e.entries.iter().find(|x| x.name == entry).map(|x| x.value)
+                            })
+                            .ok_or_else(|| {
+                                (
+                                    self.line(),
+                                    format!("Unknown enum entry {}::{}", name, entry),
+                                )
+                            })?;
+                        self.next();
+                        self.next();
+                        parts.push(value.to_string());
+                    } else {
+                        parts.push(name);
+                    }
+                }
+                Tok::Number(n) => parts.push(n),
+                Tok::Str(s) => parts.push(format!("\"{}\"", s)),
+            }
+            self.next();
+        }
+
+        if parts.is_empty() {
+            return Err((start, self.unexpected("a value").1));
+        }
+        Ok(parts.join(" "))
+    }
+}
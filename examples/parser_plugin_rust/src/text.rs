@@ -0,0 +1,100 @@
+//! Decoding of text sources: CSV files, XML documents and the other text formats.
+
+use encoding_rs::{Encoding, GBK, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Decodes text bytes: UTF-8 or UTF-16 with a BOM, UTF-8 without, GBK otherwise.
+pub fn decode(bytes: &[u8]) -> (String, &'static Encoding) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return (text.into_owned(), encoding);
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (text.to_string(), UTF_8);
+    }
+    (GBK.decode_without_bom_handling(bytes).0.into_owned(), GBK)
+}
+
+/// Decodes an XML document: a BOM wins, then the `encoding` of the XML declaration
+/// (`<?xml version="1.0" encoding="ISO-8859-1"?>`), then the guesses of [`decode`].
+pub fn decode_xml(bytes: &[u8]) -> (String, &'static Encoding) {
+    if Encoding::for_bom(bytes).is_some() {
+        return decode(bytes);
+    }
+    // UTF-16 without BOM, told apart by the `<?` that starts the declaration
+    let declared = if bytes.starts_with(b"<\0?\0") {
+        Some(UTF_16LE)
+    } else if bytes.starts_with(b"\0<\0?") {
+        Some(UTF_16BE)
+    } else {
+        declared_encoding(bytes)
+    };
+    match declared {
+        Some(encoding) => {
+            let (text, _) = encoding.decode_without_bom_handling(bytes);
+            (text.into_owned(), encoding)
+        }
+        None => decode(bytes),
+    }
+}
+
+/// Encoding named by the `encoding` pseudo-attribute of the XML declaration, read as
+/// ASCII, which every encoding it may name agrees with.
+fn declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = &bytes[..bytes.len().min(256)];
+    let head = String::from_utf8_lossy(head);
+    let decl = head.strip_prefix("<?xml")?;
+    let decl = &decl[..decl.find("?>")?];
+    let value = decl
+        .split_once("encoding")?
+        .1
+        .trim_start()
+        .strip_prefix('=')?;
+    let value = value.trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let label = value[1..].split(quote).next()?;
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::WINDOWS_1252;
+
+    #[test]
+    fn decodes_bom_utf8_and_gbk() {
+        let (text, encoding) = decode(b"\xEF\xBB\xBFREG,FIELD");
+        assert_eq!((text.as_str(), encoding), ("REG,FIELD", UTF_8));
+
+        let (text, encoding) = decode(b"\xFF\xFER\x00,\x00W\x00");
+        assert_eq!((text.as_str(), encoding), ("R,W", UTF_16LE));
+
+        // "寄存器" in GBK
+        let (text, encoding) = decode(b"\xBC\xC4\xB4\xE6\xC6\xF7,ADDR");
+        assert_eq!((text.as_str(), encoding), ("寄存器,ADDR", GBK));
+    }
+
+    #[test]
+    fn honours_the_xml_declaration() {
+        // "Zähler" in Latin-1, which is not valid UTF-8 and would be read as GBK
+        let xml = b"<?xml version=\"1.0\" encoding='ISO-8859-1'?><name>Z\xE4hler</name>";
+        let (text, encoding) = decode_xml(xml);
+        assert_eq!(encoding, WINDOWS_1252);
+        assert!(text.ends_with("<name>Zähler</name>"));
+
+        let (text, encoding) = decode_xml("<?xml version=\"1.0\"?><a/>".as_bytes());
+        assert_eq!(
+            (text.as_str(), encoding),
+            ("<?xml version=\"1.0\"?><a/>", UTF_8)
+        );
+
+        let utf16: Vec<u8> = "<?xml version=\"1.0\"?><a/>"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let (text, encoding) = decode_xml(&utf16);
+        assert_eq!(
+            (text.as_str(), encoding),
+            ("<?xml version=\"1.0\"?><a/>", UTF_16LE)
+        );
+    }
+}
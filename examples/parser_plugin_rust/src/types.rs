@@ -1,13 +1,29 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::cheader::ConfidenceReport;
+use crate::diagnostic::Diagnostic;
+
+/// Mirrors `ImportPluginResult` of the TypeScript side
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportPluginResult {
+    pub success: bool,
+    pub data: Option<ImportData>,
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// How sure a heuristic import (C headers) is about each item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ConfidenceReport>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImportData {
     pub project: ImportProject,
     #[serde(rename = "memoryMaps")]
     pub memory_maps: Vec<ImportMemoryMap>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImportProject {
     pub name: String,
     pub description: Option<String>,
@@ -16,22 +32,14 @@ pub struct ImportProject {
     pub version: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct VLNV {
-    pub vendor: String,
-    pub library: String,
-    pub name: String,
-    pub version: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImportMemoryMap {
     pub name: String,
     #[serde(rename = "addressBlocks")]
     pub address_blocks: Vec<ImportAddressBlock>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImportAddressBlock {
     pub name: String,
     #[serde(rename = "baseAddress")]
@@ -41,7 +49,7 @@ pub struct ImportAddressBlock {
     pub registers: Vec<ImportRegister>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImportRegister {
     pub name: String,
     #[serde(rename = "addressOffset")]
@@ -51,7 +59,7 @@ pub struct ImportRegister {
     pub fields: Vec<ImportField>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImportField {
     pub name: String,
     pub description: Option<String>,
@@ -60,6 +68,23 @@ pub struct ImportField {
     #[serde(rename = "bitWidth")]
     pub bit_width: u32,
     pub access: String, // "read-write", "read-only", etc.
+    #[serde(rename = "modifiedWriteValue")]
+    pub modified_write_value: Option<String>, // "oneToClear", "oneToSet", etc.
+    #[serde(rename = "readAction")]
+    pub read_action: Option<String>, // "clear", "set", "modify"
     #[serde(rename = "resetValue")]
     pub reset_value: String, // IP-XACT usually uses string for values (e.g. "0x0")
+    #[serde(
+        rename = "enumeratedValues",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub enumerated_values: Vec<ImportEnumeratedValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportEnumeratedValue {
+    pub name: String,
+    pub value: String,
+    pub description: Option<String>,
 }
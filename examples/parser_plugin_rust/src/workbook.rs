@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Seek};
+
+use crate::columns::{ColumnMap, SheetRole};
+use crate::csv_bundle::CsvBundle;
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::excel::{fill_merged, Sheet, ToDataFrame};
+use crate::format::{detect_format, Format};
+use crate::ods;
+use crate::options::ParseOptions;
+use crate::xlsb;
+use calamine::{CellType, Data, DataType, Dimensions, Ods, Range, Reader, Sheets, Xls, Xlsb, Xlsx};
+
+/// Sheets of a workbook, or of the CSV files of a bundle.
+pub enum Book<RS> {
+    Workbook {
+        sheets: Box<Sheets<RS>>,
+        /// Merged regions by sheet name where calamine does not expose them (.xlsb, .ods)
+        merges: HashMap<String, Vec<Dimensions>>,
+    },
+    Csv(CsvBundle),
+}
+
+impl<RS> Book<RS>
+where
+    RS: Read + Seek,
+{
+    fn sheet_names(&self) -> Vec<String> {
+        match self {
+            Book::Workbook { sheets, .. } => sheets.sheet_names(),
+            Book::Csv(bundle) => bundle.sheet_names(),
+        }
+    }
+
+    /// Cells of sheet `name`, with merged regions filled.
+    fn worksheet_range(&mut self, name: &str) -> Result<Range<Data>, Error> {
+        let (wb, own_merges) = match self {
+            Book::Workbook { sheets, merges } => (sheets, merges),
+            Book::Csv(bundle) => {
+                return bundle
+                    .worksheet_range(name)
+                    .ok_or_else(|| Error::NotFound(name.into()))
+            }
+        };
+
+        let mut range = wb.worksheet_range(name)?;
+        let merges = match &mut **wb {
+            Sheets::Xlsx(wb) => wb.worksheet_merge_cells(name).transpose()?,
+            Sheets::Xls(wb) => wb.worksheet_merge_cells(name),
+            Sheets::Xlsb(_) | Sheets::Ods(_) => own_merges.get(name).cloned(),
+        };
+        fill_merged(&mut range, &merges.unwrap_or_default());
+
+        Ok(range)
+    }
+}
+
+/// Opens `data` with the reader matching its detected format.
+pub fn open_workbook(data: &[u8]) -> Result<Book<Cursor<&[u8]>>, Error> {
+    let cursor = Cursor::new(data);
+    let format = detect_format(data);
+    let open_err = |e: calamine::Error| Error::Open(format, e);
+
+    let mut merges = HashMap::new();
+    let sheets = match format {
+        Format::Xlsx => Sheets::Xlsx(Xlsx::new(cursor).map_err(|e| open_err(e.into()))?),
+        Format::Xlsb => {
+            let wb = Xlsb::new(cursor).map_err(|e| open_err(e.into()))?;
+            merges = xlsb::merge_cells(data)?;
+            Sheets::Xlsb(wb)
+        }
+        Format::Xls => Sheets::Xls(Xls::new(cursor).map_err(|e| open_err(e.into()))?),
+        Format::Ods => {
+            let wb = Ods::new(cursor).map_err(|e| open_err(e.into()))?;
+            merges = ods::merge_cells(data)?;
+            Sheets::Ods(wb)
+        }
+        Format::CsvBundle => return Ok(Book::Csv(CsvBundle::from_zip(data)?)),
+        Format::Unsupported(_) => return Err(Error::Unsupported(format)),
+    };
+
+    Ok(Book::Workbook {
+        sheets: Box::new(sheets),
+        merges,
+    })
+}
+
+/// Lazily converts worksheets into DataFrames.
+///
+/// Sheets are only read when something refers to them by name, so cover
+/// sheets such as "Revision History" never take part in the import. Every
+/// request converts the sheet again, callers keep what they build from it.
+/// Headers are renamed to their logical names through the [`ColumnMap`].
+pub struct SheetLoader<RS> {
+    wb: Book<RS>,
+    columns: ColumnMap,
+    // explicit 1-based header row, detected within the first `scan_rows` rows otherwise
+    header_row: Option<usize>,
+    scan_rows: usize,
+    // normalized name -> name as written in the workbook
+    names: HashMap<String, String>,
+    referenced: HashSet<String>,
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+impl<RS> SheetLoader<RS>
+where
+    RS: Read + Seek,
+{
+    pub fn new(wb: Book<RS>, columns: ColumnMap, opts: &ParseOptions) -> Self {
+        let names = wb
+            .sheet_names()
+            .into_iter()
+            .map(|name| (normalize(&name), name))
+            .collect();
+
+        Self {
+            wb,
+            columns,
+            header_row: opts.header_row,
+            scan_rows: opts.header_scan_rows.max(1),
+            names,
+            referenced: HashSet::new(),
+        }
+    }
+
+    /// Returns the sheet of `role` under the first of its names present in the workbook.
+    pub fn load_role(
+        &mut self,
+        role: SheetRole,
+        diags: &mut Vec<Diagnostic>,
+    ) -> Result<Sheet, Error> {
+        let names = self.columns.sheet_names(role).to_vec();
+        let name = names
+            .iter()
+            .find(|name| self.names.contains_key(&normalize(name)))
+            .cloned()
+            // reported as missing under its usual name
+            .or_else(|| role.sheet_names().first().map(|s| s.to_string()))
+            .unwrap_or_default();
+        self.load(&name, role, diags)
+    }
+
+    /// Converts sheet `name` (case-insensitive).
+    ///
+    /// The header row in use is reported as an info diagnostic on conversion.
+    pub fn load(
+        &mut self,
+        name: &str,
+        role: SheetRole,
+        diags: &mut Vec<Diagnostic>,
+    ) -> Result<Sheet, Error> {
+        let key = normalize(name);
+        self.referenced.insert(key.clone());
+
+        let sheet_name = self
+            .names
+            .get(&key)
+            .ok_or_else(|| Error::NotFound(name.into()))?;
+        let range = self.wb.worksheet_range(sheet_name)?;
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+        let (header, how) = match self.header_row {
+            Some(row) => {
+                let header = row
+                    .checked_sub(start_row as usize + 1)
+                    .filter(|header| *header < range.height())
+                    .ok_or_else(|| Error::HeaderRow(sheet_name.clone(), row))?;
+                (header, "configured")
+            }
+            None => (self.detect_header(&range, role), "detected"),
+        };
+        let mut df = range.to_data_frame(header)?;
+        self.columns.apply(role, &mut df)?;
+        let sheet = Sheet {
+            name: sheet_name.clone(),
+            df,
+            header_row: start_row as usize + header + 1,
+            first_col: start_col as usize,
+        };
+        diags.push(
+            Diagnostic::info(
+                "header-row",
+                format!("Using row {} as header ({})", sheet.header_row, how),
+            )
+            .in_sheet(&sheet.name)
+            .at(Some(sheet.header_row), None),
+        );
+
+        Ok(sheet)
+    }
+
+    /// Index (within the used range) of the row among the first `scan_rows` naming the
+    /// most logical columns of `role`; the first row when none names any.
+    fn detect_header<T>(&self, range: &Range<T>, role: SheetRole) -> usize
+    where
+        T: DataType + CellType + std::fmt::Display,
+    {
+        let mut best = (0, 0);
+        for (i, row) in range.rows().take(self.scan_rows).enumerate() {
+            let cells: Vec<String> = row.iter().map(|cell| cell.to_string()).collect();
+            let score = self.columns.header_score(role, &cells);
+            if score > best.1 {
+                best = (i, score);
+            }
+        }
+        best.0
+    }
+
+    /// Sheets that were never requested through [`SheetLoader::load`], in workbook order.
+    pub fn unreferenced(&self) -> Vec<String> {
+        self.wb
+            .sheet_names()
+            .into_iter()
+            .filter(|name| !self.referenced.contains(&normalize(name)))
+            .collect()
+    }
+}
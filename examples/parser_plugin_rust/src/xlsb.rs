@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use calamine::Dimensions;
+use roxmltree::Document;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use crate::error::Error;
+
+// MS-XLSB record types
+const BRT_BUNDLE_SH: u16 = 0x009C;
+const BRT_MERGE_CELL: u16 = 0x00B0;
+
+/// Merged regions of every worksheet of the .xlsb in `data`, by sheet name.
+///
+/// calamine reads the cells of an .xlsb but not its `BrtMergeCell` records, which
+/// follow the cell table of each `xl/worksheets/*.bin` part.
+pub fn merge_cells(data: &[u8]) -> Result<HashMap<String, Vec<Dimensions>>, Error> {
+    let mut zip = ZipArchive::new(Cursor::new(data)).map_err(anyhow::Error::from)?;
+
+    let Some(rels) = entry(&mut zip, "xl/_rels/workbook.bin.rels")? else {
+        return Ok(HashMap::new());
+    };
+    let rels = String::from_utf8_lossy(&rels);
+    let rels = Document::parse(&rels)?;
+    let targets: HashMap<&str, &str> = rels
+        .descendants()
+        .filter(|n| n.has_tag_name("Relationship"))
+        .filter_map(|n| Some((n.attribute("Id")?, n.attribute("Target")?)))
+        .collect();
+
+    let workbook = entry(&mut zip, "xl/workbook.bin")?.unwrap_or_default();
+    let mut sheets = Vec::new();
+    for (typ, body) in records(&workbook) {
+        if typ != BRT_BUNDLE_SH || body.len() < 8 {
+            continue;
+        }
+        // hsState, iTabID, then the relationship id and name as XLWideStrings
+        let mut pos = 8;
+        let (Some(rel), Some(name)) = (wide_str(body, &mut pos), wide_str(body, &mut pos)) else {
+            continue;
+        };
+        if let Some(target) = targets.get(rel.as_str()) {
+            let path = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("xl/{}", target),
+            };
+            sheets.push((name, path));
+        }
+    }
+
+    let mut merges = HashMap::new();
+    for (name, path) in sheets {
+        let Some(sheet) = entry(&mut zip, &path)? else {
+            continue;
+        };
+        let regions: Vec<Dimensions> = records(&sheet)
+            .filter(|(typ, body)| *typ == BRT_MERGE_CELL && body.len() >= 16)
+            .map(|(_, body)| {
+                let [first_row, last_row, first_col, last_col] =
+                    [0, 4, 8, 12].map(|i| u32_at(body, i));
+                Dimensions::new((first_row, first_col), (last_row, last_col))
+            })
+            .collect();
+        merges.insert(name, regions);
+    }
+    Ok(merges)
+}
+
+/// Bytes of the zip entry `name`, `None` when the archive has no such entry.
+fn entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let mut file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(anyhow::Error::from(e).into()),
+    };
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+/// `(type, body)` of each record of a BIFF12 part; type and size are written in
+/// 7-bit groups, the high bit flagging one more byte. Stops at a truncated record.
+fn records(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let typ = var_int(data, &mut pos, 2)?;
+        let len = var_int(data, &mut pos, 4)? as usize;
+        let body = data.get(pos..pos.checked_add(len)?)?;
+        pos += len;
+        Some((typ as u16, body))
+    })
+}
+
+fn var_int(data: &[u8], pos: &mut usize, max_bytes: usize) -> Option<u32> {
+    let mut value = 0;
+    for i in 0..max_bytes {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+        value |= ((b & 0x7F) as u32) << (7 * i);
+        if b & 0x80 == 0 {
+            break;
+        }
+    }
+    Some(value)
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// An XLWideString: a character count, then UTF-16LE. `None` for the 0xFFFFFFFF
+/// of an absent one or past the end of `data`.
+fn wide_str(data: &[u8], pos: &mut usize) -> Option<String> {
+    let count = u32_at(data.get(*pos..*pos + 4)?, 0);
+    if count == u32::MAX {
+        return None;
+    }
+    let start = *pos + 4;
+    let bytes = data.get(start..start.checked_add(count as usize * 2)?)?;
+    *pos = start + bytes.len();
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
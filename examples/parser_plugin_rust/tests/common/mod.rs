@@ -0,0 +1,522 @@
+#![allow(dead_code)]
+
+use std::ffi::OsStr;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, process};
+
+use parser_plugin_rust::RtlFile;
+
+use rust_xlsxwriter::{Format, Workbook};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// A worksheet described as rows of cell strings; empty strings are left blank.
+pub type Sheet<'a> = (&'a str, Vec<Vec<&'a str>>);
+
+/// A merged region: sheet name, then first row/column and last row/column (0-based).
+pub type Merge<'a> = (&'a str, (u32, u16, u32, u16));
+
+pub fn build_workbook(sheets: &[Sheet]) -> Vec<u8> {
+    build_workbook_with_merges(sheets, &[])
+}
+
+/// Like [`build_workbook`], merging the given regions; each one shows the value of its
+/// top-left cell.
+pub fn build_workbook_with_merges(sheets: &[Sheet], merges: &[Merge]) -> Vec<u8> {
+    let mut wb = Workbook::new();
+    for (name, rows) in sheets {
+        let ws = wb.add_worksheet();
+        ws.set_name(*name).expect("sheet name");
+        for (r, row) in rows.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                if !cell.is_empty() {
+                    ws.write_string(r as u32, c as u16, *cell)
+                        .expect("write cell");
+                }
+            }
+        }
+        for (_, (r1, c1, r2, c2)) in merges.iter().filter(|(sheet, _)| sheet == name) {
+            let text = rows[*r1 as usize][*c1 as usize];
+            ws.merge_range(*r1, *c1, *r2, *c2, text, &Format::new())
+                .expect("merge range");
+        }
+    }
+    wb.save_to_buffer().expect("save workbook")
+}
+
+pub fn version_sheet() -> Sheet<'static> {
+    (
+        "version",
+        vec![
+            vec!["VENDOR", "LIBRARY", "NAME", "VERSION"],
+            vec!["example.com", "IP", "example", "1.0"],
+        ],
+    )
+}
+
+pub const BLOCK_HEADER: [&str; 8] = [
+    "ADDR",
+    "REG",
+    "FIELD",
+    "BIT",
+    "WIDTH",
+    "ATTRIBUTE",
+    "DEFAULT",
+    "DESCRIPTION",
+];
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Same sheets as [`build_workbook`], saved as an OpenDocument spreadsheet.
+pub fn build_ods(sheets: &[Sheet], merges: &[Merge]) -> Vec<u8> {
+    let mut content = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" office:version="1.2"><office:body><office:spreadsheet>"#,
+    );
+    for (name, rows) in sheets {
+        content.push_str(&format!(
+            r#"<table:table table:name="{}">"#,
+            escape_xml(name)
+        ));
+        let merges: Vec<_> = merges
+            .iter()
+            .filter(|(sheet, _)| sheet == name)
+            .map(|(_, region)| *region)
+            .collect();
+        for (r, row) in rows.iter().enumerate() {
+            let r = r as u32;
+            content.push_str("<table:table-row>");
+            for (c, cell) in row.iter().enumerate() {
+                let c = c as u16;
+                let region = merges
+                    .iter()
+                    .find(|&&(r0, c0, r1, c1)| (r0..=r1).contains(&r) && (c0..=c1).contains(&c));
+                // the cells a region covers are written as covered cells without a value
+                let spans = match region {
+                    Some(&(r0, c0, r1, c1)) if (r0, c0) == (r, c) => format!(
+                        r#" table:number-rows-spanned="{}" table:number-columns-spanned="{}""#,
+                        r1 - r0 + 1,
+                        c1 - c0 + 1
+                    ),
+                    Some(_) => {
+                        content.push_str("<table:covered-table-cell/>");
+                        continue;
+                    }
+                    None => String::new(),
+                };
+                if cell.is_empty() {
+                    content.push_str(&format!("<table:table-cell{}/>", spans));
+                } else {
+                    content.push_str(&format!(
+                        r#"<table:table-cell{} office:value-type="string"><text:p>{}</text:p></table:table-cell>"#,
+                        spans,
+                        escape_xml(cell)
+                    ));
+                }
+            }
+            content.push_str("</table:table-row>");
+        }
+        content.push_str("</table:table>");
+    }
+    content.push_str("</office:spreadsheet></office:body></office:document-content>");
+
+    let manifest = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2"><manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/><manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/></manifest:manifest>"#;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default();
+    zip.start_file("mimetype", stored).expect("mimetype");
+    zip.write_all(b"application/vnd.oasis.opendocument.spreadsheet")
+        .expect("write mimetype");
+    zip.start_file("META-INF/manifest.xml", deflated)
+        .expect("manifest");
+    zip.write_all(manifest.as_bytes()).expect("write manifest");
+    zip.start_file("content.xml", deflated).expect("content");
+    zip.write_all(content.as_bytes()).expect("write content");
+    zip.finish().expect("finish ods").into_inner()
+}
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// BIFF8 record: type, body length, body.
+fn biff_record(typ: u16, body: &[u8]) -> Vec<u8> {
+    [&typ.to_le_bytes(), &(body.len() as u16).to_le_bytes(), body].concat()
+}
+
+/// Same sheets as [`build_workbook_with_merges`], saved as an Excel 97-2003 workbook: a
+/// compound file whose "Workbook" stream holds BIFF8 records.
+pub fn build_xls(sheets: &[Sheet], merges: &[Merge]) -> Vec<u8> {
+    let bof = |dt: u16| {
+        biff_record(
+            0x0809,
+            &[&0x0600u16.to_le_bytes()[..], &dt.to_le_bytes(), &[0; 12]].concat(),
+        )
+    };
+    let eof = biff_record(0x000A, &[]);
+
+    let substreams: Vec<Vec<u8>> = sheets
+        .iter()
+        .map(|(name, rows)| {
+            let mut stream = bof(0x0010);
+            for (r, row) in rows.iter().enumerate() {
+                for (c, cell) in row.iter().enumerate().filter(|(_, cell)| !cell.is_empty()) {
+                    let label = [
+                        &(r as u16).to_le_bytes()[..],
+                        &(c as u16).to_le_bytes(),
+                        &[0, 0],
+                        &(cell.encode_utf16().count() as u16).to_le_bytes(),
+                        &[1],
+                        &utf16(cell),
+                    ]
+                    .concat();
+                    stream.extend(biff_record(0x0204, &label));
+                }
+            }
+            let regions: Vec<_> = merges.iter().filter(|(sheet, _)| sheet == name).collect();
+            if !regions.is_empty() {
+                let mut body = (regions.len() as u16).to_le_bytes().to_vec();
+                for (_, (r1, c1, r2, c2)) in regions {
+                    for v in [*r1 as u16, *r2 as u16, *c1, *c2] {
+                        body.extend(v.to_le_bytes());
+                    }
+                }
+                stream.extend(biff_record(0x00E5, &body));
+            }
+            stream.extend(&eof);
+            stream
+        })
+        .collect();
+
+    let bound_sheet = |pos: usize, name: &str| {
+        let body = [
+            &(pos as u32).to_le_bytes()[..],
+            &[0, 0, name.encode_utf16().count() as u8, 1],
+            &utf16(name),
+        ]
+        .concat();
+        biff_record(0x0085, &body)
+    };
+    let globals_len = bof(0x0005).len()
+        + sheets
+            .iter()
+            .map(|(name, _)| bound_sheet(0, name).len())
+            .sum::<usize>()
+        + eof.len();
+    let mut stream = bof(0x0005);
+    let mut pos = globals_len;
+    for ((name, _), substream) in sheets.iter().zip(&substreams) {
+        stream.extend(bound_sheet(pos, name));
+        pos += substream.len();
+    }
+    stream.extend(&eof);
+    stream.extend(substreams.concat());
+    // smaller streams would live in the mini stream
+    stream.resize(stream.len().max(4096).next_multiple_of(512), 0);
+
+    build_cfb("Workbook", &stream)
+}
+
+/// Version 3 compound file holding the single stream `name`: the FAT in sector 0, the
+/// directory in sector 1, then the stream.
+pub fn build_cfb(name: &str, stream: &[u8]) -> Vec<u8> {
+    const FREE: u32 = 0xFFFF_FFFF;
+    const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+    let sectors = stream.len().div_ceil(512) as u32;
+    assert!(sectors <= 126, "stream too long for a single FAT sector");
+
+    let mut header = vec![0u8; 512];
+    header[..8].copy_from_slice(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1");
+    for (at, v) in [(24, 0x003Eu16), (26, 3), (28, 0xFFFE), (30, 9), (32, 6)] {
+        header[at..at + 2].copy_from_slice(&v.to_le_bytes());
+    }
+    for (at, v) in [
+        (44, 1u32),
+        (48, 1),
+        (56, 4096),
+        (60, END_OF_CHAIN),
+        (68, END_OF_CHAIN),
+        (76, 0),
+    ] {
+        header[at..at + 4].copy_from_slice(&v.to_le_bytes());
+    }
+    for at in (80..512).step_by(4) {
+        header[at..at + 4].copy_from_slice(&FREE.to_le_bytes());
+    }
+
+    let mut fat = vec![0xFFFF_FFFD, END_OF_CHAIN];
+    fat.extend((3..sectors + 2).chain([END_OF_CHAIN]));
+    fat.resize(128, FREE);
+    let fat: Vec<u8> = fat.into_iter().flat_map(u32::to_le_bytes).collect();
+
+    let entry = |name: &str, typ: u8, child: u32, start: u32, size: u32| {
+        let mut entry = vec![0u8; 128];
+        let name = utf16(&format!("{}\0", name));
+        entry[..name.len()].copy_from_slice(&name);
+        entry[64..66].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        entry[66] = typ;
+        entry[67] = 1;
+        for (at, v) in [
+            (68, FREE),
+            (72, FREE),
+            (76, child),
+            (116, start),
+            (120, size),
+        ] {
+            entry[at..at + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        entry
+    };
+    let mut directory = entry("Root Entry", 5, 1, END_OF_CHAIN, 0);
+    directory.extend(entry(name, 2, FREE, 2, stream.len() as u32));
+    directory.resize(512, 0);
+
+    [header, fat, directory, stream.to_vec()].concat()
+}
+
+/// BIFF12 record: type and body length in 7-bit groups, then the body.
+fn brt_record(typ: u16, body: &[u8]) -> Vec<u8> {
+    let mut record = if typ < 0x80 {
+        vec![typ as u8]
+    } else {
+        vec![(typ & 0x7F) as u8 | 0x80, (typ >> 7) as u8]
+    };
+    let mut len = body.len();
+    loop {
+        let more = len >= 0x80;
+        record.push((len & 0x7F) as u8 | if more { 0x80 } else { 0 });
+        len >>= 7;
+        if !more {
+            break;
+        }
+    }
+    record.extend(body);
+    record
+}
+
+fn wide_str(text: &str) -> Vec<u8> {
+    [
+        &(text.encode_utf16().count() as u32).to_le_bytes()[..],
+        &utf16(text),
+    ]
+    .concat()
+}
+
+/// Same sheets as [`build_workbook_with_merges`], saved as an Excel binary workbook.
+pub fn build_xlsb(sheets: &[Sheet], merges: &[Merge]) -> Vec<u8> {
+    let mut rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    let mut workbook = Vec::new();
+    let mut files = Vec::new();
+    for (i, (name, rows)) in sheets.iter().enumerate() {
+        let id = format!("rId{}", i + 1);
+        let target = format!("worksheets/sheet{}.bin", i + 1);
+        rels.push_str(&format!(
+            r#"<Relationship Id="{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="{}"/>"#,
+            id, target
+        ));
+        let bundle = [
+            &[0; 4][..],
+            &(i as u32 + 1).to_le_bytes(),
+            &wide_str(&id),
+            &wide_str(name),
+        ]
+        .concat();
+        workbook.extend(brt_record(0x009C, &bundle));
+
+        let last_row = rows.len().saturating_sub(1) as u32;
+        let last_col = rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(1)
+            .saturating_sub(1) as u32;
+        let dimension: Vec<u8> = [0, last_row, 0, last_col]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        let mut sheet = brt_record(0x0081, &[]);
+        sheet.extend(brt_record(0x0094, &dimension));
+        sheet.extend(brt_record(0x0091, &[]));
+        for (r, row) in rows.iter().enumerate() {
+            sheet.extend(brt_record(0x0000, &(r as u32).to_le_bytes()));
+            for (c, cell) in row.iter().enumerate().filter(|(_, cell)| !cell.is_empty()) {
+                let body = [&(c as u32).to_le_bytes()[..], &[0; 4], &wide_str(cell)].concat();
+                sheet.extend(brt_record(0x0006, &body));
+            }
+        }
+        sheet.extend(brt_record(0x0092, &[]));
+        for (_, (r1, c1, r2, c2)) in merges.iter().filter(|(sheet, _)| sheet == name) {
+            let body: Vec<u8> = [*r1, *r2, *c1 as u32, *c2 as u32]
+                .into_iter()
+                .flat_map(u32::to_le_bytes)
+                .collect();
+            sheet.extend(brt_record(0x00B0, &body));
+        }
+        sheet.extend(brt_record(0x0082, &[]));
+        files.push((format!("xl/{}", target), sheet));
+    }
+    rels.push_str("</Relationships>");
+    workbook.extend(brt_record(0x0090, &[]));
+    workbook.extend(brt_record(0x009D, &[0; 4]));
+
+    let mut entries = vec![
+        ("xl/_rels/workbook.bin.rels", rels.into_bytes()),
+        ("xl/workbook.bin", workbook),
+    ];
+    entries.extend(
+        files
+            .iter()
+            .map(|(path, sheet)| (path.as_str(), sheet.clone())),
+    );
+    build_zip(&entries)
+}
+
+/// Zip archive holding `files` (name, content) as stored entries.
+pub fn build_zip(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        zip.start_file(*name, SimpleFileOptions::default())
+            .expect("zip entry");
+        zip.write_all(content).expect("write entry");
+    }
+    zip.finish().expect("finish zip").into_inner()
+}
+
+/// Rows joined with `delimiter`, quoting cells that contain it, a quote or a line break.
+pub fn to_csv(rows: &[Vec<&str>], delimiter: char) -> String {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    if cell.contains([delimiter, '"', '\n']) {
+                        format!("\"{}\"", cell.replace('"', "\"\""))
+                    } else {
+                        cell.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(&delimiter.to_string())
+        })
+        .map(|line| line + "\r\n")
+        .collect()
+}
+
+const WORDML: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+/// Word paragraph with the style `style` ("" for none).
+pub fn docx_paragraph(style: &str, text: &str) -> String {
+    let ppr = if style.is_empty() {
+        String::new()
+    } else {
+        format!(r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, style)
+    };
+    format!(
+        r#"<w:p>{}<w:r><w:t xml:space="preserve">{}</w:t></w:r></w:p>"#,
+        ppr,
+        escape_xml(text)
+    )
+}
+
+/// Word table of plain text cells; a line break in a cell becomes `<w:br/>`.
+pub fn docx_table(rows: &[Vec<&str>]) -> String {
+    let mut xml = String::from("<w:tbl>");
+    for row in rows {
+        xml.push_str("<w:tr>");
+        for cell in row {
+            let runs = cell
+                .split('\n')
+                .map(|line| format!("<w:t>{}</w:t>", escape_xml(line)))
+                .collect::<Vec<_>>()
+                .join("<w:br/>");
+            xml.push_str(&format!("<w:tc><w:p><w:r>{}</w:r></w:p></w:tc>", runs));
+        }
+        xml.push_str("</w:tr>");
+    }
+    xml.push_str("</w:tbl>");
+    xml
+}
+
+/// Word document with `body` (paragraphs and tables) and the built-in heading styles.
+pub fn build_docx(body: &str) -> Vec<u8> {
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="{}"><w:body>{}<w:sectPr/></w:body></w:document>"#,
+        WORDML, body
+    );
+    let mut styles = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><w:styles xmlns:w="{}">"#,
+        WORDML
+    );
+    styles.push_str(
+        r#"<w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style>"#,
+    );
+    styles.push_str(
+        r#"<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/></w:style>"#,
+    );
+    for level in 1..=3 {
+        styles.push_str(&format!(
+            r#"<w:style w:type="paragraph" w:styleId="Heading{0}"><w:name w:val="heading {0}"/><w:pPr><w:outlineLvl w:val="{1}"/></w:pPr></w:style>"#,
+            level,
+            level - 1
+        ));
+    }
+    styles.push_str("</w:styles>");
+    let content_types = r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+
+    build_zip(&[
+        ("[Content_Types].xml", content_types.as_bytes().to_vec()),
+        ("word/document.xml", document.into_bytes()),
+        ("word/styles.xml", styles.into_bytes()),
+    ])
+}
+
+/// Whether `tool` can be run, i.e. is on the PATH.
+pub fn has_tool(tool: &str) -> bool {
+    Command::new(tool).arg("--version").output().is_ok()
+}
+
+/// Writes `files` to a fresh directory of the system temp directory named after
+/// `name`.
+pub fn write_rtl(name: &str, files: &[RtlFile]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("{}_{}", name, process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("create directory");
+    for file in files {
+        fs::write(dir.join(&file.name), &file.content).expect("write file");
+    }
+    dir
+}
+
+/// Output and errors of `program` run in `dir`, panicking with them when it fails.
+pub fn run(dir: &Path, program: impl AsRef<OsStr>, args: &[&str]) -> String {
+    let program = program.as_ref();
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("run program");
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        output.status.success(),
+        "{:?} {:?}: {}",
+        program,
+        args,
+        text
+    );
+    text
+}
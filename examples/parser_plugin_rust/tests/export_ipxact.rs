@@ -0,0 +1,279 @@
+use parser_plugin_rust::{
+    export_ipxact, parse_excel_to_import_data, parse_ipxact_to_import_data, ImportData,
+    ImportField, ImportRegister,
+};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+use std::{env, fs, process};
+
+/// `data` as JSON with the numbers written as numbers, `"0"` and `"0x0"` being
+/// the same reset value.
+fn normalized(data: &ImportData) -> Value {
+    fn walk(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let number = matches!(
+                        key.as_str(),
+                        "baseAddress" | "range" | "addressOffset" | "resetValue" | "value"
+                    );
+                    match value {
+                        Value::String(s) if number => {
+                            let parsed = match s.strip_prefix("0x").or(s.strip_prefix("0X")) {
+                                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                                None => s.parse().ok(),
+                            };
+                            if let Some(n) = parsed {
+                                *value = Value::from(n);
+                            }
+                        }
+                        _ => walk(value),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(walk),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(data).expect("serialize");
+    walk(&mut value);
+    value
+}
+
+#[test]
+fn round_trips_example_workbook() {
+    let data = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    let xml = export_ipxact(&data);
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ipxact:component xmlns:ipxact=\"http://www.accellera.org/XMLSchema/IPXACT/1685-2022\""));
+
+    let back = parse_ipxact_to_import_data(xml.as_bytes()).expect("export should reimport");
+    assert_eq!(normalized(&back), normalized(&data));
+}
+
+const TIMER_2014: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>acme.com</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>timer</ipxact:name>
+  <ipxact:version>1.2</ipxact:version>
+  <ipxact:description>Timer &amp; watchdog</ipxact:description>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>map</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>regs</ipxact:name>
+        <ipxact:baseAddress>BASE + 'h100</ipxact:baseAddress>
+        <ipxact:range>'h40</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>STAT</ipxact:name>
+          <ipxact:description>Status, cleared on read</ipxact:description>
+          <ipxact:addressOffset>'h8</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>IRQ</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>1'b1</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-only</ipxact:access>
+            <ipxact:readAction>clear</ipxact:readAction>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>ERR</ipxact:name>
+            <ipxact:description>Error, a &lt; b</ipxact:description>
+            <ipxact:bitOffset>1</ipxact:bitOffset>
+            <ipxact:bitWidth>2</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+            <ipxact:enumeratedValues>
+              <ipxact:enumeratedValue>
+                <ipxact:name>NONE</ipxact:name>
+                <ipxact:value>0</ipxact:value>
+              </ipxact:enumeratedValue>
+              <ipxact:enumeratedValue>
+                <ipxact:name>FATAL</ipxact:name>
+                <ipxact:description>Stops the timer</ipxact:description>
+                <ipxact:value>2'b11</ipxact:value>
+              </ipxact:enumeratedValue>
+            </ipxact:enumeratedValues>
+            <ipxact:modifiedWriteValue>oneToClear</ipxact:modifiedWriteValue>
+          </ipxact:field>
+        </ipxact:register>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+  <ipxact:parameters>
+    <ipxact:parameter parameterId="BASE" resolve="user">
+      <ipxact:name>BASE</ipxact:name>
+      <ipxact:value>'h4000_0000</ipxact:value>
+    </ipxact:parameter>
+  </ipxact:parameters>
+</ipxact:component>
+"#;
+
+#[test]
+fn round_trips_ipxact_2014_as_2022() {
+    let data = parse_ipxact_to_import_data(TIMER_2014.as_bytes()).expect("parse should succeed");
+    let xml = export_ipxact(&data);
+    let back = parse_ipxact_to_import_data(xml.as_bytes()).expect("export should reimport");
+    assert_eq!(normalized(&back), normalized(&data));
+
+    // parameters evaluated, access policies and escaped text
+    assert!(xml.contains("<ipxact:baseAddress>'h40000100</ipxact:baseAddress>"));
+    assert!(xml.contains("<ipxact:description>Timer &amp; watchdog</ipxact:description>"));
+    assert!(xml.contains("<ipxact:description>Error, a &lt; b</ipxact:description>"));
+    assert!(xml.contains(
+        "<ipxact:fieldAccessPolicy>
+                <ipxact:access>read-only</ipxact:access>
+                <ipxact:readAction>clear</ipxact:readAction>
+              </ipxact:fieldAccessPolicy>"
+    ));
+    let err = &back.memory_maps[0].address_blocks[0].registers[0].fields[1];
+    assert_eq!(err.modified_write_value.as_deref(), Some("oneToClear"));
+    assert_eq!(err.enumerated_values[1].value, "3");
+}
+
+#[test]
+fn writes_elements_in_schema_order() {
+    let mut data =
+        parse_ipxact_to_import_data(TIMER_2014.as_bytes()).expect("parse should succeed");
+    let regs = &mut data.memory_maps[0].address_blocks[0].registers;
+    regs.push(ImportRegister {
+        name: "SCRATCH".to_string(),
+        address_offset: "0xC".to_string(),
+        size: 16,
+        description: None,
+        fields: Vec::<ImportField>::new(),
+    });
+    let xml = export_ipxact(&data);
+
+    // `resets` sits between `bitOffset` and `bitWidth` in a 2022 field
+    let irq = &xml[xml.find("<ipxact:name>IRQ</ipxact:name>").unwrap()..];
+    let order: Vec<usize> = ["bitOffset>", "resets>", "bitWidth>", "fieldAccessPolicies>"]
+        .iter()
+        .map(|tag| irq.find(tag).unwrap())
+        .collect();
+    assert!(order.windows(2).all(|w| w[0] < w[1]), "{order:?}");
+
+    // the schema wants at least one field per register
+    let back = parse_ipxact_to_import_data(xml.as_bytes()).expect("export should reimport");
+    let scratch = &back.memory_maps[0].address_blocks[0].registers[1];
+    let fields: Vec<_> = scratch
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.access.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(fields, [("SCRATCH", 0, 16, "read-write")]);
+}
+
+/// The 1685-2022 schema is not shipped: run with `cargo test -- --ignored` once
+/// `IPXACT_2022_XSD` names a local copy of its `index.xsd` and `xmllint` is on the PATH.
+#[test]
+#[ignore = "needs IPXACT_2022_XSD and xmllint"]
+fn validates_against_the_2022_schema() {
+    let xsd = env::var_os("IPXACT_2022_XSD")
+        .filter(|p| Path::new(p).exists())
+        .expect("IPXACT_2022_XSD names no schema");
+    Command::new("xmllint")
+        .arg("--version")
+        .output()
+        .expect("xmllint not found");
+
+    let example = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    // an empty VLNV gets placeholders
+    let mut timer =
+        parse_ipxact_to_import_data(TIMER_2014.as_bytes()).expect("parse should succeed");
+    timer.project.vendor.clear();
+    timer.project.library.clear();
+    timer.project.version.clear();
+    let spaced = spaced_names(timer.clone());
+
+    for (name, data) in [("example", example), ("timer", timer), ("spaced", spaced)] {
+        let path = env::temp_dir().join(format!("export_ipxact_{}_{}.xml", name, process::id()));
+        fs::write(&path, export_ipxact(&data)).expect("write export");
+        let output = Command::new("xmllint")
+            .args(["--noout", "--schema"])
+            .arg(&xsd)
+            .arg(&path)
+            .output()
+            .expect("run xmllint");
+        fs::remove_file(&path).ok();
+        assert!(
+            output.status.success(),
+            "{}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+/// `data` with names the schema does not allow, as a spreadsheet may hold them.
+fn spaced_names(mut data: ImportData) -> ImportData {
+    let map = &mut data.memory_maps[0];
+    map.name = "register map".to_string();
+    let block = &mut map.address_blocks[0];
+    block.name = "timer regs".to_string();
+    let reg = &mut block.registers[0];
+    reg.name = "STAT (ro)".to_string();
+    reg.fields[0].name = "IRQ flag".to_string();
+    reg.fields[1].enumerated_values[0].name = "NO ERROR".to_string();
+    data
+}
+
+#[test]
+fn writes_every_name_as_a_schema_name() {
+    let data = parse_ipxact_to_import_data(TIMER_2014.as_bytes()).expect("parse should succeed");
+    let xml = export_ipxact(&spaced_names(data));
+    let back = parse_ipxact_to_import_data(xml.as_bytes()).expect("export should reimport");
+
+    let map = &back.memory_maps[0];
+    let block = &map.address_blocks[0];
+    let reg = &block.registers[0];
+    assert_eq!(
+        [
+            map.name.as_str(),
+            block.name.as_str(),
+            reg.name.as_str(),
+            reg.fields[0].name.as_str(),
+            reg.fields[1].enumerated_values[0].name.as_str(),
+        ],
+        [
+            "register_map",
+            "timer_regs",
+            "STAT__ro_",
+            "IRQ_flag",
+            "NO_ERROR"
+        ]
+    );
+}
+
+#[test]
+fn exports_fields_without_enumerated_values() {
+    // the TypeScript `ImportField` may leave `enumeratedValues` out
+    let json = r#"{
+  "project": { "name": "t", "description": null, "vendor": "acme.com", "library": "ip", "version": "1.0" },
+  "memoryMaps": [{ "name": "map", "addressBlocks": [{
+    "name": "T", "baseAddress": "0x0", "range": "0x4", "width": 32,
+    "registers": [{ "name": "R", "addressOffset": "0x0", "size": 32, "description": null,
+      "fields": [{ "name": "F", "description": null, "bitOffset": 0, "bitWidth": 8,
+        "access": "read-write", "modifiedWriteValue": null, "readAction": null, "resetValue": "0x0" }]
+    }]
+  }]}]
+}"#;
+    let data: ImportData = serde_json::from_str(json).expect("deserialize");
+    let field = &data.memory_maps[0].address_blocks[0].registers[0].fields[0];
+    assert!(field.enumerated_values.is_empty());
+    assert!(export_ipxact(&data).contains("<ipxact:name>F</ipxact:name>"));
+    assert!(!serde_json::to_string(field)
+        .expect("serialize")
+        .contains("enumeratedValues"));
+}
@@ -0,0 +1,397 @@
+mod common;
+
+use std::fs;
+
+use parser_plugin_rust::{
+    generate_sv, generate_sv_with_options, parse_excel_to_import_data, parse_ipxact_to_import_data,
+    Bus, RtlOptions,
+};
+
+#[test]
+fn generates_example_workbook() {
+    let data = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    let files = generate_sv(&data).expect("generation should succeed");
+    let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["block0_csr.sv", "block1_csr.sv"]);
+
+    let sv = &files[0].content;
+    assert!(sv.contains("module block0_csr (\n  input  logic clk,\n  input  logic rst_n,\n"));
+    assert!(sv.contains("  input  logic [11:0] req_addr,\n"));
+    assert!(sv.contains("  output logic rsp_error,\n  // reg0\n"));
+    assert!(sv.ends_with("  end\n\nendmodule\n"));
+
+    // decoder, reset and write-1-to-clear with its hardware set
+    assert!(sv.contains("  assign reg1_hit = req_addr[11:2] == 10'h1;\n"));
+    assert!(sv.contains("      reg0_field0_q <= 32'h1234;\n"));
+    assert!(sv.contains(
+        "    if (reg2_field0_we) reg2_field0_next = reg2_field0_next & ~(req_wdata[31:0] & wmask[31:0]);\n    reg2_field0_next = reg2_field0_next | reg2_field0_set;\n"
+    ));
+    // reserved bits have no port and read as zero
+    assert!(!sv.contains("reserved"));
+    assert!(sv.contains(
+        "    if (reg1_hit) begin\n      rsp_rdata[7:0] = reg1_field0_q;\n      rsp_rdata[23:16] = reg1_field1_q;\n    end\n"
+    ));
+}
+
+const DMA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>acme.com</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>dma</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>map</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>DMA Channel</ipxact:name>
+        <ipxact:baseAddress>0</ipxact:baseAddress>
+        <ipxact:range>16</ipxact:range>
+        <ipxact:width>16</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>CMD</ipxact:name>
+          <ipxact:addressOffset>'h2</ipxact:addressOffset>
+          <ipxact:size>16</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>START</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+            <ipxact:modifiedWriteValue>oneToSet</ipxact:modifiedWriteValue>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>KEY</ipxact:name>
+            <ipxact:bitOffset>8</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>8'hA5</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>8</ipxact:bitWidth>
+            <ipxact:access>writeOnce</ipxact:access>
+          </ipxact:field>
+        </ipxact:register>
+        <ipxact:register>
+          <ipxact:name>LEVEL</ipxact:name>
+          <ipxact:addressOffset>'h4</ipxact:addressOffset>
+          <ipxact:size>16</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>COUNT</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:bitWidth>12</ipxact:bitWidth>
+            <ipxact:access>read-only</ipxact:access>
+          </ipxact:field>
+        </ipxact:register>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>
+"#;
+
+#[test]
+fn generates_hardware_ports_per_field() {
+    let data = parse_ipxact_to_import_data(DMA.as_bytes()).expect("parse should succeed");
+    let files = generate_sv(&data).expect("generation should succeed");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].name, "dma_channel_csr.sv");
+    let sv = &files[0].content;
+
+    assert!(sv.contains(
+        "  input  logic [15:0] req_wdata,\n  input  logic [1:0] req_wstrb,\n  output logic [15:0] rsp_rdata,\n"
+    ));
+    assert!(sv.contains(
+        "  // cmd\n  output logic cmd_start_q,\n  input  logic cmd_start_clr,\n  output logic [7:0] cmd_key_q,\n  output logic cmd_key_we,\n  // level\n  input  logic [11:0] level_count_d\n);\n"
+    ));
+    assert!(sv.contains("  assign cmd_hit = req_addr[3:1] == 3'h1;\n"));
+
+    // hardware clears what software sets, software wins
+    assert!(sv.contains(
+        "    cmd_start_next = cmd_start_next & ~cmd_start_clr;\n    if (cmd_start_we) cmd_start_next = cmd_start_next | (req_wdata[0] & wmask[0]);\n"
+    ));
+    // only the first write counts
+    assert!(sv.contains("  assign cmd_key_we = cmd_wr && |wmask[15:8] && !cmd_key_written;\n"));
+    assert!(sv.contains("      cmd_key_q <= 8'hA5;\n      cmd_key_written <= 1'b0;\n"));
+    assert!(sv.contains("      if (cmd_key_we) cmd_key_written <= 1'b1;\n"));
+    // write-only data reads as zero, the hardware value of read-only fields is passed through
+    assert!(sv.contains(
+        "    if (cmd_hit) begin\n      rsp_rdata[0] = cmd_start_q;\n    end\n    if (level_hit) begin\n      rsp_rdata[11:0] = level_count_d;\n    end\n"
+    ));
+    assert!(sv.contains("  assign rsp_error = req && !(cmd_hit || level_hit);\n"));
+}
+
+#[test]
+fn reports_what_cannot_be_generated() {
+    let xml = DMA
+        .replace("<ipxact:addressOffset>'h2<", "<ipxact:addressOffset>'h3<")
+        .replace(
+            "<ipxact:access>read-only</ipxact:access>",
+            "<ipxact:access>read-only</ipxact:access>\n<ipxact:readAction>modify</ipxact:readAction>",
+        );
+    let data = parse_ipxact_to_import_data(xml.as_bytes()).expect("parse should succeed");
+    let err = generate_sv(&data).expect_err("generation should fail");
+    assert_eq!(
+        err.to_string(),
+        "error[unaligned-register] DMA Channel: Register CMD: offset 0x3 is not aligned to the 16-bit data width (and 1 more)"
+    );
+
+    let mut data = data;
+    let regs = &mut data.memory_maps[0].address_blocks[0].registers;
+    regs[0].address_offset = "0x2".to_string();
+    regs[1].fields[0].read_action = None;
+    // the importers reject overlapping fields, the generator checks them again
+    regs[0].fields[1].bit_offset = 0;
+    let err = generate_sv(&data).expect_err("generation should fail");
+    assert_eq!(
+        err.to_string(),
+        "error[field-overlap] DMA Channel: Field CMD.KEY: bits overlap field start"
+    );
+}
+
+#[test]
+fn reports_names_generated_twice() {
+    let data = parse_ipxact_to_import_data(DMA.as_bytes()).expect("parse should succeed");
+
+    // the record of CMD would have `start_clr` for the clear input of START and KEY
+    let mut clash = data.clone();
+    clash.memory_maps[0].address_blocks[0].registers[0].fields[1].name = "START_CLR".to_string();
+    let err = generate_sv(&clash).expect_err("generation should fail");
+    assert_eq!(
+        err.to_string(),
+        "error[duplicate-name] DMA Channel: Register cmd: record element start_clr is already taken"
+    );
+
+    // CMD.B_C and CMD_B.C are both `cmd_b_c_q`
+    let mut clash = data;
+    let regs = &mut clash.memory_maps[0].address_blocks[0].registers;
+    regs[0].fields[1].name = "B_C".to_string();
+    regs[1].name = "CMD_B".to_string();
+    regs[1].fields[0].name = "C".to_string();
+    regs[1].fields[0].access = "read-write".to_string();
+    let err = generate_sv(&clash).expect_err("generation should fail");
+    assert_eq!(
+        err.to_string(),
+        "error[duplicate-name] DMA Channel: Register cmd_b: port cmd_b_c_q is already taken by register cmd"
+    );
+}
+
+#[test]
+fn reports_field_bits_past_u32() {
+    let mut data = parse_ipxact_to_import_data(DMA.as_bytes()).expect("parse should succeed");
+    let field = &mut data.memory_maps[0].address_blocks[0].registers[0].fields[0];
+    field.bit_offset = u32::MAX;
+    field.bit_width = 2;
+    let err = generate_sv(&data).expect_err("generation should fail");
+    assert_eq!(
+        err.to_string(),
+        "error[field-out-of-range] DMA Channel: Field CMD.START: bits [4294967296:4294967295] are outside the 16-bit register"
+    );
+}
+
+#[test]
+fn wraps_blocks_in_bus_adapters_with_testbenches() {
+    let data = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    let opts: RtlOptions = serde_json::from_str(r#"{ "bus": "axi4-lite" }"#).expect("options");
+    let files = generate_sv_with_options(&data, &opts).expect("generation should succeed");
+    let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "block0_csr.sv",
+            "block0_axi4lite.sv",
+            "block0_axi4lite_tb.sv",
+            "block1_csr.sv",
+            "block1_axi4lite.sv",
+            "block1_axi4lite_tb.sv",
+        ]
+    );
+    assert!(files[1].content.contains(
+        "  input  logic aclk,\n  input  logic aresetn,\n  input  logic awvalid,\n  output logic awready,\n"
+    ));
+    assert!(files[1]
+        .content
+        .contains("        bresp <= rsp_error ? 2'b10 : 2'b00;\n"));
+    assert!(files[1]
+        .content
+        .contains("  block0_csr u_csr (\n    .clk(aclk),\n    .rst_n(aresetn),\n    .req(req),\n"));
+
+    // reset values, the error response of a hole and write/read back, lane by lane
+    let tb = &files[2].content;
+    assert!(tb.contains("module block0_axi4lite_tb;\n"));
+    assert!(tb.contains("    read(\"reg0 reset\", 12'h0, 32'h1234, 32'hFFFFFFFF, 1'b0);\n"));
+    assert!(tb.contains(
+        "    write(\"unmapped 0x1C\", 12'h1C, 32'hFFFFFFFF, 4'hF, 1'b1);\n    read(\"unmapped 0x1C\", 12'h1C, 32'h0, 32'h0, 1'b1);\n"
+    ));
+    assert!(tb.contains(
+        "    write(\"reg1 byte 0 write\", 12'h4, 32'h0, 4'h1, 1'b0);\n    read(\"reg1 byte 0 write\", 12'h4, 32'hFF0000, 32'hFF00FF, 1'b0);\n"
+    ));
+    assert!(tb.contains("    if (errors == 0) $display(\"PASS\");\n"));
+}
+
+#[test]
+fn adapts_every_bus() {
+    let data = parse_ipxact_to_import_data(DMA.as_bytes()).expect("parse should succeed");
+    let expected = [
+        (
+            Bus::Apb3,
+            "dma_channel_apb3",
+            "  assign req_wstrb = '1;\n  assign prdata = rsp_rdata;\n",
+        ),
+        (
+            Bus::Apb4,
+            "dma_channel_apb4",
+            "  assign req_wstrb = pstrb;\n",
+        ),
+        (
+            Bus::Axi4Lite,
+            "dma_channel_axi4lite",
+            "  assign rd_go = !wr_go && arvalid && !rvalid;\n",
+        ),
+        (
+            Bus::AhbLite,
+            "dma_channel_ahblite",
+            "  assign hresp = (req && rsp_error) || d_error;\n",
+        ),
+        (Bus::Wishbone, "dma_channel_wb", "    .rst_n(!rst_i),\n"),
+    ];
+    for (bus, module, line) in expected {
+        let files = generate_sv_with_options(&data, &RtlOptions { bus: Some(bus) })
+            .expect("generation should succeed");
+        let [_, adapter, tb] = &files[..] else {
+            panic!("{bus:?}: {} files", files.len());
+        };
+        assert_eq!(adapter.name, format!("{module}.sv"));
+        assert!(adapter.content.contains(line), "{bus:?}");
+        assert!(
+            adapter
+                .content
+                .contains("  input  logic [11:0] level_count_d\n);\n"),
+            "{bus:?}"
+        );
+        assert!(
+            tb.content.contains(&format!("  {module} dut (\n")),
+            "{bus:?}"
+        );
+    }
+
+    // APB3 has no byte strobes to test
+    let data = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    for (bus, lanes) in [(Bus::Apb3, false), (Bus::Apb4, true)] {
+        let files = generate_sv_with_options(&data, &RtlOptions { bus: Some(bus) })
+            .expect("generation should succeed");
+        assert_eq!(files[2].content.contains("byte 0 write"), lanes, "{bus:?}");
+    }
+}
+
+/// Needs a simulator, so it is ignored by default: run with `cargo test -- --ignored`
+/// once `iverilog` or `verilator` is on the PATH.
+#[test]
+#[ignore = "needs iverilog or verilator"]
+fn passes_the_bus_testbenches_in_a_simulator() {
+    let simulator = ["iverilog", "verilator"]
+        .into_iter()
+        .find(|tool| common::has_tool(tool))
+        .expect("neither iverilog nor verilator found");
+    let example = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    let dma = parse_ipxact_to_import_data(DMA.as_bytes()).expect("parse should succeed");
+
+    for bus in [
+        Bus::Apb3,
+        Bus::Apb4,
+        Bus::Axi4Lite,
+        Bus::AhbLite,
+        Bus::Wishbone,
+    ] {
+        for data in [&example, &dma] {
+            let files = generate_sv_with_options(data, &RtlOptions { bus: Some(bus) })
+                .expect("generation should succeed");
+            // the CSR module, its adapter and testbench of each block
+            for block in files.chunks(3) {
+                let [csr, adapter, tb] = [0, 1, 2].map(|i| block[i].name.as_str());
+                let top = tb.trim_end_matches(".sv");
+                let dir = common::write_rtl(top, block);
+                let output = if simulator == "iverilog" {
+                    common::run(&dir, "iverilog", &["-g2012", "-o", top, tb, adapter, csr]);
+                    common::run(&dir, "vvp", &[top])
+                } else {
+                    common::run(
+                        &dir,
+                        "verilator",
+                        &[
+                            "--binary",
+                            "--timing",
+                            "-Wno-fatal",
+                            "--top-module",
+                            top,
+                            tb,
+                            adapter,
+                            csr,
+                        ],
+                    );
+                    common::run(&dir, dir.join("obj_dir").join(format!("V{}", top)), &[])
+                };
+                fs::remove_dir_all(&dir).ok();
+                assert!(output.contains("PASS"), "{}: {}", top, output);
+            }
+        }
+    }
+}
+
+#[test]
+fn tests_a_block_spanning_the_whole_address_space() {
+    let mut data = parse_ipxact_to_import_data(DMA.as_bytes()).expect("parse should succeed");
+    data.memory_maps[0].address_blocks[0].range = "0xFFFFFFFFFFFFFFFF".to_string();
+    let files = generate_sv_with_options(
+        &data,
+        &RtlOptions {
+            bus: Some(Bus::Apb4),
+        },
+    )
+    .expect("generation should succeed");
+    let tb = &files[2].content;
+    assert!(tb.contains("  localparam int AW = 64;\n"));
+    // the hole is looked for among the first words only
+    assert!(tb.contains("    write(\"unmapped 0x0\", 64'h0, 16'hFFFF, 2'h3, 1'b1);\n"));
+}
+
+#[test]
+fn declares_one_bit_access_ports_as_vectors() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>acme.com</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>gpio</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>map</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>GPIO</ipxact:name>
+        <ipxact:baseAddress>0</ipxact:baseAddress>
+        <ipxact:range>2</ipxact:range>
+        <ipxact:width>8</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>DATA</ipxact:name>
+          <ipxact:addressOffset>1</ipxact:addressOffset>
+          <ipxact:size>8</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>PINS</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:bitWidth>8</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+          </ipxact:field>
+        </ipxact:register>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>
+"#;
+    let data = parse_ipxact_to_import_data(xml.as_bytes()).expect("parse should succeed");
+    let files = generate_sv(&data).expect("generation should succeed");
+    let sv = &files[0].content;
+
+    // an 8-bit block of two bytes has a one bit address and a single byte lane
+    assert!(sv.contains(
+        "  input  logic [0:0] req_addr,\n  input  logic [7:0] req_wdata,\n  input  logic [0:0] req_wstrb,\n"
+    ));
+    assert!(sv.contains("  assign wmask = {{8{req_wstrb[0]}}};\n"));
+    assert!(sv.contains("  assign data_hit = req_addr[0:0] == 1'h1;\n"));
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use std::fs;
+
+use parser_plugin_rust::{
+    generate_sv_with_options, generate_vhdl, parse_excel_to_import_data,
+    parse_ipxact_to_import_data, Bus, RtlOptions,
+};
+
+const IRQ: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2022">
+  <ipxact:vendor>acme.com</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>irq</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>map</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>IRQ</ipxact:name>
+        <ipxact:baseAddress>0</ipxact:baseAddress>
+        <ipxact:range>'h10</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>STATUS</ipxact:name>
+          <ipxact:addressOffset>0</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>DONE</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>1</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:fieldAccessPolicies><ipxact:fieldAccessPolicy>
+              <ipxact:access>read-write</ipxact:access>
+              <ipxact:modifiedWriteValue>oneToClear</ipxact:modifiedWriteValue>
+            </ipxact:fieldAccessPolicy></ipxact:fieldAccessPolicies>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>OVERRUN</ipxact:name>
+            <ipxact:bitOffset>4</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>'hF</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>4</ipxact:bitWidth>
+            <ipxact:fieldAccessPolicies><ipxact:fieldAccessPolicy>
+              <ipxact:access>read-only</ipxact:access>
+              <ipxact:readAction>clear</ipxact:readAction>
+            </ipxact:fieldAccessPolicy></ipxact:fieldAccessPolicies>
+          </ipxact:field>
+        </ipxact:register>
+        <ipxact:register>
+          <ipxact:name>ENABLE</ipxact:name>
+          <ipxact:addressOffset>4</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>MASK</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:bitWidth>16</ipxact:bitWidth>
+            <ipxact:fieldAccessPolicies><ipxact:fieldAccessPolicy>
+              <ipxact:access>read-write</ipxact:access>
+            </ipxact:fieldAccessPolicy></ipxact:fieldAccessPolicies>
+          </ipxact:field>
+        </ipxact:register>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>
+"#;
+
+#[test]
+fn generates_example_workbook() {
+    let data = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    let files = generate_vhdl(&data).expect("generation should succeed");
+    let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "block0_csr.vhd",
+            "block0_csr_tb.vhd",
+            "block1_csr.vhd",
+            "block1_csr_tb.vhd"
+        ]
+    );
+
+    let vhdl = &files[0].content;
+    assert!(vhdl.contains("package block0_csr_pkg is\n"));
+    assert!(vhdl.contains("entity block0_csr is\n"));
+    assert!(vhdl.contains("    req_addr  : in  std_logic_vector(11 downto 0);\n"));
+    assert!(vhdl.contains("      reg0_field0_q <= 32x\"1234\";\n"));
+    assert!(vhdl.ends_with("end architecture;\n"));
+}
+
+#[test]
+fn gathers_hardware_ports_in_records() {
+    let data = parse_ipxact_to_import_data(IRQ.as_bytes()).expect("parse should succeed");
+    let files = generate_vhdl(&data).expect("generation should succeed");
+    let vhdl = &files[0].content;
+
+    assert!(vhdl.contains(
+        "  type irq_status_in_t is record\n    done_set : std_logic;\n    overrun_set : std_logic_vector(3 downto 0);\n  end record;\n  type irq_in_t is record\n    status : irq_status_in_t;\n  end record;\n"
+    ));
+    assert!(vhdl.contains(
+        "  constant irq_in_zero : irq_in_t := (status => (done_set => '0', overrun_set => (others => '0')));\n"
+    ));
+    assert!(vhdl.contains(
+        "  type irq_out_t is record\n    status : irq_status_out_t;\n    enable : irq_enable_out_t;\n  end record;\n"
+    ));
+    assert!(vhdl.contains("  hw_out.enable.mask <= enable_mask_q;\n"));
+
+    // the side effects of the SystemVerilog, in the same order
+    assert!(vhdl.contains(
+        "    v := status_done_q;\n    if status_done_we = '1' then\n      v := v and not (req_wdata(0) and wmask(0));\n    end if;\n    v := v or hw_in.status.done_set;\n"
+    ));
+    assert!(vhdl.contains(
+        "    if status_rd = '1' then\n      v := (others => '0');\n    end if;\n    v := v or hw_in.status.overrun_set;\n"
+    ));
+    assert!(vhdl.contains("  enable_mask_we <= enable_wr and (or wmask(15 downto 0));\n"));
+    assert!(vhdl.contains("  status_hit <= '1' when req_addr(3 downto 2) = 2x\"0\" else '0';\n"));
+}
+
+#[test]
+fn runs_the_vectors_of_the_systemverilog_testbench() {
+    let data = parse_ipxact_to_import_data(IRQ.as_bytes()).expect("parse should succeed");
+    let vhdl = generate_vhdl(&data).expect("generation should succeed");
+    let sv = generate_sv_with_options(
+        &data,
+        &RtlOptions {
+            bus: Some(Bus::Apb4),
+        },
+    )
+    .expect("generation should succeed");
+
+    let calls = |tb: &str, prefixes: &[&str]| -> Vec<String> {
+        tb.lines()
+            .map(str::trim)
+            .filter(|l| prefixes.iter().any(|p| l.starts_with(p)))
+            .map(|l| {
+                l[l.find('"').unwrap()..]
+                    .split(',')
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+    let vhdl_calls = calls(&vhdl[1].content, &["transfer(\""]);
+    let sv_calls = calls(&sv[2].content, &["write(\"", "read(\""]);
+    assert_eq!(vhdl_calls, sv_calls);
+    assert_eq!(
+        vhdl_calls,
+        [
+            "\"status reset\"",
+            "\"enable reset\"",
+            "\"unmapped 0x8\"",
+            "\"unmapped 0x8\"",
+            "\"status clear on read\"",
+            "\"status write 1 to clear\"",
+            "\"status write 1 to clear\"",
+            "\"enable write\"",
+            "\"enable write\"",
+            "\"enable byte 0 write\"",
+            "\"enable byte 0 write\"",
+            "\"enable clear\"",
+            "\"enable clear\"",
+        ]
+    );
+
+    let tb = &vhdl[1].content;
+    assert!(tb.contains(
+        "    transfer(\"status reset\", '0', 4x\"0\", 32x\"F1\", 4x\"0\", 32x\"F1\", '0');\n"
+    ));
+    assert!(tb.contains(
+        "    transfer(\"enable byte 0 write\", '1', 4x\"4\", 32x\"0\", 4x\"1\", 32x\"0\", '0');\n"
+    ));
+    assert!(tb.contains("  signal hw_in     : irq_in_t := irq_in_zero;\n"));
+}
+
+/// Needs GHDL, so it is ignored by default: run with `cargo test -- --ignored` once
+/// `ghdl` is on the PATH.
+#[test]
+#[ignore = "needs ghdl"]
+fn passes_the_testbenches_in_ghdl() {
+    assert!(common::has_tool("ghdl"), "ghdl not found");
+    let example = parse_excel_to_import_data(include_bytes!("../../example.xlsx"))
+        .expect("parse should succeed");
+    let irq = parse_ipxact_to_import_data(IRQ.as_bytes()).expect("parse should succeed");
+
+    for data in [&example, &irq] {
+        let files = generate_vhdl(data).expect("generation should succeed");
+        // the CSR entity and testbench of each block
+        for block in files.chunks(2) {
+            let [csr, tb] = [0, 1].map(|i| block[i].name.as_str());
+            let top = tb.trim_end_matches(".vhd");
+            let dir = common::write_rtl(top, block);
+            common::run(&dir, "ghdl", &["-a", "--std=08", csr, tb]);
+            let output = common::run(&dir, "ghdl", &["-r", "--std=08", top]);
+            fs::remove_dir_all(&dir).ok();
+            assert!(output.contains("PASS"), "{}: {}", top, output);
+        }
+    }
+}
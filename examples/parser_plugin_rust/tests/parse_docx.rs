@@ -0,0 +1,202 @@
+mod common;
+
+use common::{build_docx, docx_paragraph as p, docx_table as table};
+use parser_plugin_rust::{
+    parse_docx_to_import_data, parse_docx_with_options, ParseOptions, Severity,
+};
+
+const FIELD_HEADER: [&str; 6] = ["BIT", "FIELD", "WIDTH", "ACCESS", "RESET", "DESCRIPTION"];
+
+fn timer_docx() -> Vec<u8> {
+    build_docx(
+        &[
+            p("Title", "Timer SoC"),
+            p("", "Register reference of the timer subsystem."),
+            p("Heading1", "TIMER (0x4000_0000)"),
+            p("Heading2", "CTRL register (0x00)"),
+            table(&[
+                vec!["Table 1. CTRL fields"],
+                FIELD_HEADER.to_vec(),
+                vec!["[0]", "EN", "1", "RW", "0", "Enable"],
+                vec!["[2:1]", "MODE", "2", "RW", "0x1", "Mode\nselect"],
+            ]),
+            p("Heading2", "STATUS (0x04)"),
+            table(&[
+                FIELD_HEADER.to_vec(),
+                vec!["[0]", "DONE", "1", "W1C", "0", "Done"],
+            ]),
+            p("Heading1", "WDG @ 0x4000_1000"),
+            table(&[
+                common::BLOCK_HEADER.to_vec(),
+                vec![
+                    "0x0", "LOAD", "VAL", "[15:0]", "16", "RW", "0xFFFF", "Reload",
+                ],
+                vec!["0x4", "KICK", "KEY", "[7:0]", "8", "WO", "0", "Kick"],
+            ]),
+        ]
+        .concat(),
+    )
+}
+
+#[test]
+fn infers_blocks_and_registers_from_headings() {
+    let outcome = parse_docx_with_options(&timer_docx(), &ParseOptions::default());
+    assert!(outcome.diagnostics.is_empty(), "{:?}", outcome.diagnostics);
+    let data = outcome.into_result().expect("parse should succeed");
+    assert_eq!(data.project.name, "Timer SoC");
+
+    let blocks: Vec<_> = data.memory_maps[0]
+        .address_blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.width))
+        .collect();
+    // as wide as their widest register
+    assert_eq!(
+        blocks,
+        [("TIMER", "0x40000000", 8), ("WDG", "0x40001000", 16)]
+    );
+
+    // the field tables of the register headings, the caption row skipped
+    let timer = &data.memory_maps[0].address_blocks[0];
+    let regs: Vec<_> = timer
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(regs, [("CTRL", "0x0"), ("STATUS", "0x4")]);
+    let fields: Vec<_> = timer.registers[0]
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width))
+        .collect();
+    assert_eq!(fields, [("EN", 0, 1), ("MODE", 1, 2)]);
+    assert_eq!(
+        timer.registers[0].fields[1].description.as_deref(),
+        Some("Mode\nselect")
+    );
+    assert_eq!(
+        timer.registers[1].fields[0].modified_write_value.as_deref(),
+        Some("oneToClear")
+    );
+
+    let wdg: Vec<_> = data.memory_maps[0].address_blocks[1]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(wdg, [("LOAD", "0x0"), ("KICK", "0x4")]);
+}
+
+#[test]
+fn places_blocks_with_address_map_table() {
+    let docx = build_docx(
+        &[
+            p("Heading1", "DMA Controller"),
+            table(&[
+                vec!["VENDOR", "LIBRARY", "NAME", "VERSION"],
+                vec!["acme.com", "IP", "dma", "2.1"],
+            ]),
+            table(&[
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["dma", "0x1000", "0x100"],
+            ]),
+            p("Heading2", "DMA"),
+            table(&[
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CH_CTRL", "START", "[0]", "1", "RW", "0", "Start"],
+            ]),
+            p("Heading2", "Appendix"),
+            table(&[
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "X", "Y", "[0]", "1", "RO", "0", "Not mapped"],
+            ]),
+        ]
+        .concat(),
+    );
+    let outcome = parse_docx_with_options(&docx, &ParseOptions::default());
+    let warnings: Vec<String> = outcome.warnings().map(|d| d.to_string()).collect();
+    assert_eq!(
+        warnings,
+        ["warning[unreferenced-sheet] Appendix: Heading is not referenced by the address map and was ignored"]
+    );
+    let data = outcome.into_result().expect("parse should succeed");
+    assert_eq!(
+        (data.project.name.as_str(), data.project.version.as_str()),
+        ("dma", "2.1")
+    );
+
+    let blocks: Vec<_> = data.memory_maps[0]
+        .address_blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.range.as_str()))
+        .collect();
+    assert_eq!(blocks, [("dma", "0x1000", "0x100")]);
+    assert_eq!(
+        data.memory_maps[0].address_blocks[0].registers[0].name,
+        "CH_CTRL"
+    );
+}
+
+#[test]
+fn reports_rows_within_tables() {
+    let xlsx = common::build_workbook(&[common::version_sheet()]);
+    let err = parse_docx_to_import_data(&xlsx).expect_err("not a docx");
+    assert_eq!(
+        err.to_string(),
+        "error[unsupported-format]: Unsupported file format: Excel workbook (.xlsx)"
+    );
+
+    let docx = build_docx(
+        &[
+            p("Heading1", "UART"),
+            p("Heading2", "CTRL"),
+            table(&[
+                FIELD_HEADER.to_vec(),
+                vec!["[0]", "EN", "1", "RW", "0", "Enable"],
+            ]),
+            p("Heading2", "DATA (0x4)"),
+            table(&[
+                FIELD_HEADER.to_vec(),
+                vec!["[7:0]", "VAL", "8", "RX", "0", "Data"],
+            ]),
+            p("Heading2", "FIFO"),
+            table(&[
+                FIELD_HEADER.to_vec(),
+                vec!["[3:0]", "LEVEL", "4", "RO", "0", "Level"],
+            ]),
+        ]
+        .concat(),
+    );
+    let outcome = parse_docx_with_options(&docx, &ParseOptions::default());
+    let warnings: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "warning[missing-offset] CTRL row 1: Register heading CTRL gives no offset, placed at 0x0",
+            "warning[unknown-access] DATA!D2: Field DATA.VAL: unknown access \"RX\", defaulting to read-write",
+            "warning[missing-offset] FIFO row 1: Register heading FIFO gives no offset, placed at 0x8",
+        ]
+    );
+    // registers without an offset follow the ones before them
+    let data = outcome.data.expect("lenient import");
+    let regs: Vec<_> = data.memory_maps[0].address_blocks[0]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(regs, [("CTRL", "0x0"), ("DATA", "0x4"), ("FIFO", "0x8")]);
+
+    let strict = parse_docx_with_options(
+        &docx,
+        &ParseOptions {
+            strict: true,
+            ..Default::default()
+        },
+    );
+    assert!(strict.data.is_none());
+}
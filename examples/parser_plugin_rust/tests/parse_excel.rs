@@ -1,4 +1,9 @@
-use parser_plugin_rust::parse_excel_to_import_data;
+mod common;
+
+use parser_plugin_rust::{
+    parse_excel_to_import_data, parse_excel_with_diagnostics, parse_excel_with_options,
+    ParseOptions, Severity,
+};
 
 #[test]
 fn parses_example_workbook_to_import_data() {
@@ -20,3 +25,915 @@ fn parses_example_workbook_to_import_data() {
         assert!(!blk.registers.is_empty());
     }
 }
+
+#[test]
+fn ignores_unreferenced_sheets_with_warning() {
+    let data = common::build_workbook(&[
+        ("Revision History", vec![]),
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![vec!["BLOCK", "OFFSET", "RANGE"], vec!["uart", "0x0", "0x100"]],
+        ),
+        (
+            "uart",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+            ],
+        ),
+        ("Notes", vec![vec!["anything goes here"]]),
+    ]);
+
+    let outcome = parse_excel_with_diagnostics(&data);
+    let import_data = outcome.data.as_ref().expect("parse should succeed");
+
+    let blocks = &import_data.memory_maps[0].address_blocks;
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].registers[0].name, "CTRL");
+
+    let warnings: Vec<_> = outcome.warnings().collect();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].code, "unreferenced-sheet");
+    assert_eq!(warnings[0].sheet.as_deref(), Some("Revision History"));
+    assert_eq!(warnings[1].sheet.as_deref(), Some("Notes"));
+}
+
+#[test]
+fn instantiates_one_block_sheet_several_times() {
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "INSTANCE", "OFFSET", "RANGE"],
+                vec!["uart", "UART0", "0x1000", "0x100"],
+                vec!["uart", "UART1", "0x2000", "0x100"],
+                vec!["uart", "", "0x3000", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["0x4", "DATA", "VALUE", "[7:0]", "8", "RW", "0", "Data"],
+            ],
+        ),
+    ]);
+
+    let import_data = parse_excel_to_import_data(&data).expect("parse should succeed");
+    let blocks = &import_data.memory_maps[0].address_blocks;
+
+    let names: Vec<_> = blocks.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["UART0", "UART1", "uart"]);
+
+    let bases: Vec<_> = blocks.iter().map(|b| b.base_address.as_str()).collect();
+    assert_eq!(bases, ["0x1000", "0x2000", "0x3000"]);
+
+    for blk in blocks {
+        let regs: Vec<_> = blk.registers.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(regs, ["CTRL", "DATA"]);
+    }
+
+    // two rows without INSTANCE would give two blocks named after the sheet
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "INSTANCE", "OFFSET", "RANGE"],
+                vec!["uart", "", "0x1000", "0x100"],
+                vec!["UART", "", "0x2000", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+            ],
+        ),
+    ]);
+    let errors: Vec<String> = parse_excel_with_diagnostics(&data)
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        ["error[duplicate-block] address_map!A3: Block UART is already mapped on row 2, name each instance in an INSTANCE column"]
+    );
+}
+
+#[test]
+fn maps_access_shorthands_and_warns_on_unknown() {
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![vec!["BLOCK", "OFFSET", "RANGE"], vec!["blk", "0x0", "0x100"]],
+        ),
+        (
+            "blk",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "STATUS", "DONE", "[0]", "1", "W1C", "0", "Done"],
+                vec!["", "", "ERR", "[1]", "1", "RC", "0", "Error"],
+                vec!["", "", "ODD", "[2]", "1", "XYZ", "0", "Odd"],
+            ],
+        ),
+    ]);
+
+    let outcome = parse_excel_with_diagnostics(&data);
+    let import_data = outcome.data.as_ref().expect("parse should succeed");
+    let fields = &import_data.memory_maps[0].address_blocks[0].registers[0].fields;
+
+    assert_eq!(fields[0].access, "read-write");
+    assert_eq!(fields[0].modified_write_value.as_deref(), Some("oneToClear"));
+    assert_eq!(fields[0].read_action, None);
+
+    assert_eq!(fields[1].access, "read-only");
+    assert_eq!(fields[1].modified_write_value, None);
+    assert_eq!(fields[1].read_action.as_deref(), Some("clear"));
+
+    assert_eq!(fields[2].access, "read-write");
+    let warnings: Vec<_> = outcome.warnings().collect();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, "unknown-access");
+    assert!(warnings[0].message.contains("\"XYZ\""));
+    assert_eq!(warnings[0].sheet.as_deref(), Some("blk"));
+    assert_eq!(warnings[0].row, Some(4));
+    assert_eq!(warnings[0].column.as_deref(), Some("F"));
+}
+
+#[test]
+fn reads_enumerated_values_from_enum_column_and_description() {
+    let mut header = common::BLOCK_HEADER.to_vec();
+    header.push("ENUM");
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["blk", "0x0", "0x100"],
+            ],
+        ),
+        (
+            "blk",
+            vec![
+                header,
+                vec![
+                    "0x0",
+                    "CTRL",
+                    "MODE",
+                    "[1:0]",
+                    "2",
+                    "RW",
+                    "0",
+                    "Mode select",
+                    "0: OFF - powered down\n1: ON",
+                ],
+                vec![
+                    "",
+                    "",
+                    "SPEED",
+                    "[3:2]",
+                    "2",
+                    "RW",
+                    "0",
+                    "0: slow, 1: fast",
+                    "",
+                ],
+                vec!["", "", "EN", "[4]", "1", "RW", "0", "Enable", ""],
+                vec![
+                    "",
+                    "",
+                    "BUSY",
+                    "[5]",
+                    "1",
+                    "RO",
+                    "0",
+                    "0: idle, 1: busy",
+                    "see datasheet",
+                ],
+            ],
+        ),
+    ]);
+
+    let outcome = parse_excel_with_diagnostics(&data);
+    let warnings: Vec<String> = outcome.warnings().map(|d| d.to_string()).collect();
+    assert_eq!(
+        warnings,
+        ["warning[invalid-enum] blk!I5: Field CTRL.BUSY: ENUM \"see datasheet\" has no \"value: name\" entry, reading the description instead"]
+    );
+    let strict = parse_excel_with_options(
+        &data,
+        &ParseOptions {
+            strict: true,
+            ..Default::default()
+        },
+    );
+    assert!(strict.data.is_none());
+
+    let import_data = outcome.data.expect("parse should succeed");
+    let fields = &import_data.memory_maps[0].address_blocks[0].registers[0].fields;
+
+    let mode = &fields[0].enumerated_values;
+    assert_eq!(mode.len(), 2);
+    assert_eq!(
+        (mode[0].value.as_str(), mode[0].name.as_str()),
+        ("0", "OFF")
+    );
+    assert_eq!(mode[0].description.as_deref(), Some("powered down"));
+    assert_eq!((mode[1].value.as_str(), mode[1].name.as_str()), ("1", "ON"));
+
+    let speed: Vec<_> = fields[1]
+        .enumerated_values
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert_eq!(speed, ["slow", "fast"]);
+
+    assert!(fields[2].enumerated_values.is_empty());
+    assert_eq!(fields[3].enumerated_values.len(), 2);
+}
+
+#[test]
+fn reads_register_descriptions() {
+    let mut header = common::BLOCK_HEADER.to_vec();
+    header.push("REG_DESCRIPTION");
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["blk", "0x0", "0x100"],
+            ],
+        ),
+        (
+            "blk",
+            vec![
+                header,
+                vec![
+                    "0x0",
+                    "CTRL",
+                    "EN",
+                    "[0]",
+                    "1",
+                    "RW",
+                    "0",
+                    "Enable",
+                    "Control register",
+                ],
+                vec!["", "", "MODE", "[2:1]", "2", "RW", "0", "Mode", ""],
+                vec!["0x4", "STATUS", "", "", "", "", "", "Status register", ""],
+                vec!["", "", "BUSY", "[0]", "1", "RO", "0", "Busy", ""],
+                vec!["0x8", "DATA", "VALUE", "[7:0]", "8", "RW", "0", "Data", ""],
+            ],
+        ),
+    ]);
+
+    let import_data = parse_excel_to_import_data(&data).expect("parse should succeed");
+    let regs = &import_data.memory_maps[0].address_blocks[0].registers;
+
+    let summary: Vec<_> = regs
+        .iter()
+        .map(|r| (r.name.as_str(), r.description.as_deref(), r.fields.len()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("CTRL", Some("Control register"), 2),
+            ("STATUS", Some("Status register"), 1),
+            ("DATA", None, 1),
+        ]
+    );
+
+    let status = &regs[1];
+    assert_eq!(status.size, 1);
+    assert_eq!(status.fields[0].name, "BUSY");
+    assert_eq!(status.fields[0].access, "read-only");
+}
+
+#[test]
+fn collects_located_diagnostics_instead_of_stopping_at_first_error() {
+    let data = common::build_workbook(&[
+        (
+            "version",
+            vec![
+                vec!["VENDOR", "LIBRARY", "NAME"],
+                vec!["example.com", "IP", "example"],
+            ],
+        ),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["blk", "0x0", "0x100"],
+                vec!["missing", "0x100", "0x100"],
+                vec!["blk", "", "0x100"],
+            ],
+        ),
+        (
+            "blk",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[x:y]", "2", "RW", "0", "Mode"],
+            ],
+        ),
+    ]);
+
+    let outcome = parse_excel_with_diagnostics(&data);
+    assert!(outcome.data.is_none());
+    assert!(outcome.has_errors());
+
+    let located: Vec<_> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| {
+            (
+                d.code.as_str(),
+                d.sheet.as_deref().unwrap_or_default(),
+                d.row,
+                d.column.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        located,
+        [
+            ("missing-column", "version", Some(1), None),
+            ("missing-value", "blk", Some(3), Some("D")),
+            ("missing-sheet", "address_map", Some(3), Some("A")),
+            ("missing-value", "address_map", Some(4), Some("B")),
+        ]
+    );
+
+    let err = parse_excel_to_import_data(&data).expect_err("parse should fail");
+    assert!(err.to_string().contains("VERSION"));
+    assert!(err.to_string().contains("(and 3 more)"));
+}
+
+#[test]
+fn reports_inherited_addr_overlapping_fields_and_taken_offsets() {
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![vec!["BLOCK", "OFFSET", "RANGE"], vec!["blk", "0x0", "0x100"]],
+        ),
+        (
+            "blk",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[1:0]", "2", "RW", "0", "Mode"],
+                vec!["", "STATUS", "BUSY", "[0]", "1", "RO", "0", "Busy"],
+                vec!["0x4", "DATA", "VALUE", "[31:0]", "32", "RW", "0", "Data"],
+                vec!["0x4", "ALIAS", "VALUE", "[31:0]", "32", "RW", "0", "Data"],
+                vec!["0x8", "WIDE", "VALUE", "[64:1]", "64", "RW", "0", "Data"],
+            ],
+        ),
+    ]);
+
+    let outcome = parse_excel_with_diagnostics(&data);
+    assert!(outcome.data.is_none());
+
+    let errors: Vec<_> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "error[field-overlap] blk!D3: Field CTRL.MODE overlaps another field",
+            "error[missing-value] blk!A4: Register STATUS has no ADDR, it would share 0x0 with the register above",
+            "error[register-overlap] blk!A6: Register ALIAS: offset 0x4 is already taken by DATA",
+            "error[field-out-of-range] blk!D7: Field WIDE.VALUE [64:1] does not fit in 64 bits",
+        ]
+    );
+}
+
+#[test]
+fn report_lists_silently_defaulted_values_as_warnings() {
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE", "WIDTH"],
+                vec!["blk", "0x0", "0x100", "wide"],
+            ],
+        ),
+        (
+            "blk",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "one", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[2:1]", "2", "RW", "zero", "Mode"],
+            ],
+        ),
+    ]);
+
+    let report = parse_excel_with_diagnostics(&data).into_report();
+    assert!(report.success);
+    assert!(report.error.is_none());
+
+    let blk = &report.data.as_ref().expect("data").memory_maps[0].address_blocks[0];
+    assert_eq!(blk.width, 32);
+    assert_eq!(blk.registers[0].fields[0].bit_width, 1);
+    assert_eq!(blk.registers[0].fields[1].reset_value, "zero");
+
+    assert_eq!(
+        report.warnings,
+        [
+            "warning[invalid-number] address_map!D2: Block blk: WIDTH \"wide\" is not a number, using 32",
+            "warning[invalid-number] blk!E2: Field CTRL.EN: WIDTH \"one\" is not a number, using 1",
+            "warning[invalid-number] blk!G3: Field CTRL.MODE: DEFAULT \"zero\" is not a number, imported as is",
+        ]
+    );
+}
+
+#[test]
+fn report_carries_error_summary_on_failure() {
+    let data = common::build_workbook(&[common::version_sheet()]);
+
+    let report = parse_excel_with_diagnostics(&data).into_report();
+    assert!(!report.success);
+    assert!(report.data.is_none());
+    assert_eq!(
+        report.error.as_deref(),
+        Some("error[missing-sheet]: Sheet \"address_map\" not found")
+    );
+}
+
+#[test]
+fn strict_mode_turns_defaulted_values_into_errors() {
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE", "WIDTH"],
+                vec!["blk", "0x0", "0x100", "32"],
+            ],
+        ),
+        (
+            "blk",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[2:1]", "2", "RWX", "0", "Mode"],
+                vec!["0x4", "STAT", "BUSY", "[0]", "1", "RO", "0x", "Busy"],
+            ],
+        ),
+    ]);
+
+    let lenient = parse_excel_with_diagnostics(&data);
+    assert!(lenient.data.is_some());
+    assert_eq!(lenient.warnings().count(), 2);
+
+    let strict = parse_excel_with_options(
+        &data,
+        &ParseOptions {
+            strict: true,
+            ..Default::default()
+        },
+    );
+    assert!(strict.data.is_none());
+    let errors: Vec<String> = strict
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "error[unknown-access] blk!F3: Field CTRL.MODE: unknown access \"RWX\"",
+            "error[invalid-number] blk!G4: Field STAT.BUSY: DEFAULT \"0x\" is not a number",
+        ]
+    );
+
+    // without a WIDTH column every block would silently be 32-bit
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["blk", "0x0", "0x100"],
+            ],
+        ),
+        (
+            "blk",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+            ],
+        ),
+    ]);
+    assert!(!parse_excel_with_diagnostics(&data).has_errors());
+    let strict = parse_excel_with_options(
+        &data,
+        &ParseOptions {
+            strict: true,
+            ..Default::default()
+        },
+    );
+    let errors: Vec<String> = strict
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        ["error[default-width] address_map row 1: No WIDTH column"]
+    );
+}
+
+#[test]
+fn maps_configured_headers_and_sheet_names() {
+    let data = common::build_workbook(&[
+        (
+            "版本",
+            vec![
+                vec!["Vendor", "Library", "Name", "Version"],
+                vec!["example.com", "IP", "example", "1.0"],
+            ],
+        ),
+        (
+            "地址映射",
+            vec![
+                vec!["模块", "Base Address", "Range"],
+                vec!["uart", "0x1000", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                vec![
+                    "Offset",
+                    "寄存器",
+                    "位域",
+                    "Bits",
+                    "位宽",
+                    "Access",
+                    "Reset Value",
+                    "Desc",
+                ],
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[2:1]", "2", "W1C", "0", "Mode"],
+            ],
+        ),
+    ]);
+
+    let opts: ParseOptions = serde_json::from_str(
+        r#"{
+            "columns": {
+                "REG": ["寄存器"],
+                "FIELD": ["位域"],
+                "WIDTH": ["位宽"],
+                "BLOCK": ["模块"]
+            },
+            "sheets": { "version": ["版本"], "addressMap": ["地址映射"] }
+        }"#,
+    )
+    .expect("options");
+
+    let outcome = parse_excel_with_options(&data, &opts);
+    assert!(!outcome.has_errors(), "{:?}", outcome.diagnostics);
+    let data = outcome.data.expect("data");
+
+    let blk = &data.memory_maps[0].address_blocks[0];
+    assert_eq!(blk.name, "uart");
+    assert_eq!(blk.base_address, "0x1000");
+    let reg = &blk.registers[0];
+    assert_eq!(reg.name, "CTRL");
+    assert_eq!(reg.fields.len(), 2);
+    assert_eq!(reg.fields[1].name, "MODE");
+    assert_eq!(reg.fields[1].bit_offset, 1);
+    assert_eq!(reg.fields[1].bit_width, 2);
+    assert_eq!(
+        reg.fields[1].modified_write_value.as_deref(),
+        Some("oneToClear")
+    );
+
+    // without the mapping the Chinese headers are not understood
+    let outcome = parse_excel_with_diagnostics(&common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["uart", "0x0", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                vec![
+                    "ADDR",
+                    "寄存器",
+                    "FIELD",
+                    "BIT",
+                    "WIDTH",
+                    "ATTRIBUTE",
+                    "DEFAULT",
+                    "DESCRIPTION",
+                ],
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+            ],
+        ),
+    ]));
+    assert!(outcome
+        .diagnostics
+        .iter()
+        .any(|d| d.to_string() == "error[missing-column] uart row 1: Column \"REG\" not found"));
+}
+
+#[test]
+fn detects_header_row_below_title_banner() {
+    let data = common::build_workbook(&[
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["uart", "0x0", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                vec!["UART register map"],
+                vec![],
+                vec!["Legend: RW = read-write, RO = read-only"],
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[x:y]", "2", "RW", "0", "Mode"],
+            ],
+        ),
+    ]);
+
+    let outcome = parse_excel_with_diagnostics(&data);
+    let headers: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.code == "header-row")
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        headers,
+        [
+            "info[header-row] version row 1: Using row 1 as header (detected)",
+            "info[header-row] address_map row 1: Using row 1 as header (detected)",
+            "info[header-row] uart row 4: Using row 4 as header (detected)",
+        ]
+    );
+
+    // rows keep their spreadsheet numbering below the banner
+    let errors: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        ["error[missing-value] uart!D6: Field CTRL.MODE: bit position \"[x:y]\" cannot be parsed"]
+    );
+
+    let opts = ParseOptions {
+        header_row: Some(4),
+        ..Default::default()
+    };
+    let outcome = parse_excel_with_options(&data, &opts);
+    let errors: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        ["error[header-row]: Header row 4 is outside of sheet \"version\"",]
+    );
+}
+
+#[test]
+fn fills_merged_cells_but_not_blank_ones() {
+    let block = (
+        "blk",
+        vec![
+            common::BLOCK_HEADER.to_vec(),
+            vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+            vec!["", "", "MODE", "[2:1]", "2", "", "0x1", "Mode"],
+            vec!["", "", "LOCK", "[3]", "1", "RO", "", "Lock"],
+        ],
+    );
+    let address_map = (
+        "address_map",
+        vec![
+            vec!["BLOCK", "OFFSET", "RANGE"],
+            vec!["blk", "0x0", "0x100"],
+        ],
+    );
+
+    // ATTRIBUTE merged over EN and MODE: MODE is read-write as displayed.
+    // DEFAULT of LOCK is genuinely blank and must not inherit 0x1 from MODE.
+    let data = common::build_workbook_with_merges(
+        &[common::version_sheet(), address_map.clone(), block.clone()],
+        &[
+            ("blk", (1, 0, 3, 0)),
+            ("blk", (1, 1, 3, 1)),
+            ("blk", (1, 5, 2, 5)),
+        ],
+    );
+    let outcome = parse_excel_with_diagnostics(&data);
+    let errors: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        ["error[missing-value] blk!G4: Field CTRL.LOCK: DEFAULT is empty"]
+    );
+
+    // without the merge, the blank ATTRIBUTE of MODE is reported as well
+    let data = common::build_workbook(&[common::version_sheet(), address_map, block]);
+    let outcome = parse_excel_with_diagnostics(&data);
+    let errors: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "error[missing-value] blk!F3: Field CTRL.MODE: ATTRIBUTE is empty",
+            "error[missing-value] blk!G4: Field CTRL.LOCK: DEFAULT is empty",
+        ]
+    );
+}
+
+#[test]
+fn reads_ods_like_xlsx_and_names_unsupported_formats() {
+    let sheets = [
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["uart", "0x1000", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[2:1]", "2", "W1C", "0x1", "Mode"],
+                vec!["0x4", "STAT", "BUSY", "[0]", "1", "RO", "0", "Busy"],
+            ],
+        ),
+    ];
+
+    let from_xlsx = parse_excel_to_import_data(&common::build_workbook(&sheets)).expect("xlsx");
+    let from_ods = parse_excel_to_import_data(&common::build_ods(&sheets, &[])).expect("ods");
+    assert_eq!(
+        serde_json::to_value(&from_ods).expect("json"),
+        serde_json::to_value(&from_xlsx).expect("json")
+    );
+
+    let err = parse_excel_to_import_data(b"%PDF-1.7 ...").expect_err("pdf");
+    assert_eq!(
+        err.to_string(),
+        "error[unsupported-format]: Unsupported file format: PDF document"
+    );
+}
+
+#[test]
+fn reads_xls_xlsb_and_ods_with_merged_cells_like_xlsx() {
+    let sheets = [
+        common::version_sheet(),
+        (
+            "address_map",
+            vec![
+                vec!["BLOCK", "OFFSET", "RANGE"],
+                vec!["uart", "0x1000", "0x100"],
+            ],
+        ),
+        (
+            "uart",
+            vec![
+                common::BLOCK_HEADER.to_vec(),
+                vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "Enable"],
+                vec!["", "", "MODE", "[2:1]", "2", "", "0x1", "Mode"],
+                vec!["0x4", "STAT", "BUSY", "[0]", "1", "RO", "0", "Busy"],
+            ],
+        ),
+    ];
+    // ADDR, REG and ATTRIBUTE of CTRL merged over its two fields
+    let merges = [
+        ("uart", (1, 0, 2, 0)),
+        ("uart", (1, 1, 2, 1)),
+        ("uart", (1, 5, 2, 5)),
+    ];
+
+    let from_xlsx =
+        parse_excel_to_import_data(&common::build_workbook_with_merges(&sheets, &merges))
+            .expect("xlsx");
+    let from_xls = parse_excel_to_import_data(&common::build_xls(&sheets, &merges)).expect("xls");
+    let from_xlsb =
+        parse_excel_to_import_data(&common::build_xlsb(&sheets, &merges)).expect("xlsb");
+    let from_ods = parse_excel_to_import_data(&common::build_ods(&sheets, &merges)).expect("ods");
+    let expected = serde_json::to_value(&from_xlsx).expect("json");
+    assert_eq!(serde_json::to_value(&from_xls).expect("json"), expected);
+    assert_eq!(serde_json::to_value(&from_xlsb).expect("json"), expected);
+    assert_eq!(serde_json::to_value(&from_ods).expect("json"), expected);
+
+    // other compound files are named, not read as a broken .xls
+    let err = parse_excel_to_import_data(&common::build_cfb("WordDocument", &[0; 4096]))
+        .expect_err("doc");
+    assert_eq!(
+        err.to_string(),
+        "error[unsupported-format]: Unsupported file format: Word 97-2003 document (.doc)"
+    );
+}
+
+#[test]
+fn reads_zipped_csv_bundle_like_a_workbook() {
+    let version = common::version_sheet();
+    let address_map = (
+        "address_map",
+        vec![
+            vec!["BLOCK", "OFFSET", "RANGE"],
+            vec!["uart", "0x1000", "0x100"],
+        ],
+    );
+    let uart = (
+        "uart",
+        vec![
+            common::BLOCK_HEADER.to_vec(),
+            vec!["0x0", "CTRL", "EN", "[0]", "1", "RW", "0", "使能"],
+            vec!["", "", "MODE", "[2:1]", "2", "RW", "0", "0: off, 1: on"],
+            vec![
+                "0x4",
+                "STAT",
+                "BUSY",
+                "[0]",
+                "1",
+                "RO",
+                "0",
+                "Busy; set by HW",
+            ],
+        ],
+    );
+
+    // UTF-8 with BOM, ';' separated UTF-16LE with BOM, and GBK
+    let mut version_csv = b"\xEF\xBB\xBF".to_vec();
+    version_csv.extend(common::to_csv(&version.1, ',').into_bytes());
+    let mut address_map_csv = b"\xFF\xFE".to_vec();
+    address_map_csv.extend(
+        common::to_csv(&address_map.1, ';')
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes),
+    );
+    let uart_text = common::to_csv(&uart.1, ',');
+    let (uart_csv, _, _) = encoding_rs::GBK.encode(&uart_text);
+
+    let bundle = common::build_zip(&[
+        ("spec/version.csv", version_csv),
+        ("spec/address_map.csv", address_map_csv),
+        ("spec/uart.csv", uart_csv.into_owned()),
+        ("spec/README.md", b"not a sheet".to_vec()),
+    ]);
+
+    let from_csv = parse_excel_to_import_data(&bundle).expect("csv bundle");
+    let from_xlsx =
+        parse_excel_to_import_data(&common::build_workbook(&[version, address_map, uart]))
+            .expect("xlsx");
+    assert_eq!(
+        serde_json::to_value(&from_csv).expect("json"),
+        serde_json::to_value(&from_xlsx).expect("json")
+    );
+    assert_eq!(
+        from_csv.memory_maps[0].address_blocks[0].registers[0].fields[0]
+            .description
+            .as_deref(),
+        Some("使能")
+    );
+}
@@ -0,0 +1,369 @@
+use parser_plugin_rust::{
+    parse_header_to_import_data, parse_header_with_confidence, parse_header_with_options,
+    Confidence, ImportAddressBlock, ImportPluginResult, ParseOptions, Severity,
+};
+
+fn blocks(src: &str) -> Vec<ImportAddressBlock> {
+    parse_header_to_import_data(src.as_bytes())
+        .expect("parse should succeed")
+        .memory_maps
+        .remove(0)
+        .address_blocks
+}
+
+fn warnings(src: &str) -> Vec<String> {
+    parse_header_with_options(src.as_bytes(), &ParseOptions::default())
+        .warnings()
+        .map(|d| d.to_string())
+        .collect()
+}
+
+#[test]
+fn recovers_registers_from_offset_and_address_macros() {
+    let src = r#"/* foo_regs.h - legacy timer driver */
+#ifndef FOO_REGS_H
+#define FOO_REGS_H
+
+#define FOO_BASE             0x40002000UL
+#define FOO_CTRL_OFFSET      0x00        /* Control register */
+#define FOO_LOAD_OFFSET      0x08
+#define FOO_IRQ_ADDR         (FOO_BASE + 0x10)
+#define FOO_IRQ_PEND_OFFSET  3
+
+#define BAR_DATA_OFFSET      0x20
+
+#endif /* FOO_REGS_H */
+"#;
+    let (outcome, report) = parse_header_with_confidence(src.as_bytes(), &ParseOptions::default());
+    let data = outcome.into_result().expect("parse should succeed");
+    // named after the include guard
+    assert_eq!(data.project.name, "FOO_REGS");
+
+    // without a base, a block at 0 named after the first name token
+    let blocks = &data.memory_maps[0].address_blocks;
+    let names: Vec<_> = blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.range.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [("FOO", "0x40002000", "0x14"), ("BAR", "0x0", "0x24")]
+    );
+    assert_eq!(report.get("BAR").unwrap().confidence, Confidence::Medium);
+
+    let regs: Vec<_> = blocks[0]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(regs, [("CTRL", "0x0"), ("LOAD", "0x8"), ("IRQ", "0x10")]);
+    assert_eq!(
+        blocks[0].registers[0].description.as_deref(),
+        Some("Control register")
+    );
+
+    // a register without field macros is one field spanning it
+    assert_eq!(blocks[0].registers[1].fields[0].bit_width, 32);
+
+    // an _OFFSET macro under a register is a bit position
+    let pend = &blocks[0].registers[2].fields[0];
+    assert_eq!((pend.name.as_str(), pend.bit_offset), ("PEND", 3));
+    let pend = report.get("FOO.IRQ.PEND").expect("reported");
+    assert_eq!((pend.confidence, pend.line), (Confidence::Medium, Some(9)));
+}
+
+#[test]
+fn recovers_fields_from_shift_mask_and_width_macros() {
+    let src = r#"#define FOO_BASE             0x40002000UL
+#define FOO_CTRL_OFFSET      0x00
+#define FOO_CTRL_RESET       0x00000102U
+#define FOO_CTRL_EN_SHIFT    0
+#define FOO_CTRL_EN_MASK     (1u << FOO_CTRL_EN_SHIFT)
+#define FOO_CTRL_MODE_SHIFT  1
+#define FOO_CTRL_MODE_MASK   0x3         /* Counting mode */
+#define FOO_CTRL_MODE_UP     0
+#define FOO_CTRL_MODE_DOWN   1
+#define FOO_CTRL_MODE_PWM    (2 << FOO_CTRL_MODE_SHIFT)
+#define FOO_CTRL_DIV_POS     8
+#define FOO_CTRL_DIV_WIDTH   4
+"#;
+    let (outcome, report) = parse_header_with_confidence(src.as_bytes(), &ParseOptions::default());
+    let data = outcome.into_result().expect("parse should succeed");
+    let ctrl = &data.memory_maps[0].address_blocks[0].registers[0];
+
+    // shifted and unshifted masks, position with width, reset split per field
+    let fields: Vec<_> = ctrl
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.reset_value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("EN", 0, 1, "0x0"),
+            ("MODE", 1, 2, "0x1"),
+            ("DIV", 8, 4, "0x1")
+        ]
+    );
+    assert_eq!(ctrl.fields[1].description.as_deref(), Some("Counting mode"));
+    assert_eq!(
+        report.get("FOO.CTRL.MODE").unwrap().reason,
+        "position and unshifted mask"
+    );
+
+    // the other macros named after a field, shifted or not
+    let modes: Vec<_> = ctrl.fields[1]
+        .enumerated_values
+        .iter()
+        .map(|e| (e.name.as_str(), e.value.as_str()))
+        .collect();
+    assert_eq!(modes, [("UP", "0"), ("DOWN", "1"), ("PWM", "2")]);
+}
+
+#[test]
+fn turns_bare_register_masks_into_low_confidence_fields() {
+    let src = r#"#define FOO_BASE             0x40002000UL
+#define FOO_STATUS_OFFSET    0x04
+#define FOO_STATUS_DONE      0x1
+#define FOO_STATUS_ERR       0x6
+"#;
+    let (outcome, report) = parse_header_with_confidence(src.as_bytes(), &ParseOptions::default());
+    let infos: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Info)
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        infos,
+        [
+            "info[low-confidence] line 3: FOO.STATUS.DONE: bare mask FOO_STATUS_DONE named after the register",
+            "info[low-confidence] line 4: FOO.STATUS.ERR: bare mask FOO_STATUS_ERR named after the register",
+            "info[confidence]: 2 high, 0 medium and 2 low confidence items",
+        ]
+    );
+    assert_eq!(report.count(Confidence::Low), 2);
+
+    let data = outcome.into_result().expect("parse should succeed");
+    let status: Vec<_> = data.memory_maps[0].address_blocks[0].registers[0]
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width))
+        .collect();
+    assert_eq!(status, [("DONE", 0, 1), ("ERR", 1, 2)]);
+}
+
+#[test]
+fn skips_field_macros_that_cannot_be_placed() {
+    let src = r#"#define T_BASE 0x1000
+#define T_CNT_OFFSET 0x0
+#define T_CNT_VAL_MASK 0x5
+#define T_CNT_HI_SHIFT 4
+#define T_CNT_HI_MASK 0x90
+#define T_CNT_LO_MASK 0xFF
+#define T_CNT_TOP_SHIFT 30
+#define T_CNT_TOP_WIDTH 4
+#define BAZ_X_SHIFT 2
+"#;
+    assert_eq!(
+        warnings(src),
+        [
+            "warning[not-a-mask] line 3: Field CNT.VAL: T_CNT_VAL_MASK 0x5 is not a contiguous mask, skipped",
+            "warning[not-a-mask] line 4: Field CNT.HI: T_CNT_HI_MASK 0x90 is not a contiguous mask at bit 4, using width 1",
+            "warning[field-overlap] line 6: Field CNT.LO overlaps another field, skipped",
+            "warning[field-out-of-range] line 7: Field CNT.TOP [33:30] does not fit in 32 bits, skipped",
+            "warning[missing-offset] line 9: Field macros BAZ_X_* match no register, skipped",
+        ]
+    );
+}
+
+#[test]
+fn lays_out_cmsis_struct_instances() {
+    let src = r#"#define PERIPH_BASE   0x40000000UL
+
+typedef struct {
+  __IO uint32_t CR;        /*!< Control register */
+  __I  uint32_t SR;
+  __O  uint32_t DR;
+       uint32_t RESERVED0;
+  __IO uint16_t BRR;
+       uint16_t RESERVED1;
+  struct {
+    __IO uint32_t SRC;
+    __IO uint32_t DST;
+  } CH[2];
+} UART_TypeDef;
+
+#define UART0_BASE  (PERIPH_BASE + 0x1000UL)
+#define UART1_BASE  (PERIPH_BASE + 0x2000UL)
+#define UART0       ((UART_TypeDef *) UART0_BASE)
+#define UART1       ((UART_TypeDef *) UART1_BASE)
+"#;
+    let (outcome, report) = parse_header_with_confidence(src.as_bytes(), &ParseOptions::default());
+    assert_eq!(outcome.warnings().count(), 0, "{:?}", outcome.diagnostics);
+    let data = outcome.into_result().expect("parse should succeed");
+    assert_eq!(data.project.name, "UART0");
+
+    let blocks = &data.memory_maps[0].address_blocks;
+    let names: Vec<_> = blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.range.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("UART0", "0x40001000", "0x24"),
+            ("UART1", "0x40002000", "0x24"),
+        ]
+    );
+    assert_eq!(
+        report.get("UART1").unwrap().reason,
+        "UART_TypeDef instance at 0x40002000"
+    );
+
+    // padding skipped, natural alignment, nested struct arrays flattened
+    let uart = &blocks[1];
+    let regs: Vec<_> = uart
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str(), r.size))
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("CR", "0x0", 32),
+            ("SR", "0x4", 32),
+            ("DR", "0x8", 32),
+            ("BRR", "0x10", 16),
+            ("CH_0_SRC", "0x14", 32),
+            ("CH_0_DST", "0x18", 32),
+            ("CH_1_SRC", "0x1C", 32),
+            ("CH_1_DST", "0x20", 32),
+        ]
+    );
+    assert_eq!(
+        uart.registers[0].description.as_deref(),
+        Some("Control register")
+    );
+    let access: Vec<_> = uart.registers[..3]
+        .iter()
+        .map(|r| r.fields[0].access.as_str())
+        .collect();
+    assert_eq!(access, ["read-write", "read-only", "write-only"]);
+}
+
+#[test]
+fn reads_struct_register_fields_from_cmsis_macros_and_bit_fields() {
+    let src = r#"typedef struct {
+  __IO uint32_t CR;
+  struct {
+    __IO uint32_t SRC;
+  } CH[2];
+} UART_TypeDef;
+
+typedef union {
+  struct {
+    uint32_t EN : 1;       /*!< Watchdog enable */
+    uint32_t    : 3;
+    uint32_t LVL : 4;
+  } b;
+  uint32_t w;
+} WDG_CTRL_Type;
+
+typedef struct {
+  __IO WDG_CTRL_Type CTRL;
+} WDG_Type;
+
+#define UART0       ((UART_TypeDef *) 0x40001000UL)
+#define WDG         ((WDG_Type *) 0x40003000UL)
+
+#define UART_CR_UE_Pos        0U
+#define UART_CR_UE_Msk        (0x1UL << UART_CR_UE_Pos)
+#define UART_CR_UE            UART_CR_UE_Msk
+#define UART_CR_PS_Pos        4U
+#define UART_CR_PS_Msk        (0x3UL << UART_CR_PS_Pos)   /*!< Parity */
+#define UART_CR_PS_0          (0x1UL << UART_CR_PS_Pos)
+#define UART_CR_PS_ODD        (0x1UL << UART_CR_PS_Pos)
+#define UART_CH_SRC_ADDR_Msk  0xFFFFFFFCUL
+"#;
+    let (outcome, report) = parse_header_with_confidence(src.as_bytes(), &ParseOptions::default());
+    let data = outcome.into_result().expect("parse should succeed");
+    let blocks = &data.memory_maps[0].address_blocks;
+
+    // `_Pos`/`_Msk` pairs through the typedef name, the `UART_CR_UE` alias and
+    // `_0` bit names skipped
+    let cr = &blocks[0].registers[0];
+    let fields: Vec<_> = cr
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width))
+        .collect();
+    assert_eq!(fields, [("UE", 0, 1), ("PS", 4, 2)]);
+    assert_eq!(cr.fields[1].description.as_deref(), Some("Parity"));
+    let parity: Vec<_> = cr.fields[1]
+        .enumerated_values
+        .iter()
+        .map(|e| (e.name.as_str(), e.value.as_str()))
+        .collect();
+    assert_eq!(parity, [("ODD", "1")]);
+
+    // macros of an array member apply to every element
+    for src in &blocks[0].registers[1..] {
+        let addr = &src.fields[0];
+        assert_eq!(
+            (addr.name.as_str(), addr.bit_offset, addr.bit_width),
+            ("ADDR", 2, 30)
+        );
+    }
+
+    // bit-fields of a register union
+    let ctrl: Vec<_> = blocks[1].registers[0]
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width))
+        .collect();
+    assert_eq!(ctrl, [("EN", 0, 1), ("LVL", 4, 4)]);
+    assert_eq!(
+        blocks[1].registers[0].fields[0].description.as_deref(),
+        Some("Watchdog enable")
+    );
+    let en = report.get("WDG.CTRL.EN").expect("reported");
+    assert_eq!((en.confidence, en.line), (Confidence::High, Some(10)));
+}
+
+#[test]
+fn reports_the_confidence_with_the_import_result() {
+    let src = "#define T_BASE 0x1000\n#define T_CNT_OFFSET 0x0\n";
+    let result = ImportPluginResult::from(parse_header_with_confidence(
+        src.as_bytes(),
+        &ParseOptions::default(),
+    ));
+    assert!(result.success);
+    let paths: Vec<_> = result
+        .confidence
+        .expect("confidence of a header import")
+        .items
+        .into_iter()
+        .map(|i| i.path)
+        .collect();
+    assert_eq!(paths, ["T", "T.CNT", "T.CNT.CNT"]);
+
+    let blocks = blocks(src);
+    assert_eq!(blocks[0].registers[0].name, "CNT");
+}
+
+#[test]
+fn reports_headers_without_registers() {
+    let err = parse_header_to_import_data(b"#define VERSION 3\n").expect_err("no registers");
+    assert_eq!(
+        err.to_string(),
+        "error[no-registers]: No registers found: expected _OFFSET or _ADDR macros, or struct instances"
+    );
+}
@@ -0,0 +1,351 @@
+use parser_plugin_rust::{
+    parse_hjson_to_import_data, parse_hjson_with_options, ImportRegister, ParseOptions,
+};
+
+fn registers(src: &str) -> Vec<ImportRegister> {
+    parse_hjson_to_import_data(src.as_bytes())
+        .expect("parse should succeed")
+        .memory_maps[0]
+        .address_blocks[0]
+        .registers
+        .clone()
+}
+
+fn errors(src: &str) -> Vec<String> {
+    parse_hjson_with_options(src.as_bytes(), &ParseOptions::default())
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect()
+}
+
+#[test]
+fn adds_the_interrupt_and_alert_registers_reggen_derives() {
+    let src = r#"// Copyright lowRISC contributors.
+{
+  name:          "gpio",
+  human_name:    "General-Purpose I/O Controller",
+  one_line_desc: "General-purpose I/O pin control",
+  interrupt_list: [
+    { name: "gpio", width: "2", desc: "raised if any of GPIO pin detects configured interrupt mode" }
+    { name: "done", desc: "Status interrupt", type: "status" }
+  ],
+  alert_list: [
+    { name: "fatal_fault", desc: "This fatal alert is triggered when a fatal TL-UL bus integrity fault is detected." }
+  ],
+  registers: [
+    { name: "DATA_IN", swaccess: "ro", hwaccess: "hwo", fields: [{ bits: "31:0" }] }
+  ]
+}
+"#;
+    let data = parse_hjson_to_import_data(src.as_bytes()).expect("parse should succeed");
+    assert_eq!(data.project.name, "gpio");
+    assert_eq!(
+        data.project.description.as_deref(),
+        Some("General-purpose I/O pin control")
+    );
+
+    let registers = &data.memory_maps[0].address_blocks[0].registers;
+    let regs: Vec<_> = registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("INTR_STATE", "0x0"),
+            ("INTR_ENABLE", "0x4"),
+            ("INTR_TEST", "0x8"),
+            ("ALERT_TEST", "0xC"),
+            ("DATA_IN", "0x10"),
+        ]
+    );
+
+    // one field per interrupt, event interrupts are cleared by writing 1
+    let state: Vec<_> = registers[0]
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.access.as_str(),
+                f.modified_write_value.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        state,
+        [
+            ("gpio", 0, 2, "read-write", Some("oneToClear")),
+            ("done", 2, 1, "read-only", None)
+        ]
+    );
+    assert_eq!(registers[2].fields[0].access, "write-only");
+
+    // a single unnamed field takes the register name
+    assert_eq!(registers[4].fields[0].name, "DATA_IN");
+}
+
+#[test]
+fn places_registers_with_skipto_reserved_and_windows() {
+    let src = r#"{
+  name: "uart",
+  registers: [
+    { name: "CTRL", fields: [{ bits: "0", name: "en" }] }
+    { skipto: "0x40" }
+    { name: "STATUS", fields: [{ bits: "0", name: "busy" }] }
+    { reserved: "2" }
+    { name: "RDATA", fields: [{ bits: "7:0", name: "data" }] }
+    { window: { name: "FIFO", items: "16", validbits: "8", swaccess: "rw" } }
+    { sameaddr: [] }
+  ]
+}
+"#;
+    let outcome = parse_hjson_with_options(src.as_bytes(), &ParseOptions::default());
+    let warnings: Vec<String> = outcome.warnings().map(|d| d.to_string()).collect();
+    assert_eq!(
+        warnings,
+        ["warning[unsupported-element] line 10: sameaddr is not imported"]
+    );
+    let map = outcome
+        .into_result()
+        .expect("parse should succeed")
+        .memory_maps
+        .remove(0);
+
+    // the window is aligned to its size
+    let blocks: Vec<_> = map
+        .address_blocks
+        .iter()
+        .map(|b| {
+            (
+                b.name.as_str(),
+                b.base_address.as_str(),
+                b.range.as_str(),
+                b.width,
+            )
+        })
+        .collect();
+    assert_eq!(
+        blocks,
+        [("uart", "0x0", "0x50", 32), ("FIFO", "0x80", "0x40", 8)]
+    );
+    let regs: Vec<_> = map.address_blocks[0]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [("CTRL", "0x0"), ("STATUS", "0x40"), ("RDATA", "0x4C")]
+    );
+
+    assert_eq!(
+        errors("{\n  name: \"x\"\n  registers: [\n    { name: \"R\", fields: [{ bits: \"0\" }] }\n    { skipto: \"0x0\" }\n  ]\n}"),
+        ["error[address-overlap] line 5: skipto 0x0 is below the next free offset 0x4"]
+    );
+}
+
+#[test]
+fn expands_multiregs_compacting_a_single_field() {
+    let src = r#"{
+  name: "gpio",
+  param_list: [
+    { name: "NumIOs", type: "int", default: "4", local: "true" }
+  ],
+  registers: [
+    { multireg: {
+        name: "MASK",
+        desc: '''
+              Mask of a pin.
+              One bit per pin.
+              ''',
+        count: "NumIOs",
+        cname: "GPIO",
+        fields: [
+          { bits: "1:0", name: "m", resval: "1",
+            enum: [
+              { value: "0", name: "off", desc: "Disabled" },
+              { value: "0x3", name: "on" },
+            ]
+          }
+        ]
+      }
+    },
+    { multireg: {
+        name: "CTRL",
+        count: "2",
+        cname: "CH",
+        compact: "false",
+        resval: "0x80000001",
+        fields: [
+          { bits: "0", name: "en" }
+          { bits: "31", name: "lock" }
+        ]
+      }
+    }
+  ]
+}
+"#;
+    let registers = registers(src);
+    let regs: Vec<_> = registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [("MASK_0", "0x0"), ("CTRL_0", "0x4"), ("CTRL_1", "0x8")]
+    );
+
+    // NumIOs = 4 two-bit fields packed into one register
+    let mask = &registers[0];
+    assert_eq!(
+        mask.description.as_deref(),
+        Some("Mask of a pin.\nOne bit per pin.")
+    );
+    let fields: Vec<_> = mask
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.reset_value.as_str()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("m_0", 0, "0x1"),
+            ("m_1", 2, "0x1"),
+            ("m_2", 4, "0x1"),
+            ("m_3", 6, "0x1")
+        ]
+    );
+    let enums: Vec<_> = mask.fields[3]
+        .enumerated_values
+        .iter()
+        .map(|e| (e.name.as_str(), e.value.as_str()))
+        .collect();
+    assert_eq!(enums, [("off", "0"), ("on", "3")]);
+
+    // not compacted: every copy keeps the fields, reset sliced from the register
+    let ctrl: Vec<_> = registers[2]
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.reset_value.as_str()))
+        .collect();
+    assert_eq!(ctrl, [("en", "0x1"), ("lock", "0x1")]);
+}
+
+#[test]
+fn maps_reggen_swaccess_values() {
+    let src = r#"{
+  name: "intr",
+  registers: [
+    { name: "INTR_CTRL_EN",
+      swaccess: "rw1s",
+      fields: [
+        { bits: "0", name: "rising" }
+        { bits: "1", name: "falling", swaccess: "r0w1c" }
+        { bits: "2", name: "level", swaccess: "rc", resval: "x" }
+        { bits: "3", name: "lock", swaccess: "rw0c" }
+      ]
+    }
+  ]
+}
+"#;
+    let registers = registers(src);
+    let fields: Vec<_> = registers[0]
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.access.as_str(),
+                f.modified_write_value.as_deref(),
+                f.read_action.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("read-write", Some("oneToSet"), None),
+            ("write-only", Some("oneToClear"), None),
+            ("read-only", None, Some("clear")),
+            ("read-write", Some("zeroToClear"), None),
+        ]
+    );
+}
+
+#[test]
+fn keeps_one_memory_map_per_bus_interface() {
+    let src = r#"
+name: "otp"
+registers: {
+  core: [
+    { name: "STATUS", swaccess: "ro", fields: [{ bits: "3:0" }] }
+  ]
+  prim: [
+    { skipto: "0x10" }
+    { name: "CSR0", fields: [{ bits: "7:0", name: "field0" }] }
+  ]
+}
+"#;
+    let data = parse_hjson_to_import_data(src.as_bytes()).expect("parse should succeed");
+    let maps: Vec<_> = data
+        .memory_maps
+        .iter()
+        .map(|m| {
+            (
+                m.name.as_str(),
+                m.address_blocks[0].registers[0].name.as_str(),
+                m.address_blocks[0].registers[0].address_offset.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(maps, [("core", "STATUS", "0x0"), ("prim", "CSR0", "0x10")]);
+}
+
+#[test]
+fn checks_bit_ranges_up_to_the_register_width() {
+    // a field as wide as a 64-bit register takes the whole reset
+    let src = r#"{
+  name: "wide",
+  regwidth: "64",
+  registers: [
+    { name: "R", resval: "0xFFFFFFFFFFFFFFFF", fields: [{ bits: "63:0" }] }
+  ]
+}
+"#;
+    let field = &registers(src)[0].fields[0];
+    assert_eq!(
+        (field.bit_width, field.reset_value.as_str()),
+        (64, "0xFFFFFFFFFFFFFFFF")
+    );
+
+    let src = r#"{
+  name: "x",
+  registers: [
+    { name: "R", fields: [
+      { bits: "7:0", name: "a" }
+      { bits: "4", name: "b" }
+      { bits: "127:0", name: "c" }
+    ]}
+  ]
+}
+"#;
+    assert_eq!(
+        errors(src),
+        [
+            "error[field-overlap] line 6: Field R.b overlaps another field",
+            "error[field-out-of-range] line 7: Field R.c [127:0] does not fit in 32 bits",
+        ]
+    );
+}
+
+#[test]
+fn reports_syntax_errors_with_lines() {
+    let err =
+        parse_hjson_to_import_data(b"{\n  name: \"x\"\n  registers: [\n    { name: \"R\"\n  ]\n}")
+            .expect_err("unbalanced braces");
+    assert!(err.to_string().starts_with("error[syntax-error] line 5"));
+}
@@ -0,0 +1,239 @@
+use parser_plugin_rust::{parse_ipxact_to_import_data, parse_ipxact_with_options, ParseOptions};
+
+const UART_2014: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>example.com</ipxact:vendor>
+  <ipxact:library>peripherals</ipxact:library>
+  <ipxact:name>uart</ipxact:name>
+  <ipxact:version>2.1</ipxact:version>
+  <ipxact:description>Simple UART</ipxact:description>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>uart_map</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>regs</ipxact:name>
+        <ipxact:baseAddress>UART_BASE</ipxact:baseAddress>
+        <ipxact:range>'h100</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>CTRL</ipxact:name>
+          <ipxact:description>Control register</ipxact:description>
+          <ipxact:addressOffset>'h0</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>EN</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>1'b1</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>PARITY</ipxact:name>
+            <ipxact:bitOffset>1</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>0</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>2</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+            <ipxact:enumeratedValues>
+              <ipxact:enumeratedValue>
+                <ipxact:name>NONE</ipxact:name>
+                <ipxact:value>0</ipxact:value>
+              </ipxact:enumeratedValue>
+              <ipxact:enumeratedValue>
+                <ipxact:name>ODD</ipxact:name>
+                <ipxact:description>Odd parity</ipxact:description>
+                <ipxact:value>2'b01</ipxact:value>
+              </ipxact:enumeratedValue>
+            </ipxact:enumeratedValues>
+          </ipxact:field>
+        </ipxact:register>
+        <ipxact:register>
+          <ipxact:name>STAT</ipxact:name>
+          <ipxact:addressOffset>'h4</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:access>read-only</ipxact:access>
+          <ipxact:field>
+            <ipxact:name>RXNE</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>0</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:readAction>clear</ipxact:readAction>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>ERR</ipxact:name>
+            <ipxact:bitOffset>1</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>0</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+            <ipxact:modifiedWriteValue>oneToClear</ipxact:modifiedWriteValue>
+          </ipxact:field>
+        </ipxact:register>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+  <ipxact:parameters>
+    <ipxact:parameter parameterId="UART_BASE" resolve="user">
+      <ipxact:name>UART_BASE</ipxact:name>
+      <ipxact:value>'h4000_0000 + 'h1000</ipxact:value>
+    </ipxact:parameter>
+  </ipxact:parameters>
+</ipxact:component>
+"#;
+
+#[test]
+fn reads_ipxact_2014_component() {
+    let data = parse_ipxact_to_import_data(UART_2014.as_bytes()).expect("parse should succeed");
+
+    assert_eq!(data.project.name, "uart");
+    assert_eq!(data.project.vendor, "example.com");
+    assert_eq!(data.project.version, "2.1");
+    assert_eq!(data.project.description.as_deref(), Some("Simple UART"));
+
+    let blk = &data.memory_maps[0].address_blocks[0];
+    assert_eq!(data.memory_maps[0].name, "uart_map");
+    assert_eq!(blk.base_address, "0x40001000");
+    assert_eq!(blk.range, "0x100");
+
+    let ctrl = &blk.registers[0];
+    assert_eq!(ctrl.description.as_deref(), Some("Control register"));
+    assert_eq!(ctrl.fields[0].reset_value, "0x1");
+    let parity = &ctrl.fields[1];
+    assert_eq!((parity.bit_offset, parity.bit_width), (1, 2));
+    let enums: Vec<_> = parity
+        .enumerated_values
+        .iter()
+        .map(|e| (e.name.as_str(), e.value.as_str(), e.description.as_deref()))
+        .collect();
+    assert_eq!(
+        enums,
+        [("NONE", "0", None), ("ODD", "1", Some("Odd parity"))]
+    );
+
+    // register access is inherited by fields that do not override it
+    let stat = &blk.registers[1];
+    assert_eq!(stat.address_offset, "0x4");
+    assert_eq!(stat.fields[0].access, "read-only");
+    assert_eq!(stat.fields[0].read_action.as_deref(), Some("clear"));
+    assert_eq!(stat.fields[1].access, "read-write");
+    assert_eq!(
+        stat.fields[1].modified_write_value.as_deref(),
+        Some("oneToClear")
+    );
+}
+
+#[test]
+fn reads_ipxact_2009_register_reset_and_block_access() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<spirit:component xmlns:spirit="http://www.spiritconsortium.org/XMLSchema/SPIRIT/1685-2009">
+  <spirit:vendor>example.com</spirit:vendor>
+  <spirit:library>ip</spirit:library>
+  <spirit:name>timer</spirit:name>
+  <spirit:version>1.0</spirit:version>
+  <spirit:memoryMaps>
+    <spirit:memoryMap>
+      <spirit:name>map</spirit:name>
+      <spirit:addressBlock>
+        <spirit:name>timer</spirit:name>
+        <spirit:baseAddress>0x2000</spirit:baseAddress>
+        <spirit:range>4096</spirit:range>
+        <spirit:width>32</spirit:width>
+        <spirit:access>read-only</spirit:access>
+        <spirit:register>
+          <spirit:name>CNT</spirit:name>
+          <spirit:dim>2</spirit:dim>
+          <spirit:addressOffset>0x10</spirit:addressOffset>
+          <spirit:size>32</spirit:size>
+          <spirit:reset><spirit:value>0x0000A500</spirit:value></spirit:reset>
+          <spirit:field>
+            <spirit:name>LO</spirit:name>
+            <spirit:bitOffset>0</spirit:bitOffset>
+            <spirit:bitWidth>8</spirit:bitWidth>
+          </spirit:field>
+          <spirit:field>
+            <spirit:name>HI</spirit:name>
+            <spirit:bitOffset>8</spirit:bitOffset>
+            <spirit:bitWidth>8</spirit:bitWidth>
+          </spirit:field>
+        </spirit:register>
+      </spirit:addressBlock>
+    </spirit:memoryMap>
+  </spirit:memoryMaps>
+</spirit:component>
+"#;
+
+    let data = parse_ipxact_to_import_data(xml.as_bytes()).expect("parse should succeed");
+    let blk = &data.memory_maps[0].address_blocks[0];
+    assert_eq!(blk.range, "0x1000");
+
+    let regs: Vec<_> = blk
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(regs, [("CNT_0", "0x10"), ("CNT_1", "0x14")]);
+
+    let fields = &blk.registers[1].fields;
+    assert_eq!(fields[0].reset_value, "0x0");
+    assert_eq!(fields[1].reset_value, "0xA5");
+    assert!(fields.iter().all(|f| f.access == "read-only"));
+}
+
+#[test]
+fn reports_unevaluable_values_and_fails_in_strict_mode() {
+    let xml = UART_2014.replace(
+        "<ipxact:size>32</ipxact:size>",
+        "<ipxact:size>REG_W</ipxact:size>",
+    );
+
+    let lenient = parse_ipxact_with_options(xml.as_bytes(), &ParseOptions::default());
+    assert!(lenient.data.is_some());
+    let warnings: Vec<String> = lenient.warnings().map(|d| d.to_string()).collect();
+    assert_eq!(
+        warnings,
+        [
+            "warning[invalid-number] line 20: Register CTRL: size \"REG_W\" cannot be evaluated, using 32",
+            "warning[invalid-number] line 50: Register STAT: size \"REG_W\" cannot be evaluated, using 32",
+        ]
+    );
+
+    let strict = parse_ipxact_with_options(
+        xml.as_bytes(),
+        &ParseOptions {
+            strict: true,
+            ..Default::default()
+        },
+    );
+    assert!(strict.data.is_none());
+    assert!(strict.has_errors());
+
+    let err = parse_ipxact_to_import_data(b"<ipxact:component").expect_err("broken xml");
+    assert!(err.to_string().starts_with("error[read-error]: XML error"));
+}
+
+#[test]
+fn reports_fields_out_of_the_register_or_overlapping() {
+    let xml = UART_2014
+        .replace(
+            "<ipxact:name>PARITY</ipxact:name>\n            <ipxact:bitOffset>1</ipxact:bitOffset>",
+            "<ipxact:name>PARITY</ipxact:name>\n            <ipxact:bitOffset>64</ipxact:bitOffset>",
+        )
+        .replace(
+            "<ipxact:bitWidth>1</ipxact:bitWidth>\n            <ipxact:readAction>",
+            "<ipxact:bitWidth>2</ipxact:bitWidth>\n            <ipxact:readAction>",
+        );
+
+    let outcome = parse_ipxact_with_options(xml.as_bytes(), &ParseOptions::default());
+    assert!(outcome.data.is_none());
+    let errors: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "error[field-out-of-range] line 28: Field CTRL.PARITY [65:64] does not fit in 32 bits",
+            "error[field-overlap] line 59: Field STAT.ERR overlaps another field",
+        ]
+    );
+}
@@ -0,0 +1,219 @@
+use parser_plugin_rust::{
+    parse_asciidoc_to_import_data, parse_markdown_to_import_data, parse_markdown_with_options,
+    ParseOptions, Severity,
+};
+
+const CHIP_MD: &str = r#"# Timer SoC
+
+Register tables exported from the design spec.
+
+## TIMER (0x4000_0000)
+
+| ADDR | REG    | FIELD | BIT    | WIDTH | ATTRIBUTE | DEFAULT | DESCRIPTION        |
+|:-----|:-------|:------|:-------|------:|:---------:|--------:|:-------------------|
+| 0x0  | `CTRL` | EN    | [0]    | 1     | RW        | 0       | **Enable**         |
+|      |        | MODE  | [2:1]  | 2     | RW        | 0x1     | Mode<br>select     |
+| 0x4  | STATUS | DONE  | [0]    | 1     | W1C       | 0       | Done \| sticky     |
+
+```
+| ADDR | REG |
+|------|-----|
+| 0x8  | NOT_A_REG |
+```
+
+## WDG @ 0x4000_1000
+
+| ADDR | REG  | FIELD | BIT    | WIDTH | ATTRIBUTE | DEFAULT | DESCRIPTION |
+|------|------|-------|--------|-------|-----------|---------|-------------|
+| 0x0  | LOAD | VAL   | [15:0] | 16    | RW        | 0xFFFF  | Reload      |
+
+Scratch
+-------
+
+| ADDR | REG  | FIELD | BIT    | WIDTH | ATTRIBUTE | DEFAULT | DESCRIPTION |
+|------|------|-------|--------|-------|-----------|---------|-------------|
+| 0x10 | PAD  | X     | [0]    | 1     | RO        | 0       | Scratch     |
+"#;
+
+#[test]
+fn reads_markdown_tables_under_headings() {
+    let outcome = parse_markdown_with_options(CHIP_MD.as_bytes(), &ParseOptions::default());
+    assert_eq!(outcome.warnings().count(), 0, "{:?}", outcome.diagnostics);
+    let infos: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Info)
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        infos,
+        ["info[default-offset] Scratch row 25: Heading gives no base address, block placed at 0x0"]
+    );
+    let data = outcome.into_result().expect("parse should succeed");
+    assert_eq!(data.project.name, "Timer SoC");
+
+    let blocks: Vec<_> = data.memory_maps[0]
+        .address_blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.range.as_str()))
+        .collect();
+    assert_eq!(
+        blocks,
+        [
+            ("TIMER", "0x40000000", "0x5"),
+            ("WDG", "0x40001000", "0x2"),
+            ("Scratch", "0x0", "0x11"),
+        ]
+    );
+
+    // inline markup stripped, code blocks skipped
+    let timer = &data.memory_maps[0].address_blocks[0];
+    let regs: Vec<_> = timer.registers.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(regs, ["CTRL", "STATUS"]);
+    let ctrl = &timer.registers[0];
+    let fields: Vec<_> = ctrl
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width))
+        .collect();
+    assert_eq!(fields, [("EN", 0, 1), ("MODE", 1, 2)]);
+    assert_eq!(ctrl.fields[0].description.as_deref(), Some("Enable"));
+    assert_eq!(ctrl.fields[1].description.as_deref(), Some("Mode\nselect"));
+    let done = &timer.registers[1].fields[0];
+    assert_eq!(done.description.as_deref(), Some("Done | sticky"));
+    assert_eq!(done.modified_write_value.as_deref(), Some("oneToClear"));
+}
+
+const DMA_ADOC: &str = r#"= DMA Controller
+
+.Blocks
+[cols="3*", options="header"]
+|===
+|BLOCK |OFFSET |RANGE
+|dma |0x1000 |0x100
+|===
+
+[cols="4*"]
+|===
+|VENDOR |LIBRARY |NAME |VERSION
+|acme.com |IP |dma |2.1
+|===
+
+== DMA
+
+[cols="8*", options="header"]
+|===
+|ADDR |REG |FIELD |BIT |WIDTH |ATTRIBUTE |DEFAULT |DESCRIPTION
+
+|0x0
+|CH_CTRL
+|START
+|[0]
+|1
+|RW
+|0
+|Start the +
+transfer
+
+|
+|
+|PRIO
+|[5:4]
+|2
+|RW
+|0x2
+|Priority
+|===
+
+== Unused
+
+|===
+|ADDR |REG |FIELD |BIT |WIDTH |ATTRIBUTE |DEFAULT |DESCRIPTION
+|0x0 |X |Y |[0] |1 |RO |0 |Not mapped
+|===
+"#;
+
+#[test]
+fn reads_asciidoc_tables_with_address_map_and_version() {
+    let data = parse_asciidoc_to_import_data(DMA_ADOC.as_bytes()).expect("parse should succeed");
+    assert_eq!(data.project.name, "dma");
+    assert_eq!(data.project.version, "2.1");
+
+    let blocks: Vec<_> = data.memory_maps[0]
+        .address_blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.range.as_str()))
+        .collect();
+    assert_eq!(blocks, [("dma", "0x1000", "0x100")]);
+
+    let reg = &data.memory_maps[0].address_blocks[0].registers[0];
+    assert_eq!(reg.name, "CH_CTRL");
+    let fields: Vec<_> = reg
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.reset_value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(fields, [("START", 0, 1, "0"), ("PRIO", 4, 2, "0x2")]);
+    assert_eq!(
+        reg.fields[0].description.as_deref(),
+        Some("Start the\ntransfer")
+    );
+}
+
+#[test]
+fn locates_row_errors_by_line() {
+    let err =
+        parse_markdown_to_import_data(b"# Notes\n\nNo tables here.\n").expect_err("no tables");
+    assert_eq!(
+        err.to_string(),
+        "error[missing-table]: No register table found under a heading"
+    );
+
+    let src = "## UART\n\n| ADDR | REG | FIELD | BIT | WIDTH | ATTRIBUTE | DEFAULT | DESCRIPTION |\n|---|---|---|---|---|---|---|---|\n| 0x0 | CTRL | EN | [0] | 1 | RW | 0 | Enable |\n| | | MODE | [2:1] | 2 | RX | 0 | Mode |\n";
+    let lenient = parse_markdown_with_options(src.as_bytes(), &ParseOptions::default());
+    let warnings: Vec<String> = lenient.warnings().map(|d| d.to_string()).collect();
+    // line 6 of the document, the ATTRIBUTE column
+    assert_eq!(
+        warnings,
+        ["warning[unknown-access] UART!F6: Field CTRL.MODE: unknown access \"RX\", defaulting to read-write"]
+    );
+
+    let strict = parse_markdown_with_options(
+        src.as_bytes(),
+        &ParseOptions {
+            strict: true,
+            ..Default::default()
+        },
+    );
+    assert!(strict.data.is_none());
+}
+
+#[test]
+fn names_blocks_and_registers_after_headings_as_identifiers() {
+    let src = "# Chip\n\n## Timer Registers (0x1000)\n\n### Control / Status (0x4)\n\n| FIELD | BIT | WIDTH | ATTRIBUTE | DEFAULT | DESCRIPTION |\n|---|---|---|---|---|---|\n| EN | [0] | 1 | RW | 0 | Enable |\n";
+    let outcome = parse_markdown_with_options(src.as_bytes(), &ParseOptions::default());
+    let blk = &outcome.data.as_ref().expect("data").memory_maps[0].address_blocks[0];
+    assert_eq!(blk.name, "Timer_Registers");
+    assert_eq!(blk.registers[0].name, "Control_Status");
+
+    let renamed: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.code == "renamed-heading")
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        renamed,
+        [
+            "info[renamed-heading] line 5: Heading \"Control / Status\" is not an identifier, register named Control_Status",
+            "info[renamed-heading] line 3: Heading \"Timer Registers\" is not an identifier, block named Timer_Registers",
+        ]
+    );
+}
@@ -0,0 +1,282 @@
+use parser_plugin_rust::{
+    parse_ralf_to_import_data, parse_ralf_with_options, ImportRegister, ParseOptions,
+};
+
+fn registers(src: &str) -> Vec<ImportRegister> {
+    parse_ralf_to_import_data(src.as_bytes())
+        .expect("parse should succeed")
+        .memory_maps[0]
+        .address_blocks[0]
+        .registers
+        .clone()
+}
+
+fn errors(src: &str) -> Vec<String> {
+    parse_ralf_with_options(src.as_bytes(), &ParseOptions::default())
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect()
+}
+
+#[test]
+fn places_the_blocks_of_the_top_system() {
+    let src = r#"# UART and DMA of a small SoC
+block uart {
+  bytes 4;
+  register CTRL @'h0 { field EN { bits 1; } }
+  register STATUS {
+    field BUSY { bits 1; }
+    constraint idle { BUSY == 0; }
+  }
+  register DATA[4] @'h10 +'h4 { field VALUE { bits 8; } }
+  memory FIFO @'h100 { size 64; bits 8; }
+}
+
+block dma {
+  bytes 4;
+  register ID @'h0 { field REV { bits 8; } }
+}
+
+system soc {
+  bytes 4;
+  doc { Demo SoC }
+  block uart=uart0 @'h4000_0000;
+  block uart=uart1;
+  block dma @'h4001_0000;
+}
+"#;
+    let outcome = parse_ralf_with_options(src.as_bytes(), &ParseOptions::default());
+    let warnings: Vec<String> = outcome.warnings().map(|d| d.to_string()).collect();
+    assert_eq!(
+        warnings,
+        [
+            "warning[unsupported-element] line 7: constraint in register STATUS is not imported",
+            "warning[unsupported-element] line 10: memory FIFO is not imported",
+            "warning[unsupported-element] line 7: constraint in register STATUS is not imported",
+            "warning[unsupported-element] line 10: memory FIFO is not imported",
+        ]
+    );
+    let data = outcome.into_result().expect("parse should succeed");
+    assert_eq!(data.project.name, "soc");
+    assert_eq!(data.project.description.as_deref(), Some("Demo SoC"));
+
+    // an unplaced block follows the previous one
+    let blocks: Vec<_> = data.memory_maps[0]
+        .address_blocks
+        .iter()
+        .map(|b| {
+            (
+                b.name.as_str(),
+                b.base_address.as_str(),
+                b.range.as_str(),
+                b.width,
+            )
+        })
+        .collect();
+    assert_eq!(
+        blocks,
+        [
+            ("uart0", "0x40000000", "0x20", 32),
+            ("uart1", "0x40000020", "0x20", 32),
+            ("dma", "0x40010000", "0x4", 32),
+        ]
+    );
+
+    let regs: Vec<_> = data.memory_maps[0].address_blocks[0]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("CTRL", "0x0"),
+            ("STATUS", "0x4"),
+            ("DATA_0", "0x10"),
+            ("DATA_1", "0x14"),
+            ("DATA_2", "0x18"),
+            ("DATA_3", "0x1C"),
+        ]
+    );
+}
+
+#[test]
+fn packs_fields_from_definitions_with_resets_and_enums() {
+    let src = r#"field ENABLE {
+  bits 1;
+  reset 1'b1;
+  doc { Enables the block }
+}
+
+block uart {
+  bytes 4;
+  register CTRL {
+    doc { Control register }
+    field ENABLE;
+    field PARITY @1 {
+      bits 2;
+      hard_reset 2'b10;
+      enum { NONE, EVEN = 2, ODD }
+    }
+    field BAUD (baud_div) @8 { bits 16; reset 'h1B2; }
+  }
+}
+"#;
+    let ctrl = &registers(src)[0];
+    assert_eq!(ctrl.description.as_deref(), Some("Control register"));
+    let fields: Vec<_> = ctrl
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.reset_value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("ENABLE", 0, 1, "0x1"),
+            ("PARITY", 1, 2, "0x2"),
+            ("BAUD", 8, 16, "0x1B2")
+        ]
+    );
+    assert_eq!(
+        ctrl.fields[0].description.as_deref(),
+        Some("Enables the block")
+    );
+
+    // enum values count up from the previous one
+    let parity: Vec<_> = ctrl.fields[1]
+        .enumerated_values
+        .iter()
+        .map(|e| (e.name.as_str(), e.value.as_str()))
+        .collect();
+    assert_eq!(parity, [("NONE", "0"), ("EVEN", "2"), ("ODD", "3")]);
+}
+
+#[test]
+fn maps_ralf_access_values() {
+    let src = r#"block b {
+  register STATUS {
+    field RXNE { bits 1; access rc; }
+    field ERR @4 { bits 1; access w1c; }
+    field TX @8 { bits 8; access wo; }
+  }
+}
+"#;
+    let registers = registers(src);
+    let fields: Vec<_> = registers[0]
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.access.as_str(),
+                f.modified_write_value.as_deref(),
+                f.read_action.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("read-only", None, Some("clear")),
+            ("read-write", Some("oneToClear"), None),
+            ("write-only", None, None),
+        ]
+    );
+}
+
+#[test]
+fn packs_left_to_right_and_flattens_regfile_arrays() {
+    let src = r#"regfile CHANNEL {
+  register SRC @'h0 { field ADDR { bits 32; } }
+  register DST @'h4 { field ADDR { bits 32; } }
+}
+
+block dma {
+  bytes 4;
+  register ID (id_reg) @'h0 { left_to_right; field MAJOR { bits 8; reset 2; } field MINOR { bits 8; } }
+  regfile CHANNEL=CH[2] @'h20 +'h10;
+}
+"#;
+    let registers = registers(src);
+    let id: Vec<_> = registers[0]
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset))
+        .collect();
+    assert_eq!(id, [("MINOR", 16), ("MAJOR", 24)]);
+
+    let regs: Vec<_> = registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("ID", "0x0"),
+            ("CH_0_SRC", "0x20"),
+            ("CH_0_DST", "0x24"),
+            ("CH_1_SRC", "0x30"),
+            ("CH_1_DST", "0x34"),
+        ]
+    );
+}
+
+#[test]
+fn imports_blocks_without_a_system() {
+    let src = "block timer {\n  bytes 2;\n  register LOAD { field VALUE { bits 16; reset 'hFFFF; } }\n}\n";
+    let data = parse_ralf_to_import_data(src.as_bytes()).expect("parse should succeed");
+    assert_eq!(data.project.name, "timer");
+    let block = &data.memory_maps[0].address_blocks[0];
+    assert_eq!((block.base_address.as_str(), block.width), ("0x0", 16));
+    assert_eq!(block.registers[0].size, 16);
+    assert_eq!(block.registers[0].fields[0].reset_value, "0xFFFF");
+}
+
+#[test]
+fn reports_fields_out_of_the_register_or_overlapping() {
+    // a field as wide as a 16-byte register fills it
+    let src = "block b {\n  register W { bytes 16; field DATA { bits 128; } }\n}";
+    assert_eq!(registers(src)[0].fields[0].bit_width, 128);
+
+    let src = r#"block b {
+  bytes 4;
+  register R {
+    field A { bits 8; }
+    field B @4 { bits 2; }
+    field C @31 { bits 2; }
+    field D @'h40 { bits 2; }
+  }
+}
+"#;
+    assert_eq!(
+        errors(src),
+        [
+            "error[field-overlap] line 5: Field R.B overlaps another field",
+            "error[field-out-of-range] line 6: Field R.C [32:31] does not fit in 32 bits",
+            "error[field-out-of-range] line 7: Field R.D [65:64] does not fit in 32 bits",
+        ]
+    );
+}
+
+#[test]
+fn reports_syntax_errors_and_unknown_definitions() {
+    let err = parse_ralf_to_import_data(
+        b"block b {\n  bytes 4;\n  register R {\n    field F { bits 1; }\n",
+    )
+    .expect_err("missing brace");
+    assert_eq!(err.to_string(), "error[syntax-error] line 1: Missing '}'");
+
+    let src = "block b {\n  register R { field F { bits 1; } }\n  register S;\n}";
+    assert_eq!(
+        errors(src),
+        ["error[unknown-definition] line 3: register S is not defined"]
+    );
+}
@@ -0,0 +1,203 @@
+use parser_plugin_rust::{parse_rdl_to_import_data, ImportRegister};
+
+fn registers(src: &str, block: usize) -> Vec<ImportRegister> {
+    let data = parse_rdl_to_import_data(src.as_bytes()).expect("parse should succeed");
+    data.memory_maps[0].address_blocks[block].registers.clone()
+}
+
+#[test]
+fn turns_addrmap_instances_into_blocks_placed_by_addressing_rules() {
+    let src = r#"
+addrmap uart_m {
+    reg { field {} f[7:0]; } CTRL @ 0x0;
+    reg { field {} f[7:0]; } STATUS;
+    reg { field {} tx[7:0]; } DATA[4] @ 0x10 += 0x4;
+};
+
+addrmap soc {
+    desc = "Demo SoC";
+    uart_m uart0 @ 0x4000_0000;
+    uart_m uart1;
+    reg { field {} id[31:0]; } ID @ 0x100;
+};
+"#;
+    let data = parse_rdl_to_import_data(src.as_bytes()).expect("parse should succeed");
+
+    // the last addrmap never instantiated is the top, its own registers form a block
+    assert_eq!(data.project.name, "soc");
+    assert_eq!(data.project.description.as_deref(), Some("Demo SoC"));
+    let blocks: Vec<_> = data.memory_maps[0]
+        .address_blocks
+        .iter()
+        .map(|b| (b.name.as_str(), b.base_address.as_str(), b.range.as_str()))
+        .collect();
+    assert_eq!(
+        blocks,
+        [
+            ("soc", "0x0", "0x104"),
+            ("uart0", "0x40000000", "0x20"),
+            ("uart1", "0x40000020", "0x20"),
+        ]
+    );
+
+    let regs: Vec<_> = data.memory_maps[0].address_blocks[1]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("CTRL", "0x0"),
+            ("STATUS", "0x4"),
+            ("DATA_0", "0x10"),
+            ("DATA_1", "0x14"),
+            ("DATA_2", "0x18"),
+            ("DATA_3", "0x1C"),
+        ]
+    );
+}
+
+#[test]
+fn packs_fields_with_parameters_defaults_and_enums() {
+    let src = r#"
+enum parity_e {
+    NONE = 2'd0 { desc = "No parity"; };
+    EVEN = 2'd2;
+    ODD;
+};
+
+reg ctrl_r #(longint unsigned DIV_W = 8) {
+    desc = "Control";
+    default sw = rw;
+    field { desc = "Enable"; } en = 1'b1;
+    field { encode = parity_e; } parity[2] = parity_e::EVEN;
+    field {} div[DIV_W] = 0;
+};
+
+addrmap top {
+    ctrl_r #(.DIV_W(16)) CTRL;
+};
+"#;
+    let ctrl = &registers(src, 0)[0];
+    assert_eq!(ctrl.description.as_deref(), Some("Control"));
+
+    // packed from bit 0, the parameter widening `div`
+    let fields: Vec<_> = ctrl
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.reset_value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("en", 0, 1, "0x1"),
+            ("parity", 1, 2, "0x2"),
+            ("div", 3, 16, "0x0")
+        ]
+    );
+
+    // enum entries without a value follow the previous one
+    let parity: Vec<_> = ctrl.fields[1]
+        .enumerated_values
+        .iter()
+        .map(|e| (e.name.as_str(), e.value.as_str(), e.description.as_deref()))
+        .collect();
+    assert_eq!(
+        parity,
+        [
+            ("NONE", "0", Some("No parity")),
+            ("EVEN", "2", None),
+            ("ODD", "3", None)
+        ]
+    );
+}
+
+#[test]
+fn maps_sw_and_side_effect_properties_to_access() {
+    let src = r#"
+reg status_r {
+    field { sw = r; hw = w; onread = rclr; } rxne[0:0] = 0;
+    field { sw = rw; hw = w; woclr; } err[4:4] = 0;
+    field { sw = w; onwrite = wot; } tx[15:8] = 0;
+};
+
+addrmap top {
+    status_r STATUS;
+    STATUS->desc = "Status";
+};
+"#;
+    let status = &registers(src, 0)[0];
+    assert_eq!(status.description.as_deref(), Some("Status"));
+    let fields: Vec<_> = status
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.access.as_str(),
+                f.modified_write_value.as_deref(),
+                f.read_action.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("read-only", None, Some("clear")),
+            ("read-write", Some("oneToClear"), None),
+            ("write-only", Some("oneToToggle"), None),
+        ]
+    );
+}
+
+#[test]
+fn flattens_regfile_arrays_and_applies_dynamic_assignments() {
+    let src = r#"
+addrmap timer {
+    default regwidth = 16;
+    regfile {
+        reg { field {} value[15:0] = 16'hFFFF; } LOAD;
+        reg { field { sw = r; } value[15:0] = 0; } COUNT;
+    } ch[2] += 0x8;
+    ch.LOAD.value->reset = 0x1234;
+};
+"#;
+    let regs = registers(src, 0);
+    let regs: Vec<_> = regs
+        .iter()
+        .map(|r| {
+            (
+                r.name.as_str(),
+                r.address_offset.as_str(),
+                r.size,
+                r.fields[0].reset_value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("ch_0_LOAD", "0x0", 16, "0x1234"),
+            ("ch_0_COUNT", "0x2", 16, "0x0"),
+            ("ch_1_LOAD", "0x8", 16, "0x1234"),
+            ("ch_1_COUNT", "0xA", 16, "0x0"),
+        ]
+    );
+}
+
+#[test]
+fn reports_syntax_errors_with_lines() {
+    let err = parse_rdl_to_import_data(b"addrmap top {\n  reg { field {} f; } r\n};")
+        .expect_err("missing ';'");
+    assert_eq!(
+        err.to_string(),
+        "error[syntax-error] line 3: Expected ';', found '}'"
+    );
+}
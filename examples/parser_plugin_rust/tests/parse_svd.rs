@@ -0,0 +1,326 @@
+use parser_plugin_rust::{
+    parse_svd_to_import_data, parse_svd_with_options, ImportAddressBlock, ParseOptions,
+};
+
+/// A device with `peripherals`, which start on line 4.
+fn device(peripherals: &str) -> String {
+    format!(
+        "<device>\n  <name>D</name>\n  <peripherals>\n{}\n  </peripherals>\n</device>",
+        peripherals
+    )
+}
+
+fn blocks(peripherals: &str) -> Vec<ImportAddressBlock> {
+    parse_svd_to_import_data(device(peripherals).as_bytes())
+        .expect("parse should succeed")
+        .memory_maps
+        .remove(0)
+        .address_blocks
+}
+
+fn warnings(peripherals: &str) -> Vec<String> {
+    parse_svd_with_options(device(peripherals).as_bytes(), &ParseOptions::default())
+        .warnings()
+        .map(|d| d.to_string())
+        .collect()
+}
+
+#[test]
+fn reads_the_device_header() {
+    let svd = r#"<device schemaVersion="1.3">
+  <vendor>Acme</vendor>
+  <name>ACME01</name>
+  <series>ACME0</series>
+  <version>1.2</version>
+  <description>Demo microcontroller</description>
+  <peripherals/>
+</device>"#;
+    let data = parse_svd_to_import_data(svd.as_bytes()).expect("parse should succeed");
+    assert_eq!(data.project.name, "ACME01");
+    assert_eq!(
+        data.project.description.as_deref(),
+        Some("Demo microcontroller")
+    );
+    assert_eq!(data.project.vendor, "Acme");
+    assert_eq!(data.project.library, "ACME0");
+    assert_eq!(data.project.version, "1.2");
+}
+
+#[test]
+fn follows_derived_from_down_to_enumerated_values() {
+    let blocks = blocks(
+        r#"    <peripheral>
+      <name>TIMER0</name>
+      <baseAddress>0x40000000</baseAddress>
+      <addressBlock><offset>0</offset><size>0x400</size></addressBlock>
+      <registers>
+        <register>
+          <name>CR</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>MODE</name>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues>
+                <name>MODE_E</name>
+                <enumeratedValue><name>ONESHOT</name><value>0</value></enumeratedValue>
+                <enumeratedValue><name>PERIODIC</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <register derivedFrom="CR">
+          <name>CR2</name>
+          <addressOffset>0x4</addressOffset>
+          <access>read-only</access>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="TIMER0">
+      <name>TIMER1</name>
+      <baseAddress>0x40000400</baseAddress>
+    </peripheral>
+    <peripheral>
+      <name>PWM</name>
+      <baseAddress>0x40001000</baseAddress>
+      <registers>
+        <register>
+          <name>CFG</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>M</name>
+              <bitRange>[1:0]</bitRange>
+              <enumeratedValues derivedFrom="TIMER0.CR.MODE.MODE_E"/>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>"#,
+    );
+
+    // the derived peripheral takes the registers and address block it lacks
+    let timer1 = &blocks[1];
+    assert_eq!(
+        (timer1.base_address.as_str(), timer1.range.as_str()),
+        ("0x40000400", "0x400")
+    );
+    let regs: Vec<_> = timer1.registers.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(regs, ["CR", "CR2"]);
+
+    // a derived register keeps the fields, with its own access
+    let cr2 = &timer1.registers[1].fields[0];
+    assert_eq!(
+        (cr2.name.as_str(), cr2.access.as_str()),
+        ("MODE", "read-only")
+    );
+
+    // no address block: the range ends after the last register
+    assert_eq!(blocks[2].range, "0x4");
+    let modes: Vec<_> = blocks[2].registers[0].fields[0]
+        .enumerated_values
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert_eq!(modes, ["ONESHOT", "PERIODIC"]);
+}
+
+#[test]
+fn flattens_clusters_and_expands_dim_arrays() {
+    let blocks = blocks(
+        r#"    <peripheral>
+      <name>TIMER</name>
+      <baseAddress>0x0</baseAddress>
+      <registers>
+        <cluster>
+          <dim>2</dim>
+          <dimIncrement>0x10</dimIncrement>
+          <dimIndex>A,B</dimIndex>
+          <name>CH%s</name>
+          <addressOffset>0x20</addressOffset>
+          <register>
+            <dim>2</dim>
+            <dimIncrement>4</dimIncrement>
+            <name>CC[%s]</name>
+            <addressOffset>0x0</addressOffset>
+          </register>
+        </cluster>
+        <register>
+          <name>OUT</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <dim>4</dim>
+              <dimIncrement>1</dimIncrement>
+              <name>P%s</name>
+              <bitOffset>0</bitOffset>
+              <bitWidth>1</bitWidth>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>"#,
+    );
+
+    let regs: Vec<_> = blocks[0]
+        .registers
+        .iter()
+        .map(|r| (r.name.as_str(), r.address_offset.as_str()))
+        .collect();
+    assert_eq!(
+        regs,
+        [
+            ("CHA_CC_0", "0x20"),
+            ("CHA_CC_1", "0x24"),
+            ("CHB_CC_0", "0x30"),
+            ("CHB_CC_1", "0x34"),
+            ("OUT", "0x0"),
+        ]
+    );
+
+    // a register without fields is a single field spanning it
+    let cc = &blocks[0].registers[0].fields;
+    assert_eq!((cc[0].name.as_str(), cc[0].bit_width), ("CC", 32));
+
+    let out: Vec<_> = blocks[0].registers[4]
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset))
+        .collect();
+    assert_eq!(out, [("P0", 0), ("P1", 1), ("P2", 2), ("P3", 3)]);
+}
+
+#[test]
+fn reads_each_bit_position_form_with_inherited_access_and_reset() {
+    let peripherals = r#"    <peripheral>
+      <name>UART</name>
+      <baseAddress>0x0</baseAddress>
+      <registers>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x0</addressOffset>
+          <access>read-only</access>
+          <resetValue>0x0000A5</resetValue>
+          <fields>
+            <field>
+              <name>CNT</name>
+              <bitRange>[15:8]</bitRange>
+              <readAction>clear</readAction>
+            </field>
+            <field>
+              <name>IF</name>
+              <lsb>0</lsb><msb>0</msb>
+              <access>read-write</access>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+            </field>
+            <field>
+              <name>PAR</name>
+              <bitOffset>1</bitOffset>
+              <bitWidth>2</bitWidth>
+              <enumeratedValues>
+                <enumeratedValue><name>EVEN</name><value>#10</value></enumeratedValue>
+                <enumeratedValue><name>OTHER</name><isDefault>true</isDefault></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>"#;
+    assert_eq!(
+        warnings(peripherals),
+        ["warning[unsupported-element] line 31: Field SR.PAR: isDefault value OTHER is not imported"]
+    );
+
+    let blocks = blocks(peripherals);
+    // sorted by offset, the reset sliced from the register's
+    let fields: Vec<_> = blocks[0].registers[0]
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_offset,
+                f.bit_width,
+                f.access.as_str(),
+                f.reset_value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("IF", 0, 1, "read-write", "0x1"),
+            ("PAR", 1, 2, "read-only", "0x2"),
+            ("CNT", 8, 8, "read-only", "0x0"),
+        ]
+    );
+    let [iflag, par, cnt] = &blocks[0].registers[0].fields[..] else {
+        panic!("three fields");
+    };
+    assert_eq!(iflag.modified_write_value.as_deref(), Some("oneToClear"));
+    assert_eq!(cnt.read_action.as_deref(), Some("clear"));
+    // binary `#10` as a plain number
+    assert_eq!(par.enumerated_values[0].value, "2");
+}
+
+#[test]
+fn reports_fields_out_of_the_register_or_overlapping() {
+    let peripherals = r#"    <peripheral>
+      <name>P</name>
+      <baseAddress>0x0</baseAddress>
+      <registers>
+        <register>
+          <name>R</name>
+          <addressOffset>0x0</addressOffset>
+          <size>16</size>
+          <fields>
+            <field><name>A</name><bitRange>[7:0]</bitRange></field>
+            <field><name>B</name><bitRange>[4:4]</bitRange></field>
+            <field><name>C</name><bitRange>[19:12]</bitRange></field>
+            <field><name>D</name><bitOffset>64</bitOffset></field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>"#;
+    let outcome = parse_svd_with_options(device(peripherals).as_bytes(), &ParseOptions::default());
+    assert!(outcome.data.is_none());
+    let errors: Vec<String> = outcome
+        .diagnostics
+        .iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "error[field-overlap] line 14: Field R.B overlaps another field",
+            "error[field-out-of-range] line 15: Field R.C [19:12] does not fit in 16 bits",
+            "error[field-out-of-range] line 16: Field R.D [64:64] does not fit in 16 bits",
+        ]
+    );
+}
+
+#[test]
+fn reports_unresolved_derivation_and_bad_numbers() {
+    let peripherals = r#"    <peripheral derivedFrom="NOPE">
+      <name>P</name>
+      <baseAddress>0x1000</baseAddress>
+      <registers>
+        <register>
+          <name>R</name>
+          <addressOffset>0x0</addressOffset>
+          <size>thirty-two</size>
+        </register>
+      </registers>
+    </peripheral>"#;
+    assert_eq!(
+        warnings(peripherals),
+        [
+            "warning[unresolved-derivation] line 4: derivedFrom=\"NOPE\" not found, ignored",
+            "warning[invalid-number] line 11: Register R: size \"thirty-two\" is not a number, ignored",
+        ]
+    );
+
+    let err = parse_svd_to_import_data(b"<component/>").expect_err("not a device");
+    assert!(err.to_string().starts_with("error[not-a-device] line 1"));
+}
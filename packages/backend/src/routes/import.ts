@@ -2,7 +2,7 @@ import { Hono } from "hono";
 import { zValidator } from "@hono/zod-validator";
 import { z } from "zod";
 import { db } from "../db";
-import { projects, memoryMaps, addressBlocks, registers, fields, resets, plugins } from "../db/schema";
+import { projects, memoryMaps, addressBlocks, registers, fields, resets, enumeratedValues, plugins } from "../db/schema";
 import { auth } from "../lib/auth";
 import type {
     ImportData,
@@ -119,9 +119,17 @@ const importExecuteSchema = z.object({
             bitOffset: z.number(),
             bitWidth: z.number(),
             access: z.string().optional(),
+            modifiedWriteValue: z.string().optional(),
+            readAction: z.string().optional(),
             resetValue: z.string().optional(),
             displayName: z.string().optional(),
             description: z.string().optional(),
+            enumeratedValues: z.array(z.object({
+              name: z.string().min(1),
+              value: z.string(),
+              displayName: z.string().optional(),
+              description: z.string().optional(),
+            })).optional(),
           })),
         })),
       })),
@@ -212,6 +220,8 @@ importRoutes.post("/execute", zValidator("json", importExecuteSchema), async (c)
                                     bitOffset: field.bitOffset,
                                     bitWidth: field.bitWidth,
                                     access: field.access ?? "read-write",
+                                    modifiedWriteValue: field.modifiedWriteValue,
+                                    readAction: field.readAction,
                                 })
                                 .returning();
 
@@ -222,6 +232,17 @@ importRoutes.post("/execute", zValidator("json", importExecuteSchema), async (c)
                                     value: field.resetValue,
                                 });
                             }
+
+                            // Create enumerated values if provided
+                            for (const ev of field.enumeratedValues ?? []) {
+                                await tx.insert(enumeratedValues).values({
+                                    fieldId: newField.id,
+                                    name: ev.name,
+                                    displayName: ev.displayName,
+                                    description: ev.description,
+                                    value: ev.value,
+                                });
+                            }
                         }
                     }
                 }
@@ -3,12 +3,48 @@ import { createPortal } from "react-dom";
 import { X, Upload, FileSpreadsheet, AlertCircle, CheckCircle, ChevronRight, ChevronLeft, Loader2 } from "lucide-react";
 import { useTranslation } from "react-i18next";
 import { useNavigate } from "react-router-dom";
-import type { ImportData, ImportPlugin, ImportPreviewResponse } from "@register-manager/shared";
+import type { ImportConfidenceReport, ImportData, ImportDiagnostic, ImportPlugin, ImportPluginResult, ImportPreviewResponse } from "@register-manager/shared";
 
 interface ImportFromExcelDialogProps {
   onClose: () => void;
 }
 
+/** "uart!E12: WIDTH is empty" */
+function formatDiagnostic(d: ImportDiagnostic): string {
+  const cell = `${d.column ?? ""}${d.row ?? ""}`;
+  const location = d.sheet ? (cell ? `${d.sheet}!${cell}` : d.sheet) : "";
+  return location ? `${location}: ${d.message}` : d.message;
+}
+
+/** Plugin entry point by file extension; spreadsheets and zipped CSV files go through parse_excel */
+const PARSE_ENTRY_BY_EXTENSION = new Map<string, string>([
+  ["xlsx", "parse_excel"],
+  ["xlsb", "parse_excel"],
+  ["xls", "parse_excel"],
+  ["ods", "parse_excel"],
+  ["zip", "parse_excel"],
+  ["xml", "parse_ipxact"],
+  ["rdl", "parse_rdl"],
+  ["svd", "parse_svd"],
+  ["hjson", "parse_hjson"],
+  ["ralf", "parse_ralf"],
+  ["h", "parse_header"],
+  ["md", "parse_markdown"],
+  ["markdown", "parse_markdown"],
+  ["adoc", "parse_asciidoc"],
+  ["asciidoc", "parse_asciidoc"],
+  ["docx", "parse_docx"],
+]);
+
+/** ".xlsx,.xlsb,..." */
+const ACCEPTED_EXTENSIONS = [...PARSE_ENTRY_BY_EXTENSION.keys()].map(ext => `.${ext}`).join(",");
+
+/** Plugin entry point for `fileName`, undefined for an unsupported extension */
+function parseEntry(fileName: string): string | undefined {
+  const dot = fileName.lastIndexOf(".");
+  return dot < 0 ? undefined : PARSE_ENTRY_BY_EXTENSION.get(fileName.slice(dot + 1).toLowerCase());
+}
+
 type Step = "upload" | "preview" | "confirm";
 
 export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
@@ -28,6 +64,8 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
   const [previewStats, setPreviewStats] = useState<ImportPreviewResponse["stats"] | null>(null);
   const [warnings, setWarnings] = useState<string[]>([]);
   const [error, setError] = useState<string | null>(null);
+  const [diagnostics, setDiagnostics] = useState<ImportDiagnostic[]>([]);
+  const [confidence, setConfidence] = useState<ImportConfidenceReport | null>(null);
   const [isLoading, setIsLoading] = useState(false);
 
   // Fetch plugins on mount
@@ -67,11 +105,11 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
   const handleDrop = useCallback((e: React.DragEvent) => {
     e.preventDefault();
     const file = e.dataTransfer.files[0];
-    if (file && (file.name.endsWith(".xlsx") || file.name.endsWith(".xls") || file.name.endsWith(".csv"))) {
+    if (file && parseEntry(file.name)) {
       setSelectedFile(file);
       setError(null);
     } else {
-      setError(t("import.errors.invalid_file_type"));
+      setError(t("import.errors.invalid_file_type", { extensions: ACCEPTED_EXTENSIONS.replace(/,/g, ", ") }));
     }
   }, [t]);
 
@@ -94,7 +132,7 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
   };
 
   // Parse file using WASM plugin
-  const parseFile = async (): Promise<ImportData | null> => {
+  const parseFile = async (): Promise<{ data: ImportData; warnings: string[] } | null> => {
     if (!selectedFile || !selectedPlugin) return null;
 
     try {
@@ -127,6 +165,7 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
       const buffer = await selectedFile.arrayBuffer();
 
       let result: ImportData;
+      let pluginWarnings: string[] = [];
 
       // Check if plugin has custom JS
       if (plugin.jsUrl) {
@@ -149,17 +188,39 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
           await module.default(urlWithCache);
         }
 
-        if (typeof module.parse_excel !== 'function') {
-          throw new Error("Plugin JS does not export parse_excel");
-        }
+        const entry = parseEntry(selectedFile.name) ?? "parse_excel";
 
-        result = module.parse_excel(new Uint8Array(buffer)) as ImportData;
+        if (typeof module[`${entry}_with_report`] === 'function') {
+          // Newer plugins return warnings (e.g. silently defaulted values) alongside the data
+          const report = module[`${entry}_with_report`](new Uint8Array(buffer)) as ImportPluginResult;
+          setDiagnostics(report.diagnostics ?? []);
+          setConfidence(report.confidence ?? null);
+          if (!report.success || !report.data) {
+            throw new Error(report.error ?? t("import.errors.parse_failed"));
+          }
+          result = report.data;
+          pluginWarnings = report.warnings ?? [];
+        } else {
+          if (typeof module[entry] !== 'function') {
+            throw new Error(`Plugin JS does not export ${entry}`);
+          }
+
+          try {
+            result = module[entry](new Uint8Array(buffer)) as ImportData;
+          } catch (parseError) {
+            // Plugins that can locate problems report every one of them, not just the first
+            if (entry === "parse_excel" && typeof module.validate_excel === 'function') {
+              setDiagnostics(module.validate_excel(new Uint8Array(buffer)) as ImportDiagnostic[]);
+            }
+            throw parseError;
+          }
+        }
       } else {
         // Plugin must have jsUrl to parse files
         throw new Error(t("import.errors.plugin_missing_js") || "Plugin does not have parser JS");
       }
 
-      return result;
+      return { data: result, warnings: pluginWarnings };
     } catch (e: any) {
       // Error handled silently
       throw new Error(t("import.errors.parse_failed") + ": " + (e.message || e));
@@ -172,10 +233,13 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
 
     setIsLoading(true);
     setError(null);
+    setDiagnostics([]);
+    setConfidence(null);
 
     try {
-      const data = await parseFile();
-      if (data) {
+      const parsed = await parseFile();
+      if (parsed) {
+        const { data, warnings: pluginWarnings } = parsed;
         setImportData(data);
 
         // Calculate stats
@@ -206,14 +270,14 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
           });
           const validation = await validateResponse.json();
           if (validation?.success) {
-            setWarnings(validation.warnings ?? []);
+            setWarnings([...pluginWarnings, ...(validation.warnings ?? [])]);
             setPreviewStats(validation.stats ?? fallbackStats);
           } else {
-            setWarnings([]);
+            setWarnings(pluginWarnings);
             setPreviewStats(fallbackStats);
           }
         } catch {
-          setWarnings([]);
+          setWarnings(pluginWarnings);
           setPreviewStats(fallbackStats);
         }
 
@@ -314,6 +378,15 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
               <div>
                 <p className="text-sm font-bold text-red-400">Error</p>
                 <p className="text-sm text-red-300">{error}</p>
+                {diagnostics.length > 0 && (
+                  <ul className="mt-2 text-xs text-red-300 space-y-1 max-h-40 overflow-y-auto">
+                    {diagnostics.map((d, i) => (
+                      <li key={i} className={d.severity === "error" ? "" : "text-yellow-300"}>
+                        • {formatDiagnostic(d)}
+                      </li>
+                    ))}
+                  </ul>
+                )}
               </div>
             </div>
           )}
@@ -383,7 +456,7 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
                   <input
                     ref={fileInputRef}
                     type="file"
-                    accept=".xlsx,.xls,.csv"
+                    accept={ACCEPTED_EXTENSIONS}
                     onChange={handleFileChange}
                     className="hidden"
                   />
@@ -399,9 +472,9 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
                     </div>
                   ) : (
                     <>
-                      <Upload className="w-10 h-10 text-surface-500 mx-auto mb-3" />
-                      <p className="text-surface-400">{t("import.drag_drop")}</p>
-                      <p className="text-sm text-surface-600 mt-1">{t("import.or_click")}</p>
+                        <Upload className="w-10 h-10 text-surface-500 mx-auto mb-3" />
+                        <p className="text-surface-400">{t("import.drag_drop")}</p>
+                        <p className="text-sm text-surface-600 mt-1">{t("import.or_click")}</p>
                     </>
                   )}
                 </div>
@@ -472,6 +545,36 @@ export function ImportFromExcelDialog({ onClose }: ImportFromExcelDialogProps) {
                 </dl>
               </div>
 
+              {/* Confidence of a heuristic import, the items not stated by the source first */}
+              {confidence && (
+                <div className="bg-surface-800 rounded-lg p-4">
+                  <h3 className="text-sm font-medium text-surface-300 mb-2">{t("import.confidence.title")}</h3>
+                  <p className="text-sm text-surface-400">
+                    {t("import.confidence.summary", {
+                      high: confidence.items.filter(i => i.confidence === "high").length,
+                      medium: confidence.items.filter(i => i.confidence === "medium").length,
+                      low: confidence.items.filter(i => i.confidence === "low").length,
+                    })}
+                  </p>
+                  {confidence.items.some(i => i.confidence !== "high") && (
+                    <>
+                      <p className="mt-2 text-xs text-surface-500">{t("import.confidence.check")}</p>
+                      <ul className="mt-1 text-xs space-y-1 max-h-40 overflow-y-auto">
+                        {confidence.items
+                          .filter(i => i.confidence !== "high")
+                          .sort((a, b) => (a.confidence === b.confidence ? 0 : a.confidence === "low" ? -1 : 1))
+                          .map((item, i) => (
+                            <li key={i} className={item.confidence === "low" ? "text-yellow-300" : "text-surface-400"}>
+                              • <span className="font-mono">{item.path}</span>
+                              {item.line !== undefined && ` (${t("import.confidence.line", { line: item.line })})`}: {item.reason}
+                            </li>
+                          ))}
+                      </ul>
+                    </>
+                  )}
+                </div>
+              )}
+
               {/* Warnings */}
               {warnings.length > 0 && (
                 <div className="bg-yellow-900/20 border border-yellow-500/30 rounded-lg p-4">
@@ -1,7 +1,7 @@
 // Excel Import Types for Register Manager
 // Defines the standard interface that all import plugins must output
 
-import type { AccessType } from "./ipxact";
+import type { AccessType, ModifiedWriteValue, ReadAction } from "./ipxact";
 
 // ============================================================================
 // Plugin System Types
@@ -31,6 +31,39 @@ export interface ImportPluginResult {
   data?: ImportData;
   error?: string;
   warnings?: string[];
+  /** Located problems reported by the plugin */
+  diagnostics?: ImportDiagnostic[];
+  /** How sure a heuristic import (C headers) is about each item */
+  confidence?: ImportConfidenceReport;
+}
+
+/** One item per recovered block, register and field */
+export interface ImportConfidenceReport {
+  items: ImportConfidenceItem[];
+}
+
+/** How a recovered block, register or field was found */
+export interface ImportConfidenceItem {
+  /** "BLOCK", "BLOCK.REG" or "BLOCK.REG.FIELD" */
+  path: string;
+  confidence: "high" | "medium" | "low";
+  reason: string;
+  /** 1-based line number */
+  line?: number;
+}
+
+/** A problem found by an import plugin, pointing at the offending cell when known */
+export interface ImportDiagnostic {
+  severity: "error" | "warning" | "info";
+  /** Stable identifier, e.g. "missing-column" */
+  code: string;
+  message: string;
+  /** Sheet name */
+  sheet?: string;
+  /** 1-based row number */
+  row?: number;
+  /** Column letter, e.g. "C" */
+  column?: string;
 }
 
 // ============================================================================
@@ -123,12 +156,30 @@ export interface ImportField {
   bitWidth: number;
   /** Access type */
   access?: AccessType;
+  /** Write side effect (e.g. "oneToClear" for W1C) */
+  modifiedWriteValue?: ModifiedWriteValue;
+  /** Read side effect (e.g. "clear" for RC) */
+  readAction?: ReadAction;
   /** Reset/default value (hex string) */
   resetValue?: string;
   /** Optional display name */
   displayName?: string;
   /** Optional description */
   description?: string;
+  /** Enumerated values (field encodings) */
+  enumeratedValues?: ImportEnumeratedValue[];
+}
+
+/** Enumerated value from import */
+export interface ImportEnumeratedValue {
+  /** Enumeration name */
+  name: string;
+  /** Encoded value (e.g., "0x1") */
+  value: string;
+  /** Optional display name */
+  displayName?: string;
+  /** Optional description */
+  description?: string;
 }
 
 // ============================================================================